@@ -0,0 +1,25 @@
+//! Feeds arbitrary engine output through the response framing and parsing, which must fail gracefully rather than
+//! panic: the engine is an external process whose output can't be fully trusted.
+//!
+//! Run with `cargo +nightly fuzz run response` from the repository root.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use paddleocr::{ImageData, Ppocr};
+
+fuzz_target!(|data: &[u8]| {
+    let mut p = Ppocr::from_streams(std::io::Cursor::new(data.to_vec()), std::io::sink());
+    // Keep requesting until the input is used up; every line is one response.
+    for _ in 0..=data.len() {
+        match p.ocr(ImageData::from_path("fuzz.png")) {
+            Ok(line) if line.is_empty() => break,
+            Ok(line) => {
+                let _ = serde_json::from_str::<paddleocr::OcrRec>(&line);
+            }
+            Err(_) => break,
+        }
+    }
+    let mut p = Ppocr::from_streams(std::io::Cursor::new(data.to_vec()), std::io::sink());
+    let _ = p.ocr_and_parse(ImageData::from_path("fuzz.png"));
+});
@@ -0,0 +1,66 @@
+#ifndef PADDLEOCR_H
+#define PADDLEOCR_H
+
+/* Generated with cbindgen from src/ffi.rs. Do not edit by hand. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+// A paddleocr-json instance.
+typedef struct Ppocr Ppocr;
+
+// Starts the engine at `exe_path`, optionally with a language `config_path` (may be `NULL`).
+//
+// Returns `NULL` if the engine could not be started. The instance must be released with `ppocr_free`.
+//
+// # Safety
+//
+// `exe_path` must be a valid NUL-terminated UTF-8 string, and `config_path` either one or `NULL`.
+Ppocr *ppocr_new(const char *exe_path, const char *config_path);
+
+// OCRs the image at `image_path`, returning the engine's JSON response.
+//
+// Returns `NULL` if the engine could not be talked to. The string must be released with `ppocr_string_free`.
+//
+// # Safety
+//
+// `p` must come from `ppocr_new` and not be used from several threads at once. `image_path` must be a valid
+// NUL-terminated UTF-8 string.
+char *ppocr_ocr_path(Ppocr *p, const char *image_path);
+
+// OCRs a base64-encoded image, returning the engine's JSON response.
+//
+// Returns `NULL` if the engine could not be talked to. The string must be released with `ppocr_string_free`.
+//
+// # Safety
+//
+// `p` must come from `ppocr_new` and not be used from several threads at once. `image_base64` must be a valid
+// NUL-terminated string.
+char *ppocr_ocr_base64(Ppocr *p, const char *image_base64);
+
+// OCRs the image in the clipboard, returning the engine's JSON response.
+//
+// Returns `NULL` if the engine could not be talked to. The string must be released with `ppocr_string_free`.
+//
+// # Safety
+//
+// `p` must come from `ppocr_new` and not be used from several threads at once.
+char *ppocr_ocr_clipboard(Ppocr *p);
+
+// Releases a string returned by this library. Passing `NULL` is a no-op.
+//
+// # Safety
+//
+// `s` must come from one of the `ppocr_ocr_*` functions and not be used afterwards.
+void ppocr_string_free(char *s);
+
+// Stops the engine and releases the instance. Passing `NULL` is a no-op.
+//
+// # Safety
+//
+// `p` must come from `ppocr_new` and not be used afterwards.
+void ppocr_free(Ppocr *p);
+
+#endif /* PADDLEOCR_H */
@@ -0,0 +1,81 @@
+use std::path::PathBuf;
+
+use ppocr::{ContentData, ImageData, Ppocr};
+use pyo3::exceptions::PyRuntimeError;
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+
+/**
+ * A paddleocr-json instance.
+ */
+#[pyclass(name = "Ppocr", module = "paddleocr")]
+struct PyPpocr {
+    inner: Ppocr,
+}
+
+fn to_dict<'py>(py: Python<'py>, content: &ContentData) -> PyResult<Bound<'py, PyDict>> {
+    let dict = PyDict::new_bound(py);
+    dict.set_item("text", &content.text)?;
+    dict.set_item("score", content.score.value())?;
+    dict.set_item("box", content.rect.iter().map(|p| p.to_vec()).collect::<Vec<_>>())?;
+    Ok(dict)
+}
+
+#[pymethods]
+impl PyPpocr {
+    /**
+     * Initialize a new instance, see `Ppocr::new`.
+     */
+    #[new]
+    #[pyo3(signature = (exe_path, config_path = None))]
+    fn new(py: Python<'_>, exe_path: PathBuf, config_path: Option<PathBuf>) -> PyResult<Self> {
+        py.allow_threads(|| Ppocr::new(exe_path, config_path).map_err(|e| e.to_string()))
+            .map(|inner| PyPpocr { inner })
+            .map_err(PyRuntimeError::new_err)
+    }
+
+    /**
+     * OCRs the image at the given path, returning the engine's raw JSON response.
+     */
+    fn ocr(&mut self, py: Python<'_>, image_path: String) -> PyResult<String> {
+        let inner = &mut self.inner;
+        Ok(py.allow_threads(|| inner.ocr(ImageData::from_path(image_path)))?)
+    }
+
+    /**
+     * OCRs an encoded image (PNG, JPEG, ...), returning the engine's raw JSON response.
+     */
+    fn ocr_bytes(&mut self, py: Python<'_>, image: Vec<u8>) -> PyResult<String> {
+        let inner = &mut self.inner;
+        Ok(py.allow_threads(|| inner.ocr(ImageData::from_bytes(image)))?)
+    }
+
+    /**
+     * OCRs the image in the clipboard, returning the engine's raw JSON response.
+     */
+    fn ocr_clipboard(&mut self, py: Python<'_>) -> PyResult<String> {
+        let inner = &mut self.inner;
+        Ok(py.allow_threads(|| inner.ocr_clipboard())?)
+    }
+
+    /**
+     * OCRs the image at the given path, returning a list of `{"text", "score", "box"}` dicts.
+     */
+    fn ocr_and_parse<'py>(
+        &mut self,
+        py: Python<'py>,
+        image_path: String,
+    ) -> PyResult<Vec<Bound<'py, PyDict>>> {
+        let inner = &mut self.inner;
+        let data = py
+            .allow_threads(|| inner.ocr_and_parse(ImageData::from_path(image_path)))
+            .map_err(PyRuntimeError::new_err)?;
+        data.iter().map(|content| to_dict(py, content)).collect()
+    }
+}
+
+#[pymodule]
+fn paddleocr(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<PyPpocr>()?;
+    Ok(())
+}
@@ -0,0 +1,176 @@
+//! Mapping results of screen captures back to the screen, for UI automation that finds text and clicks it.
+//!
+//! Results are in the pixels of the captured image, while input APIs take screen coordinates: the captured region
+//! or window starts somewhere on the screen, possibly at negative coordinates on multi-monitor setups, and on
+//! scaled displays a capture of physical pixels is larger than the logical coordinates APIs of processes that
+//! aren't DPI-aware expect. A [`Capture`] accounts for both:
+//!
+//! ```no_run
+//! use paddleocr::automation::{center_of, find_text, Capture};
+//! use paddleocr::OcrEngine;
+//!
+//! let mut p = paddleocr::Ppocr::new(".../PaddleOCR-json.exe", None).unwrap();
+//! // A window at (-1280, 200) on a display scaled to 150%, captured in physical pixels.
+//! let results = p.ocr_and_parse(paddleocr::ImageData::from_path("window.png")).unwrap();
+//! let capture = Capture::at(-1280, 200).dpi_scale(1.5);
+//! if let Some(found) = find_text(&results, "Submit") {
+//!     let (x, y) = capture.to_screen(center_of(&found));
+//!     click(x, y);
+//! }
+//! ```
+
+use std::ops::Range;
+
+use crate::geometry::CoordTransform;
+use crate::{ContentData, Rectangle};
+
+/**
+ * Where results came from on the screen. See the [module documentation](self).
+ */
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Capture {
+    /// The top-left corner of the captured region, in screen coordinates.
+    pub origin: (i32, i32),
+    /// Captured pixels per screen coordinate, such as 1.5 for a display scaled to 150% captured in physical pixels
+    /// for logical coordinates; 1.0 if they match.
+    pub scale: f64,
+    /// Maps results to the captured image, if it was processed before OCR, e.g. downscaled.
+    pub transform: CoordTransform,
+}
+
+impl Default for Capture {
+    fn default() -> Self {
+        Capture::at(0, 0)
+    }
+}
+
+impl Capture {
+    /**
+     * A capture of the region starting at `(x, y)` on the screen, unscaled.
+     */
+    pub fn at(x: i32, y: i32) -> Capture {
+        Capture {
+            origin: (x, y),
+            scale: 1.0,
+            transform: CoordTransform::identity(),
+        }
+    }
+
+    /**
+     * Sets [`scale`](Capture::scale). Scales that aren't positive are ignored.
+     */
+    pub fn dpi_scale(mut self, scale: f64) -> Capture {
+        if scale > 0.0 {
+            self.scale = scale;
+        }
+        self
+    }
+
+    /**
+     * Sets [`transform`](Capture::transform).
+     */
+    pub fn transform(mut self, transform: CoordTransform) -> Capture {
+        self.transform = transform;
+        self
+    }
+
+    /**
+     * Maps a point of the results to screen coordinates, rounding to the nearest.
+     */
+    pub fn to_screen(&self, point: [usize; 2]) -> (i32, i32) {
+        let [x, y] = self.transform.apply_point(point);
+        (
+            (self.origin.0 as f64 + x as f64 / self.scale).round() as i32,
+            (self.origin.1 as f64 + y as f64 / self.scale).round() as i32,
+        )
+    }
+
+    /**
+     * Maps the corners of a box of the results to screen coordinates.
+     */
+    pub fn rect_to_screen(&self, rect: &Rectangle) -> [(i32, i32); 4] {
+        rect.map(|p| self.to_screen(p))
+    }
+}
+
+/**
+ * Text found in a detection by [`find_text`].
+ */
+#[derive(Debug, Clone)]
+pub struct TextMatch<'a> {
+    /// The index of the detection in the results.
+    pub index: usize,
+    pub content: &'a ContentData,
+    /// The matched characters (not bytes) in the detection's text.
+    pub chars: Range<usize>,
+    /// The estimated box of the matched characters, as [`words::span`](crate::words::span) gives it.
+    pub rect: Rectangle,
+}
+
+/**
+ * The first detection of `results` whose text contains `needle`, in reading order if the results are sorted. The
+ * comparison is exact; normalize the text beforehand for anything looser.
+ */
+pub fn find_text<'a>(results: &'a [ContentData], needle: &str) -> Option<TextMatch<'a>> {
+    results.iter().enumerate().find_map(|(index, content)| {
+        let byte = content.text.find(needle)?;
+        let start = content.text[..byte].chars().count();
+        let chars = start..start + needle.chars().count();
+        Some(TextMatch {
+            index,
+            content,
+            rect: crate::words::span(content, chars.clone()),
+            chars,
+        })
+    })
+}
+
+/**
+ * The center of a match's box, where to click it, in the coordinates of the results.
+ */
+pub fn center_of(found: &TextMatch) -> [usize; 2] {
+    let (x, y) = found
+        .rect
+        .iter()
+        .fold((0, 0), |(x, y), p| (x + p[0], y + p[1]));
+    [(x + 2) / 4, (y + 2) / 4]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{center_of, find_text, Capture};
+    use crate::geometry::CoordTransform;
+    use crate::{Confidence, ContentData};
+
+    #[test]
+    fn find_and_map() {
+        let item = |text: &str, rect| ContentData {
+            rect,
+            score: Confidence(0.9),
+            text: text.to_string(),
+            orientation: None,
+            extra: Default::default(),
+        };
+        let results = [
+            item("Cancel", [[0, 0], [60, 0], [60, 20], [0, 20]]),
+            item("OK · Submit", [[100, 40], [210, 40], [210, 60], [100, 60]]),
+        ];
+        let found = find_text(&results, "Submit").unwrap();
+        assert_eq!((found.index, found.chars.clone()), (1, 5..11));
+        assert_eq!(found.rect, [[150, 40], [210, 40], [210, 60], [150, 60]]);
+        assert_eq!(center_of(&found), [180, 50]);
+        assert!(find_text(&results, "submit").is_none());
+
+        assert_eq!(Capture::default().to_screen([180, 50]), (180, 50));
+        let capture = Capture::at(-1280, 200).dpi_scale(1.5);
+        assert_eq!(capture.to_screen([180, 50]), (-1160, 233));
+        assert_eq!(capture.dpi_scale(0.0), capture);
+        // Recognized on a copy downscaled by half.
+        let halved = capture.transform(CoordTransform::resized((400, 200), (200, 100)));
+        assert_eq!(halved.to_screen([90, 25]), (-1160, 233));
+        assert_eq!(
+            Capture::at(10, 10).rect_to_screen(&found.rect),
+            [(160, 50), (220, 50), (220, 70), (160, 70)]
+        );
+    }
+}
@@ -0,0 +1,236 @@
+//! Barcodes and QR codes alongside text, for documents and screenshots that mix both.
+//!
+//! The engine only finds text, so codes are decoded by a [`BarcodeReader`] run on the same image, such as
+//! [`RxingReader`] with the `rxing` feature, and merged with the OCR results by [`ocr_with_barcodes`].
+
+use crate::{ContentData, ImageData, OcrEngine, Rectangle};
+
+/**
+ * A decoded barcode or QR code.
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Barcode {
+    /// The symbology as named by the reader, e.g. `QR_CODE` or `EAN_13`.
+    pub format: String,
+    pub payload: String,
+    pub rect: Rectangle,
+}
+
+/**
+ * Something found in an image: recognized text or a decoded code.
+ */
+#[derive(Debug, Clone)]
+pub enum Detection {
+    Text(ContentData),
+    Barcode(Barcode),
+}
+
+impl Detection {
+    pub fn rect(&self) -> &Rectangle {
+        match self {
+            Detection::Text(content) => &content.rect,
+            Detection::Barcode(barcode) => &barcode.rect,
+        }
+    }
+}
+
+/**
+ * A barcode decoder.
+ */
+pub trait BarcodeReader {
+    fn read(&mut self, image: &ImageData) -> Result<Vec<Barcode>, String>;
+}
+
+impl<F> BarcodeReader for F
+where
+    F: FnMut(&ImageData) -> Result<Vec<Barcode>, String>,
+{
+    fn read(&mut self, image: &ImageData) -> Result<Vec<Barcode>, String> {
+        self(image)
+    }
+}
+
+/**
+ * A [`BarcodeReader`] decoding every symbology supported by the `rxing` crate, several codes per image. Requires the
+ * `rxing` feature.
+ *
+ * Images are opened from their path or decoded from base64; the engine's special `clipboard` path can't be read.
+ * Boxes are the axis-aligned bounds of the points `rxing` locates, such as the finder patterns of QR codes, so they
+ * may not cover the whole code.
+ */
+#[cfg(feature = "rxing")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RxingReader;
+
+#[cfg(feature = "rxing")]
+impl BarcodeReader for RxingReader {
+    fn read(&mut self, image: &ImageData) -> Result<Vec<Barcode>, String> {
+        use base64::Engine;
+
+        let decoded = match image {
+            ImageData::ImagePathDict { image_path } => image::open(image_path),
+            ImageData::ImageBase64Dict { image_base64 } => {
+                let bytes = base64::engine::general_purpose::STANDARD
+                    .decode(image_base64)
+                    .map_err(|e| format!("invalid base64: {}", e))?;
+                image::load_from_memory(&bytes)
+            }
+        }
+        .map_err(|e| format!("failed to read {}: {}", image.describe(), e))?;
+        match rxing::helpers::detect_multiple_in_image(decoded) {
+            Ok(results) => Ok(results.iter().map(barcode_of).collect()),
+            Err(rxing::Exceptions::NotFoundException(_)) => Ok(Vec::new()),
+            Err(e) => Err(e.to_string()),
+        }
+    }
+}
+
+#[cfg(feature = "rxing")]
+fn barcode_of(result: &rxing::RXingResult) -> Barcode {
+    let points = result.getPoints();
+    let edge = |coord: fn(&rxing::Point) -> f32, max: bool| {
+        let values = points.iter().map(|p| coord(p).max(0.0).round() as usize);
+        if max {
+            values.max().unwrap_or(0)
+        } else {
+            values.min().unwrap_or(0)
+        }
+    };
+    let (left, top) = (edge(|p| p.x, false), edge(|p| p.y, false));
+    let (right, bottom) = (edge(|p| p.x, true), edge(|p| p.y, true));
+    Barcode {
+        format: format!("{:?}", result.getBarcodeFormat()),
+        payload: result.getText().to_string(),
+        rect: [[left, top], [right, top], [right, bottom], [left, bottom]],
+    }
+}
+
+/**
+OCRs the image and reads its codes, returning both ordered top to bottom, then left to right.
+
+An image without text is not an error here, as it may still hold codes.
+
+# Examples
+
+```no_run
+use paddleocr::barcode::{ocr_with_barcodes, Detection, RxingReader};
+
+let mut p = paddleocr::Ppocr::new(".../PaddleOCR-json.exe", None).unwrap();
+for detection in ocr_with_barcodes(&mut p, Path::new("label.png").into(), &mut RxingReader).unwrap() {
+    match detection {
+        Detection::Text(text) => println!("text: {}", text.text),
+        Detection::Barcode(code) => println!("{}: {}", code.format, code.payload),
+    }
+}
+```
+*/
+pub fn ocr_with_barcodes<E, R>(
+    engine: &mut E,
+    image: ImageData,
+    reader: &mut R,
+) -> Result<Vec<Detection>, String>
+where
+    E: OcrEngine + ?Sized,
+    R: BarcodeReader + ?Sized,
+{
+    let barcodes = reader.read(&image)?;
+    let response = engine.ocr_response(image)?;
+    if response.code != 100 && response.code != 101 {
+        return response.into_result().map(|_| Vec::new());
+    }
+    let mut detections: Vec<Detection> = response
+        .data
+        .into_iter()
+        .map(Detection::Text)
+        .chain(barcodes.into_iter().map(Detection::Barcode))
+        .collect();
+    detections.sort_by_key(|d| (d.rect()[0][1], d.rect()[0][0]));
+    Ok(detections)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ocr_with_barcodes, Barcode, Detection};
+    use crate::{ImageData, OcrEngine};
+
+    struct Canned(&'static str);
+    impl OcrEngine for Canned {
+        fn ocr(&mut self, _image: ImageData) -> std::io::Result<String> {
+            Ok(self.0.to_string())
+        }
+    }
+
+    #[test]
+    fn merged() {
+        let mut reader = |_: &ImageData| {
+            Ok(vec![Barcode {
+                format: "QR_CODE".to_string(),
+                payload: "https://example.com".to_string(),
+                rect: [[0, 0], [50, 0], [50, 50], [0, 50]],
+            }])
+        };
+        let mut engine = Canned(
+            r#"{"code":100,"data":[{"box":[[0,60],[90,60],[90,80],[0,80]],"score":0.9,"text":"Scan me"}]}"#,
+        );
+        let detections =
+            ocr_with_barcodes(&mut engine, ImageData::from_path("a.png"), &mut reader).unwrap();
+        assert!(
+            matches!(&detections[0], Detection::Barcode(b) if b.payload == "https://example.com")
+        );
+        assert!(matches!(&detections[1], Detection::Text(t) if t.text == "Scan me"));
+
+        let mut engine = Canned(r#"{"code":101,"data":"No text found in image."}"#);
+        let detections =
+            ocr_with_barcodes(&mut engine, ImageData::from_path("a.png"), &mut reader).unwrap();
+        assert_eq!(detections.len(), 1);
+
+        let mut engine = Canned(r#"{"code":200,"data":"Image not found."}"#);
+        assert!(
+            ocr_with_barcodes(&mut engine, ImageData::from_path("a.png"), &mut reader).is_err()
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "rxing")]
+    fn rxing_reader() {
+        use rxing::Writer;
+
+        use super::{BarcodeReader, RxingReader};
+
+        let matrix = rxing::MultiFormatWriter
+            .encode(
+                "https://example.com",
+                &rxing::BarcodeFormat::QR_CODE,
+                200,
+                200,
+            )
+            .unwrap();
+        let path = std::env::temp_dir().join(format!("paddleocr-qr-{}.png", std::process::id()));
+        image::DynamicImage::from(&matrix).save(&path).unwrap();
+        let read = RxingReader.read(&ImageData::from_path(path.to_string_lossy()));
+        let bytes = std::fs::read(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        let codes = read.unwrap();
+        assert_eq!(codes.len(), 1);
+        assert_eq!(codes[0].format, "QR_CODE");
+        assert_eq!(codes[0].payload, "https://example.com");
+        let [[left, top], _, [right, bottom], _] = codes[0].rect;
+        assert!(left < right && top < bottom && right <= 200 && bottom <= 200);
+        assert_eq!(
+            RxingReader.read(&ImageData::from_bytes(bytes)).unwrap(),
+            codes
+        );
+
+        let blank = image::DynamicImage::new_luma8(100, 100);
+        let mut png = std::io::Cursor::new(Vec::new());
+        blank.write_to(&mut png, image::ImageFormat::Png).unwrap();
+        assert!(RxingReader
+            .read(&ImageData::from_bytes(png.into_inner()))
+            .unwrap()
+            .is_empty());
+        assert!(RxingReader
+            .read(&ImageData::from_path("clipboard"))
+            .is_err());
+    }
+}
@@ -0,0 +1,337 @@
+//! OCR of many images in one call, which can be stopped between images.
+
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::{ContentData, ImageData, OcrEngine};
+
+/// File extensions [`ocr_dir`], [`find_images`] and [`glob`] pick up, compared case-insensitively.
+const IMAGE_EXTENSIONS: [&str; 8] = ["bmp", "jpeg", "jpg", "png", "tif", "tiff", "webp", "jp2"];
+
+/// The parsed results for one image, as from [`OcrEngine::ocr_and_parse`].
+type Parsed = Result<Vec<ContentData>, String>;
+
+/**
+ * The results of a batch, which may have been stopped before the end.
+ */
+#[derive(Debug, Clone)]
+pub struct Batch<T> {
+    /// One result per image handled, in order.
+    pub results: Vec<T>,
+    /// Whether the batch was stopped early, so that `results` covers only the first images.
+    pub interrupted: bool,
+}
+
+/**
+OCRs and parses `images` one after another, checking `stop` before each and stopping when it is set.
+
+# Examples
+
+```no_run
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+let stop = Arc::new(AtomicBool::new(false));
+let cancel = stop.clone(); // for the Cancel button: cancel.store(true, Ordering::Relaxed)
+let mut p = paddleocr::Ppocr::new(".../PaddleOCR-json.exe", None).unwrap();
+let images = [".../a.png", ".../b.png"].map(paddleocr::ImageData::from_path);
+let batch = paddleocr::batch::ocr_batch(&mut p, images, &stop);
+if batch.interrupted {
+    eprintln!("cancelled after {} images", batch.results.len());
+}
+```
+*/
+pub fn ocr_batch<E, I>(engine: &mut E, images: I, stop: &AtomicBool) -> Batch<Parsed>
+where
+    E: OcrEngine + ?Sized,
+    I: IntoIterator<Item = ImageData>,
+{
+    let mut results = Vec::new();
+    for image in images {
+        if stop.load(Ordering::Relaxed) {
+            return Batch {
+                results,
+                interrupted: true,
+            };
+        }
+        results.push(engine.ocr_and_parse(image));
+    }
+    Batch {
+        results,
+        interrupted: false,
+    }
+}
+
+/**
+ * OCRs and parses the images directly in `dir`, by file name order, checking `stop` before each. Files are picked
+ * by their extension, such as `.png` or `.jpg`.
+ */
+pub fn ocr_dir<E, P>(
+    engine: &mut E,
+    dir: P,
+    stop: &AtomicBool,
+) -> std::io::Result<Batch<(PathBuf, Parsed)>>
+where
+    E: OcrEngine + ?Sized,
+    P: AsRef<Path>,
+{
+    let paths = find_images(dir, false)?;
+    let images = paths.iter().map(|path| path.as_path().into());
+    let batch = ocr_batch(engine, images, stop);
+    Ok(Batch {
+        results: paths.into_iter().zip(batch.results).collect(),
+        interrupted: batch.interrupted,
+    })
+}
+
+fn is_image(path: &Path) -> bool {
+    path.extension().is_some_and(|extension| {
+        IMAGE_EXTENSIONS
+            .iter()
+            .any(|image| extension.eq_ignore_ascii_case(image))
+    }) && path.is_file()
+}
+
+/**
+ * The images in `dir`, and with `recursive` in its subdirectories too, sorted by path. Files are picked by their
+ * extension, as for [`ocr_dir`].
+ */
+pub fn find_images<P: AsRef<Path>>(dir: P, recursive: bool) -> std::io::Result<Vec<PathBuf>> {
+    let mut paths = Vec::new();
+    let mut dirs = vec![dir.as_ref().to_path_buf()];
+    while let Some(dir) = dirs.pop() {
+        for entry in std::fs::read_dir(dir)? {
+            let path = entry?.path();
+            if recursive && path.is_dir() {
+                dirs.push(path);
+            } else if is_image(&path) {
+                paths.push(path);
+            }
+        }
+    }
+    paths.sort();
+    Ok(paths)
+}
+
+/**
+The images matching a glob `pattern`, sorted by path. In each `/`-separated component, `*` matches any run of
+characters and `?` any single one; a `**` component matches any number of directories. Only files with image
+extensions are returned, and names starting with `.` are only matched by patterns starting with `.`.
+
+# Examples
+
+```no_run
+let scans = paddleocr::batch::glob("scans/**/*.png").unwrap();
+```
+*/
+pub fn glob(pattern: &str) -> std::io::Result<Vec<PathBuf>> {
+    let components: Vec<&str> = pattern.split(['/', '\\']).collect();
+    // The directory before the first wildcard is read as is.
+    let literal = components
+        .iter()
+        .position(|component| component.contains(['*', '?']))
+        .unwrap_or(components.len());
+    let base = components[..literal].join("/");
+    let mut paths = Vec::new();
+    if literal == components.len() {
+        if is_image(Path::new(&base)) {
+            paths.push(PathBuf::from(base));
+        }
+        return Ok(paths);
+    }
+    // `a/*.png` reads `a/`, `*.png` reads the working directory.
+    let base = if literal == 0 {
+        PathBuf::new()
+    } else {
+        PathBuf::from(format!("{}/", base))
+    };
+    walk(&base, &components[literal..], &mut paths)?;
+    paths.sort();
+    paths.dedup();
+    Ok(paths)
+}
+
+fn walk(dir: &Path, components: &[&str], paths: &mut Vec<PathBuf>) -> std::io::Result<()> {
+    let Some((&component, rest)) = components.split_first() else {
+        return Ok(());
+    };
+    if component == "**" {
+        walk(dir, rest, paths)?;
+    }
+    let read = if dir.as_os_str().is_empty() {
+        Path::new(".")
+    } else {
+        dir
+    };
+    let entries = match std::fs::read_dir(read) {
+        Ok(entries) => entries,
+        // A directory named by the pattern that doesn't exist has no matches.
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+        Err(e) => return Err(e),
+    };
+    for entry in entries {
+        let entry = entry?;
+        let name = entry.file_name();
+        let name = name.to_string_lossy();
+        if name.starts_with('.') && !component.starts_with('.') {
+            continue;
+        }
+        let path = dir.join(&*name);
+        if component == "**" {
+            if path.is_dir() {
+                walk(&path, components, paths)?;
+            }
+        } else if wildcard(component, &name) {
+            if rest.is_empty() {
+                if is_image(&path) {
+                    paths.push(path);
+                }
+            } else if path.is_dir() {
+                walk(&path, rest, paths)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Whether `name` matches `pattern`, with `*` and `?` as wildcards.
+fn wildcard(pattern: &str, name: &str) -> bool {
+    let (pattern, name): (Vec<char>, Vec<char>) =
+        (pattern.chars().collect(), name.chars().collect());
+    // The last `*` seen, and where in `name` it would resume.
+    let (mut p, mut n, mut star) = (0, 0, None);
+    while n < name.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                star = Some((p, n));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == name[n] => {
+                p += 1;
+                n += 1;
+            }
+            _ => match star {
+                Some((star_p, star_n)) => {
+                    p = star_p + 1;
+                    n = star_n + 1;
+                    star = Some((star_p, star_n + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Arc;
+
+    use super::{find_images, glob, ocr_batch, ocr_dir, wildcard};
+    use crate::{ImageData, OcrEngine};
+
+    /// Sets `stop` after answering `remaining` requests.
+    struct Stopper {
+        stop: Arc<AtomicBool>,
+        remaining: usize,
+    }
+    impl OcrEngine for Stopper {
+        fn ocr(&mut self, _image: ImageData) -> std::io::Result<String> {
+            self.remaining -= 1;
+            if self.remaining == 0 {
+                self.stop.store(true, Ordering::Relaxed);
+            }
+            Ok(r#"{"code":101,"data":"No text found in image."}"#.to_string())
+        }
+    }
+
+    #[test]
+    fn interrupted() {
+        let stop = Arc::new(AtomicBool::new(false));
+        let mut engine = Stopper {
+            stop: stop.clone(),
+            remaining: 2,
+        };
+        let batch = ocr_batch(
+            &mut engine,
+            ["a", "b", "c"].map(ImageData::from_path),
+            &stop,
+        );
+        assert!(batch.interrupted);
+        assert_eq!(batch.results.len(), 2);
+    }
+
+    #[test]
+    fn dir() {
+        let dir = std::env::temp_dir().join(format!("paddleocr-batch-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        for file in ["b.PNG", "a.jpg", "notes.txt"] {
+            std::fs::write(dir.join(file), "").unwrap();
+        }
+        let stop = AtomicBool::new(false);
+        let mut engine = Stopper {
+            stop: Arc::default(),
+            remaining: usize::MAX,
+        };
+        let batch = ocr_dir(&mut engine, &dir, &stop);
+        std::fs::remove_dir_all(&dir).unwrap();
+        let batch = batch.unwrap();
+        assert!(!batch.interrupted);
+        let paths: Vec<_> = batch.results.iter().map(|(path, _)| path.clone()).collect();
+        assert_eq!(paths, [dir.join("a.jpg"), dir.join("b.PNG")]);
+    }
+
+    #[test]
+    fn globs() {
+        assert!(wildcard("*.png", "a.png") && wildcard("a?c*", "abc") && wildcard("*", ""));
+        assert!(!wildcard("*.png", "a.jpg") && !wildcard("a?", "a"));
+
+        let dir = std::env::temp_dir().join(format!("paddleocr-glob-{}", std::process::id()));
+        for file in [
+            "a.png",
+            "b.txt",
+            "sub/c.png",
+            "sub/deeper/d.png",
+            "sub/.hidden/e.png",
+        ] {
+            let path = dir.join(file);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, "").unwrap();
+        }
+        let base = dir.to_string_lossy().replace('\\', "/");
+        let found = (
+            glob(&format!("{}/**/*.png", base)),
+            glob(&format!("{}/*", base)),
+            glob(&format!("{}/sub/*/*.png", base)),
+            find_images(&dir, true),
+        );
+        std::fs::remove_dir_all(&dir).unwrap();
+        let name = |paths: Vec<std::path::PathBuf>| -> Vec<String> {
+            paths
+                .iter()
+                .map(|path| {
+                    path.strip_prefix(&dir)
+                        .unwrap()
+                        .to_string_lossy()
+                        .replace('\\', "/")
+                })
+                .collect()
+        };
+        assert_eq!(
+            name(found.0.unwrap()),
+            ["a.png", "sub/c.png", "sub/deeper/d.png"]
+        );
+        assert_eq!(name(found.1.unwrap()), ["a.png"]);
+        assert_eq!(name(found.2.unwrap()), ["sub/deeper/d.png"]);
+        assert_eq!(
+            name(found.3.unwrap()),
+            [
+                "a.png",
+                "sub/.hidden/e.png",
+                "sub/c.png",
+                "sub/deeper/d.png"
+            ]
+        );
+    }
+}
@@ -0,0 +1,167 @@
+//! `paddleocr annotate`: draws what the engine found over the image, as SVG or HTML, or as PNG with the `image`
+//! feature.
+
+use std::path::Path;
+
+use paddleocr::export::Coloring;
+use paddleocr::{ContentData, ImageData, Ppocr};
+
+/// OCRs `image` and writes it with the detections drawn over it to `output`, by its extension.
+pub fn run(p: &mut Ppocr, image: &str, output: &Path) -> Result<(), String> {
+    let extension = output
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .map(str::to_ascii_lowercase);
+    let formats = if cfg!(feature = "image") {
+        &["svg", "html", "png"][..]
+    } else {
+        &["svg", "html"]
+    };
+    if !extension.as_deref().is_some_and(|e| formats.contains(&e)) {
+        return Err(format!(
+            "{}: can only write .{}",
+            output.display(),
+            formats.join(", .")
+        ));
+    }
+    let bytes = std::fs::read(image).map_err(|e| format!("{}: {}", image, e))?;
+    // Refer to the image by an absolute path, so the output can be opened from anywhere.
+    let path = std::fs::canonicalize(image).map_err(|e| format!("{}: {}", image, e))?;
+    let path = path.to_string_lossy();
+    let image_data = ImageData::from_path(path.strip_prefix(r"\\?\").unwrap_or(&path));
+
+    let results = match p.ocr_response(image_data.clone()) {
+        Ok(response) if response.code == 100 || response.code == 101 => response.data,
+        Ok(response) => return Err(format!("{}: {}", image, response)),
+        Err(e) => return Err(format!("{}: {}", image, e)),
+    };
+    #[cfg(feature = "image")]
+    if extension.as_deref() == Some("png") {
+        let decoded = image::load_from_memory(&bytes).map_err(|e| format!("{}: {}", image, e))?;
+        return paddleocr::export::draw(&decoded, &results, Coloring::Confidence)
+            .save_with_format(output, image::ImageFormat::Png)
+            .map_err(|e| format!("{}: {}", output.display(), e));
+    }
+    let file = std::fs::File::create(output).map_err(|e| format!("{}: {}", output.display(), e))?;
+    let written = match extension.as_deref() {
+        Some("html") => paddleocr::export::to_html(file, &image_data, &results),
+        _ => {
+            let size = dimensions(&bytes).unwrap_or_else(|| extent(&results));
+            paddleocr::export::to_svg(file, &image_data, size, &results, Coloring::Confidence)
+        }
+    };
+    written.map_err(|e| format!("{}: {}", output.display(), e))
+}
+
+/// The width and height of a PNG, JPEG, GIF or BMP image, from its header.
+fn dimensions(image: &[u8]) -> Option<(usize, usize)> {
+    let u16_be =
+        |at: usize| Some(u16::from_be_bytes(image.get(at..at + 2)?.try_into().ok()?) as usize);
+    let u16_le =
+        |at: usize| Some(u16::from_le_bytes(image.get(at..at + 2)?.try_into().ok()?) as usize);
+    let u32_be =
+        |at: usize| Some(u32::from_be_bytes(image.get(at..at + 4)?.try_into().ok()?) as usize);
+    let i32_le = |at: usize| Some(i32::from_le_bytes(image.get(at..at + 4)?.try_into().ok()?));
+    if image.starts_with(b"\x89PNG\r\n\x1a\n") {
+        return Some((u32_be(16)?, u32_be(20)?));
+    }
+    if image.starts_with(b"GIF8") {
+        return Some((u16_le(6)?, u16_le(8)?));
+    }
+    if image.starts_with(b"BM") {
+        return Some((
+            i32_le(18)?.unsigned_abs() as usize,
+            i32_le(22)?.unsigned_abs() as usize,
+        ));
+    }
+    if image.starts_with(b"\xff\xd8") {
+        // Walk the segments up to the frame header.
+        let mut at = 2;
+        while *image.get(at)? == 0xff {
+            let marker = *image.get(at + 1)?;
+            match marker {
+                0xc0..=0xcf if !matches!(marker, 0xc4 | 0xc8 | 0xcc) => {
+                    return Some((u16_be(at + 7)?, u16_be(at + 5)?));
+                }
+                0x01 | 0xd0..=0xd7 => at += 2,
+                _ => at += 2 + u16_be(at + 2)?,
+            }
+        }
+    }
+    None
+}
+
+/// The smallest size holding all detections, for images whose header isn't understood.
+fn extent(results: &[ContentData]) -> (usize, usize) {
+    results
+        .iter()
+        .flat_map(|content| content.rect.iter())
+        .fold((1, 1), |(w, h), [x, y]| (w.max(x + 1), h.max(y + 1)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::dimensions;
+
+    #[test]
+    fn sizes() {
+        let mut png = b"\x89PNG\r\n\x1a\n\0\0\0\x0dIHDR".to_vec();
+        png.extend(640u32.to_be_bytes());
+        png.extend(480u32.to_be_bytes());
+        assert_eq!(dimensions(&png), Some((640, 480)));
+
+        let mut bmp = vec![b'B', b'M'];
+        bmp.resize(18, 0);
+        bmp.extend(3i32.to_le_bytes());
+        bmp.extend((-2i32).to_le_bytes());
+        assert_eq!(dimensions(&bmp), Some((3, 2)));
+
+        // An APP0 segment, then the frame header: 8 bits, 100 high, 200 wide.
+        let jpeg = [
+            &b"\xff\xd8\xff\xe0\x00\x04\x00\x00"[..],
+            b"\xff\xc0\x00\x11\x08\x00\x64\x00\xc8",
+        ]
+        .concat();
+        assert_eq!(dimensions(&jpeg), Some((200, 100)));
+        assert_eq!(dimensions(b"not an image"), None);
+    }
+
+    #[test]
+    #[cfg(feature = "image")]
+    fn png() {
+        use std::io::Cursor;
+
+        use image::{Rgb, RgbImage, Rgba};
+        use paddleocr::Ppocr;
+
+        let dir = std::env::temp_dir().join(format!("paddleocr-annotate-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let (input, output) = (dir.join("scan.png"), dir.join("annotated.png"));
+        RgbImage::from_pixel(40, 20, Rgb([255; 3]))
+            .save(&input)
+            .unwrap();
+        let mut p = Ppocr::from_streams(
+            Cursor::new(concat!(
+                "OCR init completed.\n",
+                r#"{"code":100,"data":[{"box":[[5,5],[30,5],[30,15],[5,15]],"score":1,"text":"hi"}]}"#,
+                "\n"
+            )),
+            std::io::sink(),
+        );
+        super::run(&mut p, input.to_str().unwrap(), &output).unwrap();
+
+        let annotated = image::open(&output).unwrap().to_rgba8();
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!((annotated.width(), annotated.height()), (40, 20));
+        assert_eq!(*annotated.get_pixel(5, 5), Rgba([11, 218, 11, 255]));
+        assert_eq!(*annotated.get_pixel(0, 0), Rgba([255, 255, 255, 255]));
+
+        let mut p = Ppocr::from_streams(std::io::empty(), std::io::sink());
+        let error = super::run(&mut p, "scan.png", &dir.join("a.jpg")).unwrap_err();
+        assert!(
+            error.ends_with("can only write .svg, .html, .png"),
+            "{}",
+            error
+        );
+    }
+}
@@ -0,0 +1,144 @@
+//! `paddleocr bench`: latency and throughput of a set of engines over a corpus.
+
+use std::fmt;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Mutex, PoisonError};
+use std::time::{Duration, Instant};
+
+use paddleocr::{ImageData, OcrEngine, OcrResponse};
+
+/// The outcome of a benchmark run.
+#[derive(Debug)]
+pub struct Report {
+    /// Latencies of the successful requests, sorted.
+    latencies: Vec<Duration>,
+    failures: usize,
+    elapsed: Duration,
+    engines: usize,
+}
+
+impl Report {
+    fn percentile(&self, p: f64) -> Duration {
+        let i = ((self.latencies.len() - 1) as f64 * p).round() as usize;
+        self.latencies[i]
+    }
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let requests = self.latencies.len() + self.failures;
+        writeln!(
+            f,
+            "{} requests on {} engine(s) in {:.2?}, {} failed",
+            requests, self.engines, self.elapsed, self.failures
+        )?;
+        if self.latencies.is_empty() {
+            return Ok(());
+        }
+        let mean = self.latencies.iter().sum::<Duration>() / self.latencies.len() as u32;
+        writeln!(
+            f,
+            "latency: min {:.2?}, p50 {:.2?}, p90 {:.2?}, p99 {:.2?}, max {:.2?}, mean {:.2?}",
+            self.latencies[0],
+            self.percentile(0.5),
+            self.percentile(0.9),
+            self.percentile(0.99),
+            self.latencies[self.latencies.len() - 1],
+            mean
+        )?;
+        writeln!(
+            f,
+            "throughput: {:.2} images/s",
+            self.latencies.len() as f64 / self.elapsed.as_secs_f64()
+        )
+    }
+}
+
+/**
+ * OCRs each image `iterations` times, spreading the requests over `engines` on one thread each. A request fails if
+ * the engine errs or answers with a code other than 100 (text found) or 101 (no text).
+ */
+pub fn run<E, F>(engines: Vec<E>, images: &[String], iterations: usize, image_data: F) -> Report
+where
+    E: OcrEngine + Send,
+    F: Fn(&str) -> ImageData + Sync,
+{
+    let total = images.len() * iterations;
+    let next = AtomicUsize::new(0);
+    let results = Mutex::new((Vec::with_capacity(total), 0));
+    let engine_count = engines.len();
+    let start = Instant::now();
+    std::thread::scope(|scope| {
+        for mut engine in engines {
+            let (next, results, image_data) = (&next, &results, &image_data);
+            scope.spawn(move || loop {
+                let i = next.fetch_add(1, Ordering::Relaxed);
+                if i >= total {
+                    break;
+                }
+                let image = image_data(&images[i % images.len()]);
+                let request = Instant::now();
+                let ok = engine
+                    .ocr(image)
+                    .ok()
+                    .and_then(|raw| OcrResponse::parse(raw).ok())
+                    .is_some_and(|response| response.code == 100 || response.code == 101);
+                let latency = request.elapsed();
+                let mut results = results.lock().unwrap_or_else(PoisonError::into_inner);
+                if ok {
+                    results.0.push(latency);
+                } else {
+                    results.1 += 1;
+                }
+            });
+        }
+    });
+    let (mut latencies, failures) = results.into_inner().unwrap_or_else(PoisonError::into_inner);
+    latencies.sort();
+    Report {
+        latencies,
+        failures,
+        elapsed: start.elapsed(),
+        engines: engine_count,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use paddleocr::{ImageData, OcrEngine};
+
+    use super::run;
+
+    /// Takes 1 ms per request, and fails on `bad.png`.
+    struct Sleepy;
+    impl OcrEngine for Sleepy {
+        fn ocr(&mut self, image: ImageData) -> std::io::Result<String> {
+            std::thread::sleep(Duration::from_millis(1));
+            match image {
+                ImageData::ImagePathDict { image_path } if image_path == "bad.png" => {
+                    Ok(r#"{"code":200,"data":"Image path dose not exist."}"#.to_string())
+                }
+                _ => Ok(r#"{"code":101,"data":"No text found in image."}"#.to_string()),
+            }
+        }
+    }
+
+    #[test]
+    fn bench() {
+        let images = ["a.png".to_string(), "bad.png".to_string()];
+        let report = run(vec![Sleepy, Sleepy, Sleepy], &images, 5, |image: &str| {
+            ImageData::from_path(image)
+        });
+        assert_eq!((report.latencies.len(), report.failures), (5, 5));
+        assert!(report.latencies[0] >= Duration::from_millis(1));
+        let printed = report.to_string();
+        assert!(
+            printed.starts_with("10 requests on 3 engine(s)"),
+            "{}",
+            printed
+        );
+        assert!(printed.contains("p99"));
+    }
+}
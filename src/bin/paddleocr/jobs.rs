@@ -0,0 +1,160 @@
+//! `--jobs`: OCR of the inputs on a pool of engines, with the results handled in input order.
+
+use std::collections::BTreeMap;
+use std::io::{IsTerminal, Result as IoResult, Write};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::mpsc;
+
+use paddleocr::{ImageData, PpocrPool};
+
+/// OCRs `images` with up to `jobs` requests in flight, passing each response to `each` in the order of `images`.
+/// Stops at the first error `each` returns.
+pub fn run<T, F>(
+    pool: &PpocrPool,
+    images: &[String],
+    jobs: usize,
+    image_data: fn(&str) -> ImageData,
+    mut each: F,
+) -> Result<(), T>
+where
+    F: FnMut(&str, IoResult<String>) -> Result<(), T>,
+{
+    let next = AtomicUsize::new(0);
+    let stop = AtomicBool::new(false);
+    std::thread::scope(|scope| {
+        let (sender, receiver) = mpsc::channel();
+        for _ in 0..jobs.min(images.len()) {
+            let sender = sender.clone();
+            let (next, stop) = (&next, &stop);
+            scope.spawn(move || {
+                while !stop.load(Ordering::Relaxed) {
+                    let i = next.fetch_add(1, Ordering::Relaxed);
+                    let Some(image) = images.get(i) else {
+                        break;
+                    };
+                    if sender.send((i, pool.ocr(image_data(image)))).is_err() {
+                        break;
+                    }
+                }
+            });
+        }
+        drop(sender);
+
+        // Responses arrive in any order; hold on to them until those before them are handled.
+        let mut pending = BTreeMap::new();
+        let mut handled = 0;
+        for (i, response) in receiver {
+            pending.insert(i, response);
+            while let Some(response) = pending.remove(&handled) {
+                if let Err(e) = each(&images[handled], response) {
+                    stop.store(true, Ordering::Relaxed);
+                    return Err(e);
+                }
+                handled += 1;
+            }
+        }
+        Ok(())
+    })
+}
+
+/// A progress bar on stderr, shown only when it is a terminal and not `hidden`.
+pub struct Progress {
+    total: usize,
+    done: usize,
+    shown: bool,
+}
+
+impl Progress {
+    pub fn new(total: usize, hidden: bool) -> Progress {
+        Progress {
+            total,
+            done: 0,
+            shown: !hidden && total > 1 && std::io::stderr().is_terminal(),
+        }
+    }
+
+    pub fn tick(&mut self) {
+        self.done += 1;
+        if self.shown {
+            const WIDTH: usize = 30;
+            let filled = WIDTH * self.done / self.total;
+            eprint!(
+                "\r[{}{}] {}/{}",
+                "=".repeat(filled),
+                " ".repeat(WIDTH - filled),
+                self.done,
+                self.total
+            );
+            std::io::stderr().flush().ok();
+        }
+    }
+}
+
+impl Drop for Progress {
+    fn drop(&mut self) {
+        if self.shown {
+            // Clear the bar.
+            eprint!("\r{}\r", " ".repeat(40 + 2 * self.total.to_string().len()));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use paddleocr::{ImageData, OcrEngine, PpocrPool};
+
+    use super::run;
+
+    /// Answers with the image's path, taking longer for earlier images.
+    struct Slow;
+    impl OcrEngine for Slow {
+        fn ocr(&mut self, image: ImageData) -> std::io::Result<String> {
+            let ImageData::ImagePathDict { image_path } = image else {
+                unreachable!()
+            };
+            let delay = 5 - image_path.parse::<u64>().unwrap().min(5);
+            std::thread::sleep(Duration::from_millis(delay * 10));
+            Ok(image_path)
+        }
+    }
+
+    #[test]
+    fn ordered() {
+        let pool = PpocrPool::new([Slow, Slow, Slow]);
+        let images: Vec<String> = (0..8).map(|i| i.to_string()).collect();
+        let mut seen = Vec::new();
+        let result = run(
+            &pool,
+            &images,
+            3,
+            |image| ImageData::from_path(image),
+            |image, response| {
+                assert_eq!(response.unwrap(), image);
+                seen.push(image.to_string());
+                Ok::<_, ()>(())
+            },
+        );
+        assert!(result.is_ok());
+        assert_eq!(seen, images);
+
+        // An error stops handling.
+        let mut handled = 0;
+        let result = run(
+            &pool,
+            &images,
+            3,
+            |image| ImageData::from_path(image),
+            |image, _| {
+                handled += 1;
+                if image == "2" {
+                    Err("failed")
+                } else {
+                    Ok(())
+                }
+            },
+        );
+        assert_eq!((result, handled), (Err("failed"), 3));
+    }
+}
@@ -0,0 +1,453 @@
+//! Command-line front end: OCRs the given images, serves requests from other programs with `--rpc`, benchmarks the
+//! engine with `bench`, draws its detections over an image with `annotate`, or terminates engines leaked by crashed
+//! programs with `cleanup`.
+
+use std::io::{IsTerminal, Write};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+use paddleocr::{ImageData, OcrResponse, Ppocr, PpocrPool};
+
+mod annotate;
+mod bench;
+mod jobs;
+mod rpc;
+mod status;
+
+use status::Status;
+
+const USAGE: &str = "\
+Usage: paddleocr [OPTIONS] [IMAGE]...
+       paddleocr [OPTIONS] --rpc
+       paddleocr bench [OPTIONS] [IMAGE]...
+       paddleocr annotate [OPTIONS] IMAGE -o <OUT>
+       paddleocr cleanup
+
+OCRs each IMAGE (a file path, a directory of images, a glob pattern such as `'scans/**/*.png'`, or
+`clipboard`) and prints the engine's JSON response, one per line.
+`bench` instead reports the latency distribution and throughput of OCRing the images.
+`annotate` writes the image with each detection's box, colored by score, and its text and score on hover.
+`cleanup` terminates engines left running by programs using this library that crashed, and prints their PIDs.
+
+Exit status: 0 if text was found (in any IMAGE), 1 if no text was, 2 if an IMAGE is missing or unreadable,
+3 if the engine failed, and 4 if the options are wrong or the engine couldn't be started with them.
+
+Options:
+  -e, --exe <PATH>     PaddleOCR-json executable [default: $PADDLEOCR_EXE]
+  -c, --config <PATH>  language config file passed to the engine
+  -f, --format <FMT>   output format: `json` (the engine's responses as is), `jsonl` (one
+                       parsed object with the image's path per line) or `text` (the
+                       recognized text in reading order, a line per line) [default: json]
+  -t, --text           same as `--format text`
+  -q, --quiet          print nothing but the results; failures only show in the exit status
+      --json-only      same as `--quiet`
+  -j, --jobs <N>       OCR N images at a time, on as many engines, showing progress on stderr
+                       when stdout is not the terminal [default: 1]
+  -r, --recursive      include images in the subdirectories of directory IMAGEs
+      --per-file       write each image's result next to it, as IMAGE.json, IMAGE.jsonl or
+                       IMAGE.txt, instead of to stdout
+      --rpc            serve newline-delimited JSON-RPC 2.0 on stdin/stdout
+  -n, --iterations <N> bench: how many times to OCR each image [default: 1]
+  -p, --pool <K>       bench: how many engines to run in parallel [default: 1]
+  -o, --output <OUT>   annotate: where to write the image, as `.svg`, `.html` or, with the `image`
+                       feature, `.png`
+  -h, --help           print this help
+";
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+enum Format {
+    #[default]
+    Json,
+    Jsonl,
+    Text,
+}
+
+#[derive(Debug, Default, PartialEq)]
+struct Args {
+    exe: Option<PathBuf>,
+    config: Option<PathBuf>,
+    format: Format,
+    quiet: bool,
+    recursive: bool,
+    per_file: bool,
+    rpc: bool,
+    bench: bool,
+    annotate: bool,
+    cleanup: bool,
+    output: Option<PathBuf>,
+    iterations: Option<usize>,
+    pool: Option<usize>,
+    jobs: Option<usize>,
+    help: bool,
+    images: Vec<String>,
+}
+
+fn parse_args<I: IntoIterator<Item = String>>(args: I) -> Result<Args, String> {
+    let mut parsed = Args::default();
+    let mut args = args.into_iter().peekable();
+    match args.peek().map(String::as_str) {
+        Some("bench") => parsed.bench = true,
+        Some("annotate") => parsed.annotate = true,
+        Some("cleanup") => parsed.cleanup = true,
+        _ => {}
+    }
+    if parsed.bench || parsed.annotate || parsed.cleanup {
+        args.next();
+    }
+    while let Some(arg) = args.next() {
+        let (flag, inline_value) = match arg.split_once('=') {
+            Some((flag, value)) if flag.starts_with("--") => {
+                (flag.to_string(), Some(value.to_string()))
+            }
+            _ => (arg.clone(), None),
+        };
+        let mut value = |name: &str| {
+            inline_value
+                .clone()
+                .or_else(|| args.next())
+                .ok_or_else(|| format!("{} needs a value", name))
+        };
+        match flag.as_str() {
+            "-e" | "--exe" => parsed.exe = Some(value("--exe")?.into()),
+            "-c" | "--config" => parsed.config = Some(value("--config")?.into()),
+            "-f" | "--format" => {
+                parsed.format = match value("--format")?.as_str() {
+                    "json" => Format::Json,
+                    "jsonl" => Format::Jsonl,
+                    "text" => Format::Text,
+                    other => return Err(format!("unknown format {}", other)),
+                }
+            }
+            "-t" | "--text" => parsed.format = Format::Text,
+            "-q" | "--quiet" | "--json-only" => parsed.quiet = true,
+            "-r" | "--recursive" => parsed.recursive = true,
+            "--per-file" => parsed.per_file = true,
+            "-n" | "--iterations" => parsed.iterations = Some(count(&value("--iterations")?)?),
+            "-p" | "--pool" => parsed.pool = Some(count(&value("--pool")?)?),
+            "-j" | "--jobs" => parsed.jobs = Some(count(&value("--jobs")?)?),
+            "-o" | "--output" => parsed.output = Some(value("--output")?.into()),
+            "--rpc" => parsed.rpc = true,
+            "-h" | "--help" => parsed.help = true,
+            "--" => parsed.images.extend(args.by_ref()),
+            _ if flag.starts_with('-') && flag != "-" => {
+                return Err(format!("unknown option {}", arg))
+            }
+            _ => parsed.images.push(arg),
+        }
+    }
+    if parsed.annotate && !parsed.help && (parsed.images.len() != 1 || parsed.output.is_none()) {
+        return Err("annotate needs one image and --output".to_string());
+    }
+    if parsed.cleanup && !parsed.images.is_empty() {
+        return Err("cleanup takes no images".to_string());
+    }
+    Ok(parsed)
+}
+
+/// A positive number.
+fn count(value: &str) -> Result<usize, String> {
+    match value.parse() {
+        Ok(0) | Err(_) => Err(format!("{} is not a positive number", value)),
+        Ok(n) => Ok(n),
+    }
+}
+
+fn image_data(image: &str) -> ImageData {
+    if image == "clipboard" {
+        ImageData::from_path("clipboard")
+    } else {
+        PathBuf::from(image).as_path().into()
+    }
+}
+
+/// Expands directories and glob patterns among `inputs` into the images they hold, keeping other inputs as is.
+fn expand(inputs: &[String], recursive: bool) -> Result<Vec<String>, String> {
+    let mut images = Vec::new();
+    for input in inputs {
+        let paths = if input.contains(['*', '?']) {
+            paddleocr::batch::glob(input)
+        } else if Path::new(input).is_dir() {
+            paddleocr::batch::find_images(input, recursive)
+        } else {
+            images.push(input.clone());
+            continue;
+        };
+        match paths {
+            Ok(paths) if paths.is_empty() => return Err(format!("no images in {}", input)),
+            Ok(paths) => {
+                images.extend(paths.iter().map(|path| path.to_string_lossy().into_owned()))
+            }
+            Err(e) => return Err(format!("{}: {}", input, e)),
+        }
+    }
+    Ok(images)
+}
+
+/// The recognized text in reading order, a line per line of the image.
+fn text(response: &OcrResponse) -> String {
+    paddleocr::layout::lines(&response.data)
+        .iter()
+        .map(|line| line.text())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Prints an error, unless `--quiet` asked for nothing but results.
+fn report(quiet: bool, message: impl std::fmt::Display) {
+    if !quiet {
+        eprintln!("error: {}", message);
+    }
+}
+
+fn main() -> ExitCode {
+    let args = match parse_args(std::env::args().skip(1)) {
+        Ok(args) => args,
+        Err(e) => {
+            eprintln!("error: {}\n\n{}", e, USAGE);
+            return Status::Config.into();
+        }
+    };
+    let quiet = args.quiet;
+    if args.cleanup && !args.help {
+        return match paddleocr::cleanup_orphans() {
+            Ok(pids) => {
+                pids.iter().for_each(|pid| println!("{}", pid));
+                ExitCode::SUCCESS
+            }
+            Err(e) => {
+                report(quiet, format_args!("cleanup failed: {}", e));
+                Status::Engine.into()
+            }
+        };
+    }
+    if args.help || (!args.rpc && args.images.is_empty()) {
+        print!("{}", USAGE);
+        return ExitCode::SUCCESS;
+    }
+    let images = match expand(&args.images, args.recursive) {
+        Ok(images) => images,
+        Err(e) => {
+            report(quiet, e);
+            return Status::Input.into();
+        }
+    };
+    let Some(exe) = args
+        .exe
+        .or_else(|| std::env::var_os("PADDLEOCR_EXE").map(PathBuf::from))
+    else {
+        report(quiet, "no engine given, use --exe or set PADDLEOCR_EXE");
+        return Status::Config.into();
+    };
+
+    if args.bench {
+        let mut engines = Vec::new();
+        for _ in 0..args.pool.unwrap_or(1) {
+            match Ppocr::new(&exe, args.config.clone()) {
+                Ok(p) => engines.push(p),
+                Err(e) => {
+                    report(quiet, format_args!("failed to start the engine: {}", e));
+                    return Status::Config.into();
+                }
+            }
+        }
+        let iterations = args.iterations.unwrap_or(1);
+        print!("{}", bench::run(engines, &images, iterations, image_data));
+        return ExitCode::SUCCESS;
+    }
+
+    let mut p = match Ppocr::new(&exe, args.config.clone()) {
+        Ok(p) => p,
+        Err(e) => {
+            report(quiet, format_args!("failed to start the engine: {}", e));
+            return Status::Config.into();
+        }
+    };
+
+    if args.annotate {
+        let output = args.output.unwrap_or_default();
+        return match annotate::run(&mut p, &images[0], &output) {
+            Ok(()) => ExitCode::SUCCESS,
+            Err(e) => {
+                report(quiet, e);
+                Status::Input.into()
+            }
+        };
+    }
+
+    if args.rpc {
+        return match rpc::serve(&mut p, std::io::stdin().lock(), std::io::stdout().lock()) {
+            Ok(()) => ExitCode::SUCCESS,
+            Err(e) => {
+                report(quiet, e);
+                Status::Engine.into()
+            }
+        };
+    }
+
+    let mut output = Output {
+        format: args.format,
+        per_file: args.per_file,
+        quiet,
+        stdout: std::io::stdout().lock(),
+    };
+    // Results printed to the terminal would be mixed up with the bar.
+    let hidden = quiet || (!args.per_file && std::io::stdout().is_terminal());
+    let mut progress = jobs::Progress::new(images.len(), hidden);
+    let mut overall = Status::NoText;
+    let mut each = |image: &str, response| {
+        let written = output.write(image, response);
+        progress.tick();
+        overall = overall.merge(written?);
+        Ok(())
+    };
+    let jobs = args.jobs.unwrap_or(1);
+    let done: Result<(), Status> = if jobs == 1 {
+        images
+            .iter()
+            .try_for_each(|image| each(image, p.ocr(image_data(image))))
+    } else {
+        let mut engines = vec![p];
+        for _ in 1..jobs {
+            match Ppocr::new(&exe, args.config.clone()) {
+                Ok(p) => engines.push(p),
+                Err(e) => {
+                    report(quiet, format_args!("failed to start the engine: {}", e));
+                    return Status::Config.into();
+                }
+            }
+        }
+        let pool = PpocrPool::new(engines);
+        jobs::run(&pool, &images, jobs, image_data, each)
+    };
+    match done {
+        Ok(()) => overall.into(),
+        Err(status) => status.into(),
+    }
+}
+
+/// Where and how results are written.
+struct Output<W> {
+    format: Format,
+    per_file: bool,
+    quiet: bool,
+    stdout: W,
+}
+
+impl<W: Write> Output<W> {
+    /**
+     * Writes the response to `image` and returns its status, or reports why there is none and returns the status to
+     * stop with. Engine errors in the `json` format are written as is rather than stopped at.
+     */
+    fn write(&mut self, image: &str, response: std::io::Result<String>) -> Result<Status, Status> {
+        let quiet = self.quiet;
+        let fail = |status, message: std::fmt::Arguments| {
+            report(quiet, message);
+            status
+        };
+        let response =
+            response.map_err(|e| fail(Status::Engine, format_args!("{}: {}", image, e)))?;
+        let parsed = OcrResponse::parse(&response).map_err(|e| {
+            fail(
+                Status::Engine,
+                format_args!("{}: malformed response: {}", image, e),
+            )
+        })?;
+        let status = Status::from_code(parsed.code);
+        let mut file;
+        let out: &mut dyn Write = if self.per_file {
+            let extension = match self.format {
+                Format::Json => "json",
+                Format::Jsonl => "jsonl",
+                Format::Text => "txt",
+            };
+            let path = format!("{}.{}", image, extension);
+            file = std::fs::File::create(&path)
+                .map_err(|e| fail(Status::Input, format_args!("{}: {}", path, e)))?;
+            &mut file
+        } else {
+            &mut self.stdout
+        };
+        let written = match self.format {
+            Format::Json => writeln!(out, "{}", response.trim_end()),
+            Format::Jsonl => paddleocr::export::to_jsonl(&mut *out, [(image, parsed)]),
+            Format::Text => match status {
+                Status::Text | Status::NoText => match text(&parsed) {
+                    text if text.is_empty() => Ok(()),
+                    text => writeln!(out, "{}", text),
+                },
+                _ => return Err(fail(status, format_args!("{}: {}", image, parsed))),
+            },
+        };
+        written.map_err(|e| fail(Status::Input, format_args!("{}", e)))?;
+        Ok(status)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use paddleocr::OcrResponse;
+
+    use super::{expand, parse_args, text, Args, Format};
+
+    fn parse(args: &[&str]) -> Result<Args, String> {
+        parse_args(args.iter().map(|a| a.to_string()))
+    }
+
+    #[test]
+    fn args() {
+        assert_eq!(
+            parse(&["-e", "ocr.exe", "--config=en.txt", "a.png", "--", "--b.png"]).unwrap(),
+            Args {
+                exe: Some("ocr.exe".into()),
+                config: Some("en.txt".into()),
+                images: vec!["a.png".into(), "--b.png".into()],
+                ..Default::default()
+            }
+        );
+        assert!(parse(&["--rpc"]).unwrap().rpc);
+        assert_eq!(parse(&["-f", "jsonl"]).unwrap().format, Format::Jsonl);
+        assert!(parse(&["--format=xml"]).is_err());
+        let quiet = parse(&["--text", "--json-only", "a.png"]).unwrap();
+        assert_eq!((quiet.format, quiet.quiet), (Format::Text, true));
+        let bulk = parse(&["-r", "--per-file", "scans"]).unwrap();
+        assert!(bulk.recursive && bulk.per_file);
+        assert_eq!(parse(&["--jobs=4", "a.png"]).unwrap().jobs, Some(4));
+        assert!(parse(&["-j", "0"]).is_err());
+        assert!(parse(&["--exe"]).is_err());
+        assert!(parse(&["--frobnicate"]).is_err());
+
+        let bench = parse(&["bench", "-n", "3", "--pool=2", "bench"]).unwrap();
+        assert!(bench.bench);
+        assert_eq!((bench.iterations, bench.pool), (Some(3), Some(2)));
+        assert_eq!(bench.images, ["bench"]);
+        assert!(parse(&["bench", "--pool", "0"]).is_err());
+
+        let annotate = parse(&["annotate", "a.png", "-o", "a.svg"]).unwrap();
+        assert!(annotate.annotate);
+        assert_eq!(annotate.output, Some("a.svg".into()));
+        assert!(parse(&["annotate", "a.png"]).is_err());
+        assert!(parse(&["annotate", "a.png", "b.png", "--output=a.svg"]).is_err());
+
+        assert!(parse(&["cleanup", "-q"]).unwrap().cleanup);
+        assert!(parse(&["cleanup", "a.png"]).is_err());
+    }
+
+    #[test]
+    fn plain_text() {
+        let response = OcrResponse::parse(concat!(
+            r#"{"code":100,"data":["#,
+            r#"{"box":[[60,0],[90,0],[90,10],[60,10]],"score":1,"text":"world"},"#,
+            r#"{"box":[[0,20],[50,20],[50,30],[0,30]],"score":1,"text":"second"},"#,
+            r#"{"box":[[0,1],[50,1],[50,11],[0,11]],"score":1,"text":"hello"}]}"#
+        ))
+        .unwrap();
+        assert_eq!(text(&response), "hello world\nsecond");
+    }
+
+    #[test]
+    fn expand_inputs() {
+        let inputs = ["clipboard".to_string(), "missing.png".to_string()];
+        assert_eq!(expand(&inputs, false).unwrap(), inputs);
+        assert!(expand(&["nowhere/*.png".to_string()], false).is_err());
+    }
+}
@@ -0,0 +1,88 @@
+//! `--rpc` mode: newline-delimited JSON-RPC 2.0 on stdin/stdout, mirroring how this crate talks to the engine.
+//!
+//! Methods:
+//!
+//! - `ocr`, with params `{"path": ...}` or `{"base64": ...}`: returns the engine's response object.
+//! - `ocr_clipboard`: returns the engine's response object for the clipboard image.
+//!
+//! Engine responses are returned as results whatever their `code`; only failures to talk to the engine or to parse
+//! its output are JSON-RPC errors.
+
+use std::io::{BufRead, Result as IoResult, Write};
+
+use paddleocr::jsonrpc::{self, RpcError, INTERNAL_ERROR, INVALID_PARAMS};
+use paddleocr::{ImageData, OcrEngine};
+use serde_json::Value;
+
+pub fn serve<E, R, W>(engine: &mut E, input: R, output: W) -> IoResult<()>
+where
+    E: OcrEngine,
+    R: BufRead,
+    W: Write,
+{
+    jsonrpc::serve(input, output, |method, params| {
+        handle(engine, method, params)
+    })
+}
+
+fn handle<E: OcrEngine>(engine: &mut E, method: &str, params: &Value) -> Result<Value, RpcError> {
+    let image = match method {
+        "ocr" => jsonrpc::image_param(params)
+            .ok_or_else(|| RpcError::new(INVALID_PARAMS, "ocr needs either `path` or `base64`"))?,
+        "ocr_clipboard" => ImageData::from_path("clipboard"),
+        _ => return Err(RpcError::method_not_found(method)),
+    };
+    let response = engine
+        .ocr(image)
+        .map_err(|e| RpcError::new(INTERNAL_ERROR, format!("OCR failed: {}", e)))?;
+    serde_json::from_str(&response)
+        .map_err(|e| RpcError::new(INTERNAL_ERROR, format!("Response JSON parse failed: {}", e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use paddleocr::{ImageData, OcrEngine};
+    use serde_json::Value;
+
+    struct Echo;
+    impl OcrEngine for Echo {
+        fn ocr(&mut self, image: ImageData) -> std::io::Result<String> {
+            let ImageData::ImagePathDict { image_path } = image else {
+                return Ok("not json".to_string());
+            };
+            Ok(format!(r#"{{"code":101,"data":"{}"}}"#, image_path))
+        }
+    }
+
+    #[test]
+    fn requests() {
+        let input = concat!(
+            r#"{"jsonrpc":"2.0","id":1,"method":"ocr","params":{"path":"a.png"}}"#,
+            "\n",
+            r#"{"jsonrpc":"2.0","id":2,"method":"ocr_clipboard"}"#,
+            "\n",
+            r#"{"jsonrpc":"2.0","id":3,"method":"ocr","params":{"base64":"AAAA"}}"#,
+            "\n",
+            r#"{"jsonrpc":"2.0","method":"ocr","params":{"path":"ignored.png"}}"#,
+            "\n",
+            r#"{"jsonrpc":"2.0","id":4,"method":"ocr"}"#,
+            "\n",
+            "{",
+            "\n",
+        );
+        let mut output = Vec::new();
+        super::serve(&mut Echo, input.as_bytes(), &mut output).unwrap();
+        let responses: Vec<Value> = String::from_utf8(output)
+            .unwrap()
+            .lines()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect();
+
+        assert_eq!(responses.len(), 5);
+        assert_eq!(responses[0]["result"]["data"], "a.png");
+        assert_eq!(responses[1]["result"]["data"], "clipboard");
+        assert_eq!(responses[2]["error"]["code"], -32603);
+        assert_eq!(responses[3]["error"]["code"], -32602);
+        assert_eq!(responses[4]["error"]["code"], -32700);
+    }
+}
@@ -0,0 +1,69 @@
+//! Exit statuses, so that scripts can branch on the outcome without parsing the output.
+
+use std::process::ExitCode;
+
+/// How a run went, from best to worst. The discriminant is the process exit status.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Status {
+    /// Text was found.
+    Text = 0,
+    /// The engine found no text.
+    NoText = 1,
+    /// An image is missing, unreadable or not an image, or the clipboard holds none.
+    Input = 2,
+    /// The engine failed, crashed or answered with something other than a response.
+    Engine = 3,
+    /// The command line is wrong, or the engine couldn't be started with it.
+    Config = 4,
+}
+
+impl Status {
+    /// The status of a response with the engine's `code`.
+    pub fn from_code(code: u32) -> Status {
+        match code {
+            100 => Status::Text,
+            101 => Status::NoText,
+            // Image paths, files and clipboard contents (200 to 217), and base64 images (300 and 301).
+            200..=217 | 300 | 301 => Status::Input,
+            _ => Status::Engine,
+        }
+    }
+
+    /**
+     * The status of two runs together: the worse of two failures, or else [`Status::Text`] if either found text.
+     */
+    pub fn merge(self, other: Status) -> Status {
+        if self.max(other) >= Status::Input {
+            self.max(other)
+        } else {
+            self.min(other)
+        }
+    }
+}
+
+impl From<Status> for ExitCode {
+    fn from(status: Status) -> ExitCode {
+        ExitCode::from(status as u8)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Status;
+
+    #[test]
+    fn statuses() {
+        assert_eq!(Status::from_code(100), Status::Text);
+        assert_eq!(Status::from_code(101), Status::NoText);
+        assert_eq!(Status::from_code(203), Status::Input);
+        assert_eq!(Status::from_code(212), Status::Input);
+        assert_eq!(Status::from_code(301), Status::Input);
+        assert_eq!(Status::from_code(299), Status::Engine);
+        assert_eq!(Status::from_code(400), Status::Engine);
+
+        assert_eq!(Status::NoText.merge(Status::Text), Status::Text);
+        assert_eq!(Status::NoText.merge(Status::NoText), Status::NoText);
+        assert_eq!(Status::Text.merge(Status::Input), Status::Input);
+        assert_eq!(Status::Engine.merge(Status::Input), Status::Engine);
+    }
+}
@@ -0,0 +1,624 @@
+//! Starting the engine with options beyond a config file.
+
+use std::error::Error;
+use std::path::{Path, PathBuf};
+use std::process;
+
+use crate::{OsNotSupportedError, Ppocr, PpocrClient};
+
+/**
+Options for spawning an engine, created with [`Ppocr::builder`].
+
+# Examples
+
+```no_run
+let mut p = paddleocr::Ppocr::builder(".../PaddleOCR-json.exe")
+    .config_path(".../models/config_en.txt")
+    .ensure_ascii(true)
+    .spawn()
+    .unwrap();
+```
+*/
+#[derive(Debug, Clone)]
+pub struct PpocrBuilder {
+    exe_path: PathBuf,
+    config_path: Option<PathBuf>,
+    models_path: Option<PathBuf>,
+    det_model_dir: Option<PathBuf>,
+    rec_model_dir: Option<PathBuf>,
+    cls_model_dir: Option<PathBuf>,
+    rec_char_dict_path: Option<PathBuf>,
+    tier: Option<ModelTier>,
+    ensure_ascii: Option<bool>,
+    drop_score: Option<f64>,
+    current_dir: Option<PathBuf>,
+    dll_dirs: Vec<PathBuf>,
+}
+
+/// The settings of a [`PpocrBuilder`] that engines shared with [`spawn_shared`](PpocrBuilder::spawn_shared) must
+/// agree on, with paths made absolute where they exist, so that two spellings of one file match.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct SharedKey {
+    paths: Vec<Option<PathBuf>>,
+    tier: Option<ModelTier>,
+    ensure_ascii: Option<bool>,
+    /// The bits of the score, as floats aren't `Eq`.
+    drop_score: Option<u64>,
+    dll_dirs: Vec<PathBuf>,
+}
+
+/**
+ * A preset trading recognition quality against speed, for [`PpocrBuilder::model_tier`].
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ModelTier {
+    /// The small models the engine ships with: fast on a CPU, and accurate enough for screenshots and clean scans.
+    Mobile,
+    /// The larger `*_server_*` models: noticeably more accurate on photos and dense or small text, at several times
+    /// the latency and memory. They are downloaded separately into the models directory.
+    Server,
+}
+
+impl PpocrBuilder {
+    pub(crate) fn new(exe_path: PathBuf) -> PpocrBuilder {
+        PpocrBuilder {
+            exe_path,
+            config_path: None,
+            models_path: None,
+            det_model_dir: None,
+            rec_model_dir: None,
+            cls_model_dir: None,
+            rec_char_dict_path: None,
+            tier: None,
+            ensure_ascii: None,
+            drop_score: None,
+            current_dir: None,
+            dll_dirs: Vec::new(),
+        }
+    }
+
+    /**
+     * Sets the language config file, `zh_CN` by default.
+     */
+    pub fn config_path<P: AsRef<Path>>(mut self, config_path: P) -> PpocrBuilder {
+        self.config_path = Some(config_path.as_ref().to_path_buf());
+        self
+    }
+
+    /**
+     * Sets the directory the engine loads its models and language configs from, `models` beside the executable by
+     * default, so one executable can be shared by several model sets. Checked for models when spawning.
+     */
+    pub fn models_path<P: AsRef<Path>>(mut self, models_path: P) -> PpocrBuilder {
+        self.models_path = Some(models_path.as_ref().to_path_buf());
+        self
+    }
+
+    /**
+     * Replaces the text detection model of the config with the one in `dir`, e.g. a fine-tuned one.
+     */
+    pub fn det_model_dir<P: AsRef<Path>>(mut self, dir: P) -> PpocrBuilder {
+        self.det_model_dir = Some(dir.as_ref().to_path_buf());
+        self
+    }
+
+    /**
+     * Replaces the text recognition model of the config with the one in `dir`. A model trained on another character
+     * set also needs its [`rec_char_dict_path`](PpocrBuilder::rec_char_dict_path).
+     */
+    pub fn rec_model_dir<P: AsRef<Path>>(mut self, dir: P) -> PpocrBuilder {
+        self.rec_model_dir = Some(dir.as_ref().to_path_buf());
+        self
+    }
+
+    /**
+     * Replaces the angle classification model of the config with the one in `dir`.
+     */
+    pub fn cls_model_dir<P: AsRef<Path>>(mut self, dir: P) -> PpocrBuilder {
+        self.cls_model_dir = Some(dir.as_ref().to_path_buf());
+        self
+    }
+
+    /**
+     * Replaces the character dictionary of the recognition model, a text file with one character per line.
+     */
+    pub fn rec_char_dict_path<P: AsRef<Path>>(mut self, path: P) -> PpocrBuilder {
+        self.rec_char_dict_path = Some(path.as_ref().to_path_buf());
+        self
+    }
+
+    /**
+    Picks the detection and recognition models of `tier` from the models directory, unless set explicitly with
+    [`det_model_dir`](PpocrBuilder::det_model_dir) or [`rec_model_dir`](PpocrBuilder::rec_model_dir).
+
+    Spawning fails if no detection model of the tier is installed. Server recognition models only exist for some
+    languages, and are only picked if named for the language of the config's recognition model or dictionary, e.g.
+    `en_` for `dict_en.txt`; when there is none, the recognition model of the config is kept.
+
+    # Examples
+
+    ```no_run
+    use paddleocr::ModelTier;
+
+    let mut p = paddleocr::Ppocr::builder(".../PaddleOCR-json.exe")
+        .model_tier(ModelTier::Server)
+        .spawn()
+        .unwrap();
+    ```
+    */
+    pub fn model_tier(mut self, tier: ModelTier) -> PpocrBuilder {
+        self.tier = Some(tier);
+        self
+    }
+
+    /**
+     * Whether the engine escapes non-ASCII characters in its responses as `\uXXXX`, for tooling that only handles
+     * ASCII. Parsed results are the same either way; only the raw responses differ.
+     */
+    pub fn ensure_ascii(mut self, ensure_ascii: bool) -> PpocrBuilder {
+        self.ensure_ascii = Some(ensure_ascii);
+        self
+    }
+
+    /**
+     * Has the engine drop lines recognized with a confidence below `drop_score` (`0.5` unless set), for all requests.
+     * See [`OcrOptions::drop_score`](crate::OcrOptions::drop_score) to set it per request.
+     */
+    pub fn drop_score(mut self, drop_score: f64) -> PpocrBuilder {
+        self.drop_score = Some(drop_score);
+        self
+    }
+
+    /**
+     * Sets the engine's working directory, which relative model and config paths are resolved against. By default
+     * it is the directory of the executable.
+     */
+    pub fn current_dir<P: AsRef<Path>>(mut self, dir: P) -> PpocrBuilder {
+        self.current_dir = Some(dir.as_ref().to_path_buf());
+        self
+    }
+
+    /**
+     * Adds a directory to search for DLLs, such as a shared Paddle inference library or the Visual C++ runtime,
+     * ahead of the `PATH` Windows otherwise searches. Can be called several times; earlier directories come first.
+     */
+    pub fn dll_dir<P: AsRef<Path>>(mut self, dir: P) -> PpocrBuilder {
+        self.dll_dirs.push(dir.as_ref().to_path_buf());
+        self
+    }
+
+    /**
+    Like [`spawn`](PpocrBuilder::spawn), but shares the engine with the other callers in this process that ask for
+    the same configuration, rather than starting one each: independent components of a program can each ask for an
+    engine without multiplying processes. The engine is stopped when the last handle to it is dropped.
+
+    # Examples
+
+    ```no_run
+    let builder = paddleocr::Ppocr::builder(".../PaddleOCR-json.exe").config_path(".../models/config_en.txt");
+    let a = builder.clone().spawn_shared().unwrap();
+    // The same engine.
+    let b = builder.spawn_shared().unwrap();
+    ```
+    */
+    pub fn spawn_shared(self) -> Result<PpocrClient, Box<dyn Error>> {
+        PpocrClient::shared(self.shared_key(), || self.spawn())
+    }
+
+    pub(crate) fn shared_key(&self) -> SharedKey {
+        let normalized = |path: &Path| path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+        let optional = |path: &Option<PathBuf>| path.as_deref().map(normalized);
+        SharedKey {
+            paths: vec![
+                Some(normalized(&self.exe_path)),
+                optional(&self.config_path),
+                optional(&self.models_path),
+                optional(&self.det_model_dir),
+                optional(&self.rec_model_dir),
+                optional(&self.cls_model_dir),
+                optional(&self.rec_char_dict_path),
+                optional(&self.current_dir),
+            ],
+            tier: self.tier,
+            ensure_ascii: self.ensure_ascii,
+            drop_score: self.drop_score.map(f64::to_bits),
+            dll_dirs: self.dll_dirs.iter().map(|dir| normalized(dir)).collect(),
+        }
+    }
+
+    /**
+     * Starts the engine and waits for it to finish initializing.
+     */
+    pub fn spawn(mut self) -> Result<Ppocr, Box<dyn Error>> {
+        std::env::set_var("RUST_BACKTRACE", "full");
+        if !cfg!(target_os = "windows") {
+            return Err(Box::new(OsNotSupportedError {}));
+        }
+        if !self.exe_path.exists() {
+            return Err(Box::new(std::io::Error::new(
+                std::io::ErrorKind::NotFound,
+                "Executable not found",
+            )));
+        }
+
+        let wd = match &self.current_dir {
+            Some(dir) => dir.clone(),
+            None => self
+                .exe_path
+                .canonicalize()?
+                .parent()
+                .ok_or("No parent directory found")?
+                .to_path_buf(),
+        };
+
+        if let Some(models_path) = &self.models_path {
+            check_models(models_path)?;
+        }
+        if let Some(tier) = self.tier {
+            let models = self
+                .models_path
+                .clone()
+                .unwrap_or_else(|| wd.join("models"));
+            let (det, rec) = tier_models(&models, tier, &self.rec_languages(&wd))?;
+            self.det_model_dir = self.det_model_dir.or(det);
+            self.rec_model_dir = self.rec_model_dir.or(rec);
+        }
+        for dir in [
+            &self.det_model_dir,
+            &self.rec_model_dir,
+            &self.cls_model_dir,
+        ]
+        .into_iter()
+        .flatten()
+        {
+            if !is_model(dir) {
+                return Err(Box::new(std::io::Error::new(
+                    std::io::ErrorKind::NotFound,
+                    format!("{} holds no model", dir.display()),
+                )));
+            }
+        }
+        if let Some(path) = self
+            .rec_char_dict_path
+            .as_ref()
+            .filter(|path| !path.is_file())
+        {
+            return Err(Box::new(std::io::Error::new(
+                std::io::ErrorKind::NotFound,
+                format!("{} not found", path.display()),
+            )));
+        }
+
+        let mut p = Ppocr::from_command(self.command(&wd)?)?;
+        p.exe_path = Some(self.exe_path);
+        Ok(p)
+    }
+
+    /// The languages, like `en`, in the names of the recognition model and dictionary the engine would use in `wd`:
+    /// those of the config file and the dictionary set here, or `ch` for the default config.
+    fn rec_languages(&self, wd: &Path) -> Vec<String> {
+        let mut paths: Vec<PathBuf> = self.rec_char_dict_path.iter().cloned().collect();
+        match &self.config_path {
+            Some(config) => {
+                let config = std::fs::read_to_string(wd.join(config)).unwrap_or_default();
+                for line in config.lines() {
+                    let mut words = line.split_whitespace();
+                    if let (Some("rec_model_dir" | "rec_char_dict_path"), Some(path)) =
+                        (words.next(), words.next())
+                    {
+                        paths.push(PathBuf::from(path));
+                    }
+                }
+            }
+            None => return vec!["ch".to_string()],
+        }
+        paths
+            .iter()
+            .filter_map(|path| path.file_stem())
+            .flat_map(|stem| {
+                let stem = stem.to_string_lossy();
+                stem.split('_').map(str::to_string).collect::<Vec<_>>()
+            })
+            .collect()
+    }
+
+    /// The command starting the engine in `wd`, with the DLL directories on its `PATH`.
+    fn command(&self, wd: &Path) -> Result<process::Command, Box<dyn Error>> {
+        let mut command = process::Command::new(&self.exe_path);
+        command.current_dir(wd).args(self.args());
+        if !self.dll_dirs.is_empty() {
+            let path = std::env::var_os("PATH").unwrap_or_default();
+            let dirs = self.dll_dirs.iter().cloned();
+            command.env(
+                "PATH",
+                std::env::join_paths(dirs.chain(std::env::split_paths(&path)))?,
+            );
+        }
+        Ok(command)
+    }
+
+    /// The engine's command line arguments.
+    fn args(&self) -> Vec<String> {
+        let mut args = Vec::new();
+        if let Some(config_path) = &self.config_path {
+            args.extend([
+                "--config_path".to_string(),
+                config_path.to_string_lossy().into_owned(),
+            ]);
+        }
+        let paths = [
+            ("--models_path", &self.models_path),
+            ("--det_model_dir", &self.det_model_dir),
+            ("--rec_model_dir", &self.rec_model_dir),
+            ("--cls_model_dir", &self.cls_model_dir),
+            ("--rec_char_dict_path", &self.rec_char_dict_path),
+        ];
+        for (flag, path) in paths {
+            if let Some(path) = path {
+                args.extend([flag.to_string(), path.to_string_lossy().into_owned()]);
+            }
+        }
+        if let Some(ensure_ascii) = self.ensure_ascii {
+            args.push(format!("--ensure_ascii={}", ensure_ascii));
+        }
+        if let Some(drop_score) = self.drop_score {
+            args.push(format!("--drop_score={}", drop_score));
+        }
+        args
+    }
+}
+
+/**
+ * Fails unless `dir` holds a language config (`config_*.txt`) and at least one model, a directory with
+ * `inference.pdmodel` and `inference.pdiparams`; the engine would otherwise only fail after a slow startup.
+ */
+fn check_models(dir: &Path) -> std::io::Result<()> {
+    let (mut configs, mut models) = (0, 0);
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        let name = path.file_name().unwrap_or_default().to_string_lossy();
+        if path.is_dir() {
+            if is_model(&path) {
+                models += 1;
+            }
+        } else if name.starts_with("config_") && name.ends_with(".txt") {
+            configs += 1;
+        }
+    }
+    if configs == 0 || models == 0 {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::NotFound,
+            format!(
+                "{} holds no {}",
+                dir.display(),
+                if configs == 0 {
+                    "config_*.txt"
+                } else {
+                    "model"
+                }
+            ),
+        ));
+    }
+    Ok(())
+}
+
+/**
+ * The detection and recognition models of `tier` in `models`, by the `_server_` in the names of server models. Mobile
+ * models are the configs' defaults, so none are returned for them.
+ *
+ * Recognition models only read the characters of their language, so only those whose language prefix, like `en` in
+ * `en_PP-OCRv4_rec_server_infer`, is one of `languages` are considered.
+ *
+ * With several server models installed, the one of the newest PP-OCR version is picked, e.g. `PP-OCRv4` over
+ * `PP-OCRv3`, then the first by name, so the choice doesn't depend on the order the directory is listed in.
+ */
+fn tier_models(
+    models: &Path,
+    tier: ModelTier,
+    languages: &[String],
+) -> std::io::Result<(Option<PathBuf>, Option<PathBuf>)> {
+    if tier == ModelTier::Mobile {
+        return Ok((None, None));
+    }
+    let (mut det, mut rec) = (Vec::new(), Vec::new());
+    for entry in std::fs::read_dir(models)? {
+        let path = entry?.path();
+        let name = path
+            .file_name()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .into_owned();
+        if !name.contains("_server_") || !is_model(&path) {
+            continue;
+        }
+        if name.contains("_det_") {
+            det.push((name, path));
+        } else if name.contains("_rec_") {
+            let language = name.split('_').next().unwrap_or_default();
+            if languages.iter().any(|l| l == language) {
+                rec.push((name, path));
+            }
+        }
+    }
+    let newest = |mut candidates: Vec<(String, PathBuf)>| {
+        candidates.sort_by(|(a, _), (b, _)| model_version(b).cmp(&model_version(a)).then(a.cmp(b)));
+        candidates.into_iter().next().map(|(_, path)| path)
+    };
+    match newest(det) {
+        Some(det) => Ok((Some(det), newest(rec))),
+        None => Err(std::io::Error::new(
+            std::io::ErrorKind::NotFound,
+            format!("no server detection model in {}", models.display()),
+        )),
+    }
+}
+
+/// The version in a model's name, such as 4 in `ch_PP-OCRv4_det_server_infer`, or 0 if it has none.
+fn model_version(name: &str) -> u32 {
+    name.split_once("OCRv")
+        .map(|(_, rest)| rest)
+        .and_then(|rest| {
+            let digits = rest
+                .find(|c: char| !c.is_ascii_digit())
+                .unwrap_or(rest.len());
+            rest[..digits].parse().ok()
+        })
+        .unwrap_or(0)
+}
+
+/// Whether `dir` holds an exported inference model.
+fn is_model(dir: &Path) -> bool {
+    dir.join("inference.pdmodel").is_file() && dir.join("inference.pdiparams").is_file()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{check_models, model_version, tier_models, ModelTier};
+    use crate::Ppocr;
+
+    #[test]
+    fn args() {
+        assert!(Ppocr::builder("PaddleOCR-json.exe").args().is_empty());
+        let builder = Ppocr::builder("PaddleOCR-json.exe")
+            .config_path("models/config_en.txt")
+            .ensure_ascii(true)
+            .drop_score(0.3);
+        assert_eq!(
+            builder.args(),
+            [
+                "--config_path",
+                "models/config_en.txt",
+                "--ensure_ascii=true",
+                "--drop_score=0.3"
+            ]
+        );
+    }
+
+    #[test]
+    fn command() {
+        let builder = Ppocr::builder("engine/PaddleOCR-json.exe")
+            .current_dir("/opt/ocr")
+            .dll_dir("/opt/paddle/lib")
+            .dll_dir("/opt/vcruntime");
+        let command = builder.command(std::path::Path::new("/opt/ocr")).unwrap();
+        assert_eq!(
+            command.get_current_dir(),
+            Some(std::path::Path::new("/opt/ocr"))
+        );
+        let (_, path) = command.get_envs().find(|(key, _)| *key == "PATH").unwrap();
+        let dirs: Vec<_> = std::env::split_paths(path.unwrap()).take(2).collect();
+        assert_eq!(
+            dirs,
+            ["/opt/paddle/lib", "/opt/vcruntime"].map(std::path::PathBuf::from)
+        );
+    }
+
+    #[test]
+    fn ascii_responses() {
+        let response = r#"{"code":100,"data":[{"box":[[1,2],[3,2],[3,4],[1,4]],"score":0.5,"text":"\u98de\u821e"}]}"#;
+        let mut p = Ppocr::from_streams(std::io::Cursor::new(response), std::io::sink());
+        let data = p
+            .ocr_and_parse(crate::ImageData::from_path("a.png"))
+            .unwrap();
+        assert_eq!(data[0].text, "飞舞");
+    }
+
+    #[test]
+    fn models() {
+        let dir = std::env::temp_dir().join(format!("paddleocr-models-{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("ch_PP-OCRv3_rec_infer")).unwrap();
+        assert!(check_models(&dir).is_err());
+        std::fs::write(dir.join("config_chinese.txt"), "").unwrap();
+        assert!(check_models(&dir).is_err());
+        for file in ["inference.pdmodel", "inference.pdiparams"] {
+            std::fs::write(dir.join("ch_PP-OCRv3_rec_infer").join(file), "").unwrap();
+        }
+        let checked = check_models(&dir);
+        std::fs::remove_dir_all(&dir).unwrap();
+        checked.unwrap();
+        assert!(check_models(&dir).is_err());
+
+        let args = Ppocr::builder("PaddleOCR-json")
+            .models_path("/opt/models")
+            .rec_model_dir("/opt/custom_rec")
+            .rec_char_dict_path("/opt/custom_rec/dict.txt")
+            .args();
+        assert_eq!(
+            args,
+            [
+                "--models_path",
+                "/opt/models",
+                "--rec_model_dir",
+                "/opt/custom_rec",
+                "--rec_char_dict_path",
+                "/opt/custom_rec/dict.txt",
+            ]
+        );
+    }
+
+    #[test]
+    fn tiers() {
+        let dir = std::env::temp_dir().join(format!("paddleocr-tiers-{}", std::process::id()));
+        let install = |models: &[&str]| {
+            for model in models {
+                std::fs::create_dir_all(dir.join(model)).unwrap();
+                for file in ["inference.pdmodel", "inference.pdiparams"] {
+                    std::fs::write(dir.join(model).join(file), "").unwrap();
+                }
+            }
+        };
+        install(&[
+            "ch_PP-OCRv4_det_infer",
+            "ch_PP-OCRv4_rec_infer",
+            "ch_PP-OCRv4_det_server_infer",
+        ]);
+        let chinese = ["ch".to_string()];
+        let mobile = tier_models(&dir, ModelTier::Mobile, &chinese);
+        let server = tier_models(&dir, ModelTier::Server, &chinese);
+        // Several versions, listed in whatever order the file system has them in.
+        install(&[
+            "ch_PP-OCRv10_det_server_infer",
+            "ch_PP-OCRv3_det_server_infer",
+            "ch_PP-OCRv3_rec_server_infer",
+            "en_PP-OCRv4_rec_server_infer",
+            "ch_PP-OCRv4_rec_server_infer",
+        ]);
+        let newest = tier_models(&dir, ModelTier::Server, &chinese);
+        // An English config gets the English model, and a Korean one none, rather than a Chinese one.
+        std::fs::write(
+            dir.join("config_en.txt"),
+            "# rec config\nrec_model_dir models/en_PP-OCRv3_rec_infer\nrec_char_dict_path models/dict_en.txt\n",
+        )
+        .unwrap();
+        let english = Ppocr::builder("PaddleOCR-json")
+            .config_path("config_en.txt")
+            .rec_languages(&dir);
+        let english = tier_models(&dir, ModelTier::Server, &english);
+        let korean = Ppocr::builder("PaddleOCR-json")
+            .config_path("config_korean.txt")
+            .rec_char_dict_path("models/dict_korean.txt")
+            .rec_languages(&dir);
+        let korean = tier_models(&dir, ModelTier::Server, &korean);
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(mobile.unwrap(), (None, None));
+        assert_eq!(
+            server.unwrap(),
+            (Some(dir.join("ch_PP-OCRv4_det_server_infer")), None)
+        );
+        assert_eq!(
+            newest.unwrap(),
+            (
+                Some(dir.join("ch_PP-OCRv10_det_server_infer")),
+                Some(dir.join("ch_PP-OCRv4_rec_server_infer"))
+            )
+        );
+        assert_eq!(
+            english.unwrap().1,
+            Some(dir.join("en_PP-OCRv4_rec_server_infer"))
+        );
+        assert_eq!(korean.unwrap().1, None);
+        assert!(tier_models(&dir, ModelTier::Server, &chinese).is_err());
+        assert_eq!(model_version("ch_PP-OCRv4_det_server_infer"), 4);
+        assert_eq!(model_version("custom_det_server"), 0);
+    }
+}
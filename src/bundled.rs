@@ -0,0 +1,209 @@
+//! Shipping the engine inside the executable, for single-file apps.
+//!
+//! The engine's directory is packed into a ZIP archive with stored or deflated entries (e.g. `zip -r engine.zip
+//! PaddleOCR-json`) and embedded with [`include_bytes!`]. On first use it is extracted to a cache directory named
+//! after the archive's checksum, so a new engine gets a new directory, and the directories of older ones are removed.
+//!
+//! ```no_run
+//! use paddleocr::bundled::Bundle;
+//!
+//! static ENGINE: Bundle = Bundle::new(
+//!     "my-app",
+//!     include_bytes!("../engine.zip"),
+//!     "PaddleOCR-json/PaddleOCR-json.exe",
+//! );
+//!
+//! let mut p = ENGINE.spawn().unwrap();
+//! ```
+
+use std::error::Error;
+use std::io::{Error as IoError, ErrorKind, Result as IoResult};
+use std::path::{Component, Path, PathBuf};
+
+use crate::zip;
+use crate::Ppocr;
+
+/// Written last into an extracted directory, so that an interrupted extraction is redone.
+const COMPLETE: &str = ".complete";
+
+/**
+ * An engine archive embedded in the executable.
+ */
+#[derive(Debug, Clone, Copy)]
+pub struct Bundle {
+    app: &'static str,
+    archive: &'static [u8],
+    exe: &'static str,
+}
+
+impl Bundle {
+    /**
+     * `archive` holds the engine, with the executable at `exe` inside it. `app` names the cache directory, so that
+     * apps don't remove each other's engines.
+     */
+    pub const fn new(app: &'static str, archive: &'static [u8], exe: &'static str) -> Bundle {
+        Bundle { app, archive, exe }
+    }
+
+    /**
+     * Where engines of this app are extracted: under the user's cache directory (`%LOCALAPPDATA%`,
+     * `$XDG_CACHE_HOME` or `~/.cache`), or the temporary directory if there is none.
+     */
+    pub fn cache_dir(&self) -> PathBuf {
+        let base = ["LOCALAPPDATA", "XDG_CACHE_HOME"]
+            .iter()
+            .find_map(std::env::var_os)
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(|home| Path::new(&home).join(".cache")))
+            .unwrap_or_else(std::env::temp_dir);
+        base.join(self.app).join("paddleocr")
+    }
+
+    /**
+     * Extracts the engine under [`Bundle::cache_dir`] unless it already is, and returns the path to its executable.
+     */
+    pub fn extract(&self) -> IoResult<PathBuf> {
+        self.extract_to(&self.cache_dir())
+    }
+
+    /**
+     * Like [`Bundle::extract`], into `dir` instead of the cache directory. Other versions in `dir` are removed.
+     */
+    pub fn extract_to(&self, dir: &Path) -> IoResult<PathBuf> {
+        let version = format!("{:08x}", zip::crc32(self.archive));
+        let target = dir.join(&version);
+        if !target.join(COMPLETE).exists() {
+            // Extract next to the target and move it in place, so that a concurrent start never sees half an engine.
+            let partial = dir.join(format!("{}.{}.partial", version, std::process::id()));
+            let _ = std::fs::remove_dir_all(&partial);
+            if let Err(e) = unpack(self.archive, &partial, self.exe)
+                .and_then(|_| std::fs::write(partial.join(COMPLETE), ""))
+            {
+                let _ = std::fs::remove_dir_all(&partial);
+                return Err(e);
+            }
+            if target.join(COMPLETE).exists() {
+                // Another process finished first, and may be about to start its copy.
+                let _ = std::fs::remove_dir_all(&partial);
+            } else {
+                // Only an incomplete leftover is in the way.
+                let _ = std::fs::remove_dir_all(&target);
+                if let Err(e) = std::fs::rename(&partial, &target) {
+                    let _ = std::fs::remove_dir_all(&partial);
+                    if !target.join(COMPLETE).exists() {
+                        return Err(e);
+                    }
+                }
+            }
+        }
+        remove_stale(dir, &version);
+        Ok(target.join(self.exe))
+    }
+
+    /**
+     * Extracts the engine if needed and spawns it. See [`Ppocr::new`].
+     */
+    pub fn spawn(&self) -> Result<Ppocr, Box<dyn Error>> {
+        Ppocr::builder(self.extract()?).spawn()
+    }
+}
+
+/// Writes the files of `archive` under `dir`, refusing names that would land outside of it.
+fn unpack(archive: &[u8], dir: &Path, exe: &str) -> IoResult<()> {
+    for (name, data) in zip::read(archive)? {
+        let relative = Path::new(name);
+        if !relative
+            .components()
+            .all(|c| matches!(c, Component::Normal(_)))
+        {
+            return Err(IoError::new(
+                ErrorKind::InvalidData,
+                format!("{} is outside of the archive", name),
+            ));
+        }
+        let path = dir.join(relative);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&path, data)?;
+        #[cfg(unix)]
+        if name == exe {
+            use std::os::unix::fs::PermissionsExt;
+            std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755))?;
+        }
+    }
+    if !dir.join(exe).is_file() {
+        return Err(IoError::new(
+            ErrorKind::NotFound,
+            format!("{} is not in the archive", exe),
+        ));
+    }
+    Ok(())
+}
+
+/// Removes the engines of other versions in `dir`. One still running on Windows can't be, and is left for next time.
+fn remove_stale(dir: &Path, version: &str) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let name = entry.file_name();
+        let name = name.to_string_lossy();
+        // Partial extractions belong to running processes; leave them alone.
+        if name != version && !name.ends_with(".partial") && entry.path().join(COMPLETE).exists() {
+            let _ = std::fs::remove_dir_all(entry.path());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Bundle;
+    use crate::zip::ZipWriter;
+
+    fn archive(files: &[(&str, &[u8])]) -> &'static [u8] {
+        let mut zip = ZipWriter::new(Vec::new());
+        for (name, data) in files {
+            zip.add(name, data).unwrap();
+        }
+        zip.finish().unwrap().leak()
+    }
+
+    #[test]
+    fn extract() {
+        let dir = std::env::temp_dir().join(format!("paddleocr-bundled-{}", std::process::id()));
+        let old = Bundle::new("test", archive(&[("engine/run", b"v1")]), "engine/run");
+        let new = Bundle::new(
+            "test",
+            archive(&[("engine/run", b"v2"), ("engine/models/det", b"model")]),
+            "engine/run",
+        );
+
+        let old_exe = old.extract_to(&dir).unwrap();
+        assert_eq!(std::fs::read(&old_exe).unwrap(), b"v1");
+        let exe = new.extract_to(&dir).unwrap();
+        assert_eq!(std::fs::read(&exe).unwrap(), b"v2");
+        assert_eq!(
+            std::fs::read(exe.with_file_name("models/det")).unwrap(),
+            b"model"
+        );
+        // The old version is cleaned up, and extracting again reuses the engine.
+        assert!(!old_exe.exists());
+        std::fs::write(&exe, b"patched").unwrap();
+        assert_eq!(new.extract_to(&dir).unwrap(), exe);
+        assert_eq!(std::fs::read(&exe).unwrap(), b"patched");
+        // An interrupted extraction is replaced.
+        std::fs::remove_file(exe.parent().unwrap().with_file_name(super::COMPLETE)).unwrap();
+        assert_eq!(new.extract_to(&dir).unwrap(), exe);
+        assert_eq!(std::fs::read(&exe).unwrap(), b"v2");
+
+        let escaping = Bundle::new("test", archive(&[("../evil", b"")]), "../evil");
+        let missing = Bundle::new("test", archive(&[("a", b"")]), "engine/run");
+        std::fs::remove_dir_all(&dir).unwrap();
+        let results = (escaping.extract_to(&dir), missing.extract_to(&dir));
+        std::fs::remove_dir(&dir).unwrap();
+        assert!(results.0.is_err());
+        assert!(results.1.is_err());
+        assert!(!dir.with_file_name("evil").exists());
+    }
+}
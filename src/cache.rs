@@ -0,0 +1,356 @@
+//! Remembering responses, so that an image OCRed before is not sent to the engine again.
+//!
+//! [`Cache`] is a [`Layer`]; put it in front of an engine with [`Layered`](crate::middleware::Layered), and keep a
+//! clone to control it:
+//!
+//! ```no_run
+//! use paddleocr::cache::Cache;
+//! use paddleocr::middleware::Layered;
+//! use paddleocr::OcrEngine;
+//!
+//! let cache = Cache::new(1000).ttl(std::time::Duration::from_secs(600));
+//! let p = paddleocr::Ppocr::new(".../PaddleOCR-json.exe", None).unwrap();
+//! let mut engine = Layered::new(p).layer(cache.clone());
+//! let image = paddleocr::ImageData::from_path(".../test.png");
+//! engine.ocr(image.clone()).unwrap();
+//! engine.ocr(image.clone()).unwrap(); // answered from the cache
+//! cache.invalidate(&image); // the file changed
+//! eprintln!("{:?}", cache.stats());
+//! ```
+
+use std::collections::{HashMap, VecDeque};
+use std::hash::{BuildHasher, Hash, Hasher, RandomState};
+use std::io::Result as IoResult;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::time::{Duration, Instant};
+
+use crate::middleware::Layer;
+use crate::{ImageData, OcrEngine, OcrResponse};
+
+type TtlFn = Box<dyn Fn(&ImageData) -> Option<Duration> + Send>;
+
+/**
+ * How a [`Cache`] has fared so far.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct CacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    /// Responses currently held, including expired ones not yet evicted.
+    pub entries: usize,
+}
+
+struct Entry {
+    response: String,
+    expires: Option<Instant>,
+    /// When the entry was last used, on the cache's own clock.
+    used: u64,
+}
+
+/// A 128-bit digest of a request's image, so that keys stay small however large the payload.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct Key([u64; 2]);
+
+struct Inner {
+    entries: HashMap<Key, Entry>,
+    /// Keys from least to most recently used, with the clock at each use. A pair is stale once its entry is used
+    /// again or removed; stale pairs are skipped when evicting and dropped when they pile up.
+    order: VecDeque<(Key, u64)>,
+    /// Randomly seeded hashers for the two halves of keys, so that colliding images can't be crafted.
+    hashers: [RandomState; 2],
+    capacity: usize,
+    ttl: Option<TtlFn>,
+    clock: u64,
+    hits: u64,
+    misses: u64,
+}
+
+/**
+ * A least-recently-used cache of responses, keyed by a digest of the request. Clones share the same entries.
+ *
+ * Only answers with code 100 (text found) or 101 (no text) are kept, and never for the clipboard. A path is cached
+ * by name, not contents: use a TTL or [`invalidate`](Cache::invalidate) when files change.
+ */
+#[derive(Clone)]
+pub struct Cache {
+    inner: Arc<Mutex<Inner>>,
+}
+
+impl Inner {
+    fn key(&self, image: &ImageData) -> Key {
+        Key(self.hashers.each_ref().map(|hasher| {
+            let mut hasher = hasher.build_hasher();
+            match image {
+                ImageData::ImagePathDict { image_path } => (0u8, image_path).hash(&mut hasher),
+                ImageData::ImageBase64Dict { image_base64 } => {
+                    (1u8, image_base64).hash(&mut hasher)
+                }
+            }
+            hasher.finish()
+        }))
+    }
+
+    /// Marks `key` as the most recently used, returning the clock of this use.
+    fn touch(&mut self, key: Key) -> u64 {
+        self.clock += 1;
+        self.order.push_back((key, self.clock));
+        if self.order.len() > 2 * self.capacity.max(self.entries.len()) {
+            let entries = &self.entries;
+            self.order
+                .retain(|(key, used)| entries.get(key).is_some_and(|entry| entry.used == *used));
+        }
+        self.clock
+    }
+
+    fn evict_least_recently_used(&mut self) {
+        while let Some((key, used)) = self.order.pop_front() {
+            if self
+                .entries
+                .get(&key)
+                .is_some_and(|entry| entry.used == used)
+            {
+                self.entries.remove(&key);
+                return;
+            }
+        }
+    }
+}
+
+impl Cache {
+    /**
+     * An empty cache holding up to `capacity` responses, evicting the least recently used beyond that.
+     */
+    pub fn new(capacity: usize) -> Cache {
+        Cache {
+            inner: Arc::new(Mutex::new(Inner {
+                entries: HashMap::new(),
+                order: VecDeque::new(),
+                hashers: [RandomState::new(), RandomState::new()],
+                capacity,
+                ttl: None,
+                clock: 0,
+                hits: 0,
+                misses: 0,
+            })),
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Inner> {
+        self.inner.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /**
+     * Expires every response `ttl` after it was stored.
+     */
+    pub fn ttl(self, ttl: Duration) -> Cache {
+        self.ttl_with(move |_| Some(ttl))
+    }
+
+    /**
+     * Decides the lifetime of each response by its request, e.g. shorter for files that change often; `None` keeps
+     * it until evicted.
+     */
+    pub fn ttl_with<F>(self, ttl: F) -> Cache
+    where
+        F: Fn(&ImageData) -> Option<Duration> + Send + 'static,
+    {
+        self.lock().ttl = Some(Box::new(ttl));
+        self
+    }
+
+    /**
+     * The cached response to `image`, if any and not expired.
+     */
+    pub fn get(&self, image: &ImageData) -> Option<String> {
+        let mut inner = self.lock();
+        let key = inner.key(image);
+        let response = match inner.entries.get(&key) {
+            Some(entry) if entry.expires.is_none_or(|expires| Instant::now() < expires) => {
+                let response = entry.response.clone();
+                let used = inner.touch(key);
+                if let Some(entry) = inner.entries.get_mut(&key) {
+                    entry.used = used;
+                }
+                Some(response)
+            }
+            Some(_) => {
+                inner.entries.remove(&key);
+                None
+            }
+            None => None,
+        };
+        match response {
+            Some(_) => inner.hits += 1,
+            None => inner.misses += 1,
+        }
+        response
+    }
+
+    /**
+     * Stores `response` to `image`, evicting the least recently used response if the cache is full.
+     */
+    pub fn insert(&self, image: &ImageData, response: String) {
+        let mut inner = self.lock();
+        if inner.capacity == 0 {
+            return;
+        }
+        let expires = inner
+            .ttl
+            .as_ref()
+            .and_then(|ttl| ttl(image))
+            .map(|ttl| Instant::now() + ttl);
+        let key = inner.key(image);
+        if !inner.entries.contains_key(&key) && inner.entries.len() >= inner.capacity {
+            inner.evict_least_recently_used();
+        }
+        let used = inner.touch(key);
+        inner.entries.insert(
+            key,
+            Entry {
+                response,
+                expires,
+                used,
+            },
+        );
+    }
+
+    /**
+     * Forgets the response to `image`, returning whether there was one.
+     */
+    pub fn invalidate(&self, image: &ImageData) -> bool {
+        let mut inner = self.lock();
+        let key = inner.key(image);
+        inner.entries.remove(&key).is_some()
+    }
+
+    /**
+     * Forgets all responses. The statistics are kept.
+     */
+    pub fn clear(&self) {
+        let mut inner = self.lock();
+        inner.entries.clear();
+        inner.order.clear();
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        let inner = self.lock();
+        CacheStats {
+            hits: inner.hits,
+            misses: inner.misses,
+            entries: inner.entries.len(),
+        }
+    }
+}
+
+impl Layer for Cache {
+    fn call(&mut self, image: ImageData, next: &mut dyn OcrEngine) -> IoResult<String> {
+        if matches!(&image, ImageData::ImagePathDict { image_path } if image_path == "clipboard") {
+            return next.ocr(image);
+        }
+        if let Some(response) = self.get(&image) {
+            return Ok(response);
+        }
+        let response = next.ocr(image.clone())?;
+        if OcrResponse::parse(response.as_str()).is_ok_and(|r| r.code == 100 || r.code == 101) {
+            self.insert(&image, response.clone());
+        }
+        Ok(response)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::{Cache, CacheStats};
+    use crate::middleware::Layered;
+    use crate::{ImageData, OcrEngine};
+
+    /// Answers with a count of requests, failing for `bad.png`.
+    struct Counter(u32);
+    impl OcrEngine for Counter {
+        fn ocr(&mut self, image: ImageData) -> std::io::Result<String> {
+            self.0 += 1;
+            let code = match image {
+                ImageData::ImagePathDict { image_path } if image_path == "bad.png" => 200,
+                _ => 101,
+            };
+            Ok(format!(
+                r#"{{"code":{},"data":"request {}"}}"#,
+                code, self.0
+            ))
+        }
+    }
+
+    #[test]
+    fn lru() {
+        let cache = Cache::new(2);
+        let mut engine = Layered::new(Counter(0)).layer(cache.clone());
+        let mut ocr = |path: &str| engine.ocr(ImageData::from_path(path)).unwrap();
+        assert!(ocr("a.png").contains("request 1"));
+        assert!(ocr("a.png").contains("request 1"));
+        ocr("b.png");
+        ocr("a.png");
+        // The least recently used, b.png, makes room.
+        ocr("c.png");
+        assert!(ocr("a.png").contains("request 1"));
+        assert!(ocr("b.png").contains("request 4"));
+        // Errors and the clipboard are not cached.
+        ocr("bad.png");
+        assert!(ocr("bad.png").contains("request 6"));
+        ocr("clipboard");
+        assert!(ocr("clipboard").contains("request 8"));
+
+        assert!(cache.invalidate(&ImageData::from_path("b.png")));
+        assert!(!cache.invalidate(&ImageData::from_path("b.png")));
+        assert_eq!(
+            cache.stats(),
+            CacheStats {
+                hits: 3,
+                misses: 6,
+                entries: 1
+            }
+        );
+        cache.clear();
+        assert_eq!(cache.stats().entries, 0);
+    }
+
+    #[test]
+    fn ttl() {
+        let cache = Cache::new(10).ttl_with(|image| match image {
+            ImageData::ImagePathDict { image_path } if image_path == "live.png" => {
+                Some(Duration::ZERO)
+            }
+            _ => None,
+        });
+        cache.insert(&ImageData::from_path("live.png"), "1".into());
+        cache.insert(&ImageData::from_path("still.png"), "2".into());
+        assert_eq!(cache.get(&ImageData::from_path("live.png")), None);
+        assert_eq!(
+            cache.get(&ImageData::from_path("still.png")).as_deref(),
+            Some("2")
+        );
+    }
+
+    #[test]
+    fn keys() {
+        let cache = Cache::new(2);
+        let (path, base64) = (
+            ImageData::from_path("AAAA"),
+            ImageData::from_base64("AAAA".into()),
+        );
+        cache.insert(&path, "path".into());
+        cache.insert(&base64, "base64".into());
+        assert_eq!(cache.get(&path).as_deref(), Some("path"));
+        assert_eq!(cache.get(&base64).as_deref(), Some("base64"));
+
+        // Repeated hits don't grow the recency order without bound.
+        for _ in 0..100 {
+            cache.get(&path);
+        }
+        assert!(cache.lock().order.len() <= 4);
+        cache.insert(&ImageData::from_path("c.png"), "c".into());
+        assert_eq!(cache.get(&base64), None);
+        assert_eq!(cache.get(&path).as_deref(), Some("path"));
+    }
+}
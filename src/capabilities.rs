@@ -0,0 +1,102 @@
+//! What a connected engine can do, inferred from its version and how it was started.
+
+use std::path::Path;
+
+/**
+ * Features an engine supports, as returned by [`Ppocr::capabilities`](crate::Ppocr::capabilities).
+ *
+ * The engine offers no way to ask, so these are inferred from the version it announced at startup, and are all
+ * `false` when it announced none.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Capabilities {
+    /// OCR of the clipboard image with the `clipboard` path; only the Windows builds have it, from v1.2.
+    pub clipboard: bool,
+    /// Serving requests over TCP with `--port`, from v1.3.
+    pub socket: bool,
+    /// Options given with each request rather than at startup, from v1.4.
+    pub per_call_options: bool,
+    /// Table recognition, when the engine was started with a `--table_model_dir`.
+    pub table: bool,
+}
+
+impl Capabilities {
+    /**
+     * The capabilities of engine `version` (such as `1.4.0`), started as `program` with `args`.
+     */
+    pub(crate) fn infer<'a>(
+        version: Option<&str>,
+        program: Option<&Path>,
+        mut args: impl Iterator<Item = &'a str>,
+    ) -> Capabilities {
+        let Some(version) = version.and_then(parse_version) else {
+            return Capabilities::default();
+        };
+        let windows_build = program
+            .and_then(Path::extension)
+            .is_some_and(|extension| extension.eq_ignore_ascii_case("exe"));
+        Capabilities {
+            clipboard: windows_build && version >= (1, 2, 0),
+            socket: version >= (1, 3, 0),
+            per_call_options: version >= (1, 4, 0),
+            table: args.any(|arg| arg.starts_with("--table_model_dir")),
+        }
+    }
+}
+
+/// `1.4.0` or `1.4` as `(1, 4, 0)`; suffixes such as `-beta` are ignored.
+fn parse_version(version: &str) -> Option<(u32, u32, u32)> {
+    let mut parts = version.split('.').map(|part| {
+        let digits = part
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(part.len());
+        part[..digits].parse::<u32>().ok()
+    });
+    let major = parts.next()??;
+    let minor = parts.next().flatten().unwrap_or(0);
+    let patch = parts.next().flatten().unwrap_or(0);
+    Some((major, minor, patch))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::{parse_version, Capabilities};
+
+    #[test]
+    fn versions() {
+        assert_eq!(parse_version("1.4.0"), Some((1, 4, 0)));
+        assert_eq!(parse_version("1.3"), Some((1, 3, 0)));
+        assert_eq!(parse_version("1.4.1-beta"), Some((1, 4, 1)));
+        assert_eq!(parse_version("dev"), None);
+    }
+
+    #[test]
+    fn infer() {
+        let windows = Capabilities::infer(
+            Some("1.4.0"),
+            Some(Path::new("PaddleOCR-json.exe")),
+            ["--table_model_dir=models/table"].into_iter(),
+        );
+        assert_eq!(
+            windows,
+            Capabilities {
+                clipboard: true,
+                socket: true,
+                per_call_options: true,
+                table: true,
+            }
+        );
+        let linux = Capabilities::infer(
+            Some("1.3.1"),
+            Some(Path::new("PaddleOCR-json")),
+            [].into_iter(),
+        );
+        assert!(!linux.clipboard && linux.socket && !linux.per_call_options);
+        assert_eq!(
+            Capabilities::infer(None, None, [].into_iter()),
+            Capabilities::default()
+        );
+    }
+}
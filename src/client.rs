@@ -0,0 +1,311 @@
+//! Sharing one engine between many parts of a program.
+
+use std::io::{Error as IoError, ErrorKind, Result as IoResult};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::sync::{mpsc, Arc};
+#[cfg(feature = "local")]
+use std::sync::{Mutex, PoisonError, Weak};
+
+#[cfg(feature = "local")]
+use crate::builder::SharedKey;
+use crate::{ContentData, ImageData, OcrEngine, OcrResponse, PpocrError};
+
+type Job = Box<dyn FnOnce(&mut dyn OcrEngine) + Send>;
+
+/// The engine shared under one key, locked while it is being started.
+#[cfg(feature = "local")]
+type Slot = Arc<Mutex<Weak<mpsc::Sender<Job>>>>;
+
+/// Engines started with [`PpocrClient::shared`], by their configuration; dropped ones are pruned on the next lookup.
+#[cfg(feature = "local")]
+static REGISTRY: Mutex<Vec<(SharedKey, Slot)>> = Mutex::new(Vec::new());
+
+/**
+A cheap, cloneable handle to an engine owned by a worker thread.
+
+Every clone sends its requests to the same worker, which handles them one at a time in the order they arrive, so
+components can each hold a handle without a pool or a mutex. The worker stops, and drops the engine, when the last
+handle is dropped.
+
+# Examples
+
+```no_run
+use paddleocr::PpocrClient;
+
+let client = PpocrClient::new(paddleocr::Ppocr::new(".../PaddleOCR-json.exe", None).unwrap());
+let for_thread = client.clone();
+std::thread::spawn(move || {
+    println!("{}", for_thread.ocr(Path::new(".../a.png").into()).unwrap());
+});
+println!("{}", client.ocr(Path::new(".../b.png").into()).unwrap());
+```
+*/
+#[derive(Clone)]
+pub struct PpocrClient {
+    jobs: Arc<mpsc::Sender<Job>>,
+}
+
+impl PpocrClient {
+    /**
+     * Moves `engine` to a new worker thread.
+     */
+    pub fn new<E: OcrEngine + Send + 'static>(mut engine: E) -> PpocrClient {
+        let (jobs, queue) = mpsc::channel::<Job>();
+        std::thread::spawn(move || {
+            for job in queue {
+                job(&mut engine);
+            }
+        });
+        PpocrClient {
+            jobs: Arc::new(jobs),
+        }
+    }
+
+    /**
+     * A handle to the engine registered under `key`, or to one started with `spawn` and registered if there is none.
+     * Lookups of a key wait while its engine is being started, so that two components asking at once share one too;
+     * engines under other keys start meanwhile.
+     */
+    #[cfg(feature = "local")]
+    pub(crate) fn shared<E, F>(
+        key: SharedKey,
+        spawn: F,
+    ) -> Result<PpocrClient, Box<dyn std::error::Error>>
+    where
+        E: OcrEngine + Send + 'static,
+        F: FnOnce() -> Result<E, Box<dyn std::error::Error>>,
+    {
+        let slot = {
+            let mut registry = REGISTRY.lock().unwrap_or_else(PoisonError::into_inner);
+            // Slots nobody is starting an engine in, whose engine is gone.
+            registry.retain(|(_, slot)| {
+                Arc::strong_count(slot) > 1
+                    || slot.try_lock().map_or(true, |jobs| jobs.strong_count() > 0)
+            });
+            match registry.iter().find(|(registered, _)| *registered == key) {
+                Some((_, slot)) => slot.clone(),
+                None => {
+                    let slot = Slot::default();
+                    registry.push((key, slot.clone()));
+                    slot
+                }
+            }
+        };
+        let mut jobs = slot.lock().unwrap_or_else(PoisonError::into_inner);
+        if let Some(jobs) = jobs.upgrade() {
+            return Ok(PpocrClient { jobs });
+        }
+        let client = PpocrClient::new(spawn()?);
+        *jobs = Arc::downgrade(&client.jobs);
+        Ok(client)
+    }
+
+    /**
+     * Runs `f` with the engine on the worker thread, waiting for its result.
+     */
+    fn call<T, F>(&self, f: F) -> IoResult<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(&mut dyn OcrEngine) -> T + Send + 'static,
+    {
+        let stopped = || IoError::new(ErrorKind::BrokenPipe, "the OCR worker has stopped");
+        #[cfg(feature = "otel")]
+        let f = crate::otel::propagate(f);
+        let (result, receiver) = mpsc::sync_channel(1);
+        self.jobs
+            .send(Box::new(move |engine| {
+                // A panicking request fails on its own; the engine keeps serving the others.
+                result
+                    .send(catch_unwind(AssertUnwindSafe(|| f(engine))))
+                    .ok();
+            }))
+            .map_err(|_| stopped())?;
+        match receiver.recv() {
+            Ok(Ok(value)) => Ok(value),
+            Ok(Err(_)) => Err(IoError::other("the engine panicked")),
+            Err(_) => Err(stopped()),
+        }
+    }
+
+    /**
+     * OCRs the image on the worker. See [`Ppocr::ocr`](crate::Ppocr::ocr).
+     */
+    pub fn ocr(&self, image: ImageData) -> IoResult<String> {
+        self.call(move |engine| engine.ocr(image))?
+    }
+
+    /**
+     * OCRs the image on the worker and parses the response.
+     * See [`Ppocr::ocr_and_parse`](crate::Ppocr::ocr_and_parse).
+     */
+    pub fn ocr_and_parse(&self, image: ImageData) -> Result<Vec<ContentData>, String> {
+        self.call(move |engine| engine.ocr_and_parse(image))
+            .map_err(|e| e.to_string())?
+    }
+
+    /**
+     * OCRs the image on the worker, keeping the raw JSON. See [`Ppocr::ocr_response`](crate::Ppocr::ocr_response).
+     */
+    pub fn ocr_response(&self, image: ImageData) -> Result<OcrResponse, String> {
+        self.call(move |engine| engine.ocr_response(image))
+            .map_err(|e| e.to_string())?
+    }
+
+    /**
+     * OCRs the image on the worker and parses the response, with typed errors.
+     * See [`Ppocr::try_ocr`](crate::Ppocr::try_ocr).
+     */
+    pub fn try_ocr(&self, image: ImageData) -> Result<Vec<ContentData>, PpocrError> {
+        self.call(move |engine| engine.try_ocr(image))?
+    }
+}
+
+impl OcrEngine for PpocrClient {
+    fn ocr(&mut self, image: ImageData) -> IoResult<String> {
+        PpocrClient::ocr(self, image)
+    }
+
+    fn ocr_and_parse(&mut self, image: ImageData) -> Result<Vec<ContentData>, String> {
+        PpocrClient::ocr_and_parse(self, image)
+    }
+
+    fn ocr_response(&mut self, image: ImageData) -> Result<OcrResponse, String> {
+        PpocrClient::ocr_response(self, image)
+    }
+
+    fn try_ocr(&mut self, image: ImageData) -> Result<Vec<ContentData>, PpocrError> {
+        PpocrClient::try_ocr(self, image)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    #[cfg(feature = "local")]
+    use std::sync::mpsc;
+    use std::sync::Arc;
+
+    use super::PpocrClient;
+    #[cfg(feature = "local")]
+    use crate::Ppocr;
+    use crate::{ImageData, OcrEngine};
+
+    /// Counts requests, and panics on `panic.png`.
+    struct Counter(Arc<AtomicUsize>);
+    impl OcrEngine for Counter {
+        fn ocr(&mut self, image: ImageData) -> std::io::Result<String> {
+            if let ImageData::ImagePathDict { image_path } = &image {
+                assert_ne!(image_path, "panic.png");
+            }
+            let n = self.0.fetch_add(1, Ordering::SeqCst);
+            Ok(format!(r#"{{"code":101,"data":"request {}"}}"#, n))
+        }
+    }
+
+    fn assert_shareable<T: Clone + Send + Sync>() {}
+
+    #[test]
+    fn shared() {
+        assert_shareable::<PpocrClient>();
+        let count = Arc::new(AtomicUsize::new(0));
+        let client = PpocrClient::new(Counter(count.clone()));
+        let threads: Vec<_> = (0..4)
+            .map(|_| {
+                let client = client.clone();
+                std::thread::spawn(move || {
+                    for _ in 0..5 {
+                        client.ocr(ImageData::from_path("a.png")).unwrap();
+                    }
+                })
+            })
+            .collect();
+        for thread in threads {
+            thread.join().unwrap();
+        }
+        assert_eq!(count.load(Ordering::SeqCst), 20);
+        assert_eq!(
+            client
+                .ocr_response(ImageData::from_path("a.png"))
+                .unwrap()
+                .code,
+            101
+        );
+
+        // A panicking request fails on its own, and the worker keeps serving.
+        let e = client.ocr(ImageData::from_path("panic.png")).unwrap_err();
+        assert_eq!(e.kind(), std::io::ErrorKind::Other);
+        assert_eq!(e.to_string(), "the engine panicked");
+        assert!(client.ocr(ImageData::from_path("a.png")).is_ok());
+    }
+
+    #[test]
+    #[cfg(feature = "local")]
+    fn registry() {
+        let spawned = Arc::new(AtomicUsize::new(0));
+        let count = Arc::new(AtomicUsize::new(0));
+        let key = |name: &str| Ppocr::builder(format!("registry test {}", name)).shared_key();
+        let shared = |name: &str| {
+            let (spawned, count) = (spawned.clone(), count.clone());
+            PpocrClient::shared(key(name), move || {
+                spawned.fetch_add(1, Ordering::SeqCst);
+                Ok(Counter(count))
+            })
+            .unwrap()
+        };
+        let (a, b) = (shared("a"), shared("a"));
+        a.ocr(ImageData::from_path("a.png")).unwrap();
+        let response = b.ocr(ImageData::from_path("a.png")).unwrap();
+        assert!(response.contains("request 1"));
+        assert_eq!(spawned.load(Ordering::SeqCst), 1);
+
+        let other = shared("b");
+        assert_eq!(spawned.load(Ordering::SeqCst), 2);
+        drop(other);
+        // The engine stays while any handle does.
+        drop(a);
+        shared("a");
+        assert_eq!(spawned.load(Ordering::SeqCst), 2);
+        drop(b);
+        shared("a");
+        assert_eq!(spawned.load(Ordering::SeqCst), 3);
+        assert!(PpocrClient::shared::<Counter, _>(key("c"), || { Err("failed".into()) }).is_err());
+        assert!(shared("c").ocr(ImageData::from_path("a.png")).is_ok());
+
+        // Equal settings make equal keys, and a second start of one key waits for the first.
+        assert_eq!(
+            Ppocr::builder("registry test d")
+                .drop_score(0.5)
+                .shared_key(),
+            Ppocr::builder("registry test d")
+                .drop_score(0.5)
+                .shared_key()
+        );
+        assert_ne!(
+            Ppocr::builder("registry test d")
+                .drop_score(0.5)
+                .shared_key(),
+            Ppocr::builder("registry test d")
+                .drop_score(0.6)
+                .shared_key()
+        );
+        let (started, release) = (mpsc::channel(), mpsc::channel::<()>());
+        let (started_sender, release_receiver) = (started.0, release.1);
+        let slow = std::thread::spawn(move || {
+            PpocrClient::shared(key("d"), move || {
+                started_sender.send(()).unwrap();
+                release_receiver.recv().unwrap();
+                Ok(Counter(Arc::default()))
+            })
+            .unwrap()
+        });
+        started.1.recv().unwrap();
+        // Another key isn't held up by it.
+        shared("e");
+        let waiting = std::thread::spawn(move || {
+            PpocrClient::shared::<Counter, _>(key("d"), || panic!("started twice")).unwrap()
+        });
+        release.0.send(()).unwrap();
+        let _first = slow.join().unwrap();
+        waiting.join().unwrap();
+    }
+}
@@ -0,0 +1,578 @@
+//! Reading clipboard images in Rust instead of the engine. Enabled with the `bytes` feature.
+//!
+//! The engine's own clipboard support rejects bitmaps with other than 1, 3 or 4 channels (code 217), and only sees the
+//! clipboard of the machine it runs on. [`ocr`] reads the bitmap here, converts it to PNG and sends it as base64, so it
+//! works with any [`OcrEngine`], including remote ones. A [`Watcher`] does so whenever a new image is copied.
+//!
+//! ```no_run
+//! let mut p = paddleocr::Ppocr::new(".../PaddleOCR-json.exe", None).unwrap();
+//! println!("{}", paddleocr::clipboard::ocr(&mut p).unwrap());
+//! ```
+
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::io::{Error as IoError, ErrorKind, Result as IoResult};
+use std::ops::ControlFlow;
+use std::time::Duration;
+
+use miniz_oxide::deflate::compress_to_vec_zlib;
+
+use crate::zip::crc32;
+use crate::{ImageData, OcrEngine};
+
+const BI_RGB: u32 = 0;
+const BI_BITFIELDS: u32 = 3;
+const BI_ALPHABITFIELDS: u32 = 6;
+
+/**
+ * The bitmap on the clipboard as PNG, or `None` if the clipboard holds no bitmap. Only supported on Windows.
+ */
+pub fn read_image() -> IoResult<Option<Vec<u8>>> {
+    sys::read_dib()?.map(|dib| dib_to_png(&dib)).transpose()
+}
+
+/**
+ * OCRs the bitmap on the clipboard with `engine`, as PNG. Fails with [`NotFound`](ErrorKind::NotFound) if the
+ * clipboard holds no bitmap.
+ */
+pub fn ocr<E: OcrEngine + ?Sized>(engine: &mut E) -> IoResult<String> {
+    let png = read_image()?
+        .ok_or_else(|| IoError::new(ErrorKind::NotFound, "the clipboard holds no image"))?;
+    engine.ocr(ImageData::from_bytes(png))
+}
+
+/**
+OCRs images as they are copied to the clipboard.
+
+# Examples
+
+```no_run
+use std::ops::ControlFlow;
+
+let mut p = paddleocr::Ppocr::new(".../PaddleOCR-json.exe", None).unwrap();
+paddleocr::clipboard::Watcher::new()
+    .debounce(std::time::Duration::from_millis(500))
+    .run(&mut p, |response| {
+        println!("{}", response.unwrap());
+        ControlFlow::Continue(())
+    })
+    .unwrap();
+```
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Watcher {
+    interval: Duration,
+    debounce: Duration,
+}
+
+impl Default for Watcher {
+    fn default() -> Self {
+        Watcher::new()
+    }
+}
+
+impl Watcher {
+    /**
+     * Checks the clipboard every 200 ms, and waits for it to stay the same for 300 ms.
+     */
+    pub fn new() -> Watcher {
+        Watcher {
+            interval: Duration::from_millis(200),
+            debounce: Duration::from_millis(300),
+        }
+    }
+
+    /**
+     * How often the clipboard is checked for changes. Checking is cheap; the image is only read once it changed.
+     */
+    pub fn interval(mut self, interval: Duration) -> Watcher {
+        self.interval = interval;
+        self
+    }
+
+    /**
+     * How long the clipboard must stay unchanged before it is read, so that programs putting several formats on it
+     * one after another are read once they are done.
+     */
+    pub fn debounce(mut self, debounce: Duration) -> Watcher {
+        self.debounce = debounce;
+        self
+    }
+
+    /**
+     * Passes the response to each new image on the clipboard to `callback` until it breaks. What is on the clipboard
+     * when watching starts is not OCRed, nor is an image copied again right after itself. Errors reading the clipboard
+     * are passed to `callback` as well.
+     */
+    pub fn run<E, F>(&self, engine: &mut E, callback: F) -> IoResult<()>
+    where
+        E: OcrEngine + ?Sized,
+        F: FnMut(IoResult<String>) -> ControlFlow<()>,
+    {
+        self.run_with(sys::sequence_number, read_image, engine, callback)
+    }
+
+    fn run_with<E, F>(
+        &self,
+        mut sequence_number: impl FnMut() -> IoResult<u32>,
+        mut read: impl FnMut() -> IoResult<Option<Vec<u8>>>,
+        engine: &mut E,
+        mut callback: F,
+    ) -> IoResult<()>
+    where
+        E: OcrEngine + ?Sized,
+        F: FnMut(IoResult<String>) -> ControlFlow<()>,
+    {
+        let hash = |png: &[u8]| {
+            let mut hasher = DefaultHasher::new();
+            png.hash(&mut hasher);
+            hasher.finish()
+        };
+        let mut seen = sequence_number()?;
+        let mut last = read().ok().flatten().map(|png| hash(&png));
+        loop {
+            std::thread::sleep(self.interval);
+            let mut current = sequence_number()?;
+            if current == seen {
+                continue;
+            }
+            loop {
+                std::thread::sleep(self.debounce);
+                let settled = sequence_number()?;
+                if settled == current {
+                    break;
+                }
+                current = settled;
+            }
+            seen = current;
+
+            let response = match read() {
+                Ok(None) => continue,
+                Ok(Some(png)) => {
+                    let hash = hash(&png);
+                    if last == Some(hash) {
+                        continue;
+                    }
+                    last = Some(hash);
+                    engine.ocr(ImageData::from_bytes(png))
+                }
+                Err(e) => Err(e),
+            };
+            if callback(response).is_break() {
+                return Ok(());
+            }
+        }
+    }
+}
+
+/**
+ * Converts a device-independent bitmap, as on the clipboard in the `CF_DIB` format, to an RGB PNG. Transparent pixels
+ * are put on white.
+ *
+ * Bitmaps with 1, 4, 8, 16, 24 and 32 bits per pixel are supported, uncompressed or with bit fields.
+ */
+pub fn dib_to_png(dib: &[u8]) -> IoResult<Vec<u8>> {
+    let invalid = |message: &str| IoError::new(ErrorKind::InvalidData, message.to_string());
+    let u16_at = |offset: usize| {
+        dib.get(offset..offset + 2)
+            .map(|b| u16::from_le_bytes([b[0], b[1]]))
+    };
+    let u32_at = |offset: usize| {
+        dib.get(offset..offset + 4)
+            .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+    };
+    let truncated = || invalid("truncated bitmap");
+    // Sizes come from the header, so may not fit in memory, or even in a `usize`.
+    let too_large = || invalid("bitmap too large");
+
+    let header_size = u32_at(0).ok_or_else(truncated)? as usize;
+    if header_size < 40 {
+        return Err(invalid("unsupported bitmap header"));
+    }
+    let width = u32_at(4).ok_or_else(truncated)? as i32;
+    let height = u32_at(8).ok_or_else(truncated)? as i32;
+    let bits = u16_at(14).ok_or_else(truncated)?;
+    let compression = u32_at(16).ok_or_else(truncated)?;
+    let colors_used = u32_at(32).ok_or_else(truncated)? as usize;
+    if width <= 0 || height == 0 {
+        return Err(invalid("empty bitmap"));
+    }
+    let (width, top_down, height) = (width as usize, height < 0, height.unsigned_abs() as usize);
+
+    // Bit fields follow a plain header, and are part of the larger ones.
+    let mut offset = header_size;
+    let masks = match (compression, bits) {
+        (BI_BITFIELDS | BI_ALPHABITFIELDS, 16 | 32) => {
+            let count = if compression == BI_ALPHABITFIELDS {
+                4
+            } else {
+                3
+            };
+            let at = if header_size == 40 { offset } else { 40 };
+            let mut masks = [0; 4];
+            for (i, mask) in masks.iter_mut().enumerate().take(count) {
+                *mask = u32_at(at + 4 * i).ok_or_else(truncated)?;
+            }
+            if header_size > 52 && compression == BI_BITFIELDS {
+                masks[3] = u32_at(52).ok_or_else(truncated)?;
+            }
+            if header_size == 40 {
+                offset += 4 * count;
+            }
+            masks
+        }
+        (BI_RGB, 16) => [0x7c00, 0x03e0, 0x001f, 0],
+        (BI_RGB, 32) => [0x00ff_0000, 0x0000_ff00, 0x0000_00ff, 0xff00_0000],
+        (BI_RGB, 1 | 4 | 8 | 24) => [0; 4],
+        _ => return Err(invalid("unsupported bitmap format")),
+    };
+    let palette = if bits <= 8 {
+        let count = if colors_used == 0 {
+            1 << bits
+        } else {
+            colors_used
+        };
+        let end = count
+            .checked_mul(4)
+            .and_then(|size| size.checked_add(offset))
+            .ok_or_else(too_large)?;
+        let palette = dib.get(offset..end).ok_or_else(truncated)?;
+        offset = end;
+        palette
+            .chunks_exact(4)
+            .map(|c| [c[2], c[1], c[0]])
+            .collect()
+    } else {
+        Vec::new()
+    };
+
+    let stride = width
+        .checked_mul(bits as usize)
+        .ok_or_else(too_large)?
+        .div_ceil(32)
+        * 4;
+    let end = stride
+        .checked_mul(height)
+        .and_then(|size| size.checked_add(offset))
+        .ok_or_else(too_large)?;
+    let pixels = dib.get(offset..end).ok_or_else(truncated)?;
+    // A 32-bit bitmap whose alpha is zero everywhere has none.
+    let alpha = bits == 32
+        && masks[3] != 0
+        && pixels.chunks_exact(stride).any(|row| {
+            row[..width * 4]
+                .chunks_exact(4)
+                .any(|p| u32::from_le_bytes([p[0], p[1], p[2], p[3]]) & masks[3] != 0)
+        });
+
+    let mut rgb = Vec::with_capacity(
+        width
+            .checked_mul(3)
+            .and_then(|row| row.checked_add(1))
+            .and_then(|row| row.checked_mul(height))
+            .ok_or_else(too_large)?,
+    );
+    for y in 0..height {
+        let row = if top_down { y } else { height - 1 - y };
+        let row = &pixels[row * stride..(row + 1) * stride];
+        rgb.push(0); // no filter
+        for x in 0..width {
+            let pixel = match bits {
+                1 | 4 | 8 => {
+                    let bit = x * bits as usize;
+                    let index = (row[bit / 8] >> (8 - bits as usize - bit % 8)) & ((1 << bits) - 1);
+                    *palette
+                        .get(index as usize)
+                        .ok_or_else(|| invalid("bitmap color outside its palette"))?
+                }
+                24 => [row[x * 3 + 2], row[x * 3 + 1], row[x * 3]],
+                _ => {
+                    let value = match bits {
+                        16 => u16::from_le_bytes([row[x * 2], row[x * 2 + 1]]) as u32,
+                        _ => u32::from_le_bytes([
+                            row[x * 4],
+                            row[x * 4 + 1],
+                            row[x * 4 + 2],
+                            row[x * 4 + 3],
+                        ]),
+                    };
+                    let [r, g, b, a] = masks.map(|mask| channel(value, mask));
+                    if alpha {
+                        // Over white.
+                        [r, g, b]
+                            .map(|c| ((c as u32 * a as u32 + 255 * (255 - a as u32)) / 255) as u8)
+                    } else {
+                        [r, g, b]
+                    }
+                }
+            };
+            rgb.extend(pixel);
+        }
+    }
+    Ok(encode_png(width as u32, height as u32, &rgb))
+}
+
+/// The part of `value` selected by `mask`, scaled to 8 bits.
+fn channel(value: u32, mask: u32) -> u8 {
+    if mask == 0 {
+        return 255;
+    }
+    let max = (mask >> mask.trailing_zeros()) as u64;
+    (((value & mask) >> mask.trailing_zeros()) as u64 * 255 / max) as u8
+}
+
+/// An 8-bit RGB PNG of filtered `scanlines`.
+fn encode_png(width: u32, height: u32, scanlines: &[u8]) -> Vec<u8> {
+    fn chunk(png: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+        png.extend((data.len() as u32).to_be_bytes());
+        let start = png.len();
+        png.extend(kind);
+        png.extend(data);
+        png.extend(crc32(&png[start..]).to_be_bytes());
+    }
+
+    let mut png = b"\x89PNG\r\n\x1a\n".to_vec();
+    let mut header = Vec::with_capacity(13);
+    header.extend(width.to_be_bytes());
+    header.extend(height.to_be_bytes());
+    header.extend([8, 2, 0, 0, 0]); // 8 bits, RGB, deflate, no filters, no interlace
+    chunk(&mut png, b"IHDR", &header);
+    // Screenshots, the usual clipboard images, are mostly flat areas, so even fast compression shrinks them a lot.
+    chunk(&mut png, b"IDAT", &compress_to_vec_zlib(scanlines, 3));
+    chunk(&mut png, b"IEND", &[]);
+    png
+}
+
+#[cfg(windows)]
+mod sys {
+    use std::ffi::c_void;
+    use std::io::{Error as IoError, Result as IoResult};
+    use std::ptr::null_mut;
+
+    #[link(name = "user32")]
+    extern "system" {
+        fn IsClipboardFormatAvailable(format: u32) -> i32;
+        fn OpenClipboard(owner: *mut c_void) -> i32;
+        fn CloseClipboard() -> i32;
+        fn GetClipboardData(format: u32) -> *mut c_void;
+        fn GetClipboardSequenceNumber() -> u32;
+    }
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn GlobalLock(memory: *mut c_void) -> *mut c_void;
+        fn GlobalUnlock(memory: *mut c_void) -> i32;
+        fn GlobalSize(memory: *mut c_void) -> usize;
+    }
+
+    /// Windows converts `CF_BITMAP` and `CF_DIBV5` to this on request.
+    const CF_DIB: u32 = 8;
+
+    /// Changes whenever the clipboard's contents do.
+    pub(super) fn sequence_number() -> IoResult<u32> {
+        // SAFETY: `GetClipboardSequenceNumber` has no preconditions.
+        Ok(unsafe { GetClipboardSequenceNumber() })
+    }
+
+    pub(super) fn read_dib() -> IoResult<Option<Vec<u8>>> {
+        // SAFETY: `IsClipboardFormatAvailable` has no preconditions.
+        if unsafe { IsClipboardFormatAvailable(CF_DIB) } == 0 {
+            return Ok(None);
+        }
+        // SAFETY: a null owner associates the clipboard with this task.
+        if unsafe { OpenClipboard(null_mut()) } == 0 {
+            return Err(IoError::last_os_error());
+        }
+        struct Close;
+        impl Drop for Close {
+            fn drop(&mut self) {
+                // SAFETY: the clipboard was opened above.
+                unsafe { CloseClipboard() };
+            }
+        }
+        let _close = Close;
+
+        // SAFETY: the clipboard is open; the handle stays owned by it.
+        let memory = unsafe { GetClipboardData(CF_DIB) };
+        if memory.is_null() {
+            return Err(IoError::last_os_error());
+        }
+        // SAFETY: `memory` is a global memory handle from the clipboard.
+        let data = unsafe { GlobalLock(memory) };
+        if data.is_null() {
+            return Err(IoError::last_os_error());
+        }
+        // SAFETY: the locked block is `GlobalSize` bytes long, and copied before it is unlocked.
+        let dib =
+            unsafe { std::slice::from_raw_parts(data as *const u8, GlobalSize(memory)) }.to_vec();
+        // SAFETY: locked above.
+        unsafe { GlobalUnlock(memory) };
+        Ok(Some(dib))
+    }
+}
+
+#[cfg(not(windows))]
+mod sys {
+    use std::io::{Error as IoError, ErrorKind, Result as IoResult};
+
+    fn unsupported() -> IoError {
+        IoError::new(
+            ErrorKind::Unsupported,
+            "reading the clipboard is only supported on Windows",
+        )
+    }
+
+    pub(super) fn sequence_number() -> IoResult<u32> {
+        Err(unsupported())
+    }
+
+    pub(super) fn read_dib() -> IoResult<Option<Vec<u8>>> {
+        Err(unsupported())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ops::ControlFlow;
+    use std::time::Duration;
+
+    use miniz_oxide::inflate::decompress_to_vec_zlib;
+
+    use super::{dib_to_png, Watcher};
+    use crate::zip::crc32;
+    use crate::{ImageData, OcrEngine};
+
+    /// A bitmap header for `width * height` pixels of `bits`, followed by `rest`.
+    fn dib(width: i32, height: i32, bits: u16, compression: u32, rest: &[u8]) -> Vec<u8> {
+        let mut dib = Vec::new();
+        dib.extend(40u32.to_le_bytes());
+        dib.extend(width.to_le_bytes());
+        dib.extend(height.to_le_bytes());
+        dib.extend(1u16.to_le_bytes());
+        dib.extend(bits.to_le_bytes());
+        dib.extend(compression.to_le_bytes());
+        dib.extend([0; 20]);
+        dib.extend(rest);
+        dib
+    }
+
+    /// The scanlines of a PNG written by `encode_png`, checking its chunks along the way.
+    fn scanlines(png: &[u8]) -> (u32, u32, Vec<u8>) {
+        assert_eq!(&png[..8], b"\x89PNG\r\n\x1a\n");
+        let mut rest = &png[8..];
+        let (mut size, mut data) = ((0, 0), Vec::new());
+        while !rest.is_empty() {
+            let length = u32::from_be_bytes(rest[..4].try_into().unwrap()) as usize;
+            let (chunk, crc) = (&rest[4..8 + length], &rest[8 + length..12 + length]);
+            assert_eq!(crc32(chunk).to_be_bytes(), crc);
+            match &chunk[..4] {
+                b"IHDR" => {
+                    let at = |i: usize| u32::from_be_bytes(chunk[i..i + 4].try_into().unwrap());
+                    size = (at(4), at(8));
+                }
+                b"IDAT" => data = decompress_to_vec_zlib(&chunk[4..]).unwrap(),
+                _ => {}
+            }
+            rest = &rest[12 + length..];
+        }
+        (size.0, size.1, data)
+    }
+
+    #[test]
+    fn convert() {
+        // 24 bits, bottom-up, rows padded to 4 bytes: blue then white on top, red then green below.
+        let pixels = [0, 0, 255, 0, 255, 0, 0, 0, 255, 0, 0, 255, 255, 255, 0, 0];
+        let png = dib_to_png(&dib(2, 2, 24, 0, &pixels)).unwrap();
+        assert_eq!(
+            scanlines(&png),
+            (
+                2,
+                2,
+                vec![0, 0, 0, 255, 255, 255, 255, 0, 255, 0, 0, 0, 255, 0]
+            )
+        );
+
+        // 1 bit with a black and white palette, top-down.
+        let mut rest = vec![0, 0, 0, 0, 255, 255, 255, 0];
+        rest.extend([0b1000_0000, 0, 0, 0]);
+        let png = dib_to_png(&dib(2, -1, 1, 0, &rest)).unwrap();
+        assert_eq!(scanlines(&png).2, [0, 255, 255, 255, 0, 0, 0]);
+
+        // 32 bits with half-transparent black, put on white.
+        let png = dib_to_png(&dib(1, 1, 32, 0, &[0, 0, 0, 128])).unwrap();
+        assert_eq!(scanlines(&png).2, [0, 127, 127, 127]);
+        // Without any alpha, opaque.
+        let png = dib_to_png(&dib(1, 1, 32, 0, &[10, 20, 30, 0])).unwrap();
+        assert_eq!(scanlines(&png).2, [0, 30, 20, 10]);
+
+        assert!(dib_to_png(&dib(2, 2, 24, 0, &pixels[..8])).is_err());
+        assert!(dib_to_png(&dib(1, 1, 24, 1, &[0; 4])).is_err());
+        assert!(dib_to_png(b"BM").is_err());
+    }
+
+    #[test]
+    fn oversized() {
+        // Sizes whose products overflow, on 32-bit targets too, fail instead of panicking or allocating.
+        for (width, height, bits) in [
+            (i32::MAX, 1, 32),
+            (i32::MAX, i32::MIN, 24),
+            (0x4000_0000, 0x4000_0000, 24),
+        ] {
+            let error = dib_to_png(&dib(width, height, bits, 0, &[0; 16])).unwrap_err();
+            assert_eq!(error.kind(), std::io::ErrorKind::InvalidData);
+        }
+        // A palette count running past the end of memory.
+        let mut header = dib(1, 1, 8, 0, &[0; 4]);
+        header[32..36].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert!(dib_to_png(&header).is_err());
+    }
+
+    #[test]
+    fn compressed() {
+        // A white 24-bit screen.
+        let (width, height) = (1920, 1080);
+        let png = dib_to_png(&dib(width, height, 24, 0, &vec![255; 1920 * 3 * 1080])).unwrap();
+        assert!(png.len() < 64 * 1024, "{} bytes", png.len());
+        let (w, h, data) = scanlines(&png);
+        assert_eq!((w, h), (1920, 1080));
+        assert!(data
+            .chunks_exact(1 + 1920 * 3)
+            .all(|row| row[0] == 0 && row[1..].iter().all(|&c| c == 255)));
+    }
+
+    /// Answers with the size of the image it was sent.
+    struct Size;
+    impl OcrEngine for Size {
+        fn ocr(&mut self, image: ImageData) -> std::io::Result<String> {
+            match image {
+                ImageData::ImageBase64Dict { image_base64 } => Ok(image_base64.len().to_string()),
+                ImageData::ImagePathDict { .. } => unreachable!(),
+            }
+        }
+    }
+
+    #[test]
+    fn watch() {
+        // Unchanged, then changing twice before it settles, then copies of the same image, text, and another image.
+        let mut numbers = [1, 1, 2, 3, 3, 4, 4, 5, 5, 6, 6].into_iter();
+        let mut images = [None, Some(&b"a"[..]), Some(b"a"), None, Some(b"bbbb")].into_iter();
+        let mut responses = Vec::new();
+        Watcher::new()
+            .interval(Duration::ZERO)
+            .debounce(Duration::ZERO)
+            .run_with(
+                || Ok(numbers.next().unwrap()),
+                || Ok(images.next().unwrap().map(<[u8]>::to_vec)),
+                &mut Size,
+                |response| {
+                    responses.push(response.unwrap());
+                    match responses.len() {
+                        2 => ControlFlow::Break(()),
+                        _ => ControlFlow::Continue(()),
+                    }
+                },
+            )
+            .unwrap();
+        assert_eq!(responses, ["4", "8"]);
+        assert_eq!(numbers.next(), None);
+    }
+}
@@ -0,0 +1,212 @@
+//! Passing paths to engines on Windows systems whose ANSI code page is not UTF-8.
+//!
+//! The engine opens images with ANSI file APIs, so a path with characters outside the system code page (e.g.
+//! Japanese on a Chinese system) can't be opened unless "Beta: Use Unicode UTF-8" is enabled. Such paths are replaced
+//! by their 8.3 short names when those fit, or by the image's contents with the `bytes` feature.
+
+use std::error::Error;
+use std::fmt;
+use std::io::{Error as IoError, ErrorKind, Result as IoResult};
+
+use crate::ImageData;
+
+/**
+ * An image path the engine can't open because of the system's ANSI code page, with no way around it. Returned as the
+ * inner error of an [`std::io::Error`] of kind [`InvalidInput`](std::io::ErrorKind::InvalidInput).
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PathNotRepresentable {
+    pub path: String,
+    /// The system's ANSI code page, e.g. `936` for Simplified Chinese.
+    pub codepage: u32,
+}
+impl fmt::Display for PathNotRepresentable {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} has characters outside the system code page {}, so the engine can't open it; rename it, enable \
+             \"Beta: Use Unicode UTF-8\" in the region settings, or send the image as bytes",
+            self.path, self.codepage
+        )
+    }
+}
+impl Error for PathNotRepresentable {}
+
+/**
+ * Rewrites a path the engine couldn't open on this system into one it can, or fails with [`PathNotRepresentable`].
+ */
+pub(crate) fn fit_codepage(image: ImageData) -> IoResult<ImageData> {
+    match sys::ansi_codepage() {
+        Some(codepage) => fit(image, codepage, sys::representable, sys::short_path),
+        None => Ok(image),
+    }
+}
+
+fn fit<R, S>(
+    image: ImageData,
+    codepage: u32,
+    representable: R,
+    short_path: S,
+) -> IoResult<ImageData>
+where
+    R: Fn(u32, &str) -> bool,
+    S: Fn(&str) -> Option<String>,
+{
+    let image_path = match &image {
+        ImageData::ImagePathDict { image_path } if !representable(codepage, image_path) => {
+            image_path
+        }
+        _ => return Ok(image),
+    };
+    if let Some(short) = short_path(image_path).filter(|short| representable(codepage, short)) {
+        return Ok(ImageData::from_path(short));
+    }
+    #[cfg(feature = "bytes")]
+    if let Ok(bytes) = std::fs::read(image_path) {
+        return Ok(ImageData::from_bytes(bytes));
+    }
+    Err(IoError::new(
+        ErrorKind::InvalidInput,
+        PathNotRepresentable {
+            path: image_path.clone(),
+            codepage,
+        },
+    ))
+}
+
+#[cfg(windows)]
+mod sys {
+    use std::ptr::{null, null_mut};
+
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn GetACP() -> u32;
+        fn WideCharToMultiByte(
+            codepage: u32,
+            flags: u32,
+            wide: *const u16,
+            wide_len: i32,
+            multi: *mut u8,
+            multi_len: i32,
+            default_char: *const u8,
+            used_default: *mut i32,
+        ) -> i32;
+        fn GetShortPathNameW(long: *const u16, short: *mut u16, len: u32) -> u32;
+    }
+
+    const CP_UTF8: u32 = 65001;
+    const WC_NO_BEST_FIT_CHARS: u32 = 0x400;
+
+    /// The ANSI code page, unless it is UTF-8.
+    pub(super) fn ansi_codepage() -> Option<u32> {
+        // SAFETY: `GetACP` has no preconditions.
+        let codepage = unsafe { GetACP() };
+        (codepage != CP_UTF8).then_some(codepage)
+    }
+
+    pub(super) fn representable(codepage: u32, s: &str) -> bool {
+        let wide: Vec<u16> = s.encode_utf16().collect();
+        if wide.is_empty() {
+            return true;
+        }
+        let mut used_default = 0;
+        // SAFETY: `wide` is valid for its length, and a null output buffer of length 0 only measures.
+        let len = unsafe {
+            WideCharToMultiByte(
+                codepage,
+                WC_NO_BEST_FIT_CHARS,
+                wide.as_ptr(),
+                wide.len() as i32,
+                null_mut(),
+                0,
+                null(),
+                &mut used_default,
+            )
+        };
+        len > 0 && used_default == 0
+    }
+
+    /// The 8.3 short form of an existing path, if the volume has short names.
+    pub(super) fn short_path(path: &str) -> Option<String> {
+        let wide: Vec<u16> = path.encode_utf16().chain([0]).collect();
+        // SAFETY: `wide` is NUL-terminated; a null buffer of length 0 asks for the required length.
+        let len = unsafe { GetShortPathNameW(wide.as_ptr(), null_mut(), 0) };
+        if len == 0 {
+            return None;
+        }
+        let mut short = vec![0u16; len as usize];
+        // SAFETY: `short` holds `len` elements.
+        let written = unsafe { GetShortPathNameW(wide.as_ptr(), short.as_mut_ptr(), len) };
+        if written == 0 || written >= len {
+            return None;
+        }
+        String::from_utf16(&short[..written as usize]).ok()
+    }
+}
+
+#[cfg(not(windows))]
+mod sys {
+    /// Only Windows has ANSI code pages.
+    pub(super) fn ansi_codepage() -> Option<u32> {
+        None
+    }
+
+    pub(super) fn representable(_: u32, _: &str) -> bool {
+        true
+    }
+
+    pub(super) fn short_path(_: &str) -> Option<String> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{fit, PathNotRepresentable};
+    use crate::ImageData;
+
+    #[test]
+    fn fallbacks() {
+        let ascii = |_: u32, s: &str| s.is_ascii();
+        let path = |image: ImageData| match image {
+            ImageData::ImagePathDict { image_path } => Some(image_path),
+            _ => None,
+        };
+
+        let image = fit(ImageData::from_path("C:\\scan.png"), 936, ascii, |_| None).unwrap();
+        assert_eq!(path(image).as_deref(), Some("C:\\scan.png"));
+        let short = |_: &str| Some("C:\\SCAN~1.PNG".to_string());
+        let image = fit(ImageData::from_path("C:\\スキャン.png"), 936, ascii, short).unwrap();
+        assert_eq!(path(image).as_deref(), Some("C:\\SCAN~1.PNG"));
+
+        // No short name, and the file can't be read either.
+        let e = fit(
+            ImageData::from_path("C:\\スキャン.png"),
+            936,
+            ascii,
+            |_| None,
+        )
+        .unwrap_err();
+        let diagnostic = e.get_ref().unwrap().downcast_ref::<PathNotRepresentable>();
+        assert_eq!(diagnostic.map(|d| d.codepage), Some(936));
+        assert!(e.to_string().contains("UTF-8"));
+    }
+
+    #[test]
+    #[cfg(feature = "bytes")]
+    fn bytes_fallback() {
+        let file =
+            std::env::temp_dir().join(format!("paddleocr-\u{30b9}-{}.png", std::process::id()));
+        std::fs::write(&file, b"png").unwrap();
+        let image = fit(
+            file.as_path().into(),
+            936,
+            |_, s: &str| s.is_ascii(),
+            |_| None,
+        );
+        std::fs::remove_file(&file).unwrap();
+        assert!(
+            matches!(image.unwrap(), ImageData::ImageBase64Dict { image_base64 } if image_base64 == "cG5n")
+        );
+    }
+}
@@ -0,0 +1,224 @@
+//! Recognition confidence, and statistics and calibration over it.
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::transform::ResultTransform;
+use crate::ContentData;
+
+/**
+ * The engine's confidence in a recognized text, nominally from `0.0` to `1.0`.
+ *
+ * Serialized as a plain number, and comparable with `f64` directly (`score >= 0.8`).
+ */
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, PartialOrd, Default)]
+#[cfg_attr(
+    feature = "schemars",
+    derive(schemars::JsonSchema),
+    schemars(description = "The recognizer's confidence, nominally from 0 to 1.")
+)]
+#[serde(transparent)]
+pub struct Confidence(pub f64);
+
+impl Confidence {
+    pub fn value(self) -> f64 {
+        self.0
+    }
+
+    /**
+     * Whether the confidence reaches `threshold`.
+     */
+    pub fn is_reliable(self, threshold: f64) -> bool {
+        self.0 >= threshold
+    }
+
+    /**
+     * A total order over confidences, for sorting; see [`f64::total_cmp`].
+     */
+    pub fn total_cmp(&self, other: &Confidence) -> std::cmp::Ordering {
+        self.0.total_cmp(&other.0)
+    }
+}
+
+impl From<f64> for Confidence {
+    fn from(value: f64) -> Self {
+        Confidence(value)
+    }
+}
+
+impl From<Confidence> for f64 {
+    fn from(confidence: Confidence) -> Self {
+        confidence.0
+    }
+}
+
+impl PartialEq<f64> for Confidence {
+    fn eq(&self, other: &f64) -> bool {
+        self.0 == *other
+    }
+}
+
+impl PartialOrd<f64> for Confidence {
+    fn partial_cmp(&self, other: &f64) -> Option<std::cmp::Ordering> {
+        self.0.partial_cmp(other)
+    }
+}
+
+impl fmt::Display for Confidence {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(&self.0, f)
+    }
+}
+
+fn sorted_scores(data: &[ContentData]) -> Vec<f64> {
+    let mut scores: Vec<f64> = data.iter().map(|c| c.score.0).collect();
+    scores.sort_by(f64::total_cmp);
+    scores
+}
+
+/**
+ * The `p`-th percentile (`0.0..=100.0`) of the confidences in `data`, by linear interpolation between the closest
+ * ranks. `None` if `data` is empty.
+ */
+pub fn percentile(data: &[ContentData], p: f64) -> Option<Confidence> {
+    let scores = sorted_scores(data);
+    if scores.is_empty() {
+        return None;
+    }
+    let rank = p.clamp(0.0, 100.0) / 100.0 * (scores.len() - 1) as f64;
+    let (low, high) = (rank.floor() as usize, rank.ceil() as usize);
+    let value = scores[low] + (scores[high] - scores[low]) * (rank - low as f64);
+    Some(Confidence(value))
+}
+
+/**
+ * Summary statistics of the confidences of a result set.
+ */
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ConfidenceStats {
+    pub count: usize,
+    pub min: Confidence,
+    pub max: Confidence,
+    pub mean: Confidence,
+    pub median: Confidence,
+}
+
+impl ConfidenceStats {
+    /**
+     * Statistics over `data`, or `None` if it is empty.
+     */
+    pub fn of(data: &[ContentData]) -> Option<ConfidenceStats> {
+        let scores = sorted_scores(data);
+        Some(ConfidenceStats {
+            count: scores.len(),
+            min: Confidence(*scores.first()?),
+            max: Confidence(*scores.last()?),
+            mean: Confidence(scores.iter().sum::<f64>() / scores.len() as f64),
+            median: percentile(data, 50.0)?,
+        })
+    }
+}
+
+/**
+ * Counts the confidences of `data` in `bins` equal-width buckets over `0.0..=1.0`. Values outside that range go
+ * into the first or last bucket.
+ */
+pub fn histogram(data: &[ContentData], bins: usize) -> Vec<usize> {
+    let mut counts = vec![0; bins];
+    if bins == 0 {
+        return counts;
+    }
+    for c in data {
+        let bin = (c.score.0.clamp(0.0, 1.0) * bins as f64) as usize;
+        counts[bin.min(bins - 1)] += 1;
+    }
+    counts
+}
+
+/**
+ * A calibration curve mapping the raw confidences of a particular model to calibrated ones, e.g. to the observed
+ * accuracy at that confidence, so one threshold means the same across models.
+ *
+ * The curve is piecewise linear through the given `(raw, calibrated)` points and flat beyond the outermost ones.
+ * It can be registered with [`Ppocr::add_transform`](crate::Ppocr::add_transform) to calibrate every result.
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct Calibration {
+    points: Vec<(f64, f64)>,
+}
+
+impl Calibration {
+    /**
+     * Creates a curve through `points`, which are sorted by their raw value. `None` if there are no points or any
+     * is NaN.
+     */
+    pub fn new(mut points: Vec<(f64, f64)>) -> Option<Calibration> {
+        if points.is_empty() || points.iter().any(|(r, c)| r.is_nan() || c.is_nan()) {
+            return None;
+        }
+        points.sort_by(|a, b| a.0.total_cmp(&b.0));
+        Some(Calibration { points })
+    }
+
+    pub fn apply(&self, confidence: Confidence) -> Confidence {
+        let x = confidence.0;
+        let upper = self.points.partition_point(|(raw, _)| *raw < x);
+        let value = match (upper.checked_sub(1), self.points.get(upper)) {
+            (None, Some(&(_, first))) => first,
+            (Some(i), None) => self.points[i].1,
+            (Some(i), Some(&(x1, y1))) => {
+                let (x0, y0) = self.points[i];
+                y0 + (y1 - y0) * (x - x0) / (x1 - x0)
+            }
+            (None, None) => x,
+        };
+        Confidence(value)
+    }
+}
+
+impl ResultTransform for Calibration {
+    fn transform(&self, mut data: Vec<ContentData>) -> Vec<ContentData> {
+        for c in &mut data {
+            c.score = self.apply(c.score);
+        }
+        data
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{histogram, percentile, Calibration, Confidence, ConfidenceStats};
+    use crate::{detection, ContentData};
+
+    fn with_scores(scores: &[f64]) -> Vec<ContentData> {
+        scores
+            .iter()
+            .map(|&s| detection("", [[0, 0]; 4], s))
+            .collect()
+    }
+
+    #[test]
+    fn statistics() {
+        let data = with_scores(&[0.9, 0.5, 1.0, 0.7]);
+        assert_eq!(percentile(&data, 0.0), Some(Confidence(0.5)));
+        assert_eq!(percentile(&data, 100.0), Some(Confidence(1.0)));
+        assert!((percentile(&data, 50.0).unwrap().value() - 0.8).abs() < 1e-9);
+        let stats = ConfidenceStats::of(&data).unwrap();
+        assert_eq!((stats.count, stats.min.0, stats.max.0), (4, 0.5, 1.0));
+        assert!((stats.mean.0 - 0.775).abs() < 1e-9);
+        assert_eq!(ConfidenceStats::of(&[]), None);
+        assert_eq!(histogram(&data, 4), [0, 0, 2, 2]);
+        assert!(data[0].score.is_reliable(0.9) && data[0].score > 0.8);
+    }
+
+    #[test]
+    fn calibration() {
+        let curve = Calibration::new(vec![(0.9, 0.8), (0.5, 0.2)]).unwrap();
+        assert_eq!(curve.apply(Confidence(0.1)), 0.2);
+        assert!((curve.apply(Confidence(0.7)).0 - 0.5).abs() < 1e-9);
+        assert_eq!(curve.apply(Confidence(0.9)), 0.8);
+        assert_eq!(curve.apply(Confidence(1.0)), 0.8);
+        assert!(Calibration::new(Vec::new()).is_none());
+    }
+}
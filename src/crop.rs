@@ -0,0 +1,247 @@
+//! Cutting detections out of an image, to save text snippets or feed them to other models. Images are [`Frame`]s, or
+//! `image::DynamicImage`s with the `image` feature.
+
+use crate::frame::{Frame, Image};
+use crate::geometry::{homography, RectangleExt, UNIT_SQUARE};
+use crate::{ContentData, Rectangle};
+
+/**
+ * How [`crop_detections`] cuts out each detection.
+ */
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CropOptions {
+    /// Pixels added around each box, which recognizers usually do better with.
+    pub margin: usize,
+    /// Whether to straighten each box into an upright rectangle, undoing rotation, slant and perspective, rather
+    /// than cutting out its axis-aligned bounds.
+    pub rectify: bool,
+}
+
+impl CropOptions {
+    /**
+     * Axis-aligned crops without margin.
+     */
+    pub fn new() -> CropOptions {
+        CropOptions::default()
+    }
+
+    /**
+     * Sets [`margin`](CropOptions::margin).
+     */
+    pub fn margin(mut self, margin: usize) -> CropOptions {
+        self.margin = margin;
+        self
+    }
+
+    /**
+     * Sets [`rectify`](CropOptions::rectify).
+     */
+    pub fn rectify(mut self, rectify: bool) -> CropOptions {
+        self.rectify = rectify;
+        self
+    }
+}
+
+/**
+Cuts each detection out of `image`, as the engine reported it on that image, into images of the same type.
+Detections entirely outside the image are left out.
+
+# Examples
+
+```no_run
+use paddleocr::crop::{crop_detections, CropOptions};
+use paddleocr::frame::{Frame, PixelFormat};
+use paddleocr::OcrEngine;
+
+let (width, height, rgba) = load_rgba("scan.png");
+let frame = Frame::new(width, height, PixelFormat::Rgba, rgba).unwrap();
+let mut p = paddleocr::Ppocr::new(".../PaddleOCR-json.exe", None).unwrap();
+let results = p.ocr_and_parse(paddleocr::ImageData::from_path("scan.png")).unwrap();
+let options = CropOptions::new().margin(4).rectify(true);
+for (i, (content, snippet)) in crop_detections(&frame, &results, options).into_iter().enumerate() {
+    std::fs::write(format!("{}.bmp", i), snippet.encode_bmp(snippet.bounds())).unwrap();
+}
+```
+
+With the `image` feature, decoded images work the same:
+
+```no_run
+use paddleocr::crop::{crop_detections, CropOptions};
+use paddleocr::OcrEngine;
+
+let scan = image::open("scan.png").unwrap();
+let mut p = paddleocr::Ppocr::new(".../PaddleOCR-json.exe", None).unwrap();
+let results = p.ocr_and_parse(paddleocr::ImageData::from_path("scan.png")).unwrap();
+for (i, (_, snippet)) in crop_detections(&scan, &results, CropOptions::new()).into_iter().enumerate() {
+    snippet.save(format!("{}.png", i)).unwrap();
+}
+```
+*/
+pub fn crop_detections<I: Image>(
+    image: &I,
+    results: &[ContentData],
+    options: CropOptions,
+) -> Vec<(ContentData, I)> {
+    let frame = image.to_frame();
+    results
+        .iter()
+        .filter_map(|content| {
+            let snippet = crop(&frame, &content.rect, options)?;
+            Some((content.clone(), I::from_frame(snippet)))
+        })
+        .collect()
+}
+
+/**
+ * Cuts the quadrilateral `rect` out of `frame`, or `None` if it lies entirely outside.
+ */
+pub fn crop(frame: &Frame, rect: &Rectangle, options: CropOptions) -> Option<Frame> {
+    let rect = rect.expand(options.margin);
+    let bounds = rect.aabb().intersection(&frame.bounds())?;
+    let corners = rect.map(|[x, y]| [x as f64, y as f64]);
+    // Boxes too degenerate to straighten, such as those of zero height, are cut out as they are.
+    let project = options
+        .rectify
+        .then(|| homography(UNIT_SQUARE, corners))
+        .flatten();
+    let Some(project) = project else {
+        let mut data = Vec::with_capacity(bounds.area() * 4);
+        for y in bounds.y..bounds.bottom() {
+            let start = (y * frame.width + bounds.x) * 4;
+            data.extend(&frame.data[start..start + bounds.w * 4]);
+        }
+        return Frame::new(bounds.w, bounds.h, frame.format, data);
+    };
+
+    let distance = |a: usize, b: usize| {
+        let ([ax, ay], [bx, by]) = (corners[a], corners[b]);
+        (bx - ax).hypot(by - ay)
+    };
+    let width = (distance(0, 1).max(distance(3, 2)).round() as usize).max(1);
+    let height = (distance(0, 3).max(distance(1, 2)).round() as usize).max(1);
+    let mut data = Vec::with_capacity(width * height * 4);
+    for j in 0..height {
+        for i in 0..width {
+            let (u, v) = (
+                (i as f64 + 0.5) / width as f64,
+                (j as f64 + 0.5) / height as f64,
+            );
+            let [x, y] = project([u, v]);
+            data.extend(sample(frame, x, y));
+        }
+    }
+    Frame::new(width, height, frame.format, data)
+}
+
+/// The pixel at `(x, y)`, interpolated bilinearly between the nearest four, clamped to the frame's edges.
+fn sample(frame: &Frame, x: f64, y: f64) -> [u8; 4] {
+    let clamp = |value: f64, size: usize| value.clamp(0.0, (size - 1) as f64);
+    let (x, y) = (clamp(x - 0.5, frame.width), clamp(y - 0.5, frame.height));
+    let (x0, y0) = (x.floor() as usize, y.floor() as usize);
+    let (x1, y1) = (
+        (x0 + 1).min(frame.width - 1),
+        (y0 + 1).min(frame.height - 1),
+    );
+    let (tx, ty) = (x - x0 as f64, y - y0 as f64);
+    let pixel = |x: usize, y: usize| &frame.data[(y * frame.width + x) * 4..][..4];
+    let mut blended = [0; 4];
+    for (channel, value) in blended.iter_mut().enumerate() {
+        let top = pixel(x0, y0)[channel] as f64 * (1.0 - tx) + pixel(x1, y0)[channel] as f64 * tx;
+        let bottom =
+            pixel(x0, y1)[channel] as f64 * (1.0 - tx) + pixel(x1, y1)[channel] as f64 * tx;
+        *value = (top * (1.0 - ty) + bottom * ty).round() as u8;
+    }
+    blended
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{crop, crop_detections, CropOptions};
+    use crate::frame::{Frame, PixelFormat};
+    use crate::{Confidence, ContentData};
+
+    /// A frame whose pixels encode their own position: red is x, green is y.
+    fn gradient(width: usize, height: usize) -> Frame {
+        let mut data = Vec::new();
+        for y in 0..height {
+            for x in 0..width {
+                data.extend([x as u8, y as u8, 0, 255]);
+            }
+        }
+        Frame::new(width, height, PixelFormat::Rgba, data).unwrap()
+    }
+
+    fn at(frame: &Frame, x: usize, y: usize) -> [u8; 2] {
+        let i = (y * frame.width + x) * 4;
+        [frame.data[i], frame.data[i + 1]]
+    }
+
+    #[test]
+    fn crops() {
+        let frame = gradient(100, 50);
+        let item = |rect| ContentData {
+            rect,
+            score: Confidence(0.9),
+            text: "a".to_string(),
+            orientation: None,
+            extra: Default::default(),
+        };
+        let results = [
+            item([[10, 20], [30, 20], [30, 30], [10, 30]]),
+            item([[200, 200], [210, 200], [210, 210], [200, 210]]),
+        ];
+        let crops = crop_detections(&frame, &results, CropOptions::new().margin(2));
+        assert_eq!(crops.len(), 1);
+        let snippet = &crops[0].1;
+        assert_eq!((snippet.width, snippet.height), (24, 14));
+        assert_eq!(at(snippet, 0, 0), [8, 18]);
+
+        // Clipped to the frame.
+        let edge = crop(
+            &frame,
+            &[[90, 40], [120, 40], [120, 60], [90, 60]],
+            CropOptions::new(),
+        )
+        .unwrap();
+        assert_eq!((edge.width, edge.height), (10, 10));
+
+        // A box tilted by 45°, straightened: its top edge runs from (20, 10) down to (30, 20).
+        let tilted = [[20, 10], [30, 20], [25, 25], [15, 15]];
+        let straight = crop(&frame, &tilted, CropOptions::new().rectify(true)).unwrap();
+        assert_eq!((straight.width, straight.height), (14, 7));
+        let [x, y] = at(&straight, 0, 0);
+        assert!(x.abs_diff(20) <= 1 && y.abs_diff(10) <= 1, "{:?}", (x, y));
+        let [x, y] = at(&straight, 13, 0);
+        assert!(x.abs_diff(29) <= 1 && y.abs_diff(20) <= 1, "{:?}", (x, y));
+        let [x, y] = at(&straight, 0, 6);
+        assert!(x.abs_diff(15) <= 1 && y.abs_diff(15) <= 1, "{:?}", (x, y));
+    }
+
+    #[test]
+    #[cfg(feature = "image")]
+    fn images() {
+        use image::{DynamicImage, Rgb, RgbImage, Rgba};
+
+        use crate::frame::{Image, PixelFormat};
+
+        let image =
+            DynamicImage::ImageRgb8(RgbImage::from_fn(40, 20, |x, y| Rgb([x as u8, y as u8, 7])));
+        let results = [ContentData {
+            rect: [[10, 5], [20, 5], [20, 15], [10, 15]],
+            score: Confidence(0.9),
+            text: "a".to_string(),
+            orientation: None,
+            extra: Default::default(),
+        }];
+        let crops = crop_detections(&image, &results, CropOptions::new());
+        assert_eq!(crops.len(), 1);
+        let snippet = crops[0].1.to_rgba8();
+        assert_eq!((snippet.width(), snippet.height()), (10, 10));
+        assert_eq!(*snippet.get_pixel(0, 0), Rgba([10, 5, 7, 255]));
+
+        // Frames captured as BGRA come out as RGBA.
+        let bgra = Frame::new(1, 1, PixelFormat::Bgra, vec![1, 2, 3, 4]).unwrap();
+        let converted = DynamicImage::from_frame(bgra).to_rgba8();
+        assert_eq!(*converted.get_pixel(0, 0), Rgba([3, 2, 1, 4]));
+    }
+}
@@ -0,0 +1,119 @@
+//! Comparing the results of two captures of the same screen or document, for tools that react to changes.
+
+use crate::geometry::iou;
+use crate::ContentData;
+
+/// How much two boxes must overlap to be taken for the same place on screen.
+const MATCH_IOU: f64 = 0.3;
+
+/**
+ * What changed between two results.
+ */
+#[derive(Debug, Clone, Default)]
+pub struct ResultDiff {
+    /// Detections in the new results with no counterpart in the old ones.
+    pub added: Vec<ContentData>,
+    /// Detections in the old results with no counterpart in the new ones.
+    pub removed: Vec<ContentData>,
+    /// Detections at the same place whose text differs, as `(old, new)`.
+    pub changed: Vec<(ContentData, ContentData)>,
+}
+
+impl ResultDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+/**
+Compares two results, pairing detections whose boxes overlap by an IoU of at least 0.3, the best overlapping pairs
+first. Paired detections with the same text are unchanged, whatever their scores, and are left out.
+
+# Examples
+
+```no_run
+use paddleocr::OcrEngine;
+
+let mut p = paddleocr::Ppocr::new(".../PaddleOCR-json.exe", None).unwrap();
+let mut previous = Vec::new();
+loop {
+    let current = p.ocr_and_parse(paddleocr::ImageData::from_path("clipboard")).unwrap_or_default();
+    let changes = paddleocr::diff::diff(&previous, &current);
+    for (old, new) in &changes.changed {
+        println!("{} -> {}", old.text, new.text);
+    }
+    previous = current;
+}
+```
+*/
+pub fn diff(old: &[ContentData], new: &[ContentData]) -> ResultDiff {
+    let mut pairs: Vec<(f64, usize, usize)> = Vec::new();
+    for (i, a) in old.iter().enumerate() {
+        for (j, b) in new.iter().enumerate() {
+            let overlap = iou(&a.rect, &b.rect);
+            if overlap >= MATCH_IOU {
+                pairs.push((overlap, i, j));
+            }
+        }
+    }
+    pairs.sort_by(|a, b| b.0.total_cmp(&a.0));
+
+    let mut old_matched = vec![false; old.len()];
+    let mut new_matched = vec![false; new.len()];
+    let mut result = ResultDiff::default();
+    for (_, i, j) in pairs {
+        if old_matched[i] || new_matched[j] {
+            continue;
+        }
+        old_matched[i] = true;
+        new_matched[j] = true;
+        if old[i].text != new[j].text {
+            result.changed.push((old[i].clone(), new[j].clone()));
+        }
+    }
+    result.removed = unmatched(old, &old_matched);
+    result.added = unmatched(new, &new_matched);
+    result
+}
+
+fn unmatched(results: &[ContentData], matched: &[bool]) -> Vec<ContentData> {
+    results
+        .iter()
+        .zip(matched)
+        .filter(|(_, &matched)| !matched)
+        .map(|(c, _)| c.clone())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::diff;
+    use crate::{detection, ContentData};
+
+    fn item(text: &str, x: usize, y: usize) -> ContentData {
+        let rect = [[x, y], [x + 100, y], [x + 100, y + 20], [x, y + 20]];
+        detection(text, rect, 0.9)
+    }
+
+    #[test]
+    fn changes() {
+        let old = [
+            item("12:00", 0, 0),
+            item("Hello", 0, 100),
+            item("gone", 0, 200),
+        ];
+        let new = [
+            item("Hello", 2, 101),
+            item("12:01", 0, 0),
+            item("new", 300, 300),
+        ];
+        let changes = diff(&old, &new);
+        assert_eq!(changes.changed.len(), 1);
+        assert_eq!(changes.changed[0].0.text, "12:00");
+        assert_eq!(changes.changed[0].1.text, "12:01");
+        assert_eq!(changes.removed[0].text, "gone");
+        assert_eq!(changes.added[0].text, "new");
+
+        assert!(diff(&old, &old).is_empty());
+    }
+}
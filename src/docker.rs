@@ -0,0 +1,165 @@
+use std::error::Error;
+use std::ffi::OsStr;
+use std::io::Result as IoResult;
+use std::process;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::{ImageData, OcrEngine, Ppocr, Terminator};
+
+static CONTAINER_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+/**
+ * A paddleocr-json engine running inside a Docker container, talked to over `docker run -i` / `docker exec -i`.
+ *
+ * This lets Linux and macOS hosts use the Linux build of the engine without installing it locally.
+ */
+pub struct DockerPpocr {
+    inner: Ppocr,
+    /// Name of the container started by [`DockerPpocr::run`], removed on drop.
+    container: Option<String>,
+}
+
+impl DockerPpocr {
+    /**
+    Starts a new container from `image` and waits for the engine in it to initialize.
+
+    `engine_command` overrides the image's default command, e.g. to pass `--config_path`; leave it empty to use the
+    image's own. The container is removed when the instance is dropped.
+
+    # Examples
+
+    ```no_run
+    use paddleocr::OcrEngine;
+
+    let mut p = paddleocr::DockerPpocr::run("paddleocr-json", ["./PaddleOCR-json"]).unwrap();
+    println!("{}", p.ocr(std::path::Path::new("/data/test.png").into()).unwrap());
+    ```
+    */
+    pub fn run<I, S>(image: &str, engine_command: I) -> Result<DockerPpocr, Box<dyn Error>>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<OsStr>,
+    {
+        let name = format!(
+            "paddleocr-{}-{}",
+            process::id(),
+            CONTAINER_COUNTER.fetch_add(1, Ordering::Relaxed)
+        );
+        let mut inner = Ppocr::from_command(run_command(&name, image, engine_command))?;
+        inner.local = false;
+        Ok(DockerPpocr {
+            inner,
+            container: Some(name),
+        })
+    }
+
+    /**
+    Starts a new engine process inside an already running container via `docker exec`.
+
+    The container itself is left running when the instance is dropped.
+
+    # Examples
+
+    ```no_run
+    let mut p = paddleocr::DockerPpocr::attach("my-ocr-container", ["./PaddleOCR-json"]).unwrap();
+    ```
+    */
+    pub fn attach<I, S>(container: &str, engine_command: I) -> Result<DockerPpocr, Box<dyn Error>>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<OsStr>,
+    {
+        let mut inner = Ppocr::from_command(exec_command(container, engine_command))?;
+        inner.local = false;
+        Ok(DockerPpocr {
+            inner,
+            container: None,
+        })
+    }
+
+    /**
+     * The name of the container started by [`DockerPpocr::run`], or `None` if attached to an existing one.
+     */
+    pub fn container(&self) -> Option<&str> {
+        self.container.as_deref()
+    }
+}
+
+fn run_command<I, S>(name: &str, image: &str, engine_command: I) -> process::Command
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<OsStr>,
+{
+    let mut command = process::Command::new("docker");
+    command
+        .args(["run", "-i", "--rm", "--name", name, image])
+        .args(engine_command);
+    command
+}
+
+fn exec_command<I, S>(container: &str, engine_command: I) -> process::Command
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<OsStr>,
+{
+    let mut command = process::Command::new("docker");
+    command.args(["exec", "-i", container]).args(engine_command);
+    command
+}
+
+impl OcrEngine for DockerPpocr {
+    fn ocr(&mut self, image: ImageData) -> IoResult<String> {
+        self.inner.ocr(image)
+    }
+
+    fn terminator(&self) -> Option<Terminator> {
+        self.inner.terminator()
+    }
+}
+
+impl Drop for DockerPpocr {
+    /**
+     * Remove the container started by `run`; killing the `docker` client alone would leave it running.
+     */
+    fn drop(&mut self) {
+        if let Some(name) = &self.container {
+            process::Command::new("docker")
+                .args(["rm", "-f", name])
+                .stdout(process::Stdio::null())
+                .stderr(process::Stdio::null())
+                .status()
+                .err();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{exec_command, run_command};
+
+    fn args(command: &std::process::Command) -> Vec<&str> {
+        command.get_args().map(|a| a.to_str().unwrap()).collect()
+    }
+
+    #[test]
+    fn commands() {
+        let run = run_command("c", "img", ["./PaddleOCR-json", "--config_path=x"]);
+        assert_eq!(run.get_program(), "docker");
+        assert_eq!(
+            args(&run),
+            [
+                "run",
+                "-i",
+                "--rm",
+                "--name",
+                "c",
+                "img",
+                "./PaddleOCR-json",
+                "--config_path=x"
+            ]
+        );
+
+        let exec = exec_command("c", ["./PaddleOCR-json"]);
+        assert_eq!(args(&exec), ["exec", "-i", "c", "./PaddleOCR-json"]);
+    }
+}
@@ -0,0 +1,214 @@
+use std::collections::HashMap;
+use std::io::Result as IoResult;
+
+use crate::geometry::iou;
+use crate::{Confidence, ContentData, ImageData, OcrEngine, OcrRec};
+
+/**
+ * Sends every image to several engines and merges their results, trading latency for accuracy on hard images.
+ *
+ * Detections from different engines are matched by the overlap of their boxes. Within each match the text is
+ * decided by confidence-weighted voting: the text whose detections have the highest total score wins, and its
+ * score becomes that total divided by the number of engines that answered, so text the engines agree on ranks
+ * above text only one of them saw.
+ */
+pub struct Ensemble {
+    engines: Vec<Box<dyn OcrEngine>>,
+    iou_threshold: f64,
+    min_votes: usize,
+}
+
+impl Ensemble {
+    /**
+    Creates an ensemble over `engines`, matching boxes with an IoU of at least `0.5` and keeping every match.
+
+    # Examples
+
+    ```no_run
+    use paddleocr::OcrEngine;
+
+    let mut ensemble = paddleocr::Ensemble::new(vec![
+        Box::new(paddleocr::Ppocr::new(".../v1.4.0/PaddleOCR-json.exe", None).unwrap()),
+        Box::new(paddleocr::Ppocr::new(".../v1.3.1/PaddleOCR-json.exe", None).unwrap()),
+    ]);
+    println!("{:?}", ensemble.ocr_and_parse(Path::new(".../test.png").into()));
+    ```
+    */
+    pub fn new(engines: Vec<Box<dyn OcrEngine>>) -> Ensemble {
+        Ensemble {
+            engines,
+            iou_threshold: 0.5,
+            min_votes: 1,
+        }
+    }
+
+    /**
+     * Sets the minimum IoU of two boxes for them to be considered the same text.
+     */
+    pub fn iou_threshold(mut self, threshold: f64) -> Ensemble {
+        self.iou_threshold = threshold;
+        self
+    }
+
+    /**
+     * Drops merged detections that fewer than `votes` engines agreed on.
+     */
+    pub fn min_votes(mut self, votes: usize) -> Ensemble {
+        self.min_votes = votes;
+        self
+    }
+
+    fn merge(&self, results: Vec<Vec<ContentData>>) -> Vec<ContentData> {
+        let answered = results.len();
+        // Each cluster holds at most one detection per engine.
+        let mut clusters: Vec<Vec<(usize, ContentData)>> = Vec::new();
+        for (engine, detections) in results.into_iter().enumerate() {
+            for detection in detections {
+                let best = clusters
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, c)| c.iter().all(|(e, _)| *e != engine))
+                    .map(|(i, c)| (i, iou(&c[0].1.rect, &detection.rect)))
+                    .filter(|(_, overlap)| *overlap >= self.iou_threshold)
+                    .max_by(|a, b| a.1.total_cmp(&b.1));
+                match best {
+                    Some((i, _)) => clusters[i].push((engine, detection)),
+                    None => clusters.push(vec![(engine, detection)]),
+                }
+            }
+        }
+
+        clusters
+            .into_iter()
+            .filter(|c| c.len() >= self.min_votes)
+            .filter_map(|cluster| {
+                let mut votes: HashMap<&str, f64> = HashMap::new();
+                for (_, d) in &cluster {
+                    *votes.entry(&d.text).or_default() += d.score.value();
+                }
+                let (text, weight) = votes.into_iter().max_by(|a, b| a.1.total_cmp(&b.1))?;
+                let representative = cluster
+                    .iter()
+                    .filter(|(_, d)| d.text == text)
+                    .max_by(|a, b| a.1.score.total_cmp(&b.1.score))?;
+                Some(ContentData {
+                    rect: representative.1.rect,
+                    score: Confidence(weight / answered as f64),
+                    text: text.to_string(),
+                    orientation: representative.1.orientation,
+                    extra: representative.1.extra.clone(),
+                })
+            })
+            .collect()
+    }
+}
+
+impl OcrEngine for Ensemble {
+    /**
+     * OCRs the image with every engine and returns the merged result in the engine's response format.
+     *
+     * Engines that fail or return an error code are left out of the vote; if none succeeds, the first engine's
+     * response (or error) is returned as is.
+     */
+    fn ocr(&mut self, image: ImageData) -> IoResult<String> {
+        let mut first = None;
+        let mut results = Vec::new();
+        for engine in &mut self.engines {
+            let response = engine.ocr(image.clone());
+            match response.as_deref().map(serde_json::from_str::<OcrRec>) {
+                Ok(Ok(OcrRec::Content { data, .. })) => results.push(data),
+                Ok(Ok(OcrRec::Message { code: 101, .. })) => results.push(Vec::new()),
+                _ => {}
+            }
+            first.get_or_insert(response);
+        }
+        if results.is_empty() {
+            return first.unwrap_or_else(|| Err(std::io::Error::other("Ensemble has no engines")));
+        }
+
+        let merged = self.merge(results);
+        let response = if merged.is_empty() {
+            OcrRec::Message {
+                code: 101,
+                data: "No text found in image.".to_string(),
+            }
+        } else {
+            OcrRec::Content {
+                code: 100,
+                data: merged,
+            }
+        };
+        serde_json::to_string(&response).map_err(std::io::Error::other)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::{prop_assert_eq, proptest};
+
+    use super::Ensemble;
+    use crate::testing::strategy;
+    use crate::{ImageData, OcrEngine};
+
+    struct Canned(&'static str);
+    impl OcrEngine for Canned {
+        fn ocr(&mut self, _image: ImageData) -> std::io::Result<String> {
+            Ok(self.0.to_string())
+        }
+    }
+
+    #[test]
+    fn voting() {
+        let mut ensemble = Ensemble::new(vec![
+            Box::new(Canned(
+                r#"{"code":100,"data":[{"box":[[0,0],[100,0],[100,20],[0,20]],"score":0.6,"text":"he1lo"},{"box":[[0,50],[100,50],[100,70],[0,70]],"score":0.9,"text":"only one"}]}"#,
+            )),
+            Box::new(Canned(
+                r#"{"code":100,"data":[{"box":[[2,1],[101,1],[101,21],[2,21]],"score":0.9,"text":"hello"}]}"#,
+            )),
+            Box::new(Canned(
+                r#"{"code":100,"data":[{"box":[[1,0],[99,0],[99,20],[1,20]],"score":0.8,"text":"hello"}]}"#,
+            )),
+            Box::new(Canned(
+                r#"{"code":200,"data":"Image path dose not exist."}"#,
+            )),
+        ]);
+        let merged = ensemble
+            .ocr_and_parse(ImageData::from_path("a.png"))
+            .unwrap();
+        assert_eq!(merged.len(), 2);
+        assert_eq!(merged[0].text, "hello");
+        assert_eq!(merged[0].rect, [[2, 1], [101, 1], [101, 21], [2, 21]]);
+        assert!((merged[0].score.value() - 1.7 / 3.0).abs() < 1e-9);
+        assert!((merged[1].score.value() - 0.3).abs() < 1e-9);
+
+        let mut ensemble = ensemble.min_votes(2);
+        let merged = ensemble
+            .ocr_and_parse(ImageData::from_path("a.png"))
+            .unwrap();
+        assert_eq!(merged.len(), 1);
+    }
+
+    proptest! {
+        #[test]
+        fn single_engine_properties(data in strategy::results(20)) {
+            let merged = Ensemble::new(Vec::new()).merge(vec![data.clone()]);
+            // with one engine nothing is merged, and every detection wins its own vote
+            prop_assert_eq!(merged.len(), data.len());
+            for (m, d) in merged.iter().zip(&data) {
+                prop_assert_eq!((m.rect, m.score, &m.text), (d.rect, d.score, &d.text));
+            }
+        }
+    }
+
+    #[test]
+    fn all_failed() {
+        let mut ensemble = Ensemble::new(vec![Box::new(Canned(
+            r#"{"code":211,"data":"Clipboard is empty."}"#,
+        ))]);
+        assert_eq!(
+            ensemble.ocr(ImageData::from_path("clipboard")).unwrap(),
+            r#"{"code":211,"data":"Clipboard is empty."}"#
+        );
+    }
+}
@@ -0,0 +1,364 @@
+//! Typed errors, so that failures can be told apart without matching on messages.
+
+use std::error::Error;
+use std::fmt;
+use std::io;
+
+/**
+ * Why OCRing an image failed, as returned by [`OcrEngine::try_ocr`](crate::OcrEngine::try_ocr).
+ */
+#[derive(Debug)]
+pub enum PpocrError {
+    /// Talking to the engine failed, e.g. because it exited.
+    Io(io::Error),
+    /// The engine's response is not the JSON it should be.
+    Parse {
+        raw: String,
+        source: serde_json::Error,
+    },
+    /// The engine couldn't read the clipboard.
+    Clipboard {
+        error: ClipboardError,
+        code: u32,
+        message: String,
+        raw: String,
+    },
+    /// The engine couldn't make sense of the request.
+    Protocol {
+        error: ProtocolError,
+        code: u32,
+        message: String,
+        raw: String,
+    },
+    /// The engine answered with a code other than 100, including 101 when it found no text.
+    Engine {
+        code: u32,
+        message: String,
+        raw: String,
+    },
+}
+
+/// Whether an I/O error of this kind means the engine exited, hung or dropped the connection, rather than that the
+/// request was at fault.
+pub(crate) fn is_retryable_kind(kind: io::ErrorKind) -> bool {
+    matches!(
+        kind,
+        io::ErrorKind::BrokenPipe
+            | io::ErrorKind::UnexpectedEof
+            | io::ErrorKind::TimedOut
+            | io::ErrorKind::Interrupted
+            | io::ErrorKind::WouldBlock
+            | io::ErrorKind::ConnectionReset
+            | io::ErrorKind::ConnectionAborted
+            | io::ErrorKind::ConnectionRefused
+    )
+}
+
+impl PpocrError {
+    /// The engine's error, or a clipboard or protocol error, for a response with `code` and `message`.
+    pub(crate) fn from_response(code: u32, message: String, raw: String) -> PpocrError {
+        if let Some(error) = ClipboardError::from_code(code) {
+            return PpocrError::Clipboard {
+                error,
+                code,
+                message,
+                raw,
+            };
+        }
+        match ProtocolError::from_response(code, &message) {
+            Some(error) => PpocrError::Protocol {
+                error,
+                code,
+                message,
+                raw,
+            },
+            None => PpocrError::Engine { code, message, raw },
+        }
+    }
+
+    /**
+     * The engine's status code, if it answered.
+     */
+    pub fn code(&self) -> Option<u32> {
+        match self {
+            PpocrError::Clipboard { code, .. }
+            | PpocrError::Protocol { code, .. }
+            | PpocrError::Engine { code, .. } => Some(*code),
+            PpocrError::Io(_) | PpocrError::Parse { .. } => None,
+        }
+    }
+
+    /**
+     * Whether the same request may well succeed if sent again: the engine exited, hung or garbled its response,
+     * which a restart fixes, or another program held the clipboard. Failures caused by the image are not.
+     */
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            PpocrError::Io(e) => is_retryable_kind(e.kind()),
+            PpocrError::Parse { .. } => true,
+            PpocrError::Clipboard { error, .. } => *error == ClipboardError::Busy,
+            PpocrError::Protocol { .. } | PpocrError::Engine { .. } => false,
+        }
+    }
+
+    /**
+     * Whether the request itself is at fault, what an HTTP server would answer with a 4xx: the image is missing,
+     * unreadable or not an image, its path can't be passed to the engine, or the clipboard holds no usable image.
+     * No text being found (code 101) is not an error of the input.
+     */
+    pub fn is_input_error(&self) -> bool {
+        match self {
+            PpocrError::Io(e) => matches!(
+                e.kind(),
+                io::ErrorKind::NotFound | io::ErrorKind::InvalidInput
+            ),
+            // Sent requests are well-formed, so a malformed one is a bug rather than bad input.
+            PpocrError::Parse { .. } | PpocrError::Protocol { .. } => false,
+            PpocrError::Clipboard { error, .. } => *error != ClipboardError::Busy,
+            // Image paths and files (200 to 203), and base64 images (300 and 301).
+            PpocrError::Engine { code, .. } => matches!(code, 200..=203 | 300 | 301),
+        }
+    }
+
+    /**
+     * The engine's response, if there was one.
+     */
+    pub fn raw(&self) -> Option<&str> {
+        match self {
+            PpocrError::Parse { raw, .. }
+            | PpocrError::Clipboard { raw, .. }
+            | PpocrError::Protocol { raw, .. }
+            | PpocrError::Engine { raw, .. } => Some(raw),
+            PpocrError::Io(_) => None,
+        }
+    }
+}
+
+impl fmt::Display for PpocrError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PpocrError::Io(e) => write!(f, "OCR failed: {}", e),
+            PpocrError::Parse { source, .. } => write!(f, "Response JSON parse failed: {}", source),
+            PpocrError::Clipboard { code, message, .. }
+            | PpocrError::Protocol { code, message, .. }
+            | PpocrError::Engine { code, message, .. } => {
+                write!(f, "Error Message {}: {}", code, message)
+            }
+        }
+    }
+}
+
+impl Error for PpocrError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            PpocrError::Io(e) => Some(e),
+            PpocrError::Parse { source, .. } => Some(source),
+            PpocrError::Clipboard { error, .. } => Some(error),
+            PpocrError::Protocol { error, .. } => Some(error),
+            PpocrError::Engine { .. } => None,
+        }
+    }
+}
+
+impl From<io::Error> for PpocrError {
+    fn from(e: io::Error) -> Self {
+        PpocrError::Io(e)
+    }
+}
+
+/**
+ * Why the engine couldn't OCR the clipboard, from codes 210 to 217, phrased for the user.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ClipboardError {
+    /// Another program held the clipboard (codes 210, 213, 215 and 216); trying again usually works.
+    Busy,
+    /// Nothing was copied (code 211).
+    Empty,
+    /// Something other than an image or an image file was copied, such as text (code 212).
+    UnsupportedFormat,
+    /// More than one file was copied (code 214).
+    TooManyFiles,
+    /// The image has other than 1, 3 or 4 channels (code 217).
+    UnsupportedChannels,
+}
+
+impl ClipboardError {
+    /**
+     * The error for an engine code, or `None` if it is not about the clipboard.
+     */
+    pub fn from_code(code: u32) -> Option<ClipboardError> {
+        match code {
+            210 | 213 | 215 | 216 => Some(ClipboardError::Busy),
+            211 => Some(ClipboardError::Empty),
+            212 => Some(ClipboardError::UnsupportedFormat),
+            214 => Some(ClipboardError::TooManyFiles),
+            217 => Some(ClipboardError::UnsupportedChannels),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for ClipboardError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            ClipboardError::Busy => "the clipboard is in use by another program, try again",
+            ClipboardError::Empty => "the clipboard is empty, copy an image first",
+            ClipboardError::UnsupportedFormat => {
+                "the clipboard holds no image, copy an image, not text"
+            }
+            ClipboardError::TooManyFiles => "several files were copied, copy a single image",
+            ClipboardError::UnsupportedChannels => {
+                "the copied image's color format is not supported, save it as PNG or JPEG first"
+            }
+        })
+    }
+}
+impl Error for ClipboardError {}
+
+/**
+ * Why the engine couldn't make sense of a request, from codes 400 to 403. Requests are sanitized before they are
+ * sent, so these point at a bug or an engine build with a stricter parser rather than at the image.
+ */
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ProtocolError {
+    /// The request is not valid JSON (code 400).
+    InvalidJson,
+    /// The engine couldn't encode its result as JSON (code 401).
+    EncodeFailed,
+    /// The value of `key` in the request couldn't be read (code 402), e.g. `image_path`.
+    InvalidKey { key: String },
+    /// The request holds no image (code 403).
+    NoValidTasks,
+}
+
+impl ProtocolError {
+    /**
+     * The error for an engine code and message, such as `Json parse key image_path failed.` for 402, or `None` if
+     * it is not about the request's JSON.
+     */
+    pub fn from_response(code: u32, message: &str) -> Option<ProtocolError> {
+        match code {
+            400 => Some(ProtocolError::InvalidJson),
+            401 => Some(ProtocolError::EncodeFailed),
+            402 => {
+                let key = message
+                    .trim()
+                    .strip_prefix("Json parse key ")
+                    .and_then(|rest| rest.strip_suffix(" failed."))
+                    .unwrap_or_default();
+                Some(ProtocolError::InvalidKey {
+                    key: key.to_string(),
+                })
+            }
+            403 => Some(ProtocolError::NoValidTasks),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for ProtocolError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ProtocolError::InvalidJson => {
+                f.write_str("the engine couldn't parse the request as JSON")
+            }
+            ProtocolError::EncodeFailed => {
+                f.write_str("the engine couldn't encode its result as JSON")
+            }
+            ProtocolError::InvalidKey { key } => {
+                write!(f, "the engine couldn't read `{}` in the request", key)
+            }
+            ProtocolError::NoValidTasks => f.write_str("the request holds no image for the engine"),
+        }
+    }
+}
+impl Error for ProtocolError {}
+
+#[cfg(test)]
+mod tests {
+    use super::{ClipboardError, PpocrError, ProtocolError};
+    use crate::OcrResponse;
+
+    #[test]
+    fn clipboard_codes() {
+        assert_eq!(ClipboardError::from_code(213), Some(ClipboardError::Busy));
+        assert_eq!(ClipboardError::from_code(203), None);
+        let response =
+            OcrResponse::parse(r#"{"code":212,"data":"Clipboard format is not valid."}"#).unwrap();
+        let error = response.clipboard_error().unwrap();
+        assert_eq!(error, ClipboardError::UnsupportedFormat);
+        assert!(error.to_string().contains("not text"));
+    }
+
+    #[test]
+    fn variants() {
+        let error = OcrResponse::parse(r#"{"code":211,"data":"Clipboard is empty."}"#)
+            .unwrap()
+            .try_into_result()
+            .unwrap_err();
+        assert!(matches!(
+            error,
+            PpocrError::Clipboard {
+                error: ClipboardError::Empty,
+                code: 211,
+                ..
+            }
+        ));
+        let raw = r#"{"code":203,"data":"Image decode failed."}"#;
+        let error = OcrResponse::parse(raw)
+            .unwrap()
+            .try_into_result()
+            .unwrap_err();
+        assert_eq!((error.code(), error.raw()), (Some(203), Some(raw)));
+        assert_eq!(error.to_string(), "Error Message 203: Image decode failed.");
+
+        let error =
+            OcrResponse::parse(r#"{"code":402,"data":"Json parse key image_path failed."}"#)
+                .unwrap()
+                .try_into_result()
+                .unwrap_err();
+        let PpocrError::Protocol {
+            error: protocol, ..
+        } = &error
+        else {
+            panic!("{:?}", error);
+        };
+        assert_eq!(
+            *protocol,
+            ProtocolError::InvalidKey {
+                key: "image_path".to_string()
+            }
+        );
+        assert!(!error.is_input_error() && !error.is_retryable());
+        assert_eq!(
+            ProtocolError::from_response(403, "No valid tasks."),
+            Some(ProtocolError::NoValidTasks)
+        );
+        assert_eq!(ProtocolError::from_response(203, ""), None);
+    }
+
+    #[test]
+    fn classification() {
+        let engine = |raw: &str| {
+            OcrResponse::parse(raw)
+                .unwrap()
+                .try_into_result()
+                .unwrap_err()
+        };
+        let decode = engine(r#"{"code":203,"data":"Image decode failed."}"#);
+        assert!(decode.is_input_error() && !decode.is_retryable());
+        let busy = engine(r#"{"code":210,"data":"Clipboard open failed."}"#);
+        assert!(busy.is_retryable() && !busy.is_input_error());
+        let empty = engine(r#"{"code":211,"data":"Clipboard is empty."}"#);
+        assert!(empty.is_input_error() && !empty.is_retryable());
+        let no_text = engine(r#"{"code":101,"data":"No text found in image."}"#);
+        assert!(!no_text.is_input_error() && !no_text.is_retryable());
+
+        let io = |kind| PpocrError::from(std::io::Error::from(kind));
+        assert!(io(std::io::ErrorKind::BrokenPipe).is_retryable());
+        assert!(io(std::io::ErrorKind::UnexpectedEof).is_retryable());
+        assert!(!io(std::io::ErrorKind::InvalidData).is_retryable());
+        assert!(io(std::io::ErrorKind::InvalidInput).is_input_error());
+    }
+}
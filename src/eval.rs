@@ -0,0 +1,563 @@
+//! Measuring accuracy against ground truth, to compare preprocessing options, engine settings or models.
+//!
+//! Accuracy is given as character and word error rates: the edit distance from the expected transcript to the
+//! recognized text, over the length of the transcript. Whitespace runs count as a single space, and the recognized
+//! text is put in reading order with [`layout::lines`](crate::layout::lines), a line per line.
+//!
+//! [`run_dataset`] runs a whole benchmark corpus through a [`PpocrPool`] and reports accuracy and latency, for
+//! regression-testing models and settings:
+//!
+//! ```no_run
+//! let engines = (0..4).map(|_| paddleocr::Ppocr::new(".../PaddleOCR-json.exe", None).unwrap());
+//! let pool = paddleocr::PpocrPool::new(engines);
+//! let report = paddleocr::eval::run_dataset(&pool, "corpus/labels.txt").unwrap();
+//! std::fs::write("report.md", report.to_markdown()).unwrap();
+//! assert!(report.evaluation.cer() < 0.02);
+//! ```
+
+use std::fmt;
+use std::io::{Error as IoError, ErrorKind, Result as IoResult};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Mutex, PoisonError};
+use std::time::{Duration, Instant};
+
+use serde_json::{json, Value};
+
+use crate::{ContentData, PpocrPool};
+
+/**
+ * The Levenshtein distance between two sequences: how many insertions, deletions and substitutions turn `a` into `b`.
+ */
+pub fn edit_distance<T: PartialEq>(a: &[T], b: &[T]) -> usize {
+    // One row of the distance matrix at a time.
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, x) in a.iter().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, y) in b.iter().enumerate() {
+            let substitution = diagonal + usize::from(x != y);
+            diagonal = row[j + 1];
+            row[j + 1] = substitution.min(row[j] + 1).min(diagonal + 1);
+        }
+    }
+    row[b.len()]
+}
+
+fn normalize(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// `errors` over `length`: 0 if both are 0, 1 per error for an empty reference.
+fn rate(errors: usize, length: usize) -> f64 {
+    match (errors, length) {
+        (0, _) => 0.0,
+        (errors, 0) => errors as f64,
+        (errors, length) => errors as f64 / length as f64,
+    }
+}
+
+/**
+ * The character error rate of `hypothesis` against `reference`. It is 0 for a perfect match and can exceed 1 when
+ * much text was made up.
+ */
+pub fn cer(reference: &str, hypothesis: &str) -> f64 {
+    let (reference, hypothesis) = (chars(reference), chars(hypothesis));
+    rate(edit_distance(&reference, &hypothesis), reference.len())
+}
+
+/**
+ * The word error rate of `hypothesis` against `reference`, words being separated by whitespace.
+ */
+pub fn wer(reference: &str, hypothesis: &str) -> f64 {
+    let (reference, hypothesis) = (words(reference), words(hypothesis));
+    rate(edit_distance(&reference, &hypothesis), reference.len())
+}
+
+fn chars(text: &str) -> Vec<char> {
+    normalize(text).chars().collect()
+}
+
+fn words(text: &str) -> Vec<&str> {
+    text.split_whitespace().collect()
+}
+
+/**
+ * The recognized text of `results`, in reading order, a line per line.
+ */
+pub fn transcript(results: &[ContentData]) -> String {
+    crate::layout::lines(results)
+        .iter()
+        .map(|line| line.text())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/**
+ * How well an image was recognized.
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImageReport {
+    pub path: String,
+    pub expected: String,
+    pub recognized: String,
+    /// Character edits needed to turn what was recognized into what was expected.
+    pub char_errors: usize,
+    /// Characters expected, counting whitespace runs as one.
+    pub chars: usize,
+    pub word_errors: usize,
+    pub words: usize,
+}
+
+impl ImageReport {
+    /**
+     * Compares the `results` for the image at `path` with its `expected` transcript.
+     */
+    pub fn new(path: &str, expected: &str, results: &[ContentData]) -> ImageReport {
+        ImageReport::from_text(path, expected, &transcript(results))
+    }
+
+    /**
+     * Compares the `recognized` text of the image at `path` with its `expected` transcript.
+     */
+    pub fn from_text(path: &str, expected: &str, recognized: &str) -> ImageReport {
+        let (expected_chars, recognized_chars) = (chars(expected), chars(recognized));
+        let (expected_words, recognized_words) = (words(expected), words(recognized));
+        ImageReport {
+            path: path.to_string(),
+            expected: expected.to_string(),
+            recognized: recognized.to_string(),
+            char_errors: edit_distance(&expected_chars, &recognized_chars),
+            chars: expected_chars.len(),
+            word_errors: edit_distance(&expected_words, &recognized_words),
+            words: expected_words.len(),
+        }
+    }
+
+    pub fn cer(&self) -> f64 {
+        rate(self.char_errors, self.chars)
+    }
+
+    pub fn wer(&self) -> f64 {
+        rate(self.word_errors, self.words)
+    }
+}
+
+/**
+ * How well a set of images was recognized. The rates are over all images' characters and words together, so that
+ * long transcripts weigh more than short ones.
+ */
+#[derive(Debug, Clone, Default)]
+pub struct Evaluation {
+    pub images: Vec<ImageReport>,
+}
+
+impl Evaluation {
+    pub fn push(&mut self, report: ImageReport) {
+        self.images.push(report);
+    }
+
+    pub fn cer(&self) -> f64 {
+        let errors = self.images.iter().map(|i| i.char_errors).sum();
+        rate(errors, self.images.iter().map(|i| i.chars).sum())
+    }
+
+    pub fn wer(&self) -> f64 {
+        let errors = self.images.iter().map(|i| i.word_errors).sum();
+        rate(errors, self.images.iter().map(|i| i.words).sum())
+    }
+
+    /**
+     * The images, worst character error rate first.
+     */
+    pub fn worst(&self) -> Vec<&ImageReport> {
+        let mut images: Vec<&ImageReport> = self.images.iter().collect();
+        images.sort_by(|a, b| b.cer().total_cmp(&a.cer()));
+        images
+    }
+}
+
+impl fmt::Display for Evaluation {
+    /**
+     * A line per image, worst first, then the totals, e.g. `a.png: CER 12.50%, WER 33.33%`.
+     */
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for image in self.worst() {
+            writeln!(
+                f,
+                "{}: CER {:.2}%, WER {:.2}%",
+                image.path,
+                image.cer() * 100.0,
+                image.wer() * 100.0
+            )?;
+        }
+        writeln!(
+            f,
+            "{} images: CER {:.2}%, WER {:.2}%",
+            self.images.len(),
+            self.cer() * 100.0,
+            self.wer() * 100.0
+        )
+    }
+}
+
+/**
+ * An image of a corpus and what it reads.
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Sample {
+    pub image: PathBuf,
+    pub expected: String,
+}
+
+/**
+ * Reads a corpus manifest, a sample per line, in either of two forms:
+ *
+ * - `{"image": "a.png", "text": "..."}`
+ * - `a.png<TAB>...`, as in PaddleOCR's recognition labels. The text may also be PaddleOCR's detection labels, a JSON
+ *   array of `{"transcription": ..., "points": ...}`, whose transcriptions are joined a line each, leaving out the
+ *   illegible `###` ones.
+ *
+ * Relative image paths are relative to the manifest. Empty lines are skipped.
+ */
+pub fn read_manifest<P: AsRef<Path>>(manifest: P) -> IoResult<Vec<Sample>> {
+    let manifest = manifest.as_ref();
+    let base = manifest.parent().unwrap_or(Path::new(""));
+    let invalid = |line: usize, message: &str| {
+        IoError::new(
+            ErrorKind::InvalidData,
+            format!("{}:{}: {}", manifest.display(), line + 1, message),
+        )
+    };
+    let mut samples = Vec::new();
+    for (i, line) in std::fs::read_to_string(manifest)?.lines().enumerate() {
+        let line = line.trim_end_matches('\r');
+        if line.trim().is_empty() {
+            continue;
+        }
+        let (image, expected) = if line.starts_with('{') {
+            let sample: Value =
+                serde_json::from_str(line).map_err(|e| invalid(i, &e.to_string()))?;
+            match (sample["image"].as_str(), sample["text"].as_str()) {
+                (Some(image), Some(text)) => (image.to_string(), text.to_string()),
+                _ => return Err(invalid(i, "expected `image` and `text` strings")),
+            }
+        } else {
+            let (image, text) = line
+                .split_once('\t')
+                .ok_or_else(|| invalid(i, "expected an image path and a tab"))?;
+            (
+                image.to_string(),
+                labels(text).unwrap_or_else(|| text.to_string()),
+            )
+        };
+        samples.push(Sample {
+            image: base.join(image),
+            expected,
+        });
+    }
+    Ok(samples)
+}
+
+/// The transcriptions of PaddleOCR detection labels, if `text` is some.
+fn labels(text: &str) -> Option<String> {
+    let labels: Vec<Value> = serde_json::from_str(text).ok()?;
+    let lines = labels
+        .iter()
+        .map(|label| label["transcription"].as_str())
+        .collect::<Option<Vec<_>>>()?;
+    Some(
+        lines
+            .into_iter()
+            .filter(|line| *line != "###")
+            .collect::<Vec<_>>()
+            .join("\n"),
+    )
+}
+
+/**
+ * The outcome of [`run_dataset`].
+ */
+#[derive(Debug, Clone, Default)]
+pub struct DatasetReport {
+    /// Every sample, those that failed with nothing recognized.
+    pub evaluation: Evaluation,
+    /// The latency of each successful request, sorted.
+    pub latencies: Vec<Duration>,
+    /// The images the engine failed on, and why.
+    pub failures: Vec<(String, String)>,
+    pub elapsed: Duration,
+}
+
+impl DatasetReport {
+    /// The latency below which a fraction `p` of the requests completed.
+    fn percentile(&self, p: f64) -> Duration {
+        match self.latencies.len() {
+            0 => Duration::ZERO,
+            n => self.latencies[((n - 1) as f64 * p).round() as usize],
+        }
+    }
+
+    /**
+     * The report as JSON: totals, latency percentiles in milliseconds, failures, and per-image rates.
+     */
+    pub fn to_json(&self) -> Value {
+        let ms = |duration: Duration| duration.as_secs_f64() * 1000.0;
+        let failures: Vec<Value> = self
+            .failures
+            .iter()
+            .map(|(image, error)| json!({ "image": image, "error": error }))
+            .collect();
+        let samples: Vec<Value> = self
+            .evaluation
+            .images
+            .iter()
+            .map(|image| {
+                json!({
+                    "image": image.path,
+                    "cer": image.cer(),
+                    "wer": image.wer(),
+                    "expected": image.expected,
+                    "recognized": image.recognized,
+                })
+            })
+            .collect();
+        json!({
+            "images": self.evaluation.images.len(),
+            "cer": self.evaluation.cer(),
+            "wer": self.evaluation.wer(),
+            "failures": failures,
+            "elapsed_ms": ms(self.elapsed),
+            "latency_ms": {
+                "p50": ms(self.percentile(0.5)),
+                "p90": ms(self.percentile(0.9)),
+                "p99": ms(self.percentile(0.99)),
+                "max": ms(self.percentile(1.0)),
+            },
+            "samples": samples,
+        })
+    }
+
+    /**
+     * The report as Markdown: a summary table, then the images by character error rate, worst first.
+     */
+    pub fn to_markdown(&self) -> String {
+        let percent = |rate: f64| format!("{:.2}%", rate * 100.0);
+        let mut markdown = format!(
+            "| Images | Failures | CER | WER | p50 | p90 | p99 | Elapsed |\n\
+             |---:|---:|---:|---:|---:|---:|---:|---:|\n\
+             | {} | {} | {} | {} | {:.2?} | {:.2?} | {:.2?} | {:.2?} |\n",
+            self.evaluation.images.len(),
+            self.failures.len(),
+            percent(self.evaluation.cer()),
+            percent(self.evaluation.wer()),
+            self.percentile(0.5),
+            self.percentile(0.9),
+            self.percentile(0.99),
+            self.elapsed,
+        );
+        markdown.push_str("\n| Image | CER | WER |\n|---|---:|---:|\n");
+        for image in self.evaluation.worst() {
+            let path = image.path.replace('|', "\\|");
+            markdown.push_str(&format!(
+                "| {} | {} | {} |\n",
+                path,
+                percent(image.cer()),
+                percent(image.wer())
+            ));
+        }
+        if !self.failures.is_empty() {
+            markdown.push_str("\n| Failed image | Error |\n|---|---|\n");
+            for (image, error) in &self.failures {
+                let (image, error) = (image.replace('|', "\\|"), error.replace('|', "\\|"));
+                markdown.push_str(&format!("| {} | {} |\n", image, error));
+            }
+        }
+        markdown
+    }
+}
+
+/**
+ * OCRs every sample of the `manifest` (see [`read_manifest`]) on the `pool`, one request per engine at a time, and
+ * reports the accuracy and latency. Samples the engine fails on count as recognizing nothing.
+ */
+pub fn run_dataset<P: AsRef<Path>>(pool: &PpocrPool, manifest: P) -> IoResult<DatasetReport> {
+    let samples = read_manifest(manifest)?;
+    Ok(run_samples(pool, &samples))
+}
+
+fn run_samples(pool: &PpocrPool, samples: &[Sample]) -> DatasetReport {
+    let next = AtomicUsize::new(0);
+    let results = Mutex::new(vec![None; samples.len()]);
+    let start = Instant::now();
+    std::thread::scope(|scope| {
+        for _ in 0..pool.size().min(samples.len()) {
+            let (next, results) = (&next, &results);
+            scope.spawn(move || loop {
+                let i = next.fetch_add(1, Ordering::Relaxed);
+                let Some(sample) = samples.get(i) else {
+                    break;
+                };
+                let request = Instant::now();
+                let outcome = match pool.ocr_response(sample.image.as_path().into()) {
+                    Ok(response) if response.code == 100 || response.code == 101 => {
+                        Ok((transcript(&response.data), request.elapsed()))
+                    }
+                    Ok(response) => Err(response.to_string()),
+                    Err(e) => Err(e),
+                };
+                results.lock().unwrap_or_else(PoisonError::into_inner)[i] = Some(outcome);
+            });
+        }
+    });
+
+    let mut report = DatasetReport {
+        elapsed: start.elapsed(),
+        ..DatasetReport::default()
+    };
+    let results = results.into_inner().unwrap_or_else(PoisonError::into_inner);
+    for (sample, outcome) in samples.iter().zip(results) {
+        let path = sample.image.to_string_lossy();
+        let recognized = match outcome {
+            Some(Ok((text, latency))) => {
+                report.latencies.push(latency);
+                text
+            }
+            Some(Err(e)) => {
+                report.failures.push((path.to_string(), e));
+                String::new()
+            }
+            None => {
+                report
+                    .failures
+                    .push((path.to_string(), "not run".to_string()));
+                String::new()
+            }
+        };
+        report
+            .evaluation
+            .push(ImageReport::from_text(&path, &sample.expected, &recognized));
+    }
+    report.latencies.sort();
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{cer, edit_distance, read_manifest, run_dataset, wer, Evaluation, ImageReport};
+    use crate::{Confidence, ContentData, ImageData, OcrEngine, PpocrPool};
+
+    /// Reads `hello world` in `a.png`, `hallo` in `b.png`, nothing in `c.png`, and fails on anything else.
+    struct Reader;
+    impl OcrEngine for Reader {
+        fn ocr(&mut self, image: ImageData) -> std::io::Result<String> {
+            let ImageData::ImagePathDict { image_path } = image else {
+                unreachable!()
+            };
+            let item = |text: &str| {
+                format!(
+                    r#"{{"code":100,"data":[{{"box":[[0,0],[9,0],[9,9],[0,9]],"score":1,"text":"{}"}}]}}"#,
+                    text
+                )
+            };
+            Ok(match image_path.rsplit(['/', '\\']).next() {
+                Some("a.png") => item("hello world"),
+                Some("b.png") => item("hallo"),
+                Some("c.png") => r#"{"code":101,"data":"No text found in image."}"#.to_string(),
+                _ => r#"{"code":200,"data":"Image path dose not exist."}"#.to_string(),
+            })
+        }
+    }
+
+    #[test]
+    fn rates() {
+        assert_eq!(edit_distance(b"kitten", b"sitting"), 3);
+        assert_eq!(edit_distance::<u8>(b"", b"abc"), 3);
+        assert_eq!(cer("hello  world", "hello world"), 0.0);
+        assert_eq!(cer("abcd", "abxd"), 0.25);
+        assert_eq!(cer("", ""), 0.0);
+        assert_eq!(cer("", "ab"), 2.0);
+        assert_eq!(wer("the quick brown fox", "the quack brown"), 0.5);
+        assert_eq!(cer("漢字", "漢子"), 0.5);
+    }
+
+    #[test]
+    fn reports() {
+        let item = |text: &str, y: usize| ContentData {
+            rect: [[0, y], [100, y], [100, y + 10], [0, y + 10]],
+            score: Confidence(0.9),
+            text: text.to_string(),
+            orientation: None,
+            extra: Default::default(),
+        };
+        let mut evaluation = Evaluation::default();
+        let report = ImageReport::new(
+            "a.png",
+            "second\nfirst",
+            &[item("first", 20), item("second", 0)],
+        );
+        assert_eq!(report.recognized, "second\nfirst");
+        assert_eq!((report.cer(), report.wer()), (0.0, 0.0));
+        evaluation.push(report);
+        evaluation.push(ImageReport::from_text("b.png", "abcd", "abxd"));
+        // 1 error in 12 + 4 characters, 1 in 3 words.
+        assert_eq!(evaluation.cer(), 1.0 / 16.0);
+        assert_eq!(evaluation.wer(), 1.0 / 3.0);
+        let printed = evaluation.to_string();
+        assert!(
+            printed.starts_with("b.png: CER 25.00%, WER 100.00%\n"),
+            "{}",
+            printed
+        );
+        assert!(printed.ends_with("2 images: CER 6.25%, WER 33.33%\n"));
+    }
+
+    #[test]
+    fn dataset() {
+        let dir = std::env::temp_dir().join(format!("paddleocr-eval-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let manifest = dir.join("labels.txt");
+        std::fs::write(
+            &manifest,
+            concat!(
+                "a.png\thello world\r\n",
+                "\n",
+                "b.png\t",
+                r####"[{"transcription":"hello","points":[]},{"transcription":"###","points":[]}]"####,
+                "\n",
+                r#"{"image":"c.png","text":""}"#,
+                "\n",
+                "d.png\tmissing\n",
+            ),
+        )
+        .unwrap();
+        let samples = read_manifest(&manifest).unwrap();
+        assert_eq!(samples.len(), 4);
+        assert_eq!(samples[0].image, dir.join("a.png"));
+        assert_eq!(samples[1].expected, "hello");
+
+        let pool = PpocrPool::new([Reader, Reader]);
+        let report = run_dataset(&pool, &manifest).unwrap();
+        assert_eq!(report.latencies.len(), 3);
+        assert_eq!(report.failures.len(), 1);
+        assert!(report.failures[0].1.starts_with("200: "));
+        // 1 error in `hello`, 7 in `missing`; 1 and 1 word.
+        assert_eq!(report.evaluation.cer(), 8.0 / 23.0);
+        assert_eq!(report.evaluation.wer(), 2.0 / 4.0);
+
+        let json = report.to_json();
+        assert_eq!(json["images"], 4);
+        assert_eq!(json["samples"][1]["recognized"], "hallo");
+        let markdown = report.to_markdown();
+        assert!(
+            markdown.contains("| 4 | 1 | 34.78% | 50.00% |"),
+            "{}",
+            markdown
+        );
+        assert!(markdown.contains("| Failed image | Error |"));
+
+        std::fs::write(&manifest, "no tab\n").unwrap();
+        assert!(read_manifest(&manifest).is_err());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,257 @@
+//! Ownership of a spawned engine process: draining and forwarding its stderr, and noticing when it exits.
+
+use std::collections::VecDeque;
+use std::io::{BufRead, BufReader, Result as IoResult};
+use std::process::{Child, ExitStatus};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::time::{Duration, Instant};
+
+/// How many of the last stderr lines are kept for [`ExitInfo::stderr_tail`].
+const TAIL_LINES: usize = 20;
+
+/**
+ * Why and how the engine went away, as passed to [`Ppocr::on_exit`](crate::Ppocr::on_exit).
+ */
+#[derive(Debug, Clone)]
+pub struct ExitInfo {
+    /// The exit status, or `None` if the engine closed its stderr but did not exit within a second.
+    pub status: Option<ExitStatus>,
+    /// The last lines the engine wrote to stderr, oldest first; usually the reason for a crash.
+    pub stderr_tail: Vec<String>,
+}
+
+/**
+ * What happens to a spawned engine when its [`Ppocr`](crate::Ppocr) is dropped.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DropBehavior {
+    /// Kill it right away.
+    #[default]
+    Kill,
+    /// Close its input so it can exit by itself, and kill it if it hasn't after the timeout.
+    Graceful(Duration),
+    /// Leave it running, e.g. when other components still talk to it.
+    Detach,
+}
+
+/**
+ * How serious a line the engine wrote to stderr is, as guessed from its glog prefix (`I`, `W`, `E` or `F` followed
+ * by the date) or its wording.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum LogLevel {
+    Info,
+    Warn,
+    Error,
+}
+
+impl LogLevel {
+    fn classify(line: &str) -> LogLevel {
+        let mut chars = line.chars();
+        let glog = chars
+            .next()
+            .filter(|_| chars.take(4).all(|c| c.is_ascii_digit()) && line.len() > 5);
+        match glog {
+            Some('I') => return LogLevel::Info,
+            Some('W') => return LogLevel::Warn,
+            Some('E' | 'F') => return LogLevel::Error,
+            _ => {}
+        }
+        let lower = line.to_lowercase();
+        if ["error", "fail", "exception", "fatal", "abort"]
+            .iter()
+            .any(|word| lower.contains(word))
+        {
+            LogLevel::Error
+        } else if lower.contains("warn") {
+            LogLevel::Warn
+        } else {
+            LogLevel::Info
+        }
+    }
+}
+
+#[cfg(feature = "log")]
+impl From<LogLevel> for log::Level {
+    fn from(level: LogLevel) -> Self {
+        match level {
+            LogLevel::Info => log::Level::Info,
+            LogLevel::Warn => log::Level::Warn,
+            LogLevel::Error => log::Level::Error,
+        }
+    }
+}
+
+/**
+ * The `log` target of the engine's stderr lines, which are forwarded to the `log` facade with the `log` feature.
+ * Records carry the engine's process ID as the `pid` key-value.
+ */
+#[cfg(feature = "log")]
+pub const LOG_TARGET: &str = "paddleocr::engine";
+
+/**
+ * A line the engine wrote to stderr, as passed to [`Ppocr::on_log`](crate::Ppocr::on_log).
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogLine {
+    /// The engine's process ID, to tell engines apart.
+    pub pid: u32,
+    pub level: LogLevel,
+    pub message: String,
+}
+
+type ExitCallback = Box<dyn FnMut(ExitInfo) + Send>;
+type LogCallback = Box<dyn FnMut(LogLine) + Send>;
+
+#[derive(Default)]
+struct Watch {
+    tail: Mutex<VecDeque<String>>,
+    callback: Mutex<Option<ExitCallback>>,
+    log: Mutex<Option<LogCallback>>,
+    /// Set before the engine is killed on purpose, so that exit is not reported.
+    expected: AtomicBool,
+    exited: Mutex<Option<ExitInfo>>,
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+pub(crate) struct EngineProcess {
+    child: Arc<Mutex<Child>>,
+    id: u32,
+    watch: Arc<Watch>,
+}
+
+impl EngineProcess {
+    /**
+     * Takes over a child spawned with piped stderr, draining that pipe on a background thread so the engine never
+     * blocks writing to it.
+     */
+    pub(crate) fn new(mut child: Child) -> EngineProcess {
+        let stderr = child.stderr.take();
+        let process = EngineProcess {
+            id: child.id(),
+            child: Arc::new(Mutex::new(child)),
+            watch: Arc::default(),
+        };
+        if let Some(stderr) = stderr {
+            let (child, watch, id) = (process.child.clone(), process.watch.clone(), process.id);
+            std::thread::spawn(move || drain(BufReader::new(stderr), id, &child, &watch));
+        }
+        process
+    }
+
+    pub(crate) fn id(&self) -> u32 {
+        self.id
+    }
+
+    pub(crate) fn with<T>(&self, f: impl FnOnce(&mut Child) -> T) -> T {
+        f(&mut lock(&self.child))
+    }
+
+    pub(crate) fn kill(&self) -> IoResult<()> {
+        self.watch.expected.store(true, Ordering::SeqCst);
+        self.with(Child::kill)
+    }
+
+    /// [`kill`](EngineProcess::kill), callable from any thread.
+    pub(crate) fn terminator(&self) -> crate::Terminator {
+        let (child, watch) = (self.child.clone(), self.watch.clone());
+        Arc::new(move || {
+            watch.expected.store(true, Ordering::SeqCst);
+            lock(&child).kill().ok();
+        })
+    }
+
+    /**
+     * Waits for an engine whose input was closed to exit, killing it at the deadline.
+     */
+    pub(crate) fn shutdown(&self, timeout: Duration) -> IoResult<()> {
+        self.watch.expected.store(true, Ordering::SeqCst);
+        let deadline = Instant::now() + timeout;
+        while Instant::now() < deadline {
+            if self.with(Child::try_wait)?.is_some() {
+                return Ok(());
+            }
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        self.with(|child| child.kill().and_then(|_| child.wait()).map(drop))
+    }
+
+    /**
+     * Gives up on the engine without stopping it; its exit is no longer reported.
+     */
+    pub(crate) fn detach(&self) {
+        self.watch.expected.store(true, Ordering::SeqCst);
+    }
+
+    pub(crate) fn on_log(&self, callback: LogCallback) {
+        *lock(&self.watch.log) = Some(callback);
+    }
+
+    pub(crate) fn on_exit(&self, callback: ExitCallback) {
+        // Hold the slot while checking, so an exit on the drain thread can't slip in between.
+        let mut slot = lock(&self.watch.callback);
+        let exited = lock(&self.watch.exited).clone();
+        match exited {
+            Some(info) if !self.watch.expected.load(Ordering::SeqCst) => {
+                let mut callback = callback;
+                callback(info);
+            }
+            Some(_) => {}
+            None => *slot = Some(callback),
+        }
+    }
+}
+
+fn drain<R: BufRead>(mut stderr: R, pid: u32, child: &Mutex<Child>, watch: &Watch) {
+    let mut line = Vec::new();
+    while let Ok(n) = stderr.read_until(b'\n', &mut line) {
+        if n == 0 {
+            break;
+        }
+        let message = String::from_utf8_lossy(&line).trim_end().to_string();
+        line.clear();
+        if message.is_empty() {
+            continue;
+        }
+        let level = LogLevel::classify(&message);
+        #[cfg(feature = "log")]
+        log::log!(target: LOG_TARGET, level.into(), pid = pid; "engine {}: {}", pid, message);
+        if let Some(callback) = lock(&watch.log).as_mut() {
+            callback(LogLine {
+                pid,
+                level,
+                message: message.clone(),
+            });
+        }
+        let mut tail = lock(&watch.tail);
+        if tail.len() == TAIL_LINES {
+            tail.pop_front();
+        }
+        tail.push_back(message);
+    }
+
+    // Closing stderr normally means the engine is exiting; give it a moment to finish.
+    let deadline = Instant::now() + Duration::from_secs(1);
+    let status = loop {
+        match lock(child).try_wait() {
+            Ok(None) if Instant::now() < deadline => std::thread::sleep(Duration::from_millis(10)),
+            Ok(status) => break status,
+            Err(_) => break None,
+        }
+    };
+    let info = ExitInfo {
+        status,
+        stderr_tail: lock(&watch.tail).iter().cloned().collect(),
+    };
+    let mut callback = lock(&watch.callback);
+    *lock(&watch.exited) = Some(info.clone());
+    if !watch.expected.load(Ordering::SeqCst) {
+        if let Some(callback) = callback.as_mut() {
+            callback(info);
+        }
+    }
+}
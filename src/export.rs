@@ -0,0 +1,1398 @@
+//! Writing OCR results out in formats meant for other tools.
+
+use std::io::{Result as IoResult, Write};
+
+use serde::Serialize;
+use serde_json::{json, Map};
+
+use crate::geometry::{Aabb, RectangleExt};
+#[cfg(any(feature = "docx", feature = "epub"))]
+use crate::layout::{self, Block};
+#[cfg(any(feature = "docx", feature = "epub"))]
+use crate::zip::ZipWriter;
+use crate::{ContentData, ImageData, OcrResponse};
+
+#[derive(Serialize)]
+#[cfg_attr(
+    feature = "schemars",
+    derive(schemars::JsonSchema),
+    schemars(description = "The engine's answer to the image at `path`.")
+)]
+pub(crate) struct JsonlRecord<'a> {
+    /// The image the response is for.
+    path: &'a str,
+    #[serde(flatten)]
+    response: &'a OcrResponse,
+}
+
+/**
+Writes one JSON object per image, `{"path", "code", "data"}` plus `"message"` for responses without detections, each
+on its own line.
+
+Records are written as `results` yields them, so a lazy iterator over a large batch never holds more than one
+response in memory.
+
+# Examples
+
+```no_run
+use paddleocr::OcrEngine;
+
+let mut p = paddleocr::Ppocr::new(".../PaddleOCR-json.exe", None).unwrap();
+let paths = ["a.png", "b.png"];
+let results = paths
+    .iter()
+    .map(|path| (path, p.ocr_response(Path::new(path).into()).unwrap()));
+paddleocr::export::to_jsonl(std::io::stdout().lock(), results).unwrap();
+```
+*/
+pub fn to_jsonl<W, I, S>(mut writer: W, results: I) -> IoResult<()>
+where
+    W: Write,
+    I: IntoIterator<Item = (S, OcrResponse)>,
+    S: AsRef<str>,
+{
+    for (path, response) in results {
+        let record = JsonlRecord {
+            path: path.as_ref(),
+            response: &response,
+        };
+        serde_json::to_writer(&mut writer, &record)?;
+        writer.write_all(b"\n")?;
+    }
+    writer.flush()
+}
+
+/**
+Writes the results for one image as an ICDAR 2015 ground-truth file: a line per detection, its corners clockwise from
+the top left and then its text, `x1,y1,x2,y2,x3,y3,x4,y4,text`. The text is last, so commas in it need no escaping.
+
+# Examples
+
+```no_run
+use paddleocr::OcrEngine;
+
+let mut p = paddleocr::Ppocr::new(".../PaddleOCR-json.exe", None).unwrap();
+let results = p.ocr_and_parse(paddleocr::ImageData::from_path("img_1.jpg")).unwrap();
+let gt = std::fs::File::create("gt_img_1.txt").unwrap();
+paddleocr::export::to_icdar(gt, &results).unwrap();
+```
+*/
+pub fn to_icdar<W: Write>(mut writer: W, results: &[ContentData]) -> IoResult<()> {
+    for content in results {
+        for [x, y] in content.rect {
+            write!(writer, "{},{},", x, y)?;
+        }
+        // A line break would start a new detection.
+        writeln!(writer, "{}", content.text.replace(['\r', '\n'], " "))?;
+    }
+    writer.flush()
+}
+
+/**
+Writes the results for a set of images as a COCO-Text (v2) dataset: `imgs` with each image's file name and size,
+`anns` with each detection's bounding box, polygon (`mask`) and text, and `imgToAnns` linking them. Images and
+annotations are numbered from 1 in the order given. Every detection is marked legible and machine printed, in an
+unknown language (`"na"`).
+
+# Examples
+
+```no_run
+use paddleocr::OcrEngine;
+
+let mut p = paddleocr::Ppocr::new(".../PaddleOCR-json.exe", None).unwrap();
+let images = [("a.jpg", (640, 480)), ("b.jpg", (800, 600))];
+let results = images.iter().map(|&(name, size)| {
+    (name, size, p.ocr_and_parse(paddleocr::ImageData::from_path(name)).unwrap_or_default())
+});
+let dataset = std::fs::File::create("cocotext.json").unwrap();
+paddleocr::export::to_coco_text(dataset, results).unwrap();
+```
+*/
+pub fn to_coco_text<W, I, S, R>(mut writer: W, images: I) -> IoResult<()>
+where
+    W: Write,
+    I: IntoIterator<Item = (S, (usize, usize), R)>,
+    S: AsRef<str>,
+    R: AsRef<[ContentData]>,
+{
+    let (mut imgs, mut anns, mut img_to_anns) = (Map::new(), Map::new(), Map::new());
+    let mut ann_id = 0;
+    for (i, (file_name, (width, height), results)) in images.into_iter().enumerate() {
+        let image_id = i + 1;
+        imgs.insert(
+            image_id.to_string(),
+            json!({
+                "id": image_id,
+                "file_name": file_name.as_ref(),
+                "width": width,
+                "height": height,
+                "set": "train",
+            }),
+        );
+        let mut ids = Vec::new();
+        for content in results.as_ref() {
+            ann_id += 1;
+            let b = content.rect.aabb();
+            anns.insert(
+                ann_id.to_string(),
+                json!({
+                    "id": ann_id,
+                    "image_id": image_id,
+                    "bbox": [b.x, b.y, b.w, b.h],
+                    "area": b.area(),
+                    "mask": content.rect.concat(),
+                    "utf8_string": content.text,
+                    "legibility": "legible",
+                    "class": "machine printed",
+                    "language": "na",
+                }),
+            );
+            ids.push(ann_id);
+        }
+        img_to_anns.insert(image_id.to_string(), json!(ids));
+    }
+    let dataset = json!({
+        "info": { "description": "Generated from OCR results by paddleocr" },
+        "cats": {},
+        "imgs": imgs,
+        "anns": anns,
+        "imgToAnns": img_to_anns,
+    });
+    serde_json::to_writer(&mut writer, &dataset)?;
+    writer.flush()
+}
+
+/**
+Writes the recognized text of one page as a Word document, with the structure found by [`layout::blocks`]: large
+single lines become headings, widely spaced columns become tables, and the remaining lines are joined into
+paragraphs. Enabled with the `docx` feature.
+
+# Examples
+
+```no_run
+use paddleocr::OcrEngine;
+
+let mut p = paddleocr::Ppocr::new(".../PaddleOCR-json.exe", None).unwrap();
+let results = p.ocr_and_parse(Path::new(".../scan.png").into()).unwrap();
+paddleocr::export::to_docx(std::fs::File::create("scan.docx").unwrap(), &results).unwrap();
+```
+*/
+#[cfg(feature = "docx")]
+pub fn to_docx<W: Write>(writer: W, results: &[ContentData]) -> IoResult<()> {
+    let mut body = String::new();
+    for block in layout::blocks(results) {
+        match block {
+            Block::Heading(text) => body.push_str(&format!(
+                r#"<w:p><w:pPr><w:pStyle w:val="Heading1"/></w:pPr>{}</w:p>"#,
+                docx_run(&text)
+            )),
+            Block::Paragraph(text) => body.push_str(&format!("<w:p>{}</w:p>", docx_run(&text))),
+            Block::Table(rows) => {
+                let columns = rows.first().map_or(0, Vec::len);
+                body.push_str(concat!(
+                    r#"<w:tbl><w:tblPr><w:tblW w:w="0" w:type="auto"/><w:tblBorders>"#,
+                    r#"<w:top w:val="single" w:sz="4"/><w:left w:val="single" w:sz="4"/>"#,
+                    r#"<w:bottom w:val="single" w:sz="4"/><w:right w:val="single" w:sz="4"/>"#,
+                    r#"<w:insideH w:val="single" w:sz="4"/><w:insideV w:val="single" w:sz="4"/>"#,
+                    "</w:tblBorders></w:tblPr><w:tblGrid>",
+                ));
+                body.push_str(&r#"<w:gridCol w:w="2000"/>"#.repeat(columns));
+                body.push_str("</w:tblGrid>");
+                for row in rows {
+                    body.push_str("<w:tr>");
+                    for cell in row {
+                        body.push_str(&format!("<w:tc><w:p>{}</w:p></w:tc>", docx_run(&cell)));
+                    }
+                    body.push_str("</w:tr>");
+                }
+                // Word merges a table with whatever follows it unless a paragraph sits in between.
+                body.push_str("</w:tbl><w:p/>");
+            }
+        }
+    }
+
+    let mut zip = ZipWriter::new(writer);
+    zip.add("[Content_Types].xml", DOCX_CONTENT_TYPES.as_bytes())?;
+    zip.add("_rels/.rels", DOCX_RELS.as_bytes())?;
+    zip.add(
+        "word/_rels/document.xml.rels",
+        DOCX_DOCUMENT_RELS.as_bytes(),
+    )?;
+    zip.add("word/styles.xml", DOCX_STYLES.as_bytes())?;
+    zip.add(
+        "word/document.xml",
+        format!(
+            r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<w:document xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main"><w:body>{}</w:body></w:document>"#,
+            body
+        )
+        .as_bytes(),
+    )?;
+    zip.finish()?;
+    Ok(())
+}
+
+#[cfg(feature = "docx")]
+fn docx_run(text: &str) -> String {
+    format!(
+        r#"<w:r><w:t xml:space="preserve">{}</w:t></w:r>"#,
+        escape_xml(text)
+    )
+}
+
+#[cfg(feature = "docx")]
+const DOCX_CONTENT_TYPES: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<Types xmlns="http://schemas.openxmlformats.org/package/2006/content-types">
+<Default Extension="rels" ContentType="application/vnd.openxmlformats-package.relationships+xml"/>
+<Default Extension="xml" ContentType="application/xml"/>
+<Override PartName="/word/document.xml" ContentType="application/vnd.openxmlformats-officedocument.wordprocessingml.document.main+xml"/>
+<Override PartName="/word/styles.xml" ContentType="application/vnd.openxmlformats-officedocument.wordprocessingml.styles+xml"/>
+</Types>"#;
+
+#[cfg(feature = "docx")]
+const DOCX_RELS: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships">
+<Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/officeDocument" Target="word/document.xml"/>
+</Relationships>"#;
+
+#[cfg(feature = "docx")]
+const DOCX_DOCUMENT_RELS: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships">
+<Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/styles" Target="styles.xml"/>
+</Relationships>"#;
+
+#[cfg(feature = "docx")]
+const DOCX_STYLES: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<w:styles xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main">
+<w:style w:type="paragraph" w:default="1" w:styleId="Normal"><w:name w:val="Normal"/></w:style>
+<w:style w:type="paragraph" w:styleId="Heading1"><w:name w:val="heading 1"/><w:basedOn w:val="Normal"/><w:next w:val="Normal"/>
+<w:pPr><w:keepNext/><w:spacing w:before="240" w:after="120"/><w:outlineLvl w:val="0"/></w:pPr>
+<w:rPr><w:b/><w:sz w:val="32"/></w:rPr></w:style>
+</w:styles>"#;
+
+/**
+Assembles scanned pages into an EPUB 3 book, for digitizing books page by page. Enabled with the `epub` feature.
+
+`pages` are the OCR results of each page, in order. Each page's text is structured with [`layout::blocks`]; a
+heading starts a new chapter, titled after it, and every page start is marked as a page break so readers can show
+the printed page numbers. `language` is a BCP 47 tag such as `zh` or `en`.
+
+# Examples
+
+```no_run
+use paddleocr::OcrEngine;
+
+let mut p = paddleocr::Ppocr::new(".../PaddleOCR-json.exe", None).unwrap();
+let pages: Vec<_> = (1..=120)
+    .map(|n| p.ocr_and_parse(Path::new(&format!("scans/{:03}.png", n)).into()).unwrap_or_default())
+    .collect();
+let book = std::fs::File::create("book.epub").unwrap();
+paddleocr::export::to_epub(book, "My Book", "en", &pages).unwrap();
+```
+*/
+#[cfg(feature = "epub")]
+pub fn to_epub<W: Write>(
+    writer: W,
+    title: &str,
+    language: &str,
+    pages: &[Vec<ContentData>],
+) -> IoResult<()> {
+    struct Chapter {
+        title: String,
+        body: String,
+    }
+    let mut chapters = Vec::new();
+    let mut chapter = Chapter {
+        title: title.to_string(),
+        body: String::new(),
+    };
+    for (i, page) in pages.iter().enumerate() {
+        let mut page_break = Some(format!(
+            r#"<span epub:type="pagebreak" id="page-{0}" role="doc-pagebreak" aria-label="{0}"></span>"#,
+            i + 1
+        ));
+        for block in layout::blocks(page) {
+            if let Block::Heading(heading) = &block {
+                if !chapter.body.trim().is_empty() {
+                    let next = Chapter {
+                        title: heading.clone(),
+                        body: String::new(),
+                    };
+                    chapters.push(std::mem::replace(&mut chapter, next));
+                } else {
+                    chapter.title = heading.clone();
+                }
+            }
+            chapter
+                .body
+                .push_str(&page_break.take().unwrap_or_default());
+            chapter.body.push_str(&match block {
+                Block::Heading(text) => format!("<h1>{}</h1>\n", escape_xml(&text)),
+                Block::Paragraph(text) => format!("<p>{}</p>\n", escape_xml(&text)),
+                Block::Table(rows) => {
+                    let rows: String = rows
+                        .iter()
+                        .map(|row| {
+                            let cells: String = row
+                                .iter()
+                                .map(|cell| format!("<td>{}</td>", escape_xml(cell)))
+                                .collect();
+                            format!("<tr>{}</tr>", cells)
+                        })
+                        .collect();
+                    format!("<table>{}</table>\n", rows)
+                }
+            });
+        }
+        // A page without any text still gets its page break.
+        if let Some(page_break) = page_break {
+            chapter.body.push_str(&page_break);
+        }
+    }
+    chapters.push(chapter);
+
+    let mut zip = ZipWriter::new(writer);
+    // The mimetype must come first and uncompressed, so it can be sniffed at a fixed offset.
+    zip.add("mimetype", b"application/epub+zip")?;
+    zip.add("META-INF/container.xml", EPUB_CONTAINER.as_bytes())?;
+
+    let mut manifest = String::new();
+    let mut spine = String::new();
+    let mut nav = String::new();
+    for (i, chapter) in chapters.iter().enumerate() {
+        let name = format!("chapter-{}.xhtml", i + 1);
+        zip.add(
+            &format!("OEBPS/{}", name),
+            format!(
+                r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE html>
+<html xmlns="http://www.w3.org/1999/xhtml" xmlns:epub="http://www.idpf.org/2007/ops" xml:lang="{lang}" lang="{lang}">
+<head><title>{title}</title></head>
+<body>
+{body}</body>
+</html>"#,
+                lang = escape_xml(language),
+                title = escape_xml(&chapter.title),
+                body = chapter.body,
+            )
+            .as_bytes(),
+        )?;
+        manifest.push_str(&format!(
+            r#"<item id="chapter-{0}" href="{1}" media-type="application/xhtml+xml"/>"#,
+            i + 1,
+            name
+        ));
+        spine.push_str(&format!(r#"<itemref idref="chapter-{}"/>"#, i + 1));
+        nav.push_str(&format!(
+            r#"<li><a href="{}">{}</a></li>"#,
+            name,
+            escape_xml(&chapter.title)
+        ));
+    }
+    let page_list: String = (1..=pages.len())
+        .map(|n| {
+            let chapter = chapters
+                .iter()
+                .position(|c| c.body.contains(&format!(r#"id="page-{}""#, n)))
+                .unwrap_or(0);
+            format!(
+                r##"<li><a href="chapter-{}.xhtml#page-{1}">{1}</a></li>"##,
+                chapter + 1,
+                n
+            )
+        })
+        .collect();
+
+    zip.add(
+        "OEBPS/nav.xhtml",
+        format!(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE html>
+<html xmlns="http://www.w3.org/1999/xhtml" xmlns:epub="http://www.idpf.org/2007/ops">
+<head><title>{0}</title></head>
+<body>
+<nav epub:type="toc" id="toc"><h1>{0}</h1><ol>{1}</ol></nav>
+<nav epub:type="page-list" hidden=""><ol>{2}</ol></nav>
+</body>
+</html>"#,
+            escape_xml(title),
+            nav,
+            page_list
+        )
+        .as_bytes(),
+    )?;
+    zip.add(
+        "OEBPS/content.opf",
+        format!(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<package xmlns="http://www.idpf.org/2007/opf" version="3.0" unique-identifier="id">
+<metadata xmlns:dc="http://purl.org/dc/elements/1.1/">
+<dc:identifier id="id">urn:paddleocr:{id:08x}</dc:identifier>
+<dc:title>{title}</dc:title>
+<dc:language>{language}</dc:language>
+<meta property="dcterms:modified">{modified}</meta>
+</metadata>
+<manifest><item id="nav" href="nav.xhtml" media-type="application/xhtml+xml" properties="nav"/>{manifest}</manifest>
+<spine>{spine}</spine>
+</package>"#,
+            id = crate::zip::crc32(
+                chapters
+                    .iter()
+                    .map(|c| c.body.as_str())
+                    .collect::<String>()
+                    .as_bytes()
+            ),
+            title = escape_xml(title),
+            language = escape_xml(language),
+            modified = utc_timestamp(std::time::SystemTime::now()),
+            manifest = manifest,
+            spine = spine,
+        )
+        .as_bytes(),
+    )?;
+    zip.finish()?;
+    Ok(())
+}
+
+#[cfg(feature = "epub")]
+const EPUB_CONTAINER: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<container version="1.0" xmlns="urn:oasis:names:tc:opendocument:xmlns:container">
+<rootfiles><rootfile full-path="OEBPS/content.opf" media-type="application/oebps-package+xml"/></rootfiles>
+</container>"#;
+
+/// Formats a time as `YYYY-MM-DDThh:mm:ssZ`.
+fn utc_timestamp(time: std::time::SystemTime) -> String {
+    let seconds = time
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs());
+    // Days to civil date, after Howard Hinnant's `civil_from_days`.
+    let days = (seconds / 86400) as i64 + 719468;
+    let era = days / 146097;
+    let day_of_era = days % 146097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year,
+        month,
+        day,
+        seconds % 86400 / 3600,
+        seconds % 3600 / 60,
+        seconds % 60
+    )
+}
+
+/**
+Writes the results for the `width`×`height` image `image_path` as a LabelMe annotation, a polygon per detection
+labeled with its text and described by its score, for correcting them in LabelMe. The image is referenced, not
+embedded, so `image_path` should be relative to where the annotation is saved, as LabelMe expects.
+
+# Examples
+
+```no_run
+use paddleocr::OcrEngine;
+
+let mut p = paddleocr::Ppocr::new(".../PaddleOCR-json.exe", None).unwrap();
+let results = p.ocr_and_parse(paddleocr::ImageData::from_path("scans/a.png")).unwrap();
+let annotation = std::fs::File::create("scans/a.json").unwrap();
+paddleocr::export::to_labelme(annotation, "a.png", (1280, 720), &results).unwrap();
+```
+*/
+pub fn to_labelme<W: Write>(
+    mut writer: W,
+    image_path: &str,
+    (width, height): (usize, usize),
+    results: &[ContentData],
+) -> IoResult<()> {
+    let shapes: Vec<_> = results
+        .iter()
+        .map(|content| {
+            json!({
+                "label": content.text,
+                "points": content.rect,
+                "group_id": null,
+                "description": format!("score {:.4}", content.score.value()),
+                "shape_type": "polygon",
+                "flags": {},
+            })
+        })
+        .collect();
+    let annotation = json!({
+        "version": "5.2.1",
+        "flags": {},
+        "shapes": shapes,
+        "imagePath": image_path,
+        "imageData": null,
+        "imageHeight": height,
+        "imageWidth": width,
+    });
+    serde_json::to_writer_pretty(&mut writer, &annotation)?;
+    writer.flush()
+}
+
+/**
+Writes the results for a set of images as Label Studio tasks to import, a JSON array with a task per image whose
+pre-annotations (`predictions`) fit Label Studio's OCR labeling template: a `polygon` from `bbox` and a `textarea`
+from `transcription` per detection, both on `image`, with coordinates in percent of the image size.
+
+`image_url` is what Label Studio loads the image from, e.g. `/data/local-files/?d=scans/a.png` or an `s3://` URL.
+
+# Examples
+
+```no_run
+use paddleocr::OcrEngine;
+
+let mut p = paddleocr::Ppocr::new(".../PaddleOCR-json.exe", None).unwrap();
+let results = p.ocr_and_parse(paddleocr::ImageData::from_path("scans/a.png")).unwrap();
+let tasks = [("/data/local-files/?d=scans/a.png", (1280, 720), results)];
+paddleocr::export::to_label_studio(std::fs::File::create("tasks.json").unwrap(), tasks).unwrap();
+```
+*/
+pub fn to_label_studio<W, I, S, R>(mut writer: W, images: I) -> IoResult<()>
+where
+    W: Write,
+    I: IntoIterator<Item = (S, (usize, usize), R)>,
+    S: AsRef<str>,
+    R: AsRef<[ContentData]>,
+{
+    let mut tasks = Vec::new();
+    for (image_url, (width, height), results) in images {
+        let results = results.as_ref();
+        let percent = |value: usize, size: usize| value as f64 * 100.0 / size.max(1) as f64;
+        let mut regions = Vec::new();
+        for (i, content) in results.iter().enumerate() {
+            let points: Vec<[f64; 2]> = content
+                .rect
+                .iter()
+                .map(|&[x, y]| [percent(x, width), percent(y, height)])
+                .collect();
+            let region = |from_name: &str, kind: &str, value| {
+                json!({
+                    "id": format!("r{}", i),
+                    "from_name": from_name,
+                    "to_name": "image",
+                    "type": kind,
+                    "original_width": width,
+                    "original_height": height,
+                    "image_rotation": 0,
+                    "score": content.score.value(),
+                    "value": value,
+                })
+            };
+            regions.push(region("bbox", "polygon", json!({ "points": points })));
+            regions.push(region(
+                "transcription",
+                "textarea",
+                json!({ "points": points, "text": [content.text] }),
+            ));
+        }
+        tasks.push(json!({
+            "data": { "image": image_url.as_ref() },
+            "predictions": [{
+                "model_version": "paddleocr",
+                "score": mean_score(results.iter()),
+                "result": regions,
+            }],
+        }));
+    }
+    serde_json::to_writer(&mut writer, &tasks)?;
+    writer.flush()
+}
+
+/**
+Writes a standalone HTML page showing the image with the recognized text laid over it as invisible but selectable
+spans, so the text can be searched, selected and copied in any browser.
+
+A path is referenced as is, so the page must be opened from where the path resolves; base64 data is embedded.
+
+# Examples
+
+```no_run
+use paddleocr::OcrEngine;
+
+let mut p = paddleocr::Ppocr::new(".../PaddleOCR-json.exe", None).unwrap();
+let image = paddleocr::ImageData::from_path("scan.png");
+let results = p.ocr_and_parse(image.clone()).unwrap();
+paddleocr::export::to_html(std::fs::File::create("scan.html").unwrap(), &image, &results).unwrap();
+```
+*/
+pub fn to_html<W: Write>(
+    mut writer: W,
+    image: &ImageData,
+    results: &[ContentData],
+) -> IoResult<()> {
+    write!(
+        writer,
+        r#"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<title>OCR</title>
+<style>
+.page {{ position: relative; display: inline-block; }}
+.page img {{ display: block; }}
+.page span {{ position: absolute; color: transparent; white-space: pre; line-height: 1; transform-origin: 0 0; cursor: text; }}
+.page span::selection {{ color: transparent; background: rgba(0, 120, 215, 0.35); }}
+</style>
+</head>
+<body>
+<div class="page">
+<img src="{}" alt="">
+"#,
+        escape_xml(&image_src(image))
+    )?;
+    for content in results {
+        let b = content.rect.aabb();
+        writeln!(
+            writer,
+            r#"<span style="left: {}px; top: {}px; width: {}px; height: {}px; font-size: {}px" title="{:.3}">{}</span>"#,
+            b.x,
+            b.y,
+            b.w,
+            b.h,
+            (b.h as f64 * 0.8).round(),
+            content.score.value(),
+            escape_xml(&content.text)
+        )?;
+    }
+    writer.write_all(
+        br#"</div>
+<script>
+// Stretch each span to its box, so that selections line up with the text in the image.
+for (const span of document.querySelectorAll(".page span")) {
+  if (span.scrollWidth > 0) span.style.transform = `scaleX(${span.clientWidth / span.scrollWidth})`;
+}
+</script>
+</body>
+</html>
+"#,
+    )?;
+    writer.flush()
+}
+
+/**
+ * How [`to_svg`] and [`draw`] color the detection outlines.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Coloring {
+    /// Every outline in the same color.
+    #[default]
+    Uniform,
+    /// A heat map from red (score 0.5 or less) through yellow to green (score 1), to spot unreliable regions.
+    Confidence,
+}
+
+impl Coloring {
+    /// The hue in degrees, saturation and lightness of the outline of `content`.
+    fn hsl(self, content: &ContentData) -> (f64, f64, f64) {
+        match self {
+            Coloring::Uniform => (210.0, 1.0, 0.45),
+            Coloring::Confidence => {
+                let hue = ((content.score.value() - 0.5) * 2.0).clamp(0.0, 1.0) * 120.0;
+                (hue, 0.9, 0.45)
+            }
+        }
+    }
+
+    /// The color of the outline of `content`, as `[r, g, b]`.
+    #[cfg(feature = "image")]
+    fn rgb(self, content: &ContentData) -> [u8; 3] {
+        let (hue, saturation, lightness) = self.hsl(content);
+        let chroma = (1.0 - (2.0 * lightness - 1.0).abs()) * saturation;
+        let f = |n: f64| {
+            let k = (n + hue / 30.0) % 12.0;
+            let value = lightness - chroma / 2.0 * (k - 3.0).min(9.0 - k).clamp(-1.0, 1.0);
+            (value * 255.0).round() as u8
+        };
+        [f(0.0), f(8.0), f(4.0)]
+    }
+}
+
+/**
+Writes an SVG of the `width`×`height` image with each detection's polygon drawn over it. Hovering a polygon shows its
+text and score.
+
+# Examples
+
+```no_run
+use paddleocr::export::Coloring;
+use paddleocr::OcrEngine;
+
+let mut p = paddleocr::Ppocr::new(".../PaddleOCR-json.exe", None).unwrap();
+let image = paddleocr::ImageData::from_path("scan.png");
+let results = p.ocr_and_parse(image.clone()).unwrap();
+let svg = std::fs::File::create("scan.svg").unwrap();
+paddleocr::export::to_svg(svg, &image, (1280, 720), &results, Coloring::Confidence).unwrap();
+```
+*/
+pub fn to_svg<W: Write>(
+    mut writer: W,
+    image: &ImageData,
+    (width, height): (usize, usize),
+    results: &[ContentData],
+    coloring: Coloring,
+) -> IoResult<()> {
+    writeln!(
+        writer,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{0}" height="{1}" viewBox="0 0 {0} {1}">"#,
+        width, height
+    )?;
+    writeln!(
+        writer,
+        r#"<image href="{}" x="0" y="0" width="{}" height="{}"/>"#,
+        escape_xml(&image_src(image)),
+        width,
+        height
+    )?;
+    for content in results {
+        let points: Vec<String> = content
+            .rect
+            .iter()
+            .map(|[x, y]| format!("{},{}", x, y))
+            .collect();
+        let (hue, saturation, lightness) = coloring.hsl(content);
+        let color = format!(
+            "hsl({:.0}, {:.0}%, {:.0}%)",
+            hue,
+            saturation * 100.0,
+            lightness * 100.0
+        );
+        writeln!(
+            writer,
+            r#"<polygon points="{}" fill="{1}" fill-opacity="0.2" stroke="{1}" stroke-width="2"><title>{2} ({3:.3})</title></polygon>"#,
+            points.join(" "),
+            color,
+            escape_xml(&content.text),
+            content.score.value()
+        )?;
+    }
+    writeln!(writer, "</svg>")?;
+    writer.flush()
+}
+
+/**
+Draws each detection's polygon over `image`, like [`to_svg`] but as pixels, e.g. to save as PNG. Enabled with the
+`image` feature.
+
+# Examples
+
+```no_run
+use paddleocr::export::Coloring;
+use paddleocr::OcrEngine;
+
+let mut p = paddleocr::Ppocr::new(".../PaddleOCR-json.exe", None).unwrap();
+let results = p.ocr_and_parse(paddleocr::ImageData::from_path("scan.png")).unwrap();
+let image = image::open("scan.png").unwrap();
+paddleocr::export::draw(&image, &results, Coloring::Confidence).save("annotated.png").unwrap();
+```
+*/
+#[cfg(feature = "image")]
+pub fn draw(
+    image: &image::DynamicImage,
+    results: &[ContentData],
+    coloring: Coloring,
+) -> image::RgbaImage {
+    let mut canvas = image.to_rgba8();
+    let (width, height) = (canvas.width() as usize, canvas.height() as usize);
+    for content in results {
+        let [r, g, b] = coloring.rgb(content);
+        let rect = &content.rect;
+        // Filled at 20% opacity, as in the SVG.
+        if let Some(bounds) = rect
+            .aabb()
+            .intersection(&Aabb::from_edges(0, 0, width, height))
+        {
+            for y in bounds.y..bounds.bottom() {
+                for x in bounds.x..bounds.right() {
+                    if crate::words::contains(rect, [x, y]) {
+                        let pixel = canvas.get_pixel_mut(x as u32, y as u32);
+                        for (channel, value) in pixel.0.iter_mut().zip([r, g, b]) {
+                            *channel = ((*channel as u32 * 4 + value as u32) / 5) as u8;
+                        }
+                    }
+                }
+            }
+        }
+        // Outlined 2 pixels wide.
+        for (i, &[x0, y0]) in rect.iter().enumerate() {
+            let [x1, y1] = rect[(i + 1) % rect.len()];
+            let steps = x0.abs_diff(x1).max(y0.abs_diff(y1)).max(1);
+            for step in 0..=steps {
+                let t = step as f64 / steps as f64;
+                let x = (x0 as f64 + (x1 as f64 - x0 as f64) * t).round() as usize;
+                let y = (y0 as f64 + (y1 as f64 - y0 as f64) * t).round() as usize;
+                for (x, y) in [(x, y), (x + 1, y), (x, y + 1), (x + 1, y + 1)] {
+                    if x < width && y < height {
+                        canvas.put_pixel(x as u32, y as u32, image::Rgba([r, g, b, 255]));
+                    }
+                }
+            }
+        }
+    }
+    canvas
+}
+
+/**
+Writes the results for the `width`×`height` image `image_filename` as [PRImA PAGE XML](https://github.com/PRImA-Research-Lab/PAGE-XML)
+(2019-07-15 schema), the interchange format of document analysis tools such as Transkribus and OCR-D.
+
+Detections are grouped into lines with [`layout::lines`](crate::layout::lines), and lines into text regions where
+they are no further apart than a line's height and overlap horizontally. Each line is split into words with
+estimated boxes, see [`words`](crate::words). Every level has its text and confidence, and the regions are listed
+in reading order.
+
+# Examples
+
+```no_run
+use paddleocr::OcrEngine;
+
+let mut p = paddleocr::Ppocr::new(".../PaddleOCR-json.exe", None).unwrap();
+let results = p.ocr_and_parse(paddleocr::ImageData::from_path("scan.png")).unwrap();
+let xml = std::fs::File::create("scan.xml").unwrap();
+paddleocr::export::to_page_xml(xml, "scan.png", (2480, 3508), &results).unwrap();
+```
+*/
+pub fn to_page_xml<W: Write>(
+    mut writer: W,
+    image_filename: &str,
+    (width, height): (usize, usize),
+    results: &[ContentData],
+) -> IoResult<()> {
+    let points = |points: &[[usize; 2]]| {
+        points
+            .iter()
+            .map(|[x, y]| format!("{},{}", x, y))
+            .collect::<Vec<_>>()
+            .join(" ")
+    };
+    let aabb_points = |b: &Aabb| {
+        points(&[
+            [b.x, b.y],
+            [b.right(), b.y],
+            [b.right(), b.bottom()],
+            [b.x, b.bottom()],
+        ])
+    };
+
+    // Lines into regions: a new one after a gap taller than the previous line, or without horizontal overlap.
+    let mut regions: Vec<(Aabb, Vec<crate::layout::Line>)> = Vec::new();
+    for line in crate::layout::lines(results) {
+        match regions.last_mut() {
+            Some((bounds, lines))
+                if lines.last().is_some_and(|last| {
+                    line.bounds.y <= last.bounds.bottom() + last.bounds.h
+                        && line.bounds.x < bounds.right()
+                        && bounds.x < line.bounds.right()
+                }) =>
+            {
+                *bounds = bounds.union(&line.bounds);
+                lines.push(line);
+            }
+            _ => regions.push((line.bounds, vec![line])),
+        }
+    }
+
+    let now = utc_timestamp(std::time::SystemTime::now());
+    write!(
+        writer,
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<PcGts xmlns="http://schema.primaresearch.org/PAGE/gts/pagecontent/2019-07-15" xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance" xsi:schemaLocation="http://schema.primaresearch.org/PAGE/gts/pagecontent/2019-07-15 http://schema.primaresearch.org/PAGE/gts/pagecontent/2019-07-15/pagecontent.xsd">
+<Metadata><Creator>paddleocr</Creator><Created>{0}</Created><LastChange>{0}</LastChange></Metadata>
+<Page imageFilename="{1}" imageWidth="{2}" imageHeight="{3}">
+"#,
+        now,
+        escape_xml(image_filename),
+        width,
+        height
+    )?;
+    if !regions.is_empty() {
+        writeln!(writer, r#"<ReadingOrder><OrderedGroup id="ro">"#)?;
+        for i in 0..regions.len() {
+            writeln!(
+                writer,
+                r#"<RegionRefIndexed index="{0}" regionRef="r{0}"/>"#,
+                i
+            )?;
+        }
+        writeln!(writer, "</OrderedGroup></ReadingOrder>")?;
+    }
+    for (r, (bounds, lines)) in regions.iter().enumerate() {
+        writeln!(
+            writer,
+            r#"<TextRegion id="r{}" type="paragraph"><Coords points="{}"/>"#,
+            r,
+            aabb_points(bounds)
+        )?;
+        for (l, line) in lines.iter().enumerate() {
+            let coords = match line.items[..] {
+                [item] => points(&item.rect),
+                _ => aabb_points(&line.bounds),
+            };
+            writeln!(
+                writer,
+                r#"<TextLine id="r{}l{}"><Coords points="{}"/>"#,
+                r, l, coords
+            )?;
+            let words = line.items.iter().flat_map(|item| {
+                crate::words::words(item)
+                    .into_iter()
+                    .map(|w| (w, item.score))
+            });
+            for (w, (word, score)) in words.enumerate() {
+                writeln!(
+                    writer,
+                    r#"<Word id="r{}l{}w{}"><Coords points="{}"/><TextEquiv conf="{:.4}"><Unicode>{}</Unicode></TextEquiv></Word>"#,
+                    r,
+                    l,
+                    w,
+                    points(&word.rect),
+                    score.value(),
+                    escape_xml(word.text)
+                )?;
+            }
+            writeln!(
+                writer,
+                r#"<TextEquiv conf="{:.4}"><Unicode>{}</Unicode></TextEquiv></TextLine>"#,
+                mean_score(line.items.iter().copied()),
+                escape_xml(&line.text())
+            )?;
+        }
+        let text: Vec<String> = lines.iter().map(|line| line.text()).collect();
+        writeln!(
+            writer,
+            r#"<TextEquiv conf="{:.4}"><Unicode>{}</Unicode></TextEquiv></TextRegion>"#,
+            mean_score(lines.iter().flat_map(|line| line.items.iter().copied())),
+            escape_xml(&text.join("\n"))
+        )?;
+    }
+    writeln!(writer, "</Page>\n</PcGts>")?;
+    writer.flush()
+}
+
+fn mean_score<'a>(results: impl Iterator<Item = &'a ContentData>) -> f64 {
+    let (sum, count) = results.fold((0.0, 0), |(sum, count), c| {
+        (sum + c.score.value(), count + 1)
+    });
+    if count == 0 {
+        0.0
+    } else {
+        sum / count as f64
+    }
+}
+
+/// The URL to load an image from in HTML or SVG: a `file:` URL for an absolute path, a relative URL for a relative
+/// one, or a data URL.
+fn image_src(image: &ImageData) -> String {
+    match image {
+        ImageData::ImagePathDict { image_path } => path_url(&image_path.replace('\\', "/")),
+        ImageData::ImageBase64Dict { image_base64 } => {
+            format!("data:{};base64,{}", sniff_mime(image_base64), image_base64)
+        }
+    }
+}
+
+/// The URL of a path with `/` separators, percent-encoded so that characters like `#` and `?` stay in the name.
+fn path_url(path: &str) -> String {
+    let encode = |part: &str| {
+        part.bytes().fold(String::new(), |mut url, byte| {
+            match byte {
+                b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b'/' => {
+                    url.push(byte as char)
+                }
+                _ => url.push_str(&format!("%{:02X}", byte)),
+            }
+            url
+        })
+    };
+    let drive = path.as_bytes();
+    if drive.len() >= 2 && drive[0].is_ascii_alphabetic() && drive[1] == b':' {
+        // `C:/scans/a.png`, which would otherwise read as a URL of scheme `c`.
+        format!("file:///{}{}", &path[..2], encode(&path[2..]))
+    } else if let Some(unc) = path.strip_prefix("//") {
+        format!("file://{}", encode(unc))
+    } else if path.starts_with('/') {
+        format!("file://{}", encode(path))
+    } else {
+        encode(path)
+    }
+}
+
+/// Guesses the media type of base64-encoded image data from its magic number.
+fn sniff_mime(base64: &str) -> &'static str {
+    [
+        ("iVBORw0KGgo", "image/png"),
+        ("/9j/", "image/jpeg"),
+        ("R0lGOD", "image/gif"),
+        ("Qk", "image/bmp"),
+        ("UklGR", "image/webp"),
+        ("SUkq", "image/tiff"),
+        ("TU0AK", "image/tiff"),
+    ]
+    .iter()
+    .find(|(magic, _)| base64.starts_with(magic))
+    .map_or("application/octet-stream", |&(_, mime)| mime)
+}
+
+/// Escapes text for use in XML (and HTML) content and attribute values.
+fn escape_xml(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            // Control characters other than tab and newlines are not allowed in XML 1.0.
+            c if c.is_control() && !matches!(c, '\t' | '\n' | '\r') => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::to_jsonl;
+    use crate::OcrResponse;
+
+    #[test]
+    fn jsonl() {
+        let results = [
+            (
+                "a.png",
+                OcrResponse::parse(
+                    r#"{"code":100,"data":[{"box":[[0,0],[1,0],[1,1],[0,1]],"score":0.5,"text":"a"}]}"#,
+                )
+                .unwrap(),
+            ),
+            (
+                "b.png",
+                OcrResponse::parse(r#"{"code":101,"data":"No text found in image."}"#).unwrap(),
+            ),
+        ];
+        let mut output = Vec::new();
+        to_jsonl(&mut output, results).unwrap();
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            concat!(
+                r#"{"path":"a.png","code":100,"data":[{"box":[[0,0],[1,0],[1,1],[0,1]],"score":0.5,"text":"a"}]}"#,
+                "\n",
+                r#"{"path":"b.png","code":101,"data":[],"message":"No text found in image."}"#,
+                "\n",
+            )
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "docx")]
+    fn docx() {
+        use crate::{Confidence, ContentData};
+
+        let item = |text: &str, y: usize, h: usize| ContentData {
+            rect: [[0, y], [100, y], [100, y + h], [0, y + h]],
+            score: Confidence(0.9),
+            text: text.to_string(),
+            orientation: None,
+            extra: Default::default(),
+        };
+        let mut output = Vec::new();
+        super::to_docx(
+            &mut output,
+            &[
+                item("Title", 0, 40),
+                item("a < b", 50, 20),
+                item("c", 72, 20),
+            ],
+        )
+        .unwrap();
+        let output = String::from_utf8_lossy(&output);
+        assert!(output.starts_with("PK"));
+        assert!(output.contains(
+            r#"<w:p><w:pPr><w:pStyle w:val="Heading1"/></w:pPr><w:r><w:t xml:space="preserve">Title</w:t></w:r></w:p><w:p><w:r><w:t xml:space="preserve">a &lt; b c</w:t></w:r></w:p>"#
+        ));
+    }
+
+    #[test]
+    #[cfg(feature = "epub")]
+    fn epub() {
+        use crate::{Confidence, ContentData};
+
+        let item = |text: &str, y: usize, h: usize| ContentData {
+            rect: [[0, y], [100, y], [100, y + h], [0, y + h]],
+            score: Confidence(0.9),
+            text: text.to_string(),
+            orientation: None,
+            extra: Default::default(),
+        };
+        let pages = [
+            vec![
+                item("Preface", 0, 40),
+                item("words", 50, 20),
+                item("more", 72, 20),
+            ],
+            vec![
+                item("continued", 0, 20),
+                item("Chapter 1", 50, 40),
+                item("text", 100, 20),
+            ],
+            vec![],
+        ];
+        let mut output = Vec::new();
+        super::to_epub(&mut output, "Book", "en", &pages).unwrap();
+        assert_eq!(&output[30..58], b"mimetypeapplication/epub+zip");
+        let output = String::from_utf8_lossy(&output);
+        assert!(output.contains(r#"<li><a href="chapter-1.xhtml">Preface</a></li><li><a href="chapter-2.xhtml">Chapter 1</a></li>"#));
+        assert!(output.contains(r##"<li><a href="chapter-1.xhtml#page-2">2</a></li><li><a href="chapter-2.xhtml#page-3">3</a></li>"##));
+        assert!(output.contains("<p>words more</p>\n<span epub:type=\"pagebreak\" id=\"page-2\""));
+    }
+
+    #[test]
+    #[cfg(feature = "epub")]
+    fn timestamps() {
+        let time = std::time::UNIX_EPOCH + std::time::Duration::from_secs(1_709_251_199);
+        assert_eq!(super::utc_timestamp(time), "2024-02-29T23:59:59Z");
+    }
+
+    #[test]
+    fn html() {
+        use crate::{Confidence, ContentData, ImageData};
+
+        let results = [ContentData {
+            rect: [[10, 20], [110, 20], [110, 40], [10, 40]],
+            score: Confidence(0.95),
+            text: "<b>&".to_string(),
+            orientation: None,
+            extra: Default::default(),
+        }];
+        let mut output = Vec::new();
+        super::to_html(
+            &mut output,
+            &ImageData::from_base64("iVBORw0KGgoAAA".to_string()),
+            &results,
+        )
+        .unwrap();
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.contains(r#"<img src="data:image/png;base64,iVBORw0KGgoAAA" alt="">"#));
+        let src = |path| super::image_src(&ImageData::from_path(path));
+        assert_eq!(src("scans/a b#1.png"), "scans/a%20b%231.png");
+        assert_eq!(src("/home/me/100%?.png"), "file:///home/me/100%25%3F.png");
+        assert_eq!(src(r"\\server\scans\a.png"), "file://server/scans/a.png");
+        assert_eq!(src("d:/扫描.png"), "file:///d:/%E6%89%AB%E6%8F%8F.png");
+        assert!(output.contains(
+            r#"<span style="left: 10px; top: 20px; width: 100px; height: 20px; font-size: 16px" title="0.950">&lt;b&gt;&amp;</span>"#
+        ));
+    }
+
+    #[test]
+    fn datasets() {
+        use serde_json::Value;
+
+        use crate::{Confidence, ContentData};
+
+        let item = |text: &str| ContentData {
+            rect: [[10, 20], [110, 22], [109, 42], [9, 40]],
+            score: Confidence(0.9),
+            text: text.to_string(),
+            orientation: None,
+            extra: Default::default(),
+        };
+        let mut output = Vec::new();
+        super::to_icdar(&mut output, &[item("a, b"), item("c\nd")]).unwrap();
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "10,20,110,22,109,42,9,40,a, b\n10,20,110,22,109,42,9,40,c d\n"
+        );
+
+        let mut output = Vec::new();
+        let images = [
+            ("a.jpg", (640, 480), vec![item("x"), item("y")]),
+            ("b.jpg", (800, 600), vec![]),
+            ("c.jpg", (800, 600), vec![item("z")]),
+        ];
+        super::to_coco_text(&mut output, images).unwrap();
+        let dataset: Value = serde_json::from_slice(&output).unwrap();
+        assert_eq!(dataset["imgs"]["2"]["file_name"], "b.jpg");
+        assert_eq!(dataset["imgs"]["1"]["width"], 640);
+        assert_eq!(dataset["imgToAnns"]["1"], serde_json::json!([1, 2]));
+        assert_eq!(dataset["imgToAnns"]["2"], serde_json::json!([]));
+        assert_eq!(dataset["imgToAnns"]["3"], serde_json::json!([3]));
+        let ann = &dataset["anns"]["3"];
+        assert_eq!(
+            (ann["image_id"].clone(), ann["utf8_string"].clone()),
+            (3.into(), "z".into())
+        );
+        assert_eq!(ann["bbox"], serde_json::json!([9, 20, 101, 22]));
+        assert_eq!(
+            ann["mask"],
+            serde_json::json!([10, 20, 110, 22, 109, 42, 9, 40])
+        );
+    }
+
+    #[test]
+    fn annotation_tools() {
+        use serde_json::{json, Value};
+
+        use crate::{Confidence, ContentData};
+
+        let results = [ContentData {
+            rect: [[10, 20], [110, 20], [110, 40], [10, 40]],
+            score: Confidence(0.95),
+            text: "hello".to_string(),
+            orientation: None,
+            extra: Default::default(),
+        }];
+        let mut output = Vec::new();
+        super::to_labelme(&mut output, "a.png", (200, 100), &results).unwrap();
+        let labelme: Value = serde_json::from_slice(&output).unwrap();
+        assert_eq!(labelme["imagePath"], "a.png");
+        assert_eq!(
+            (
+                labelme["imageWidth"].clone(),
+                labelme["imageHeight"].clone()
+            ),
+            (200.into(), 100.into())
+        );
+        let shape = &labelme["shapes"][0];
+        assert_eq!(shape["label"], "hello");
+        assert_eq!(shape["shape_type"], "polygon");
+        assert_eq!(
+            shape["points"],
+            json!([[10, 20], [110, 20], [110, 40], [10, 40]])
+        );
+        assert_eq!(shape["description"], "score 0.9500");
+
+        let mut output = Vec::new();
+        super::to_label_studio(
+            &mut output,
+            [
+                ("/data/a.png", (200, 100), &results[..]),
+                ("/data/b.png", (10, 10), &[]),
+            ],
+        )
+        .unwrap();
+        let tasks: Value = serde_json::from_slice(&output).unwrap();
+        assert_eq!(tasks.as_array().unwrap().len(), 2);
+        assert_eq!(tasks[0]["data"]["image"], "/data/a.png");
+        let result = &tasks[0]["predictions"][0]["result"];
+        assert_eq!(result[0]["type"], "polygon");
+        assert_eq!(result[0]["value"]["points"][2], json!([55.0, 40.0]));
+        assert_eq!(result[1]["from_name"], "transcription");
+        assert_eq!(result[1]["value"]["text"], json!(["hello"]));
+        assert_eq!(result[0]["id"], result[1]["id"]);
+        assert_eq!(tasks[1]["predictions"][0]["result"], json!([]));
+    }
+
+    #[test]
+    fn page_xml() {
+        use crate::{Confidence, ContentData};
+
+        let item = |text: &str, x: usize, y: usize, score: f64| ContentData {
+            rect: [[x, y], [x + 100, y], [x + 100, y + 20], [x, y + 20]],
+            score: Confidence(score),
+            text: text.to_string(),
+            orientation: None,
+            extra: Default::default(),
+        };
+        let results = [
+            item("ab cd", 0, 0, 0.9),
+            item("e&f", 120, 0, 0.7),
+            item("second", 0, 30, 1.0),
+            // Far below, so in a region of its own.
+            item("footer", 0, 200, 0.5),
+        ];
+        let mut output = Vec::new();
+        super::to_page_xml(&mut output, "a<b>.png", (300, 400), &results).unwrap();
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.contains(
+            r#"<Page imageFilename="a&lt;b&gt;.png" imageWidth="300" imageHeight="400">"#
+        ));
+        assert!(output.contains(r#"<RegionRefIndexed index="1" regionRef="r1"/>"#));
+        assert!(output.contains(
+            r#"<TextRegion id="r0" type="paragraph"><Coords points="0,0 220,0 220,50 0,50"/>"#
+        ));
+        assert!(output.contains(r#"<Word id="r0l0w1"><Coords points="60,0 100,0 100,20 60,20"/><TextEquiv conf="0.9000"><Unicode>cd</Unicode></TextEquiv></Word>"#));
+        assert!(output.contains(
+            r#"<TextEquiv conf="0.8000"><Unicode>ab cd e&amp;f</Unicode></TextEquiv></TextLine>"#
+        ));
+        assert!(
+            output.contains(r#"<TextLine id="r0l1"><Coords points="0,30 100,30 100,50 0,50"/>"#)
+        );
+        assert!(
+            output.contains("<Unicode>ab cd e&amp;f\nsecond</Unicode></TextEquiv></TextRegion>")
+        );
+        assert!(output.contains(r#"<TextRegion id="r1""#));
+        assert!(!output.contains(r#"id="r2""#));
+        assert!(output.ends_with("</Page>\n</PcGts>\n"));
+    }
+
+    #[test]
+    fn svg_heat_map() {
+        use super::Coloring;
+        use crate::{Confidence, ContentData, ImageData};
+
+        let item = |score: f64| ContentData {
+            rect: [[0, 0], [10, 0], [10, 5], [0, 5]],
+            score: Confidence(score),
+            text: "a".to_string(),
+            orientation: None,
+            extra: Default::default(),
+        };
+        let mut output = Vec::new();
+        super::to_svg(
+            &mut output,
+            &ImageData::from_path(r"C:\scans\a.png"),
+            (20, 10),
+            &[item(1.0), item(0.75), item(0.2)],
+            Coloring::Confidence,
+        )
+        .unwrap();
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.contains(
+            r#"<image href="file:///C:/scans/a.png" x="0" y="0" width="20" height="10"/>"#
+        ));
+        let colors: Vec<_> = output
+            .match_indices("stroke=\"hsl(")
+            .map(|(i, _)| &output[i + 12..i + 15])
+            .collect();
+        assert_eq!(colors, ["120", "60,", "0, "]);
+    }
+
+    #[test]
+    #[cfg(feature = "image")]
+    fn draw() {
+        use image::{DynamicImage, RgbImage, Rgba};
+
+        use super::Coloring;
+        use crate::{Confidence, ContentData};
+
+        let image = DynamicImage::ImageRgb8(RgbImage::from_pixel(20, 10, image::Rgb([255; 3])));
+        let item = |score: f64, rect| ContentData {
+            rect,
+            score: Confidence(score),
+            text: "a".to_string(),
+            orientation: None,
+            extra: Default::default(),
+        };
+        let results = [
+            item(1.0, [[2, 2], [12, 2], [12, 7], [2, 7]]),
+            // Partly outside the image.
+            item(0.2, [[15, 5], [30, 5], [30, 20], [15, 20]]),
+        ];
+        let drawn = super::draw(&image, &results, Coloring::Confidence);
+        assert_eq!((drawn.width(), drawn.height()), (20, 10));
+        // hsl(120, 90%, 45%) and hsl(0, 90%, 45%).
+        assert_eq!(*drawn.get_pixel(2, 2), Rgba([11, 218, 11, 255]));
+        assert_eq!(*drawn.get_pixel(12, 4), Rgba([11, 218, 11, 255]));
+        assert_eq!(*drawn.get_pixel(15, 9), Rgba([218, 11, 11, 255]));
+        // Tinted inside, untouched outside.
+        assert_eq!(*drawn.get_pixel(7, 4), Rgba([206, 247, 206, 255]));
+        assert_eq!(*drawn.get_pixel(0, 0), Rgba([255, 255, 255, 255]));
+        assert_eq!(*drawn.get_pixel(19, 0), Rgba([255, 255, 255, 255]));
+    }
+}
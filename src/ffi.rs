@@ -0,0 +1,143 @@
+//! A flat C API over [`Ppocr`], enabled with the `ffi` feature.
+//!
+//! The crate builds as a Rust library only; build a C library exporting this API with
+//! `cargo rustc --lib --release --features ffi --crate-type cdylib` (or `staticlib`).
+//!
+//! The header `include/paddleocr.h` is generated from this module with
+//! `cbindgen --config cbindgen.toml --output include/paddleocr.h`.
+
+use std::ffi::{c_char, CStr, CString};
+use std::path::PathBuf;
+use std::ptr;
+
+use crate::{ImageData, Ppocr};
+
+unsafe fn to_str<'a>(s: *const c_char) -> Option<&'a str> {
+    if s.is_null() {
+        return None;
+    }
+    CStr::from_ptr(s).to_str().ok()
+}
+
+fn into_c_string(s: String) -> *mut c_char {
+    CString::new(s).map_or(ptr::null_mut(), CString::into_raw)
+}
+
+/// Starts the engine at `exe_path`, optionally with a language `config_path` (may be `NULL`).
+///
+/// Returns `NULL` if the engine could not be started. The instance must be released with `ppocr_free`.
+///
+/// # Safety
+///
+/// `exe_path` must be a valid NUL-terminated UTF-8 string, and `config_path` either one or `NULL`.
+#[no_mangle]
+pub unsafe extern "C" fn ppocr_new(
+    exe_path: *const c_char,
+    config_path: *const c_char,
+) -> *mut Ppocr {
+    let Some(exe_path) = to_str(exe_path) else {
+        return ptr::null_mut();
+    };
+    let config_path = to_str(config_path).map(PathBuf::from);
+    match Ppocr::new(PathBuf::from(exe_path), config_path) {
+        Ok(p) => Box::into_raw(Box::new(p)),
+        Err(_) => ptr::null_mut(),
+    }
+}
+
+/// OCRs the image at `image_path`, returning the engine's JSON response.
+///
+/// Returns `NULL` if the engine could not be talked to. The string must be released with `ppocr_string_free`.
+///
+/// # Safety
+///
+/// `p` must come from `ppocr_new` and not be used from several threads at once. `image_path` must be a valid
+/// NUL-terminated UTF-8 string.
+#[no_mangle]
+pub unsafe extern "C" fn ppocr_ocr_path(p: *mut Ppocr, image_path: *const c_char) -> *mut c_char {
+    let (Some(p), Some(image_path)) = (p.as_mut(), to_str(image_path)) else {
+        return ptr::null_mut();
+    };
+    p.ocr(ImageData::from_path(image_path))
+        .map_or(ptr::null_mut(), into_c_string)
+}
+
+/// OCRs a base64-encoded image, returning the engine's JSON response.
+///
+/// Returns `NULL` if the engine could not be talked to. The string must be released with `ppocr_string_free`.
+///
+/// # Safety
+///
+/// `p` must come from `ppocr_new` and not be used from several threads at once. `image_base64` must be a valid
+/// NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn ppocr_ocr_base64(
+    p: *mut Ppocr,
+    image_base64: *const c_char,
+) -> *mut c_char {
+    let (Some(p), Some(image_base64)) = (p.as_mut(), to_str(image_base64)) else {
+        return ptr::null_mut();
+    };
+    p.ocr(ImageData::from_base64(image_base64.to_string()))
+        .map_or(ptr::null_mut(), into_c_string)
+}
+
+/// OCRs the image in the clipboard, returning the engine's JSON response.
+///
+/// Returns `NULL` if the engine could not be talked to. The string must be released with `ppocr_string_free`.
+///
+/// # Safety
+///
+/// `p` must come from `ppocr_new` and not be used from several threads at once.
+#[no_mangle]
+pub unsafe extern "C" fn ppocr_ocr_clipboard(p: *mut Ppocr) -> *mut c_char {
+    let Some(p) = p.as_mut() else {
+        return ptr::null_mut();
+    };
+    p.ocr_clipboard().map_or(ptr::null_mut(), into_c_string)
+}
+
+/// Releases a string returned by this library. Passing `NULL` is a no-op.
+///
+/// # Safety
+///
+/// `s` must come from one of the `ppocr_ocr_*` functions and not be used afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn ppocr_string_free(s: *mut c_char) {
+    if !s.is_null() {
+        drop(CString::from_raw(s));
+    }
+}
+
+/// Stops the engine and releases the instance. Passing `NULL` is a no-op.
+///
+/// # Safety
+///
+/// `p` must come from `ppocr_new` and not be used afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn ppocr_free(p: *mut Ppocr) {
+    if !p.is_null() {
+        drop(Box::from_raw(p));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ffi::CString;
+    use std::ptr;
+
+    use super::*;
+
+    #[test]
+    fn null_handling() {
+        unsafe {
+            let exe = CString::new("does-not-exist.exe").unwrap();
+            assert!(ppocr_new(ptr::null(), ptr::null()).is_null());
+            assert!(ppocr_new(exe.as_ptr(), ptr::null()).is_null());
+            assert!(ppocr_ocr_path(ptr::null_mut(), exe.as_ptr()).is_null());
+            assert!(ppocr_ocr_clipboard(ptr::null_mut()).is_null());
+            ppocr_string_free(ptr::null_mut());
+            ppocr_free(ptr::null_mut());
+        }
+    }
+}
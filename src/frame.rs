@@ -0,0 +1,157 @@
+//! Uncompressed images, such as screen captures, for the functions working on pixels: [`crop`](crate::crop),
+//! [`redact`](crate::redact) and, with the `bytes` feature, `screen`.
+
+use std::borrow::Cow;
+
+use crate::geometry::Aabb;
+
+/**
+ * Byte order of a frame's 4-byte pixels.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PixelFormat {
+    Rgba,
+    /// As returned by most Windows capture APIs.
+    Bgra,
+}
+
+/**
+ * An uncompressed captured frame, rows top to bottom without padding.
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Frame {
+    pub width: usize,
+    pub height: usize,
+    pub format: PixelFormat,
+    pub data: Vec<u8>,
+}
+
+impl Frame {
+    /**
+     * A frame of `width * height` 4-byte pixels, or `None` if `data` has a different length.
+     */
+    pub fn new(width: usize, height: usize, format: PixelFormat, data: Vec<u8>) -> Option<Frame> {
+        (data.len() == width * height * 4).then_some(Frame {
+            width,
+            height,
+            format,
+            data,
+        })
+    }
+
+    /**
+     * The whole frame as a region, e.g. for [`encode_bmp`](Frame::encode_bmp).
+     */
+    pub fn bounds(&self) -> Aabb {
+        Aabb {
+            x: 0,
+            y: 0,
+            w: self.width,
+            h: self.height,
+        }
+    }
+
+    /**
+     * Encodes the `region` of the frame, which must lie within it, as a 24-bit BMP the engine can decode.
+     */
+    pub fn encode_bmp(&self, region: Aabb) -> Vec<u8> {
+        let row_size = (region.w * 3).div_ceil(4) * 4;
+        let pixels_size = row_size * region.h;
+        let mut bmp = Vec::with_capacity(54 + pixels_size);
+        bmp.extend(b"BM");
+        bmp.extend((54 + pixels_size as u32).to_le_bytes());
+        bmp.extend([0; 4]);
+        bmp.extend(54u32.to_le_bytes()); // pixel data offset
+        bmp.extend(40u32.to_le_bytes()); // BITMAPINFOHEADER
+        bmp.extend((region.w as i32).to_le_bytes());
+        bmp.extend((region.h as i32).to_le_bytes()); // positive: bottom-up rows
+        bmp.extend(1u16.to_le_bytes()); // planes
+        bmp.extend(24u16.to_le_bytes()); // bits per pixel
+        bmp.extend([0; 4]); // BI_RGB
+        bmp.extend((pixels_size as u32).to_le_bytes());
+        bmp.extend([0; 16]); // resolution and palette
+        for y in (region.y..region.bottom()).rev() {
+            let start = (y * self.width + region.x) * 4;
+            for pixel in self.data[start..start + region.w * 4].chunks_exact(4) {
+                match self.format {
+                    PixelFormat::Rgba => bmp.extend([pixel[2], pixel[1], pixel[0]]),
+                    PixelFormat::Bgra => bmp.extend([pixel[0], pixel[1], pixel[2]]),
+                }
+            }
+            bmp.resize(bmp.len() + row_size - region.w * 3, 0);
+        }
+        bmp
+    }
+}
+
+/**
+ * An image that the functions working on frames, such as [`crop_detections`](crate::crop::crop_detections), also
+ * accept, returning images of the same type: a [`Frame`], or an `image::DynamicImage` with the `image` feature.
+ */
+pub trait Image: Sized {
+    /// The image as a frame, borrowed if it is one.
+    fn to_frame(&self) -> Cow<'_, Frame>;
+
+    /// An image of this type from `frame`, which has the pixel format of [`to_frame`](Image::to_frame)'s.
+    fn from_frame(frame: Frame) -> Self;
+}
+
+impl Image for Frame {
+    fn to_frame(&self) -> Cow<'_, Frame> {
+        Cow::Borrowed(self)
+    }
+
+    fn from_frame(frame: Frame) -> Self {
+        frame
+    }
+}
+
+/**
+ * Converted to RGBA.
+ */
+#[cfg(feature = "image")]
+impl Image for image::DynamicImage {
+    fn to_frame(&self) -> Cow<'_, Frame> {
+        let rgba = self.to_rgba8();
+        Cow::Owned(Frame {
+            width: rgba.width() as usize,
+            height: rgba.height() as usize,
+            format: PixelFormat::Rgba,
+            data: rgba.into_raw(),
+        })
+    }
+
+    fn from_frame(mut frame: Frame) -> Self {
+        if frame.format == PixelFormat::Bgra {
+            frame
+                .data
+                .chunks_exact_mut(4)
+                .for_each(|pixel| pixel.swap(0, 2));
+        }
+        // The frame's length always matches its size.
+        let rgba = image::RgbaImage::from_raw(frame.width as u32, frame.height as u32, frame.data)
+            .unwrap_or_default();
+        image::DynamicImage::ImageRgba8(rgba)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Frame, PixelFormat};
+    use crate::geometry::Aabb;
+
+    #[test]
+    fn bmp() {
+        let frame = Frame::new(2, 1, PixelFormat::Rgba, vec![1, 2, 3, 255, 4, 5, 6, 255]).unwrap();
+        let bmp = frame.encode_bmp(Aabb {
+            x: 0,
+            y: 0,
+            w: 2,
+            h: 1,
+        });
+        assert_eq!(&bmp[..2], b"BM");
+        assert_eq!(bmp.len(), 54 + 8);
+        assert_eq!(&bmp[54..], [3, 2, 1, 6, 5, 4, 0, 0]);
+        assert!(Frame::new(2, 2, PixelFormat::Rgba, vec![0; 8]).is_none());
+    }
+}
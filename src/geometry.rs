@@ -0,0 +1,522 @@
+//! Geometry helpers for the quadrilaterals returned by the engine.
+
+use crate::Rectangle;
+
+/**
+ * An axis-aligned rectangle: its top-left corner `(x, y)`, width `w` and height `h`.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Aabb {
+    pub x: usize,
+    pub y: usize,
+    pub w: usize,
+    pub h: usize,
+}
+
+impl Aabb {
+    /**
+     * The rectangle spanning `left..right` horizontally and `top..bottom` vertically; empty if inverted.
+     */
+    pub fn from_edges(left: usize, top: usize, right: usize, bottom: usize) -> Aabb {
+        Aabb {
+            x: left,
+            y: top,
+            w: right.saturating_sub(left),
+            h: bottom.saturating_sub(top),
+        }
+    }
+
+    pub fn right(&self) -> usize {
+        self.x + self.w
+    }
+
+    pub fn bottom(&self) -> usize {
+        self.y + self.h
+    }
+
+    pub fn area(&self) -> usize {
+        self.w * self.h
+    }
+
+    pub fn center(&self) -> (f64, f64) {
+        (
+            self.x as f64 + self.w as f64 / 2.0,
+            self.y as f64 + self.h as f64 / 2.0,
+        )
+    }
+
+    /**
+     * Whether the point lies inside, counting the top and left edges but not the bottom and right ones.
+     */
+    pub fn contains(&self, [x, y]: [usize; 2]) -> bool {
+        (self.x..self.right()).contains(&x) && (self.y..self.bottom()).contains(&y)
+    }
+
+    /**
+     * The overlap of two rectangles, or `None` if they don't overlap.
+     */
+    pub fn intersection(&self, other: &Aabb) -> Option<Aabb> {
+        let overlap = Aabb::from_edges(
+            self.x.max(other.x),
+            self.y.max(other.y),
+            self.right().min(other.right()),
+            self.bottom().min(other.bottom()),
+        );
+        (overlap.area() > 0).then_some(overlap)
+    }
+
+    /**
+     * The smallest rectangle containing both.
+     */
+    pub fn union(&self, other: &Aabb) -> Aabb {
+        Aabb::from_edges(
+            self.x.min(other.x),
+            self.y.min(other.y),
+            self.right().max(other.right()),
+            self.bottom().max(other.bottom()),
+        )
+    }
+}
+
+/**
+ * Helpers on the engine's text boxes, whose corners are ordered top-left, top-right, bottom-right, bottom-left.
+ *
+ * ```
+ * use paddleocr::geometry::RectangleExt;
+ *
+ * let rect = [[13, 5], [161, 5], [161, 27], [13, 27]];
+ * assert_eq!(rect.aabb().w, 148);
+ * ```
+ */
+pub trait RectangleExt {
+    /**
+     * The axis-aligned bounding rectangle.
+     */
+    fn aabb(&self) -> Aabb;
+
+    /**
+     * The angle of the top edge in degrees, clockwise from horizontal since the y-axis points down; in `-180.0..=180.0`.
+     */
+    fn rotation_angle(&self) -> f64;
+
+    /**
+     * The box grown outwards by `margin` pixels on every side, along its own (possibly rotated) axes. Coordinates
+     * are clamped at zero.
+     */
+    fn expand(&self, margin: usize) -> Rectangle;
+
+    /**
+     * The box as a closed [`geo::Polygon`] with the corners in the engine's order, for spatial analysis with the
+     * `geo` crate. Requires the `geo` feature.
+     */
+    #[cfg(feature = "geo")]
+    fn to_polygon(&self) -> geo::Polygon<f64>;
+}
+
+impl RectangleExt for Rectangle {
+    fn aabb(&self) -> Aabb {
+        let xs = self.iter().map(|p| p[0]);
+        let ys = self.iter().map(|p| p[1]);
+        Aabb::from_edges(
+            xs.clone().min().unwrap_or(0),
+            ys.clone().min().unwrap_or(0),
+            xs.max().unwrap_or(0),
+            ys.max().unwrap_or(0),
+        )
+    }
+
+    fn rotation_angle(&self) -> f64 {
+        let [[x0, y0], [x1, y1], ..] = *self;
+        (y1 as f64 - y0 as f64)
+            .atan2(x1 as f64 - x0 as f64)
+            .to_degrees()
+    }
+
+    fn expand(&self, margin: usize) -> Rectangle {
+        let point = |i: usize| (self[i][0] as f64, self[i][1] as f64);
+        let unit = |(ax, ay): (f64, f64), (bx, by): (f64, f64)| {
+            let (dx, dy) = (bx - ax, by - ay);
+            let len = dx.hypot(dy);
+            if len > 0.0 {
+                (dx / len, dy / len)
+            } else {
+                (0.0, 0.0)
+            }
+        };
+        // along the top edge, and down the left edge
+        let (ux, uy) = unit(point(0), point(1));
+        let (vx, vy) = match unit(point(0), point(3)) {
+            (0.0, 0.0) => (-uy, ux),
+            v => v,
+        };
+        let m = margin as f64;
+        let signs = [(-1.0, -1.0), (1.0, -1.0), (1.0, 1.0), (-1.0, 1.0)];
+        let mut expanded = *self;
+        for (i, (su, sv)) in signs.into_iter().enumerate() {
+            let (x, y) = point(i);
+            let x = x + m * (su * ux + sv * vx);
+            let y = y + m * (su * uy + sv * vy);
+            expanded[i] = [x.round().max(0.0) as usize, y.round().max(0.0) as usize];
+        }
+        expanded
+    }
+
+    #[cfg(feature = "geo")]
+    fn to_polygon(&self) -> geo::Polygon<f64> {
+        let ring: Vec<geo::Coord<f64>> = self.iter().map(|&p| to_coord(p)).collect();
+        geo::Polygon::new(ring.into(), Vec::new())
+    }
+}
+
+/**
+ * The box of a polygon made by [`RectangleExt::to_polygon`]: its exterior must have exactly four corners, with
+ * coordinates that round to non-negative integers. Requires the `geo` feature.
+ */
+#[cfg(feature = "geo")]
+pub fn from_polygon(polygon: &geo::Polygon<f64>) -> Option<Rectangle> {
+    let coords = &polygon.exterior().0;
+    // Rings are closed, repeating the first corner at the end.
+    let corners = match coords.split_last() {
+        Some((last, rest)) if rest.first() == Some(last) => rest,
+        _ => coords,
+    };
+    let corners: Vec<_> = corners
+        .iter()
+        .map(|&c| from_coord(c))
+        .collect::<Option<_>>()?;
+    corners.try_into().ok()
+}
+
+/**
+ * A point as a [`geo::Coord`]. Requires the `geo` feature.
+ */
+#[cfg(feature = "geo")]
+pub fn to_coord([x, y]: crate::Point) -> geo::Coord<f64> {
+    geo::coord! { x: x as f64, y: y as f64 }
+}
+
+/**
+ * A [`geo::Coord`] as a point, rounded to the nearest pixel, or `None` if it is negative or not finite. Requires the
+ * `geo` feature.
+ */
+#[cfg(feature = "geo")]
+pub fn from_coord(coord: geo::Coord<f64>) -> Option<crate::Point> {
+    let pixel = |v: f64| (v.is_finite() && v.round() >= 0.0).then(|| v.round() as usize);
+    Some([pixel(coord.x)?, pixel(coord.y)?])
+}
+
+#[cfg(feature = "geo")]
+impl From<Aabb> for geo::Rect<f64> {
+    fn from(b: Aabb) -> Self {
+        geo::Rect::new(to_coord([b.x, b.y]), to_coord([b.right(), b.bottom()]))
+    }
+}
+
+#[cfg(feature = "geo")]
+impl From<&crate::ContentData> for geo::Polygon<f64> {
+    fn from(content: &crate::ContentData) -> Self {
+        content.rect.to_polygon()
+    }
+}
+
+/**
+ * Intersection over union of the axis-aligned bounds of two boxes, from `0.0` (disjoint) to `1.0` (identical).
+ */
+pub fn iou(a: &Rectangle, b: &Rectangle) -> f64 {
+    let (a, b) = (a.aabb(), b.aabb());
+    let intersection = a.intersection(&b).map_or(0, |i| i.area()) as f64;
+    let union = (a.area() + b.area()) as f64 - intersection;
+    if union > 0.0 {
+        intersection / union
+    } else {
+        0.0
+    }
+}
+
+/// The corners of the unit square, in the order of a [`Rectangle`]'s.
+pub(crate) const UNIT_SQUARE: [[f64; 2]; 4] = [[0.0, 0.0], [1.0, 0.0], [1.0, 1.0], [0.0, 1.0]];
+
+/// The projective transform taking each of the quadrilateral `from`'s corners to the matching one of `to`, or `None`
+/// if either is degenerate.
+pub(crate) fn homography(
+    from: [[f64; 2]; 4],
+    to: [[f64; 2]; 4],
+) -> Option<impl Fn([f64; 2]) -> [f64; 2]> {
+    // Solve the 8 unknowns of the homography from u = (h0 x + h1 y + h2) / (h6 x + h7 y + 1), likewise for v.
+    let mut system = [[0.0; 9]; 8];
+    for (i, ([x, y], [u, v])) in from.into_iter().zip(to).enumerate() {
+        system[2 * i] = [x, y, 1.0, 0.0, 0.0, 0.0, -u * x, -u * y, u];
+        system[2 * i + 1] = [0.0, 0.0, 0.0, x, y, 1.0, -v * x, -v * y, v];
+    }
+    for column in 0..8 {
+        let pivot = (column..8)
+            .max_by(|&a, &b| system[a][column].abs().total_cmp(&system[b][column].abs()))?;
+        if system[pivot][column].abs() < 1e-12 {
+            return None;
+        }
+        system.swap(column, pivot);
+        let pivot_row = system[column];
+        for (i, row) in system.iter_mut().enumerate() {
+            if i != column {
+                let factor = row[column] / pivot_row[column];
+                for (value, pivot_value) in row.iter_mut().zip(pivot_row).skip(column) {
+                    *value -= factor * pivot_value;
+                }
+            }
+        }
+    }
+    let h: Vec<f64> = (0..8).map(|i| system[i][8] / system[i][i]).collect();
+    Some(move |[x, y]: [f64; 2]| {
+        let w = h[6] * x + h[7] * y + 1.0;
+        [
+            (h[0] * x + h[1] * y + h[2]) / w,
+            (h[3] * x + h[4] * y + h[5]) / w,
+        ]
+    })
+}
+
+/**
+ * Maps coordinates in a preprocessed image back to the original one.
+ *
+ * Build one per preprocessing step (resize, crop, pad) and chain them with [`CoordTransform::then`] in the order
+ * the steps were applied; the result maps the engine's boxes for the final image back onto the original. It is a
+ * [`ResultTransform`](crate::ResultTransform), so it can be applied to a result set or registered on an instance.
+ *
+ * ```
+ * use paddleocr::geometry::CoordTransform;
+ *
+ * // cropped the region at (100, 50), then halved its size
+ * let back = CoordTransform::cropped(100, 50).then(CoordTransform::resized((800, 600), (400, 300)));
+ * assert_eq!(back.apply_point([10, 10]), [120, 70]);
+ * ```
+ */
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CoordTransform {
+    scale: (f64, f64),
+    offset: (f64, f64),
+}
+
+impl Default for CoordTransform {
+    fn default() -> Self {
+        CoordTransform::identity()
+    }
+}
+
+impl CoordTransform {
+    pub fn identity() -> CoordTransform {
+        CoordTransform {
+            scale: (1.0, 1.0),
+            offset: (0.0, 0.0),
+        }
+    }
+
+    /**
+     * The image was resized from `original` to `processed`, both as `(width, height)`.
+     */
+    pub fn resized(original: (usize, usize), processed: (usize, usize)) -> CoordTransform {
+        let ratio = |o: usize, p: usize| if p == 0 { 1.0 } else { o as f64 / p as f64 };
+        CoordTransform {
+            scale: (
+                ratio(original.0, processed.0),
+                ratio(original.1, processed.1),
+            ),
+            offset: (0.0, 0.0),
+        }
+    }
+
+    /**
+     * The image was cropped to the region whose top-left corner is at `(x, y)`.
+     */
+    pub fn cropped(x: usize, y: usize) -> CoordTransform {
+        CoordTransform {
+            scale: (1.0, 1.0),
+            offset: (x as f64, y as f64),
+        }
+    }
+
+    /**
+     * The image was padded with `left` and `top` pixels; padding on the right and bottom doesn't move anything.
+     */
+    pub fn padded(left: usize, top: usize) -> CoordTransform {
+        CoordTransform {
+            scale: (1.0, 1.0),
+            offset: (-(left as f64), -(top as f64)),
+        }
+    }
+
+    /**
+     * The transform for when the image was processed by `next` after the steps `self` accounts for.
+     */
+    pub fn then(self, next: CoordTransform) -> CoordTransform {
+        CoordTransform {
+            scale: (self.scale.0 * next.scale.0, self.scale.1 * next.scale.1),
+            offset: (
+                next.offset.0 * self.scale.0 + self.offset.0,
+                next.offset.1 * self.scale.1 + self.offset.1,
+            ),
+        }
+    }
+
+    /**
+     * Maps a point, rounding to the nearest pixel and clamping at zero.
+     */
+    pub fn apply_point(&self, [x, y]: [usize; 2]) -> [usize; 2] {
+        let map = |v: usize, scale: f64, offset: f64| {
+            (v as f64 * scale + offset).round().max(0.0) as usize
+        };
+        [
+            map(x, self.scale.0, self.offset.0),
+            map(y, self.scale.1, self.offset.1),
+        ]
+    }
+
+    pub fn apply(&self, rect: &Rectangle) -> Rectangle {
+        rect.map(|p| self.apply_point(p))
+    }
+}
+
+impl crate::ResultTransform for CoordTransform {
+    fn transform(&self, mut data: Vec<crate::ContentData>) -> Vec<crate::ContentData> {
+        for c in &mut data {
+            c.rect = self.apply(&c.rect);
+        }
+        data
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::{prop_assert, prop_assert_eq, proptest};
+
+    use super::{homography, iou, Aabb, CoordTransform, RectangleExt, UNIT_SQUARE};
+    use crate::testing::strategy;
+
+    #[test]
+    fn homographies() {
+        let quad = [[10.0, 20.0], [110.0, 30.0], [100.0, 80.0], [5.0, 60.0]];
+        let (to_quad, to_square) = (
+            homography(UNIT_SQUARE, quad).unwrap(),
+            homography(quad, UNIT_SQUARE).unwrap(),
+        );
+        for (corner, [x, y]) in UNIT_SQUARE.into_iter().zip(quad) {
+            let [qx, qy] = to_quad(corner);
+            assert!((qx - x).abs() < 1e-9 && (qy - y).abs() < 1e-9);
+        }
+        let [u, v] = to_square(to_quad([0.25, 0.5]));
+        assert!((u - 0.25).abs() < 1e-9 && (v - 0.5).abs() < 1e-9);
+        let flat = [[0.0, 0.0], [10.0, 0.0], [20.0, 0.0], [30.0, 0.0]];
+        assert!(homography(UNIT_SQUARE, flat).is_none());
+    }
+
+    #[test]
+    fn coord_transforms() {
+        let rect = [[10, 10], [20, 10], [20, 20], [10, 20]];
+        assert_eq!(CoordTransform::identity().apply(&rect), rect);
+        assert_eq!(
+            CoordTransform::resized((200, 100), (100, 100)).apply(&rect),
+            [[20, 10], [40, 10], [40, 20], [20, 20]]
+        );
+        assert_eq!(CoordTransform::padded(15, 0).apply_point([10, 10]), [0, 10]);
+
+        // pad by 5, then double the size, then crop at (40, 40)
+        let back = CoordTransform::padded(5, 5)
+            .then(CoordTransform::resized((10, 10), (20, 20)))
+            .then(CoordTransform::cropped(40, 40));
+        // (0, 0) after cropping is (40, 40) in the doubled image, (20, 20) when padded, (15, 15) originally
+        assert_eq!(back.apply_point([0, 0]), [15, 15]);
+        assert_eq!(back.apply_point([10, 2]), [20, 16]);
+    }
+
+    #[test]
+    fn aabb() {
+        let rect = [[13, 5], [161, 8], [160, 27], [12, 24]];
+        let aabb = rect.aabb();
+        assert_eq!(
+            aabb,
+            Aabb {
+                x: 12,
+                y: 5,
+                w: 149,
+                h: 22
+            }
+        );
+        assert!(aabb.contains([12, 5]) && !aabb.contains([161, 5]));
+        let other = Aabb::from_edges(100, 20, 200, 40);
+        assert_eq!(
+            aabb.intersection(&other),
+            Some(Aabb::from_edges(100, 20, 161, 27))
+        );
+        assert_eq!(aabb.union(&other), Aabb::from_edges(12, 5, 200, 40));
+        assert_eq!(aabb.intersection(&Aabb::from_edges(0, 100, 5, 105)), None);
+    }
+
+    #[test]
+    fn rotation_and_expansion() {
+        let upright = [[10, 10], [20, 10], [20, 20], [10, 20]];
+        assert_eq!(upright.rotation_angle(), 0.0);
+        assert_eq!(upright.expand(2), [[8, 8], [22, 8], [22, 22], [8, 22]]);
+        assert_eq!(upright.expand(20), [[0, 0], [40, 0], [40, 40], [0, 40]]);
+
+        let tilted = [[10, 10], [20, 20], [10, 30], [0, 20]];
+        assert!((tilted.rotation_angle() - 45.0).abs() < 1e-9);
+        let expanded = tilted.expand(1);
+        assert!(expanded[0][1] < 10 && expanded[2][1] > 30);
+
+        let upside_down = [[20, 20], [10, 20], [10, 10], [20, 10]];
+        assert_eq!(upside_down.rotation_angle(), 180.0);
+    }
+
+    #[test]
+    fn iou_of_boxes() {
+        let a = [[0, 0], [10, 0], [10, 10], [0, 10]];
+        let b = [[5, 0], [15, 0], [15, 10], [5, 10]];
+        let c = [[20, 20], [30, 20], [30, 30], [20, 30]];
+        assert_eq!(iou(&a, &a), 1.0);
+        assert!((iou(&a, &b) - 50.0 / 150.0).abs() < 1e-9);
+        assert_eq!(iou(&a, &b), iou(&b, &a));
+        assert_eq!(iou(&a, &c), 0.0);
+    }
+
+    proptest! {
+        #[test]
+        fn iou_properties(a in strategy::rectangle(), b in strategy::rectangle()) {
+            let overlap = iou(&a, &b);
+            prop_assert!((0.0..=1.0).contains(&overlap));
+            prop_assert_eq!(overlap, iou(&b, &a));
+            prop_assert_eq!(iou(&a, &a), 1.0);
+        }
+
+        #[test]
+        #[cfg(feature = "geo")]
+        fn geo_round_trip(rect in strategy::rectangle()) {
+            prop_assert_eq!(super::from_polygon(&rect.to_polygon()), Some(rect));
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "geo")]
+    fn geo_conversions() {
+        use geo::{Contains, Intersects};
+
+        use super::{from_coord, from_polygon, to_coord};
+
+        let skewed = [[10, 20], [110, 25], [108, 45], [8, 40]];
+        let polygon = skewed.to_polygon();
+        assert_eq!(polygon.exterior().0.len(), 5);
+        assert_eq!(from_polygon(&polygon), Some(skewed));
+        assert!(polygon.contains(&geo::point! { x: 60.0, y: 32.0 }));
+        assert!(!polygon.contains(&geo::point! { x: 9.0, y: 21.0 }));
+
+        let button = geo::Rect::from(Aabb::from_edges(100, 0, 200, 30));
+        assert!(polygon.intersects(&button));
+        assert!(from_polygon(&button.to_polygon()).is_some());
+        assert_eq!(to_coord([3, 4]), geo::coord! { x: 3.0, y: 4.0 });
+        assert_eq!(from_coord(geo::coord! { x: 2.6, y: 0.2 }), Some([3, 0]));
+        assert_eq!(from_coord(geo::coord! { x: -1.0, y: 0.0 }), None);
+        assert_eq!(from_coord(geo::coord! { x: f64::NAN, y: 0.0 }), None);
+
+        let triangle =
+            geo::Polygon::new(vec![(0.0, 0.0), (1.0, 0.0), (0.0, 1.0)].into(), Vec::new());
+        assert_eq!(from_polygon(&triangle), None);
+    }
+}
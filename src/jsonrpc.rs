@@ -0,0 +1,149 @@
+//! Newline-delimited JSON-RPC 2.0 framing, shared by the [MCP server](crate::mcp) and the command line's `--rpc`
+//! mode: one request per input line, one response per output line.
+
+use std::io::{BufRead, Result as IoResult, Write};
+
+use serde_json::{json, Value};
+
+use crate::ImageData;
+
+pub const PARSE_ERROR: i64 = -32700;
+pub const METHOD_NOT_FOUND: i64 = -32601;
+pub const INVALID_PARAMS: i64 = -32602;
+pub const INTERNAL_ERROR: i64 = -32603;
+
+/**
+ * The code and message of an error response.
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RpcError {
+    pub code: i64,
+    pub message: String,
+}
+
+impl RpcError {
+    pub fn new(code: i64, message: impl Into<String>) -> RpcError {
+        RpcError {
+            code,
+            message: message.into(),
+        }
+    }
+
+    pub fn method_not_found(method: &str) -> RpcError {
+        RpcError::new(METHOD_NOT_FOUND, format!("Method not found: {}", method))
+    }
+}
+
+/**
+ * Answers the requests read line by line from `input` with `handle(method, params)`, writing one response line per
+ * request to `output`, until `input` ends. Notifications, which have no `id`, are neither handled nor answered.
+ */
+pub fn serve<R, W, F>(input: R, mut output: W, mut handle: F) -> IoResult<()>
+where
+    R: BufRead,
+    W: Write,
+    F: FnMut(&str, &Value) -> Result<Value, RpcError>,
+{
+    for line in input.lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        if let Some(response) = respond(&line, &mut handle) {
+            serde_json::to_writer(&mut output, &response)?;
+            output.write_all(b"\n")?;
+            output.flush()?;
+        }
+    }
+    Ok(())
+}
+
+/// The response to one message, or `None` for notifications.
+fn respond<F>(line: &str, handle: &mut F) -> Option<Value>
+where
+    F: FnMut(&str, &Value) -> Result<Value, RpcError>,
+{
+    let (id, result) = match serde_json::from_str::<Value>(line) {
+        Ok(request) => {
+            let id = request.get("id")?.clone();
+            let method = request["method"].as_str().unwrap_or_default();
+            (id, handle(method, &request["params"]))
+        }
+        Err(e) => (
+            Value::Null,
+            Err(RpcError::new(PARSE_ERROR, format!("Parse error: {}", e))),
+        ),
+    };
+    Some(match result {
+        Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+        Err(RpcError { code, message }) => {
+            json!({ "jsonrpc": "2.0", "id": id, "error": { "code": code, "message": message } })
+        }
+    })
+}
+
+/**
+ * The image given as a `path` or as `base64` data in `params`, preferring the path.
+ */
+pub fn image_param(params: &Value) -> Option<ImageData> {
+    match (params["path"].as_str(), params["base64"].as_str()) {
+        (Some(path), _) => Some(ImageData::from_path(path)),
+        (None, Some(base64)) => Some(ImageData::from_base64(base64.to_string())),
+        (None, None) => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Value};
+
+    use super::{RpcError, INVALID_PARAMS, PARSE_ERROR};
+
+    #[test]
+    fn framing() {
+        let input = concat!(
+            r#"{"jsonrpc":"2.0","id":1,"method":"echo","params":{"a":1}}"#,
+            "\n\n",
+            r#"{"jsonrpc":"2.0","method":"echo"}"#,
+            "\n",
+            r#"{"jsonrpc":"2.0","id":"b","method":"fail"}"#,
+            "\n{\n",
+        );
+        let mut handled = Vec::new();
+        let mut output = Vec::new();
+        super::serve(input.as_bytes(), &mut output, |method, params| {
+            handled.push(method.to_string());
+            match method {
+                "echo" => Ok(params.clone()),
+                _ => Err(RpcError::new(INVALID_PARAMS, "no")),
+            }
+        })
+        .unwrap();
+        let responses: Vec<Value> = String::from_utf8(output)
+            .unwrap()
+            .lines()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect();
+
+        assert_eq!(handled, ["echo", "fail"]);
+        assert_eq!(
+            responses,
+            [
+                json!({ "jsonrpc": "2.0", "id": 1, "result": { "a": 1 } }),
+                json!({ "jsonrpc": "2.0", "id": "b", "error": { "code": INVALID_PARAMS, "message": "no" } }),
+                json!({ "jsonrpc": "2.0", "id": null, "error": { "code": PARSE_ERROR, "message": responses[2]["error"]["message"] } }),
+            ]
+        );
+    }
+
+    #[test]
+    fn images() {
+        let image = |params: Value| super::image_param(&params).map(|i| format!("{:?}", i));
+        assert_eq!(
+            image(json!({ "path": "a.png", "base64": "AAAA" })),
+            image(json!({ "path": "a.png" }))
+        );
+        assert!(image(json!({ "base64": "AAAA" })).unwrap().contains("AAAA"));
+        assert_eq!(image(json!({})), None);
+    }
+}
@@ -0,0 +1,242 @@
+//! Reading-order reconstruction: grouping detections into lines, and lines into headings, paragraphs and tables.
+//!
+//! The heuristics only look at box positions and sizes, which is enough for the single-column documents, receipts
+//! and forms the engine is usually fed. Multi-column layouts come out interleaved.
+
+use crate::geometry::{Aabb, RectangleExt};
+use crate::ContentData;
+
+/**
+ * Detections sharing a baseline, left to right.
+ */
+#[derive(Debug, Clone)]
+pub struct Line<'a> {
+    pub items: Vec<&'a ContentData>,
+    /// The bounding box of all items.
+    pub bounds: Aabb,
+}
+
+impl Line<'_> {
+    /**
+     * The items' texts joined with spaces.
+     */
+    pub fn text(&self) -> String {
+        self.items
+            .iter()
+            .map(|c| c.text.as_str())
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+/**
+ * A unit of a reconstructed document.
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Block {
+    /// A single line set noticeably larger than the body text.
+    Heading(String),
+    /// Consecutive lines without a paragraph gap, joined with spaces.
+    Paragraph(String),
+    /// Rows of cells, from consecutive lines split into the same number of widely spaced columns.
+    Table(Vec<Vec<String>>),
+}
+
+/**
+ * Groups detections into lines, top to bottom. A detection joins a line if its vertical center lies within the
+ * line's extent.
+ */
+pub fn lines(results: &[ContentData]) -> Vec<Line<'_>> {
+    let mut sorted: Vec<(&ContentData, Aabb)> =
+        results.iter().map(|c| (c, c.rect.aabb())).collect();
+    sorted.sort_by_key(|(_, b)| (b.y, b.x));
+
+    let mut lines: Vec<Line> = Vec::new();
+    for (content, bounds) in sorted {
+        let center = bounds.center().1;
+        match lines.last_mut() {
+            Some(line) if (line.bounds.y as f64..line.bounds.bottom() as f64).contains(&center) => {
+                line.items.push(content);
+                line.bounds = line.bounds.union(&bounds);
+            }
+            _ => lines.push(Line {
+                items: vec![content],
+                bounds,
+            }),
+        }
+    }
+    for line in &mut lines {
+        line.items.sort_by_key(|c| c.rect.aabb().x);
+    }
+    lines
+}
+
+/**
+Reconstructs the document structure of one page.
+
+- A line at least 1.5 times as tall as the median line is a heading.
+- A vertical gap of more than 0.8 median line heights starts a new paragraph.
+- Two or more consecutive lines whose items are more than two line heights apart, with the same number of items,
+  form a table.
+*/
+pub fn blocks(results: &[ContentData]) -> Vec<Block> {
+    let lines = lines(results);
+    let Some(line_height) = median_height(&lines) else {
+        return Vec::new();
+    };
+    let cells = |line: &Line| -> Option<Vec<String>> {
+        let spread = line.items.windows(2).all(|pair| {
+            let gap = pair[1]
+                .rect
+                .aabb()
+                .x
+                .saturating_sub(pair[0].rect.aabb().right());
+            gap as f64 > 2.0 * line_height
+        });
+        (line.items.len() > 1 && spread)
+            .then(|| line.items.iter().map(|c| c.text.clone()).collect())
+    };
+
+    let mut blocks = Vec::new();
+    let mut paragraph: Vec<String> = Vec::new();
+    let mut previous_bottom: Option<usize> = None;
+    let mut i = 0;
+    while i < lines.len() {
+        let line = &lines[i];
+        let gap = previous_bottom.map_or(0, |bottom| line.bounds.y.saturating_sub(bottom));
+        if gap as f64 > 0.8 * line_height {
+            flush(&mut paragraph, &mut blocks);
+        }
+
+        if let Some(first) = cells(line) {
+            let mut rows = vec![first];
+            let mut j = i + 1;
+            while let Some(row) = lines.get(j).and_then(cells) {
+                if row.len() != rows[0].len() {
+                    break;
+                }
+                rows.push(row);
+                j += 1;
+            }
+            if rows.len() > 1 {
+                flush(&mut paragraph, &mut blocks);
+                blocks.push(Block::Table(rows));
+                previous_bottom = Some(lines[j - 1].bounds.bottom());
+                i = j;
+                continue;
+            }
+        }
+
+        if line.bounds.h as f64 >= 1.5 * line_height {
+            flush(&mut paragraph, &mut blocks);
+            blocks.push(Block::Heading(line.text()));
+        } else {
+            paragraph.push(line.text());
+        }
+        previous_bottom = Some(line.bounds.bottom());
+        i += 1;
+    }
+    flush(&mut paragraph, &mut blocks);
+    blocks
+}
+
+fn median_height(lines: &[Line]) -> Option<f64> {
+    let mut heights: Vec<usize> = lines.iter().map(|l| l.bounds.h).collect();
+    heights.sort_unstable();
+    heights.get(heights.len() / 2).map(|&h| h.max(1) as f64)
+}
+
+fn flush(paragraph: &mut Vec<String>, blocks: &mut Vec<Block>) {
+    if !paragraph.is_empty() {
+        blocks.push(Block::Paragraph(paragraph.join(" ")));
+        paragraph.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::{prop_assert, prop_assert_eq, proptest};
+
+    use super::{blocks, lines, Block};
+    use crate::geometry::RectangleExt;
+    use crate::testing::strategy;
+    use crate::{detection, ContentData};
+
+    fn item(text: &str, x: usize, y: usize, w: usize, h: usize) -> ContentData {
+        detection(text, [[x, y], [x + w, y], [x + w, y + h], [x, y + h]], 0.9)
+    }
+
+    #[test]
+    fn reading_order() {
+        let results = [
+            item("world", 60, 12, 50, 20),
+            item("hello", 0, 10, 50, 20),
+            item("next", 0, 32, 40, 20),
+        ];
+        let lines = lines(&results);
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0].text(), "hello world");
+        assert_eq!(lines[1].text(), "next");
+    }
+
+    #[test]
+    fn structure() {
+        let results = [
+            item("Title", 0, 0, 200, 40),
+            item("first line", 0, 50, 300, 20),
+            item("continues", 0, 72, 300, 20),
+            item("Item", 0, 120, 60, 20),
+            item("Price", 200, 120, 60, 20),
+            item("Tea", 0, 142, 40, 20),
+            item("3.50", 200, 142, 40, 20),
+            item("after", 0, 200, 60, 20),
+        ];
+        assert_eq!(
+            blocks(&results),
+            [
+                Block::Heading("Title".into()),
+                Block::Paragraph("first line continues".into()),
+                Block::Table(vec![
+                    vec!["Item".into(), "Price".into()],
+                    vec!["Tea".into(), "3.50".into()]
+                ]),
+                Block::Paragraph("after".into()),
+            ]
+        );
+    }
+
+    proptest! {
+        #[test]
+        fn line_properties(results in strategy::results(30)) {
+            let lines = lines(&results);
+            // every detection lands in exactly one line, whose bounds cover it
+            prop_assert_eq!(lines.iter().map(|l| l.items.len()).sum::<usize>(), results.len());
+            for line in &lines {
+                for item in &line.items {
+                    let b = item.rect.aabb();
+                    prop_assert_eq!(line.bounds.union(&b), line.bounds);
+                }
+                prop_assert!(line.items.windows(2).all(|w| w[0].rect.aabb().x <= w[1].rect.aabb().x));
+            }
+        }
+
+        #[test]
+        fn blocks_keep_all_words(results in strategy::results(30)) {
+            let mut before: Vec<String> = results
+                .iter()
+                .flat_map(|c| c.text.split_whitespace().map(String::from))
+                .collect();
+            let mut after: Vec<String> = blocks(&results)
+                .into_iter()
+                .flat_map(|block| match block {
+                    Block::Heading(text) | Block::Paragraph(text) => vec![text],
+                    Block::Table(rows) => rows.concat(),
+                })
+                .flat_map(|text| text.split_whitespace().map(String::from).collect::<Vec<_>>())
+                .collect();
+            before.sort();
+            after.sort();
+            prop_assert_eq!(before, after);
+        }
+    }
+}
@@ -1,12 +1,111 @@
 use std::io::Result as IoResult;
-use std::io::{BufRead, BufReader, Write};
+use std::io::{BufRead, BufReader, Read, Write};
 use std::path::Path;
+#[cfg(feature = "local")]
 use std::process;
 use std::{error::Error, fmt, path::PathBuf};
 
+pub mod automation;
+pub mod barcode;
+pub mod batch;
+#[cfg(feature = "local")]
+mod builder;
+#[cfg(feature = "bundled")]
+pub mod bundled;
+pub mod cache;
+mod capabilities;
+mod client;
+#[cfg(feature = "bytes")]
+pub mod clipboard;
+#[cfg(feature = "local")]
+mod codepage;
+pub mod confidence;
+pub mod crop;
+pub mod diff;
+#[cfg(feature = "local")]
+mod docker;
+mod ensemble;
+mod error;
+pub mod eval;
+#[cfg(feature = "local")]
+mod exit;
+pub mod export;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+pub mod frame;
+pub mod geometry;
+pub mod jsonrpc;
+pub mod layout;
+#[cfg(feature = "mcp")]
+pub mod mcp;
+pub mod middleware;
+mod options;
+#[cfg(feature = "local")]
+mod orphans;
+#[cfg(feature = "otel")]
+pub mod otel;
+mod pool;
+pub mod redact;
+mod response;
+#[cfg(feature = "schemars")]
+pub mod schema;
+#[cfg(feature = "bytes")]
+pub mod screen;
+#[cfg(feature = "search-index")]
+pub mod search;
+#[cfg(all(feature = "local", feature = "bytes"))]
+mod spill;
+#[cfg(feature = "local")]
+mod ssh;
+pub mod supervisor;
+#[cfg(feature = "local")]
+mod suspend;
+pub mod templates;
+#[cfg(feature = "testing")]
+pub mod testing;
+pub mod tiling;
+pub mod transform;
+pub mod translate;
+#[cfg(feature = "local")]
+mod wine;
+pub mod words;
+#[cfg(any(
+    feature = "bundled",
+    feature = "bytes",
+    feature = "docx",
+    feature = "epub"
+))]
+mod zip;
+#[cfg(feature = "local")]
+pub use builder::{ModelTier, PpocrBuilder};
+pub use capabilities::Capabilities;
+pub use client::PpocrClient;
+#[cfg(feature = "local")]
+pub use codepage::PathNotRepresentable;
+pub use confidence::Confidence;
+#[cfg(feature = "local")]
+pub use docker::DockerPpocr;
+pub use ensemble::Ensemble;
+pub use error::{ClipboardError, PpocrError, ProtocolError};
+#[cfg(feature = "log")]
+pub use exit::LOG_TARGET;
+#[cfg(feature = "local")]
+pub use exit::{DropBehavior, ExitInfo, LogLevel, LogLine};
+pub use options::{LimitType, OcrOptions};
+#[cfg(feature = "local")]
+pub use orphans::cleanup_orphans;
+pub use pool::{PpocrPool, Prioritized, Priority, QueueFull};
+pub use response::OcrResponse;
+#[cfg(feature = "local")]
+pub use ssh::SshPpocr;
+pub use transform::ResultTransform;
+pub use translate::{TranslationUnit, Translator};
+#[cfg(feature = "local")]
+pub use wine::{to_wine_path, WineConfig};
+
 use serde::{
     Deserialize, // for `ocr_and_parse`
-    Serialize,   // for `WriteDict`
+    Serialize,   // for `WriteDict` and re-emitting responses
 };
 
 #[derive(Debug, Clone)]
@@ -18,29 +117,252 @@ impl fmt::Display for OsNotSupportedError {
 }
 impl Error for OsNotSupportedError {}
 
+/**
+ * The engine's response exceeded the limit set with [`Ppocr::set_max_response_size`]. Found as the
+ * [`source`](RequestError::source) of the [`RequestError`] in an [`std::io::Error`] of kind
+ * [`InvalidData`](std::io::ErrorKind::InvalidData).
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResponseTooLarge {
+    /// The limit in bytes.
+    pub limit: usize,
+}
+impl fmt::Display for ResponseTooLarge {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "engine response exceeds {} bytes", self.limit)
+    }
+}
+impl Error for ResponseTooLarge {}
+
+/**
+ * A request exceeded the limit set with [`Ppocr::set_max_payload_size`], and wasn't sent. Returned as the inner
+ * error of an [`std::io::Error`] of kind [`InvalidInput`](std::io::ErrorKind::InvalidInput).
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PayloadTooLarge {
+    /// The size of the request in bytes.
+    pub size: usize,
+    /// The limit in bytes.
+    pub limit: usize,
+}
+impl fmt::Display for PayloadTooLarge {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "OCR request of {} bytes exceeds the limit of {} bytes",
+            self.size, self.limit
+        )
+    }
+}
+impl Error for PayloadTooLarge {}
+
+/**
+ * A failed OCR request, with what it was about. Returned as the inner error of an [`std::io::Error`] of the same
+ * kind as [`RequestError::source`].
+ */
+#[derive(Debug)]
+pub struct RequestError {
+    /// The image, as its path or `base64, N bytes`.
+    pub input: String,
+    /// The number of the request on its engine, counting from 1.
+    pub request_id: u64,
+    /// The size of the request as sent, in bytes.
+    pub payload_size: usize,
+    /// How long the request ran before failing. Only measured with the `local` feature, as there is no clock on
+    /// `wasm32-unknown-unknown`.
+    pub elapsed: Option<std::time::Duration>,
+    /// The engine's exit status, if it died.
+    pub exit_status: Option<std::process::ExitStatus>,
+    source: std::io::Error,
+}
+impl RequestError {
+    /// The underlying error, e.g. to find a [`ResponseTooLarge`] in it.
+    pub fn source(&self) -> &std::io::Error {
+        &self.source
+    }
+}
+impl fmt::Display for RequestError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "OCR request #{} ({}) failed",
+            self.request_id, self.input
+        )?;
+        if let Some(elapsed) = self.elapsed {
+            write!(f, " after {:.2?}", elapsed)?;
+        }
+        write!(f, ": {}", self.source)?;
+        match self.exit_status {
+            Some(status) => write!(f, "; the engine exited with {}", status),
+            None => Ok(()),
+        }
+    }
+}
+impl Error for RequestError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Some(&self.source)
+    }
+}
+
+/// A 1-bit BMP reading `HELLO 123`, for [`Ppocr::self_test`].
+const SELF_TEST_IMAGE: &[u8] = include_bytes!("../assets/self_test.bmp");
+
+/// The default for [`Ppocr::set_max_response_size`].
+const MAX_RESPONSE_SIZE: usize = 64 << 20;
+/// The default for [`Ppocr::set_max_payload_size`].
+const MAX_PAYLOAD_SIZE: usize = 256 << 20;
+
 type Point = [usize; 2];
 
-#[derive(Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(untagged)]
 pub enum OcrRec {
     Content { code: u32, data: Vec<ContentData> },
     Message { code: u32, data: String },
 }
 
-#[derive(Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[cfg_attr(
+    feature = "schemars",
+    derive(schemars::JsonSchema),
+    schemars(description = "A detected text line.")
+)]
 pub struct ContentData {
-    #[serde(rename(deserialize = "box"))]
+    /// The corners of the detection, clockwise from the top left, as `[x, y]` pixel positions.
+    #[serde(rename = "box")]
     pub rect: Rectangle,
-    pub score: f64,
+    pub score: Confidence,
     pub text: String,
+    /// The direction classifier's verdict, reported by engines started with `use_angle_cls` enabled. `None` also for
+    /// labels this version doesn't know, such as the `-1` of a line the classifier didn't run on.
+    #[serde(
+        rename = "cls_label",
+        default,
+        deserialize_with = "known_orientation",
+        skip_serializing_if = "Option::is_none"
+    )]
+    #[cfg_attr(feature = "schemars", schemars(with = "Orientation"))]
+    pub orientation: Option<Orientation>,
+    /// Fields this version doesn't know of, such as those added by newer engines, kept as the engine sent them.
+    #[serde(flatten, default, skip_serializing_if = "serde_json::Map::is_empty")]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+/// The orientation of a `cls_label`, or `None` for any label or value that isn't a known one, rather than failing the
+/// whole response.
+fn known_orientation<'de, D>(deserializer: D) -> Result<Option<Orientation>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let label = serde_json::Value::deserialize(deserializer)?;
+    Ok(label
+        .as_u64()
+        .and_then(|label| u8::try_from(label).ok())
+        .and_then(|label| Orientation::try_from(label).ok()))
+}
+
+/// A detection of `text` at `rect`, for the tests.
+#[cfg(test)]
+pub(crate) fn detection(text: &str, rect: Rectangle, score: f64) -> ContentData {
+    ContentData {
+        rect,
+        score: Confidence(score),
+        text: text.to_string(),
+        orientation: None,
+        extra: Default::default(),
+    }
+}
+
+/**
+ * Orientation of a text line as found by the engine's direction classifier.
+ */
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(try_from = "u8", into = "u8")]
+pub enum Orientation {
+    /// Reads normally (`cls_label` 0).
+    Upright,
+    /// Upside down (`cls_label` 1).
+    Rotated180,
+}
+
+impl TryFrom<u8> for Orientation {
+    type Error = String;
+
+    fn try_from(label: u8) -> Result<Self, Self::Error> {
+        match label {
+            0 => Ok(Orientation::Upright),
+            1 => Ok(Orientation::Rotated180),
+            _ => Err(format!("unknown cls_label {}", label)),
+        }
+    }
+}
+
+#[cfg(feature = "schemars")]
+impl schemars::JsonSchema for Orientation {
+    fn schema_name() -> std::borrow::Cow<'static, str> {
+        "Orientation".into()
+    }
+
+    fn json_schema(_: &mut schemars::SchemaGenerator) -> schemars::Schema {
+        schemars::json_schema!({
+            "description": "The direction classifier's verdict: 0 if upright, 1 if upside down.",
+            "enum": [0, 1],
+        })
+    }
+}
+
+impl From<Orientation> for u8 {
+    fn from(orientation: Orientation) -> Self {
+        match orientation {
+            Orientation::Upright => 0,
+            Orientation::Rotated180 => 1,
+        }
+    }
+}
+
+impl ContentData {
+    /**
+     * The field `key` of [`extra`](ContentData::extra), if the engine sent it and it is a `T`.
+     */
+    pub fn extra_field<T: serde::de::DeserializeOwned>(&self, key: &str) -> Option<T> {
+        T::deserialize(self.extra.get(key)?).ok()
+    }
+
+    /**
+     * Whether the text likely runs top to bottom, as in vertical CJK writing: its box is clearly taller than
+     * wide while holding more than one character. This is a geometric guess, the engine doesn't report it.
+     */
+    pub fn is_vertical(&self) -> bool {
+        let aabb = geometry::RectangleExt::aabb(&self.rect);
+        self.text.chars().count() > 1 && aabb.h > aabb.w.saturating_mul(3) / 2
+    }
 }
 
 pub type Rectangle = [Point; 4];
 
+impl fmt::Display for ContentData {
+    /**
+     * The text, its score and its bounds, e.g. `"飞舞的因果交流" (0.9996) @ (13,5)-(161,27)`.
+     */
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let aabb = geometry::RectangleExt::aabb(&self.rect);
+        write!(
+            f,
+            "{:?} ({:.4}) @ ({},{})-({},{})",
+            self.text,
+            self.score.0,
+            aabb.x,
+            aabb.y,
+            aabb.right(),
+            aabb.bottom()
+        )
+    }
+}
+
 /**
  * The image to be recognized.
  */
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, Serialize)]
 #[serde(untagged)]
 pub enum ImageData {
     ImagePathDict { image_path: String },
@@ -59,6 +381,85 @@ impl ImageData {
             image_path: path.to_string(),
         }
     }
+    /**
+     * The image's path, or `base64, N bytes` with the size of the decoded image, for error messages.
+     */
+    pub fn describe(&self) -> String {
+        match self {
+            ImageData::ImagePathDict { image_path } => image_path.clone(),
+            ImageData::ImageBase64Dict { image_base64 } => {
+                let padding = image_base64
+                    .bytes()
+                    .rev()
+                    .take_while(|&b| b == b'=')
+                    .count();
+                let size = (image_base64.len() / 4 * 3).saturating_sub(padding);
+                format!("base64, {} bytes", size)
+            }
+        }
+    }
+    /**
+     * Checks that a base64 image decodes, and that encoding the result gives it back: only the standard alphabet,
+     * padded to a multiple of 4 characters, without stray bits in the last character. The error says where it
+     * isn't, so a corrupt image fails here rather than as an opaque engine error.
+     */
+    fn validate(&self) -> IoResult<()> {
+        let ImageData::ImageBase64Dict { image_base64 } = self else {
+            return Ok(());
+        };
+        let invalid = |message: String| {
+            Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!(
+                    "invalid base64 image ({} characters): {}",
+                    image_base64.len(),
+                    message
+                ),
+            ))
+        };
+        let value = |b: u8| match b {
+            b'A'..=b'Z' => Some(b - b'A'),
+            b'a'..=b'z' => Some(b - b'a' + 26),
+            b'0'..=b'9' => Some(b - b'0' + 52),
+            b'+' => Some(62),
+            b'/' => Some(63),
+            _ => None,
+        };
+        let bytes = image_base64.as_bytes();
+        if bytes.is_empty() || bytes.len() % 4 != 0 {
+            return invalid("its length is not a positive multiple of 4".to_string());
+        }
+        let padding = bytes.iter().rev().take_while(|&&b| b == b'=').count();
+        if padding > 2 {
+            return invalid("it ends with more than 2 `=`".to_string());
+        }
+        let data = &bytes[..bytes.len() - padding];
+        if let Some(at) = data.iter().position(|&b| value(b).is_none()) {
+            return invalid(format!("unexpected {:?} at {}", bytes[at] as char, at));
+        }
+        // The bits of the last character beyond the encoded bytes.
+        let unused = [0, 0b11, 0b1111][padding];
+        if data.last().and_then(|&b| value(b)).unwrap_or(0) & unused != 0 {
+            return invalid("its last character has stray bits".to_string());
+        }
+        Ok(())
+    }
+    /**
+     * Strips what could make the engine fail to parse the request with codes 400 to 403: control characters in
+     * paths, which no real path holds, and the line breaks and other whitespace base64 encoders may wrap lines with.
+     */
+    fn sanitize(self) -> ImageData {
+        match self {
+            ImageData::ImagePathDict { mut image_path } => {
+                image_path.retain(|c| !c.is_control());
+                ImageData::ImagePathDict { image_path }
+            }
+            ImageData::ImageBase64Dict { mut image_base64 } => {
+                image_base64.retain(|c| !c.is_whitespace() && !c.is_control());
+                ImageData::ImageBase64Dict { image_base64 }
+            }
+        }
+    }
     /**
      * Create an `ImageData` from a base64 string.
      */
@@ -95,13 +496,126 @@ impl From<PathBuf> for ImageData {
     }
 }
 
+/**
+ * A snapshot of an engine's state, as returned by [`Ppocr::status`].
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Status {
+    /// The program the engine was started with, or `None` when connected to an existing one.
+    pub exe_path: Option<PathBuf>,
+    /// The engine's version, e.g. `1.4.0`, if it announced one.
+    pub version: Option<String>,
+    /// The language of the config file passed with `--config_path`, e.g. `en` for `config_en.txt`; `None` for the
+    /// engine's default.
+    pub language: Option<String>,
+    /// How long ago the engine was started, or `None` when connected to an existing one.
+    pub uptime: Option<std::time::Duration>,
+    /// How many requests were sent to the engine.
+    pub requests: u64,
+    /// Whether the spawned engine is still running, or `None` when connected to an existing one.
+    pub alive: Option<bool>,
+}
+
+/**
+ * Where the startup of a spawned engine went, as returned by [`Ppocr::init_report`].
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InitReport {
+    /// From starting the process until it wrote its first line, which is mostly loading the inference runtime.
+    pub spawn: std::time::Duration,
+    /// From then until it reported `OCR init completed.`, which is mostly loading the models.
+    pub model_load: std::time::Duration,
+    /// How long the first request took, which is slower than the next ones as the runtime allocates and optimizes
+    /// on first use; `None` until one succeeded. [`Ppocr::self_test`] right after starting makes one.
+    pub warm_up: Option<std::time::Duration>,
+}
+
+impl InitReport {
+    /**
+     * How long the engine took until it accepted requests.
+     */
+    pub fn ready_after(&self) -> std::time::Duration {
+        self.spawn + self.model_load
+    }
+}
+
+/**
+ * How [`Ppocr::ocr_clipboard`] retries when another program holds the clipboard, which the engine reports with codes
+ * 210, 213, 215 and 216.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClipboardRetry {
+    /// Attempts after the first one.
+    pub retries: u32,
+    /// The wait before the first retry, doubled for each further one.
+    pub delay: std::time::Duration,
+}
+
+impl ClipboardRetry {
+    /// Surfaces the first error.
+    pub const NONE: ClipboardRetry = ClipboardRetry {
+        retries: 0,
+        delay: std::time::Duration::ZERO,
+    };
+
+    /// Whether `code` means the clipboard was busy, rather than empty or holding something else.
+    pub fn is_transient(code: u32) -> bool {
+        ClipboardError::from_code(code) == Some(ClipboardError::Busy)
+    }
+}
+
+/// 3 retries, waiting 50, 100 and 200 ms.
+impl Default for ClipboardRetry {
+    fn default() -> Self {
+        ClipboardRetry {
+            retries: 3,
+            delay: std::time::Duration::from_millis(50),
+        }
+    }
+}
+
 /**
  * A paddleocr-json instance.
  */
 pub struct Ppocr {
-    #[allow(dead_code)]
-    exe_path: PathBuf,
-    process: process::Child,
+    exe_path: Option<PathBuf>,
+    /// The engine's version, as announced while initializing.
+    version: Option<String>,
+    /// The language, from the name of the config file the engine was started with.
+    language: Option<String>,
+    #[cfg(feature = "local")]
+    started: Option<std::time::Instant>,
+    #[cfg(feature = "local")]
+    init: Option<InitReport>,
+    requests: u64,
+    max_response_size: Option<usize>,
+    max_payload_size: Option<usize>,
+    /// Base64 images larger than this many bytes are sent to a spawned engine as temporary files.
+    #[cfg(all(feature = "local", feature = "bytes"))]
+    spill_threshold: Option<usize>,
+    /// Output read past the end of the last response.
+    pending: String,
+    clipboard_retry: ClipboardRetry,
+    /// How the engine was spawned, to start it again after it misbehaved.
+    #[cfg(feature = "local")]
+    launch: Option<Launch>,
+    /// The spawned engine, or `None` when connected to an existing one.
+    #[cfg(feature = "local")]
+    process: Option<exit::EngineProcess>,
+    /// Whether the engine was spawned on this machine, sharing its file system and code page; not so for engines
+    /// reached over SSH or run in a container.
+    #[cfg(feature = "local")]
+    local: bool,
+    /// Whether absolute image paths must be translated for an engine running under Wine.
+    #[cfg(feature = "local")]
+    wine_paths: bool,
+    #[cfg(feature = "local")]
+    drop_behavior: DropBehavior,
+    reader: BufReader<Box<dyn Read + Send>>,
+    writer: Box<dyn Write + Send>,
+    /// Applied in order to the results of `ocr_and_parse`.
+    transforms: Vec<Box<dyn ResultTransform + Send>>,
+    translator: Option<(Box<dyn Translator + Send>, TranslationUnit)>,
 }
 
 impl Ppocr {
@@ -112,46 +626,109 @@ impl Ppocr {
 
     ```no_run
     let mut p = paddleocr::Ppocr::new(
-        PathBuf::from(".../PaddleOCR-json.exe"), // path to binary
-        Default::default(), // language config_path, default `zh_CN`
+        ".../PaddleOCR-json.exe", // path to binary
+        None, // language config_path, default `zh_CN`
     )
     .unwrap(); // initialize
     ```
     */
-    pub fn new(exe_path: PathBuf, config_path: Option<PathBuf>) -> Result<Ppocr, Box<dyn Error>> {
-        std::env::set_var("RUST_BACKTRACE", "full");
-        if !cfg!(target_os = "windows") {
-            return Err(Box::new(OsNotSupportedError {}));
-        }
-        if !exe_path.exists() {
-            return Err(Box::new(std::io::Error::new(
-                std::io::ErrorKind::NotFound,
-                "Executable not found",
-            )));
-        }
-
-        let wd = exe_path
-            .canonicalize()?
-            .parent()
-            .ok_or_else(|| "No parent directory found")?
-            .to_path_buf();
-
-        let mut command = process::Command::new(&exe_path);
-        command.current_dir(wd);
-        if let Some(config_path) = config_path {
-            command.args(&["--config_path", &config_path.to_string_lossy()]);
-        }
-        let process = command
+    #[cfg(feature = "local")]
+    pub fn new<P, C>(exe_path: P, config_path: C) -> Result<Ppocr, Box<dyn Error>>
+    where
+        P: AsRef<Path>,
+        C: Into<Option<PathBuf>>,
+    {
+        let mut builder = Ppocr::builder(exe_path);
+        if let Some(config_path) = config_path.into() {
+            builder = builder.config_path(config_path);
+        }
+        builder.spawn()
+    }
+
+    /**
+    Like [`Ppocr::new`], but shares one engine between all callers in this process with the same `exe_path` and
+    `config_path`, counting references; see [`PpocrBuilder::spawn_shared`].
+
+    # Examples
+
+    ```no_run
+    // In two independent components, without spawning two engines:
+    let a = paddleocr::Ppocr::shared(".../PaddleOCR-json.exe", None).unwrap();
+    let b = paddleocr::Ppocr::shared(".../PaddleOCR-json.exe", None).unwrap();
+    println!("{}", b.ocr(Path::new(".../test1.png").into()).unwrap());
+    ```
+    */
+    #[cfg(feature = "local")]
+    pub fn shared<P, C>(exe_path: P, config_path: C) -> Result<PpocrClient, Box<dyn Error>>
+    where
+        P: AsRef<Path>,
+        C: Into<Option<PathBuf>>,
+    {
+        let mut builder = Ppocr::builder(exe_path);
+        if let Some(config_path) = config_path.into() {
+            builder = builder.config_path(config_path);
+        }
+        builder.spawn_shared()
+    }
+
+    /**
+     * Options for spawning the engine at `exe_path`, for settings beyond those of [`Ppocr::new`].
+     */
+    #[cfg(feature = "local")]
+    pub fn builder<P: AsRef<Path>>(exe_path: P) -> PpocrBuilder {
+        PpocrBuilder::new(exe_path.as_ref().to_path_buf())
+    }
+
+    /**
+    Spawns an engine from a prepared command and waits for it to finish initializing.
+
+    Use this when the engine has to be launched in some other way than running the executable directly, e.g. through
+    a wrapper program. Stdio of the command is always piped, and the engine is tagged for [`cleanup_orphans`].
+
+    # Examples
+
+    ```no_run
+    let mut command = std::process::Command::new("PaddleOCR-json");
+    command.args(["--config_path", "models/config_en.txt"]);
+    let mut p = paddleocr::Ppocr::from_command(command).unwrap();
+    ```
+    */
+    #[cfg(feature = "local")]
+    pub fn from_command(mut command: process::Command) -> Result<Ppocr, Box<dyn Error>> {
+        command.env(orphans::OWNER_ENV, process::id().to_string());
+        let launch = Launch::new(&command);
+        let spawned = std::time::Instant::now();
+        let mut process = command
             .stdout(process::Stdio::piped())
             .stderr(process::Stdio::piped())
             .stdin(process::Stdio::piped())
             .spawn()?;
 
-        let mut p = Ppocr { exe_path, process };
-
+        let (Some(stdout), Some(stdin)) = (process.stdout.take(), process.stdin.take()) else {
+            process.kill().err();
+            return Err("stdio not piped".into());
+        };
+        let mut p = Ppocr::from_streams(stdout, stdin);
+        p.process = Some(exit::EngineProcess::new(process));
+        p.local = true;
+        p.exe_path = Some(PathBuf::from(command.get_program()));
+        p.language = config_language(&command);
+        p.started = Some(std::time::Instant::now());
+
+        let mut first_line = None;
         for _i in 1..10 {
             match p.read_line() {
                 Ok(line) => {
+                    let now = std::time::Instant::now();
+                    let first_line = *first_line.get_or_insert(now);
+                    p.init = Some(InitReport {
+                        spawn: first_line - spawned,
+                        model_load: now - first_line,
+                        warm_up: None,
+                    });
+                    if let Some((_, version)) = line.split_once("PaddleOCR-json v") {
+                        p.version = version.split_whitespace().next().map(str::to_string);
+                    }
                     if line.contains("OCR init completed.")
                         || line.contains("Image path dose not exist")
                     {
@@ -167,103 +744,619 @@ impl Ppocr {
             }
         }
 
+        p.launch = Some(launch);
         Ok(p)
     }
 
-    fn read_line(&mut self) -> IoResult<String> {
-        let mut buff = String::new();
-        let mut stdout = BufReader::new(self.process.stdout.as_mut().unwrap());
-        match stdout.read_line(&mut buff) {
-            Ok(_siz) => Ok(buff),
-            Err(e) => Err(e),
+    /**
+     * Kills the spawned engine and starts it again with the same command, keeping the settings of this instance.
+     */
+    #[cfg(feature = "local")]
+    pub fn restart(&mut self) -> Result<(), Box<dyn Error>> {
+        let launch = self
+            .launch
+            .as_ref()
+            .ok_or("not connected to a spawned engine")?;
+        if let Some(process) = self.process.as_ref() {
+            process.kill().err();
         }
-    }
-
-    #[inline]
-    fn write_fmt(&mut self, fmt: fmt::Arguments<'_>) -> IoResult<()> {
-        let inner = self.process.stdin.as_mut().ok_or(std::io::Error::new(
-            std::io::ErrorKind::Other,
-            "stdin not piped",
-        ))?;
-        inner.write_fmt(fmt)
+        let mut fresh = Ppocr::from_command(launch.command())?;
+        std::mem::swap(&mut self.reader, &mut fresh.reader);
+        std::mem::swap(&mut self.writer, &mut fresh.writer);
+        std::mem::swap(&mut self.process, &mut fresh.process);
+        self.pending.clear();
+        self.started = fresh.started;
+        self.init = fresh.init;
+        self.version = fresh.version.take();
+        Ok(())
     }
 
     /**
-    OCRs the image at the given path. Note that the returned JSON is not parsed or checked, and a valid JSON does not necessarily mean it is successful.
+    Lists the languages installed alongside the engine, by scanning the `models` folder of `engine_dir` for `config_*.txt` files.
+
+    Each returned name can be turned back into a config path with `engine_dir/models/config_{name}.txt`.
 
     # Examples
 
     ```no_run
-    let mut p = paddleocr::Ppocr::new(
-        PathBuf::from(".../PaddleOCR-json.exe"), // path to binary
-        Default::default(), // language config_path, default `zh_CN`
-    )
-    .unwrap(); // initialize
-    println!("{}", p.ocr(Path::new(".../test.png").into()));
+    let languages = paddleocr::Ppocr::available_languages(".../PaddleOCR-json_v1.4.0").unwrap();
+    // e.g. ["chinese", "chinese_cht", "en", "japan", "korean"]
     ```
-    # Results
+    */
+    pub fn available_languages<P: AsRef<Path>>(engine_dir: P) -> IoResult<Vec<String>> {
+        let mut languages = Vec::new();
+        for entry in std::fs::read_dir(engine_dir.as_ref().join("models"))? {
+            let entry = entry?;
+            if !entry.file_type()?.is_file() {
+                continue;
+            }
+            let file_name = entry.file_name();
+            let Some(name) = file_name
+                .to_str()
+                .and_then(|n| n.strip_prefix("config_"))
+                .and_then(|n| n.strip_suffix(".txt"))
+            else {
+                continue;
+            };
+            if !name.is_empty() {
+                languages.push(name.to_string());
+            }
+        }
+        languages.sort();
+        Ok(languages)
+    }
 
-    ## Return values
+    /**
+    Connects to an engine that is already running and listening on a Windows named pipe, instead of spawning a new process.
 
-    通过API调用一次OCR，无论成功与否，都会返回一个字典。
+    `name` is either the bare pipe name or the full `\\.\pipe\...` path. The engine is expected to speak the same
+    line-based JSON protocol as over stdio, and is left running when the instance is dropped.
 
-    字典中，根含两个元素：状态码`code`和内容`data`。
+    # Examples
 
-    状态码`code`为整数，每种状态码对应一种情况：
+    ```no_run
+    let mut p = paddleocr::Ppocr::connect_pipe("PaddleOCR-json").unwrap();
+    println!("{}", p.ocr_clipboard().unwrap());
+    ```
+    */
+    #[cfg(target_os = "windows")]
+    pub fn connect_pipe<S: AsRef<str>>(name: S) -> Result<Ppocr, Box<dyn Error>> {
+        let name = name.as_ref();
+        let path = if name.starts_with(r"\\") {
+            name.to_string()
+        } else {
+            format!(r"\\.\pipe\{}", name)
+        };
+        let pipe = std::fs::OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open(path)?;
+        let reader = pipe.try_clone()?;
+        Ok(Ppocr::from_streams(reader, pipe))
+    }
 
-    ### `100` 识别到文字
+    /**
+    Talks to an engine over an arbitrary pair of streams, e.g. the two halves of a socket or pipe set up by the
+    caller. No initialization output is expected: the engine must be ready to accept requests.
 
-    - data内容为数组。数组每一项为字典，含三个元素：
-      - `text` ：文本内容，字符串。
-      - `box` ：文本包围盒，长度为4的数组，分别为左上角、右上角、右下角、左下角的`[x,y]`。整数。
-      - `score` ：识别置信度，浮点数。
-    - 例：
-      ```
-        {'code':100,'data':[{'box':[[13,5],[161,5],[161,27],[13,27]],'score':0.9996442794799805,'text':'飞舞的因果交流'}]}
-      ```
+    # Examples
 
-    ### `101` 未识别到文字
+    ```no_run
+    let stream = std::net::TcpStream::connect("127.0.0.1:1234").unwrap();
+    let mut p = paddleocr::Ppocr::from_streams(stream.try_clone().unwrap(), stream);
+    ```
+    */
+    pub fn from_streams<R, W>(reader: R, writer: W) -> Ppocr
+    where
+        R: Read + Send + 'static,
+        W: Write + Send + 'static,
+    {
+        Ppocr {
+            exe_path: None,
+            version: None,
+            language: None,
+            #[cfg(feature = "local")]
+            started: None,
+            #[cfg(feature = "local")]
+            init: None,
+            requests: 0,
+            max_response_size: Some(MAX_RESPONSE_SIZE),
+            max_payload_size: Some(MAX_PAYLOAD_SIZE),
+            #[cfg(all(feature = "local", feature = "bytes"))]
+            spill_threshold: None,
+            pending: String::new(),
+            clipboard_retry: ClipboardRetry::default(),
+            #[cfg(feature = "local")]
+            launch: None,
+            #[cfg(feature = "local")]
+            process: None,
+            #[cfg(feature = "local")]
+            local: false,
+            #[cfg(feature = "local")]
+            wine_paths: false,
+            #[cfg(feature = "local")]
+            drop_behavior: DropBehavior::Kill,
+            reader: BufReader::new(Box::new(reader)),
+            writer: Box::new(writer),
+            transforms: Vec::new(),
+            translator: None,
+        }
+    }
 
-    - data为字符串：`No text found in image. Path:"图片路径"`
-    - 例：```{'code':101,'data':'No text found in image. Path: "D:\\空白.png"'}```
-    - 这是正常现象，识别没有文字的空白图片时会出现这种结果。
+    /**
+     * The OS process ID of the spawned engine, or `None` when connected to an existing one.
+     */
+    #[cfg(feature = "local")]
+    pub fn pid(&self) -> Option<u32> {
+        self.process.as_ref().map(exit::EngineProcess::id)
+    }
 
-    ### `200` 图片路径不存在
+    /**
+     * Checks whether the spawned engine has exited, without blocking: `Ok(None)` while it is still running.
+     */
+    #[cfg(feature = "local")]
+    pub fn try_wait(&mut self) -> IoResult<Option<process::ExitStatus>> {
+        self.child()?.with(process::Child::try_wait)
+    }
 
-    - data：`Image path dose not exist. Path:"图片路径".`
-    - 例：`{'code':200,'data':'Image path dose not exist. Path: "D:\\不存在.png"'}`
-    - 注意，在系统未开启utf-8支持（`使用 Unicode UTF-8 提供全球语言支持"`）时，不能读入含emoji等特殊字符的路径（如`😀.png`）。但一般的中文及其他 Unicode 字符路径是没问题的，不受系统区域及默认编码影响。
+    /**
+     * Waits for the spawned engine to exit. The engine does not exit by itself while its input is open, so this is
+     * normally preceded by [`Ppocr::kill`] or an external signal.
+     */
+    #[cfg(feature = "local")]
+    pub fn wait(&mut self) -> IoResult<process::ExitStatus> {
+        self.child()?.with(process::Child::wait)
+    }
 
-    ### `201` 图片路径string无法转换到wstring
+    /**
+     * Kills the spawned engine. Requests fail afterwards; killing an engine that has already exited is not an error.
+     */
+    #[cfg(feature = "local")]
+    pub fn kill(&mut self) -> IoResult<()> {
+        self.child()?.kill()
+    }
 
-    - data：`Image path failed to convert to utf-16 wstring. Path: "图片路径".`
-    - 使用API时，理论上不会报这个错。
-    - 开发API时，若传入字符串的编码不合法，有可能报这个错。
+    /**
+     * A handle killing the spawned engine from another thread, even while a request to it is waiting for the
+     * response, which then fails. `None` when connected to an existing engine.
+     */
+    #[cfg(feature = "local")]
+    pub fn terminator(&self) -> Option<Terminator> {
+        self.process.as_ref().map(exit::EngineProcess::terminator)
+    }
 
-    ### `202` 图片路径存在，但无法打开文件
+    /**
+    Registers a callback for when the spawned engine exits other than through [`Ppocr::kill`] or dropping the
+    instance, e.g. because it crashed. It is called on a background thread, with the exit code and the last lines
+    the engine wrote to stderr. If the engine has already exited, it is called right away.
 
-    - data：`Image open failed. Path: "图片路径".`
-    - 可能由系统权限等原因引起。
+    Only one callback is kept; registering another replaces it.
 
-    ### `203` 图片打开成功，但读取到的内容无法被opencv解码
+    # Examples
 
-    - data：`Image decode failed. Path: "图片路径".`
-    - 注意，引擎不以文件后缀来区分各种图片，而是对存在的路径，均读入字节尝试解码。若传入的文件路径不是图片，或图片已损坏，则会报这个错。
-    - 反之，将正常图片的后缀改为别的（如`.png`改成`.jpg或.exe`），也可以被正常识别。
+    ```no_run
+    let mut p = paddleocr::Ppocr::new(".../PaddleOCR-json.exe", None).unwrap();
+    let (sender, exits) = std::sync::mpsc::channel();
+    p.on_exit(move |info| sender.send(info).unwrap()).unwrap();
+    // ...
+    if let Ok(info) = exits.try_recv() {
+        eprintln!("engine exited with {:?}: {}", info.status, info.stderr_tail.join("\n"));
+    }
+    ```
+    */
+    #[cfg(feature = "local")]
+    pub fn on_exit<F>(&mut self, callback: F) -> IoResult<()>
+    where
+        F: FnMut(ExitInfo) + Send + 'static,
+    {
+        self.child()?.on_exit(Box::new(callback));
+        Ok(())
+    }
 
-    ### `210` 剪贴板打开失败
+    /**
+    Forwards each line the spawned engine writes to stderr to `callback`, with a guessed [`LogLevel`]. Lines are
+    still kept for [`ExitInfo::stderr_tail`].
 
-    - data：`Clipboard open failed.`
-    - 可能由别的程序正在占用剪贴板等原因引起。
+    With the `log` feature, the lines also go to the `log` facade without a callback, at their guessed level, with
+    the target [`LOG_TARGET`] and the engine's process ID as the `pid` key-value.
 
-    ### `211` 剪贴板为空
+    # Examples
 
-    - data：`Clipboard is empty.`
+    ```no_run
+    use paddleocr::LogLevel;
 
-    ### `212` 剪贴板的格式不支持
+    let mut p = paddleocr::Ppocr::new(".../PaddleOCR-json.exe", None).unwrap();
+    p.on_log(|line| {
+        if line.level >= LogLevel::Warn {
+            eprintln!("engine {}: {}", line.pid, line.message);
+        }
+    })
+    .unwrap();
+    ```
+    */
+    #[cfg(feature = "local")]
+    pub fn on_log<F>(&mut self, callback: F) -> IoResult<()>
+    where
+        F: FnMut(LogLine) + Send + 'static,
+    {
+        self.child()?.on_log(Box::new(callback));
+        Ok(())
+    }
 
-    - data：`Clipboard format is not valid.`
-    - 引擎只能识别剪贴板中的位图或文件。若不是这两种格式（如复制了一段文本），则会报这个错。
+    /**
+     * Suspends the spawned engine, so an idle engine uses no CPU while keeping its models loaded. Requests sent while
+     * paused are only answered after [`Ppocr::resume`], so don't call [`Ppocr::ocr`] in between.
+     */
+    #[cfg(feature = "local")]
+    pub fn pause(&mut self) -> IoResult<()> {
+        self.child()?.with(suspend::pause)
+    }
+
+    /**
+     * Resumes an engine suspended with [`Ppocr::pause`].
+     */
+    #[cfg(feature = "local")]
+    pub fn resume(&mut self) -> IoResult<()> {
+        self.child()?.with(suspend::resume)
+    }
+
+    /**
+    Sets what happens to the spawned engine when this instance is dropped: killed by default.
+
+    # Examples
+
+    ```no_run
+    use paddleocr::DropBehavior;
+
+    let mut p = paddleocr::Ppocr::new(".../PaddleOCR-json.exe", None).unwrap();
+    p.set_drop_behavior(DropBehavior::Graceful(std::time::Duration::from_secs(2)));
+    ```
+    */
+    #[cfg(feature = "local")]
+    pub fn set_drop_behavior(&mut self, behavior: DropBehavior) {
+        self.drop_behavior = behavior;
+    }
+
+    /**
+    How long the spawned engine took to start, phase by phase, for diagnosing slow startups; `None` when connected to
+    an existing one.
+
+    # Examples
+
+    ```no_run
+    let mut p = paddleocr::Ppocr::new(".../PaddleOCR-json.exe", None).unwrap();
+    p.self_test().unwrap();
+    if let Some(init) = p.init_report() {
+        eprintln!("runtime {:?}, models {:?}, first request {:?}", init.spawn, init.model_load, init.warm_up);
+    }
+    ```
+    */
+    #[cfg(feature = "local")]
+    pub fn init_report(&self) -> Option<InitReport> {
+        self.init
+    }
+
+    /**
+    The engine's path, version, language, uptime, request count and whether it is still running.
+
+    # Examples
+
+    ```no_run
+    let mut p = paddleocr::Ppocr::new(".../PaddleOCR-json.exe", None).unwrap();
+    let status = p.status();
+    if status.alive == Some(false) {
+        eprintln!("engine {:?} died after {} requests", status.version, status.requests);
+    }
+    ```
+    */
+    pub fn status(&self) -> Status {
+        #[cfg(feature = "local")]
+        let (uptime, alive) = (
+            self.started.map(|started| started.elapsed()),
+            self.process
+                .as_ref()
+                .map(|process| matches!(process.with(process::Child::try_wait), Ok(None))),
+        );
+        #[cfg(not(feature = "local"))]
+        let (uptime, alive) = (None, None);
+        Status {
+            exe_path: self.exe_path.clone(),
+            version: self.version.clone(),
+            language: self.language.clone(),
+            uptime,
+            requests: self.requests,
+            alive,
+        }
+    }
+
+    /**
+    OCRs a small built-in image reading `HELLO 123` and checks that the text is found, as an end-to-end check that
+    the engine actually works, e.g. at startup. The error describes what went wrong.
+
+    Without the `bytes` feature the image is written to a temporary file, which a remote engine can't read.
+
+    # Examples
+
+    ```no_run
+    let mut p = paddleocr::Ppocr::new(".../PaddleOCR-json.exe", None).unwrap();
+    if let Err(e) = p.self_test() {
+        panic!("OCR is not working: {}", e);
+    }
+    ```
+    */
+    pub fn self_test(&mut self) -> Result<(), String> {
+        #[cfg(feature = "bytes")]
+        let response = self.ocr(ImageData::from_bytes(SELF_TEST_IMAGE));
+        #[cfg(not(feature = "bytes"))]
+        let response = {
+            let path = std::env::temp_dir()
+                .join(format!("paddleocr-self-test-{}.bmp", std::process::id()));
+            std::fs::write(&path, SELF_TEST_IMAGE).map_err(|e| e.to_string())?;
+            let response = self.ocr(path.as_path().into());
+            std::fs::remove_file(&path).ok();
+            response
+        };
+        let response = parse_response(response)?;
+        let text: String = response
+            .data
+            .iter()
+            .flat_map(|content| content.text.chars())
+            .filter(|c| !c.is_whitespace())
+            .collect::<String>()
+            .to_uppercase();
+        match response.code {
+            100 if text.contains("HELLO") => Ok(()),
+            100 => Err(format!("expected HELLO 123, recognized {:?}", text)),
+            _ => Err(format!("engine failed: {}", response)),
+        }
+    }
+
+    /**
+    What the engine supports, inferred from the version it announced at startup and its arguments.
+
+    # Examples
+
+    ```no_run
+    let mut p = paddleocr::Ppocr::new(".../PaddleOCR-json.exe", None).unwrap();
+    if p.capabilities().clipboard {
+        println!("{}", p.ocr_clipboard().unwrap());
+    }
+    ```
+    */
+    pub fn capabilities(&self) -> Capabilities {
+        #[cfg(feature = "local")]
+        let args = self.launch.iter().flat_map(|launch| &launch.args);
+        #[cfg(not(feature = "local"))]
+        let args = std::iter::empty::<&std::ffi::OsString>();
+        let args: Vec<_> = args.map(|arg| arg.to_string_lossy()).collect();
+        Capabilities::infer(
+            self.version.as_deref(),
+            self.exe_path.as_deref(),
+            args.iter().map(|arg| arg.as_ref()),
+        )
+    }
+
+    #[cfg(feature = "local")]
+    fn child(&self) -> IoResult<&exit::EngineProcess> {
+        self.process.as_ref().ok_or_else(|| {
+            std::io::Error::new(
+                std::io::ErrorKind::Unsupported,
+                "not connected to a spawned engine",
+            )
+        })
+    }
+
+    /**
+    Sets the largest response in bytes that is read from the engine, 64 MiB by default; `None` for no limit.
+
+    A longer response fails with [`ResponseTooLarge`] instead of filling up memory. The rest of it can't be told
+    apart from later responses, so a spawned engine is restarted; on other connections the following requests fail.
+
+    # Examples
+
+    ```no_run
+    let mut p = paddleocr::Ppocr::new(".../PaddleOCR-json.exe", None).unwrap();
+    p.set_max_response_size(Some(1 << 20));
+    ```
+    */
+    pub fn set_max_response_size(&mut self, limit: Option<usize>) {
+        self.max_response_size = limit;
+    }
+
+    /**
+    Sets the largest request in bytes that is sent to the engine, 256 MiB by default; `None` for no limit.
+
+    A larger request, such as a huge base64 image, fails with [`PayloadTooLarge`] before anything is written, rather
+    than with an engine failure or a stall of its input pipe. Images sent as temporary files instead (see
+    `set_spill_threshold`) only count with their path.
+
+    # Examples
+
+    ```no_run
+    let mut p = paddleocr::Ppocr::new(".../PaddleOCR-json.exe", None).unwrap();
+    p.set_max_payload_size(Some(16 << 20));
+    ```
+    */
+    pub fn set_max_payload_size(&mut self, limit: Option<usize>) {
+        self.max_payload_size = limit;
+    }
+
+    /**
+    Sends base64 images of more than `threshold` bytes to a spawned engine as temporary files, removed once
+    answered, instead of through its input pipe; `None`, the default, never does. This keeps the convenience of
+    [`ImageData::from_bytes`] with the speed of paths for large images. Requires the `bytes` feature.
+
+    Engines not spawned by this instance, or reached over SSH or run in a container, may not share its file system,
+    so they always get base64.
+
+    # Examples
+
+    ```no_run
+    let mut p = paddleocr::Ppocr::new(".../PaddleOCR-json.exe", None).unwrap();
+    p.set_spill_threshold(Some(1 << 20));
+    let scan = std::fs::read(".../scan.png").unwrap();
+    p.ocr(paddleocr::ImageData::from_bytes(scan)).unwrap();
+    ```
+    */
+    #[cfg(all(feature = "local", feature = "bytes"))]
+    pub fn set_spill_threshold(&mut self, threshold: Option<usize>) {
+        self.spill_threshold = threshold;
+    }
+
+    fn read_line(&mut self) -> IoResult<String> {
+        let mut buff = Vec::new();
+        match self.max_response_size {
+            Some(limit) => {
+                (&mut self.reader)
+                    .take(limit as u64 + 1)
+                    .read_until(b'\n', &mut buff)?;
+                if buff.len() > limit {
+                    return Err(self.too_large(limit));
+                }
+            }
+            None => {
+                self.reader.read_until(b'\n', &mut buff)?;
+            }
+        }
+        String::from_utf8(buff).map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))
+    }
+
+    /**
+    Reads the next JSON value the engine writes, however its output is split into lines.
+
+    A value may span several lines, several may arrive on one line, and lines that aren't JSON (such as log output
+    of some engine versions) are skipped. A malformed value is returned as is, for the caller's parsing to report.
+    */
+    fn read_response(&mut self) -> IoResult<String> {
+        let mut buff = std::mem::take(&mut self.pending);
+        loop {
+            let start = buff.len() - buff.trim_start().len();
+            buff.drain(..start);
+            if buff.starts_with('{') {
+                let mut values =
+                    serde_json::Deserializer::from_str(&buff).into_iter::<serde::de::IgnoredAny>();
+                match values.next() {
+                    Some(Ok(_)) => {
+                        let end = values.byte_offset();
+                        self.pending = buff.split_off(end);
+                        return Ok(buff);
+                    }
+                    Some(Err(e)) if e.is_eof() => {}
+                    _ => {
+                        let end = buff.find('\n').map_or(buff.len(), |i| i + 1);
+                        self.pending = buff.split_off(end);
+                        return Ok(buff);
+                    }
+                }
+            } else if let Some(i) = buff.find('\n') {
+                buff.drain(..=i);
+                continue;
+            }
+
+            let line = self.read_line()?;
+            if line.is_empty() {
+                // The engine closed its output; an empty response tells callers it is gone.
+                return Ok(if buff.starts_with('{') { buff } else { line });
+            }
+            buff.push_str(&line);
+            if let Some(limit) = self.max_response_size.filter(|&limit| buff.len() > limit) {
+                return Err(self.too_large(limit));
+            }
+        }
+    }
+
+    /// Gives up on the rest of an oversized response, restarting a spawned engine.
+    fn too_large(&mut self, limit: usize) -> std::io::Error {
+        #[cfg(feature = "local")]
+        if self.launch.is_some() {
+            self.restart().err();
+        }
+        self.pending.clear();
+        std::io::Error::new(std::io::ErrorKind::InvalidData, ResponseTooLarge { limit })
+    }
+
+    #[inline]
+    fn write_fmt(&mut self, fmt: fmt::Arguments<'_>) -> IoResult<()> {
+        self.writer.write_fmt(fmt)?;
+        self.writer.flush()
+    }
+
+    /**
+    OCRs the image at the given path. Note that the returned JSON is not parsed or checked, and a valid JSON does not necessarily mean it is successful.
+
+    # Examples
+
+    ```no_run
+    let mut p = paddleocr::Ppocr::new(
+        ".../PaddleOCR-json.exe", // path to binary
+        None, // language config_path, default `zh_CN`
+    )
+    .unwrap(); // initialize
+    println!("{}", p.ocr(Path::new(".../test.png").into()));
+    ```
+    # Results
+
+    ## Return values
+
+    通过API调用一次OCR，无论成功与否，都会返回一个字典。
+
+    字典中，根含两个元素：状态码`code`和内容`data`。
+
+    状态码`code`为整数，每种状态码对应一种情况：
+
+    ### `100` 识别到文字
+
+    - data内容为数组。数组每一项为字典，含三个元素：
+      - `text` ：文本内容，字符串。
+      - `box` ：文本包围盒，长度为4的数组，分别为左上角、右上角、右下角、左下角的`[x,y]`。整数。
+      - `score` ：识别置信度，浮点数。
+    - 例：
+      ```
+        {'code':100,'data':[{'box':[[13,5],[161,5],[161,27],[13,27]],'score':0.9996442794799805,'text':'飞舞的因果交流'}]}
+      ```
+
+    ### `101` 未识别到文字
+
+    - data为字符串：`No text found in image. Path:"图片路径"`
+    - 例：```{'code':101,'data':'No text found in image. Path: "D:\\空白.png"'}```
+    - 这是正常现象，识别没有文字的空白图片时会出现这种结果。
+
+    ### `200` 图片路径不存在
+
+    - data：`Image path dose not exist. Path:"图片路径".`
+    - 例：`{'code':200,'data':'Image path dose not exist. Path: "D:\\不存在.png"'}`
+    - 注意，在系统未开启utf-8支持（`使用 Unicode UTF-8 提供全球语言支持"`）时，不能读入含emoji等特殊字符的路径（如`😀.png`）。但一般的中文及其他 Unicode 字符路径是没问题的，不受系统区域及默认编码影响。
+
+    ### `201` 图片路径string无法转换到wstring
+
+    - data：`Image path failed to convert to utf-16 wstring. Path: "图片路径".`
+    - 使用API时，理论上不会报这个错。
+    - 开发API时，若传入字符串的编码不合法，有可能报这个错。
+
+    ### `202` 图片路径存在，但无法打开文件
+
+    - data：`Image open failed. Path: "图片路径".`
+    - 可能由系统权限等原因引起。
+
+    ### `203` 图片打开成功，但读取到的内容无法被opencv解码
+
+    - data：`Image decode failed. Path: "图片路径".`
+    - 注意，引擎不以文件后缀来区分各种图片，而是对存在的路径，均读入字节尝试解码。若传入的文件路径不是图片，或图片已损坏，则会报这个错。
+    - 反之，将正常图片的后缀改为别的（如`.png`改成`.jpg或.exe`），也可以被正常识别。
+
+    ### `210` 剪贴板打开失败
+
+    - data：`Clipboard open failed.`
+    - 可能由别的程序正在占用剪贴板等原因引起。
+
+    ### `211` 剪贴板为空
+
+    - data：`Clipboard is empty.`
+
+    ### `212` 剪贴板的格式不支持
+
+    - data：`Clipboard format is not valid.`
+    - 引擎只能识别剪贴板中的位图或文件。若不是这两种格式（如复制了一段文本），则会报这个错。
 
     ### `213` 剪贴板获取内容句柄失败
 
@@ -319,22 +1412,131 @@ impl Ppocr {
     - data：`No valid tasks.`
     - 本次传入的指令中不含有效任务。
         */
-
     pub fn ocr(&mut self, image: ImageData) -> IoResult<String> {
-        let s = serde_json::to_string(&image).unwrap().replace("\n", "");
-        self.write_fmt(format_args!("{}\n", s))?;
-        self.read_line()
+        self.ocr_with(image, &OcrOptions::default())
+    }
+
+    /**
+    OCRs the image like [`Ppocr::ocr`], overriding some of the engine's settings for this request only.
+
+    Fails with [`Unsupported`](std::io::ErrorKind::Unsupported) if `options` sets anything and the engine is known
+    to ignore per-request options; see [`Ppocr::capabilities`].
+
+    # Examples
+
+    ```no_run
+    use paddleocr::OcrOptions;
+
+    let mut p = paddleocr::Ppocr::new(".../PaddleOCR-json.exe", None).unwrap();
+    let photo = p.ocr_with(Path::new(".../photo.jpg").into(), &OcrOptions::new().cls(true));
+    let screenshot = p.ocr_with(Path::new(".../screenshot.png").into(), &OcrOptions::new().cls(false));
+    ```
+    */
+    pub fn ocr_with(&mut self, image: ImageData, options: &OcrOptions) -> IoResult<String> {
+        if !options.is_empty() && self.version.is_some() && !self.capabilities().per_call_options {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::Unsupported,
+                "the engine doesn't take per-request options",
+            ));
+        }
+        let image = image.sanitize();
+        image.validate()?;
+        #[cfg(feature = "local")]
+        let image = if self.local {
+            codepage::fit_codepage(image)?
+        } else {
+            image
+        };
+        #[cfg(all(feature = "local", feature = "bytes"))]
+        let (image, _spilled) = match &image {
+            ImageData::ImageBase64Dict { image_base64 }
+                if self.local
+                    && self
+                        .spill_threshold
+                        .is_some_and(|threshold| image_base64.len() / 4 * 3 > threshold) =>
+            {
+                let file = spill::SpillFile::write(image_base64)?;
+                (file.path().into(), Some(file))
+            }
+            _ => (image, None),
+        };
+        #[cfg(feature = "local")]
+        let image = if self.wine_paths {
+            image.into_wine()
+        } else {
+            image
+        };
+        #[derive(Serialize)]
+        struct Request<'a> {
+            #[serde(flatten)]
+            image: &'a ImageData,
+            #[serde(flatten)]
+            options: &'a OcrOptions,
+        }
+        let request = Request {
+            image: &image,
+            options,
+        };
+        let s = serde_json::to_string(&request)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidInput, e))?
+            .replace("\n", "");
+        let payload_size = s.len() + 1;
+        if let Some(limit) = self.max_payload_size.filter(|&limit| payload_size > limit) {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                PayloadTooLarge {
+                    size: payload_size,
+                    limit,
+                },
+            ));
+        }
+        self.requests += 1;
+        #[cfg(feature = "local")]
+        let start = Some(std::time::Instant::now());
+        #[cfg(not(feature = "local"))]
+        let start: Option<std::time::Instant> = None;
+        let response = self
+            .write_fmt(format_args!("{}\n", s))
+            .and_then(|_| self.read_response());
+        #[cfg(feature = "local")]
+        if let Some(init) = self.init.as_mut().filter(|init| init.warm_up.is_none()) {
+            if response.is_ok() {
+                init.warm_up = start.map(|start| start.elapsed());
+            }
+        }
+        response.map_err(|source| {
+            let error = RequestError {
+                input: image.describe(),
+                request_id: self.requests,
+                payload_size,
+                elapsed: start.map(|start| start.elapsed()),
+                exit_status: self.exit_status(),
+                source,
+            };
+            std::io::Error::new(error.source.kind(), error)
+        })
+    }
+
+    /// The spawned engine's exit status, if it has exited.
+    fn exit_status(&self) -> Option<std::process::ExitStatus> {
+        #[cfg(feature = "local")]
+        if let Some(process) = &self.process {
+            return process.with(|child| child.try_wait().ok().flatten());
+        }
+        None
     }
 
     /**
     OCRs the image in clipboard. Note that the returned JSON is not parsed or checked, and a valid JSON does not necessarily mean it is successful.
 
+    The engine reads the clipboard itself; with the `bytes` feature, `clipboard::ocr` reads it here instead.
+
     # Examples
 
     ```no_run
     let mut p = paddleocr::Ppocr::new(
-        PathBuf::from(".../PaddleOCR-json.exe"), // path to binary
-        Default::default(), // language config_path, default `zh_CN`
+        ".../PaddleOCR-json.exe", // path to binary
+        None, // language config_path, default `zh_CN`
     )
     .unwrap(); // initialize
     println!("{}", p.ocr_clipboard());
@@ -342,44 +1544,1002 @@ impl Ppocr {
     */
     #[inline]
     pub fn ocr_clipboard(&mut self) -> IoResult<String> {
+        if self.version.is_some() && !self.capabilities().clipboard {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::Unsupported,
+                "the engine can't read the clipboard",
+            ));
+        }
+        let mut delay = self.clipboard_retry.delay;
+        for _ in 0..self.clipboard_retry.retries {
+            let response = self.ocr(ImageData::from_path("clipboard"))?;
+            if !OcrResponse::parse(response.as_str())
+                .is_ok_and(|r| ClipboardRetry::is_transient(r.code))
+            {
+                return Ok(response);
+            }
+            std::thread::sleep(delay);
+            delay = delay.saturating_mul(2);
+        }
         self.ocr(ImageData::from_path("clipboard"))
     }
 
+    /**
+    Calls `callback` with the response to each image copied to the clipboard from now on, until it breaks. See
+    [`clipboard::Watcher`] to change how often the clipboard is checked.
+
+    # Examples
+
+    ```no_run
+    use std::ops::ControlFlow;
+
+    let mut p = paddleocr::Ppocr::new(".../PaddleOCR-json.exe", None).unwrap();
+    p.watch_clipboard(|response| {
+        println!("{}", response.unwrap());
+        ControlFlow::Continue(())
+    })
+    .unwrap();
+    ```
+    */
+    #[cfg(feature = "bytes")]
+    pub fn watch_clipboard<F>(&mut self, callback: F) -> IoResult<()>
+    where
+        F: FnMut(IoResult<String>) -> std::ops::ControlFlow<()>,
+    {
+        clipboard::Watcher::new().run(self, callback)
+    }
+
+    /**
+    Sets how [`Ppocr::ocr_clipboard`] retries while another program holds the clipboard. By default it retries 3
+    times; [`ClipboardRetry::NONE`] turns that off.
+
+    # Examples
+
+    ```no_run
+    use paddleocr::ClipboardRetry;
+
+    let mut p = paddleocr::Ppocr::new(".../PaddleOCR-json.exe", None).unwrap();
+    p.set_clipboard_retry(ClipboardRetry {
+        retries: 5,
+        delay: std::time::Duration::from_millis(20),
+    });
+    println!("{}", p.ocr_clipboard().unwrap());
+    ```
+    */
+    pub fn set_clipboard_retry(&mut self, retry: ClipboardRetry) {
+        self.clipboard_retry = retry;
+    }
+
+    /**
+    OCRs the image and parses the response, running the result through the transforms added with
+    [`Ppocr::add_transform`].
+    */
     pub fn ocr_and_parse(&mut self, image: ImageData) -> Result<Vec<ContentData>, String> {
-        let ocr_result = self.ocr(image);
-        let Ok(ocr_string) = ocr_result.as_ref() else {
-            return Err("OCR failed".to_string());
-        };
-        match serde_json::from_str::<OcrRec>(&ocr_string) {
-            Ok(OcrRec::Content { data, .. }) => Ok(data),
-            Ok(OcrRec::Message { code, data }) => Err(format!("Error Message {}: {}", code, data)),
-            Err(e) => Err(format!("Response JSON parse failed: {}", e)),
+        self.ocr_response(image)?.into_result()
+    }
+
+    /**
+    OCRs the image and parses the response, keeping the engine's JSON alongside the parsed fields.
+
+    Unlike [`Ppocr::ocr_and_parse`], engine error codes are not errors here: they are returned in the response.
+    The transforms added with [`Ppocr::add_transform`] are applied to `data`, but not to `raw`.
+
+    # Examples
+
+    ```no_run
+    let mut p = paddleocr::Ppocr::new(".../PaddleOCR-json.exe", None).unwrap();
+    let response = p.ocr_response(Path::new(".../test.png").into()).unwrap();
+    eprintln!("engine said {}", response.raw);
+    for line in &response.data {
+        println!("{}", line.text);
+    }
+    ```
+    */
+    pub fn ocr_response(&mut self, image: ImageData) -> Result<OcrResponse, String> {
+        let response = parse_response(self.ocr(image))?;
+        Ok(self.transform(response))
+    }
+
+    /**
+    OCRs the image and parses the response like [`Ppocr::ocr_and_parse`], with an error that tells IO failures,
+    malformed responses and the engine's error codes apart.
+
+    # Examples
+
+    ```no_run
+    use paddleocr::PpocrError;
+
+    let mut p = paddleocr::Ppocr::new(".../PaddleOCR-json.exe", None).unwrap();
+    match p.try_ocr(paddleocr::ImageData::from_path("clipboard")) {
+        Ok(data) => println!("{} lines", data.len()),
+        Err(PpocrError::Engine { code: 101, .. }) => println!("no text"),
+        Err(PpocrError::Clipboard { error, .. }) => println!("{}", error),
+        Err(e) => eprintln!("{}", e),
+    }
+    ```
+    */
+    pub fn try_ocr(&mut self, image: ImageData) -> Result<Vec<ContentData>, PpocrError> {
+        let response = try_parse_response(self.ocr(image)?)?;
+        self.transform(response).try_into_result()
+    }
+
+    /// Runs the detections of `response` through the transforms.
+    fn transform(&self, mut response: OcrResponse) -> OcrResponse {
+        let data = std::mem::take(&mut response.data);
+        response.data = self
+            .transforms
+            .iter()
+            .fold(data, |data, transform| transform.transform(data));
+        response
+    }
+
+    /**
+    Adds a post-processing step to every subsequent `ocr_and_parse` call, after the ones already added.
+
+    # Examples
+
+    ```no_run
+    use paddleocr::transform::{MinScore, SortByPosition};
+
+    let mut p = paddleocr::Ppocr::new(".../PaddleOCR-json.exe", None).unwrap();
+    p.add_transform(MinScore(0.8));
+    p.add_transform(SortByPosition);
+    ```
+    */
+    pub fn add_transform<T: ResultTransform + Send + 'static>(&mut self, transform: T) {
+        self.transforms.push(Box::new(transform));
+    }
+
+    /**
+     * Removes all transforms added with [`Ppocr::add_transform`].
+     */
+    pub fn clear_transforms(&mut self) {
+        self.transforms.clear();
+    }
+
+    /**
+    Sets the translator used by [`Ppocr::ocr_and_translate`], and whether it translates the joined text of an
+    image or each detection separately.
+
+    # Examples
+
+    ```no_run
+    let mut p = paddleocr::Ppocr::new(".../PaddleOCR-json.exe", None).unwrap();
+    p.set_translator(
+        |text: &str| Ok(my_translation_service(text)),
+        paddleocr::TranslationUnit::PerDetection,
+    );
+    let translated = p.ocr_and_translate(Path::new(".../test.png").into()).unwrap();
+    println!("{}", translated.text());
+    ```
+    */
+    pub fn set_translator<T: Translator + Send + 'static>(
+        &mut self,
+        translator: T,
+        unit: TranslationUnit,
+    ) {
+        self.translator = Some((Box::new(translator), unit));
+    }
+
+    /**
+     * Removes the translator set with [`Ppocr::set_translator`].
+     */
+    pub fn clear_translator(&mut self) {
+        self.translator = None;
+    }
+
+    /**
+     * Like [`Ppocr::ocr_and_parse`], then runs the result through the translator set with
+     * [`Ppocr::set_translator`]. Fails if no translator is set.
+     */
+    pub fn ocr_and_translate(&mut self, image: ImageData) -> Result<translate::Translated, String> {
+        if self.translator.is_none() {
+            return Err("No translator set".to_string());
+        }
+        let data = self.ocr_and_parse(image)?;
+        let (translator, unit) = self.translator.as_ref().ok_or("No translator set")?;
+        translate::translate(translator.as_ref(), *unit, data)
+    }
+}
+
+fn try_parse_response(raw: String) -> Result<OcrResponse, PpocrError> {
+    if raw.is_empty() {
+        return Err(PpocrError::Io(std::io::Error::new(
+            std::io::ErrorKind::UnexpectedEof,
+            "the engine closed its output",
+        )));
+    }
+    match OcrResponse::parse(raw.as_str()) {
+        Ok(response) => Ok(response),
+        Err(source) => Err(PpocrError::Parse { raw, source }),
+    }
+}
+
+fn parse_response(ocr_result: IoResult<String>) -> Result<OcrResponse, String> {
+    let ocr_string = ocr_result.map_err(|e| format!("OCR failed: {}", e))?;
+    OcrResponse::parse(ocr_string).map_err(|e| format!("Response JSON parse failed: {}", e))
+}
+
+/**
+ * Stops an engine from another thread, as returned by [`OcrEngine::terminator`].
+ */
+pub type Terminator = std::sync::Arc<dyn Fn() + Send + Sync>;
+
+/**
+ * Something that can OCR images: a local [`Ppocr`] instance or one of the other backends.
+ */
+pub trait OcrEngine {
+    /**
+     * OCRs the image, returning the engine's raw JSON response. See [`Ppocr::ocr`].
+     */
+    fn ocr(&mut self, image: ImageData) -> IoResult<String>;
+
+    /**
+     * OCRs the image and parses the response. See [`Ppocr::ocr_and_parse`].
+     */
+    fn ocr_and_parse(&mut self, image: ImageData) -> Result<Vec<ContentData>, String> {
+        self.ocr_response(image)?.into_result()
+    }
+
+    /**
+     * OCRs the image and parses the response, keeping the raw JSON. See [`Ppocr::ocr_response`].
+     */
+    fn ocr_response(&mut self, image: ImageData) -> Result<OcrResponse, String> {
+        parse_response(self.ocr(image))
+    }
+
+    /**
+     * OCRs the image and parses the response, with errors that tell failures apart. See [`Ppocr::try_ocr`].
+     */
+    fn try_ocr(&mut self, image: ImageData) -> Result<Vec<ContentData>, PpocrError> {
+        try_parse_response(self.ocr(image)?)?.try_into_result()
+    }
+
+    /**
+     * A handle stopping the engine while another thread uses it, so that a hung request fails, or `None` if it
+     * can't be stopped that way. [`PpocrPool::shutdown`] calls it at its deadline. See [`Ppocr::terminator`].
+     */
+    fn terminator(&self) -> Option<Terminator> {
+        None
+    }
+}
+
+impl OcrEngine for Ppocr {
+    fn ocr(&mut self, image: ImageData) -> IoResult<String> {
+        Ppocr::ocr(self, image)
+    }
+
+    fn ocr_and_parse(&mut self, image: ImageData) -> Result<Vec<ContentData>, String> {
+        Ppocr::ocr_and_parse(self, image)
+    }
+
+    fn ocr_response(&mut self, image: ImageData) -> Result<OcrResponse, String> {
+        Ppocr::ocr_response(self, image)
+    }
+
+    fn try_ocr(&mut self, image: ImageData) -> Result<Vec<ContentData>, PpocrError> {
+        Ppocr::try_ocr(self, image)
+    }
+
+    #[cfg(feature = "local")]
+    fn terminator(&self) -> Option<Terminator> {
+        Ppocr::terminator(self)
+    }
+}
+
+/**
+ * The program, arguments, working directory and environment of a spawned engine.
+ */
+#[cfg(feature = "local")]
+struct Launch {
+    program: std::ffi::OsString,
+    args: Vec<std::ffi::OsString>,
+    current_dir: Option<PathBuf>,
+    envs: Vec<(std::ffi::OsString, Option<std::ffi::OsString>)>,
+}
+
+#[cfg(feature = "local")]
+impl Launch {
+    fn new(command: &process::Command) -> Launch {
+        Launch {
+            program: command.get_program().to_owned(),
+            args: command.get_args().map(ToOwned::to_owned).collect(),
+            current_dir: command.get_current_dir().map(Path::to_path_buf),
+            envs: command
+                .get_envs()
+                .map(|(key, value)| (key.to_owned(), value.map(ToOwned::to_owned)))
+                .collect(),
+        }
+    }
+
+    fn command(&self) -> process::Command {
+        let mut command = process::Command::new(&self.program);
+        command.args(&self.args);
+        if let Some(dir) = &self.current_dir {
+            command.current_dir(dir);
         }
+        for (key, value) in &self.envs {
+            match value {
+                Some(value) => command.env(key, value),
+                None => command.env_remove(key),
+            };
+        }
+        command
+    }
+}
+
+impl fmt::Debug for Ppocr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let status = self.status();
+        f.debug_struct("Ppocr")
+            .field("exe_path", &status.exe_path)
+            .field("version", &status.version)
+            .field("language", &status.language)
+            .field("uptime", &status.uptime)
+            .field("requests", &status.requests)
+            .field("alive", &status.alive)
+            .finish()
     }
 }
 
+/// The language of the `--config_path` passed to `command`: `en` for `.../config_en.txt`.
+#[cfg(feature = "local")]
+fn config_language(command: &process::Command) -> Option<String> {
+    let mut args = command.get_args().map(|arg| arg.to_string_lossy());
+    let path = loop {
+        let arg = args.next()?;
+        if let Some(path) = arg.strip_prefix("--config_path=") {
+            break path.to_string();
+        }
+        if arg == "--config_path" {
+            break args.next()?.into_owned();
+        }
+    };
+    let stem = path.rsplit(['/', '\\']).next()?;
+    let stem = stem.rsplit_once('.').map_or(stem, |(stem, _)| stem);
+    Some(stem.strip_prefix("config_").unwrap_or(stem).to_string())
+}
+
 impl Drop for Ppocr {
     /**
-     * Kill the process when the instance is dropped.
+     * Stop the process when the instance is dropped, as set with [`Ppocr::set_drop_behavior`].
      */
     fn drop(&mut self) {
-        self.process.kill().err();
+        #[cfg(feature = "local")]
+        if let Some(process) = self.process.as_ref() {
+            match self.drop_behavior {
+                DropBehavior::Kill => {
+                    process.kill().err();
+                }
+                DropBehavior::Graceful(timeout) => {
+                    // Replacing the writer closes the engine's stdin.
+                    self.writer = Box::new(std::io::sink());
+                    process.shutdown(timeout).err();
+                }
+                DropBehavior::Detach => process.detach(),
+            }
+        }
     }
 }
 
 #[cfg(test)]
-
 mod tests {
+    #[cfg(target_os = "windows")]
     use std::path::{Path, PathBuf};
 
-    use crate::{ImageData, Ppocr};
+    use crate::{ImageData, OcrOptions, PayloadTooLarge, Ppocr, RequestError, ResponseTooLarge};
+
+    #[test]
+    fn available_languages() {
+        let dir = std::env::temp_dir().join(format!("paddleocr-langs-{}", std::process::id()));
+        let models = dir.join("models");
+        std::fs::create_dir_all(models.join("config_dir.txt")).unwrap();
+        for file in [
+            "config_en.txt",
+            "config_chinese.txt",
+            "config_.txt",
+            "en.txt",
+        ] {
+            std::fs::write(models.join(file), "").unwrap();
+        }
+        let languages = Ppocr::available_languages(&dir);
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(languages.unwrap(), vec!["chinese", "en"]);
+    }
+
+    /// A writer whose contents stay inspectable after being handed to a `Ppocr`.
+    #[derive(Clone, Default)]
+    struct SharedBuf(std::sync::Arc<std::sync::Mutex<Vec<u8>>>);
+    impl std::io::Write for SharedBuf {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn streams() {
+        let responses = concat!(
+            r#"{"code":101,"data":"No text found in image. Path: \"a.png\""}"#,
+            "\n",
+            r#"{"code":100,"data":[{"box":[[1,2],[3,2],[3,4],[1,4]],"score":0.5,"text":"hi"}]}"#,
+            "\n",
+        );
+        let requests = SharedBuf::default();
+        let mut p = Ppocr::from_streams(std::io::Cursor::new(responses), requests.clone());
+
+        assert!(p.ocr_and_parse(ImageData::from_path("a.png")).is_err());
+        let data = p.ocr_and_parse(ImageData::from_path("b.png")).unwrap();
+        assert_eq!(data[0].text, "hi");
+        assert_eq!(
+            String::from_utf8(requests.0.lock().unwrap().clone()).unwrap(),
+            "{\"image_path\":\"a.png\"}\n{\"image_path\":\"b.png\"}\n"
+        );
+    }
+
+    #[test]
+    fn payload_validation() {
+        let requests = SharedBuf::default();
+        let mut p = Ppocr::from_streams(std::io::empty(), requests.clone());
+        for (base64, message) in [
+            ("aGVsbG8", "its length is not a positive multiple of 4"),
+            ("aGVs*G8=", "unexpected '*' at 4"),
+            ("aGVsbG9=", "its last character has stray bits"),
+            ("a===", "it ends with more than 2 `=`"),
+        ] {
+            let e = p
+                .ocr(ImageData::from_base64(base64.to_string()))
+                .unwrap_err();
+            assert_eq!(e.kind(), std::io::ErrorKind::InvalidInput);
+            assert!(e.to_string().ends_with(message), "{}", e);
+        }
+
+        p.set_max_payload_size(Some(20));
+        let e = p.ocr(ImageData::from_path("a.png")).unwrap_err();
+        assert_eq!(e.kind(), std::io::ErrorKind::InvalidInput);
+        assert_eq!(
+            e.get_ref().unwrap().downcast_ref::<PayloadTooLarge>(),
+            Some(&PayloadTooLarge {
+                size: 23,
+                limit: 20
+            })
+        );
+        assert!(requests.0.lock().unwrap().is_empty());
+        assert_eq!(p.status().requests, 0);
+    }
+
+    #[test]
+    fn sanitized_requests() {
+        let responses = "{\"code\":101,\"data\":\"\"}\n".repeat(2);
+        let requests = SharedBuf::default();
+        let mut p = Ppocr::from_streams(std::io::Cursor::new(responses), requests.clone());
+        p.ocr(ImageData::from_path("a\u{0}b\r.png")).unwrap();
+        p.ocr(ImageData::from_base64("aGVs\r\nbG8=\n".to_string()))
+            .unwrap();
+        assert_eq!(
+            String::from_utf8(requests.0.lock().unwrap().clone()).unwrap(),
+            "{\"image_path\":\"ab.png\"}\n{\"image_base64\":\"aGVsbG8=\"}\n"
+        );
+    }
+
+    #[test]
+    fn clipboard_retry() {
+        use crate::ClipboardRetry;
+
+        let busy = r#"{"code":210,"data":"Clipboard open failed."}"#;
+        let responses = format!(
+            "{busy}\n{busy}\n{}\n{busy}\n",
+            r#"{"code":101,"data":"No text found in image. Path: \"clipboard\""}"#
+        );
+        let mut p = Ppocr::from_streams(std::io::Cursor::new(responses), std::io::sink());
+        p.set_clipboard_retry(ClipboardRetry {
+            retries: 2,
+            delay: std::time::Duration::ZERO,
+        });
+        assert!(p.ocr_clipboard().unwrap().contains("101"));
+        p.set_clipboard_retry(ClipboardRetry::NONE);
+        assert!(p.ocr_clipboard().unwrap().contains("210"));
+        assert_eq!(p.status().requests, 4);
+    }
+
+    #[test]
+    fn try_ocr() {
+        use crate::PpocrError;
+
+        let responses = concat!(
+            "{\"code\":\"x\"}\n",
+            r#"{"code":101,"data":"No text found in image. Path: \"a.png\""}"#,
+            "\n",
+        );
+        let mut p = Ppocr::from_streams(std::io::Cursor::new(responses), std::io::sink());
+        let image = || ImageData::from_path("a.png");
+        assert!(
+            matches!(p.try_ocr(image()), Err(PpocrError::Parse { raw, .. }) if raw == r#"{"code":"x"}"#)
+        );
+        assert!(matches!(
+            p.try_ocr(image()),
+            Err(PpocrError::Engine { code: 101, .. })
+        ));
+        assert!(matches!(p.try_ocr(image()), Err(PpocrError::Io(_))));
+    }
+
+    #[test]
+    fn transforms() {
+        let response =
+            r#"{"code":100,"data":[{"box":[[1,2],[3,2],[3,4],[1,4]],"score":0.5,"text":"hi"}]}"#;
+        let mut p = Ppocr::from_streams(
+            std::io::Cursor::new(format!("{response}\n{response}\n")),
+            std::io::sink(),
+        );
+        p.add_transform(|mut data: Vec<crate::ContentData>| {
+            data[0].text.push('!');
+            data
+        });
+        p.add_transform(|data: Vec<crate::ContentData>| [data.clone(), data].concat());
+        let data = p.ocr_and_parse(ImageData::from_path("a.png")).unwrap();
+        assert_eq!(data.len(), 2);
+        assert_eq!(data[1].text, "hi!");
+
+        p.clear_transforms();
+        assert_eq!(
+            p.ocr_and_parse(ImageData::from_path("a.png"))
+                .unwrap()
+                .len(),
+            1
+        );
+    }
+
+    #[test]
+    fn translator() {
+        let response = r#"{"code":100,"data":[{"box":[[0,0],[9,0],[9,9],[0,9]],"score":0.9,"text":"a"},{"box":[[0,9],[9,9],[9,19],[0,19]],"score":0.9,"text":"b"}]}"#;
+        let mut p = Ppocr::from_streams(
+            std::io::Cursor::new(format!("{response}\n{response}\n")),
+            std::io::sink(),
+        );
+        assert!(p.ocr_and_translate(ImageData::from_path("a.png")).is_err());
+
+        p.set_translator(
+            |text: &str| Ok(text.to_uppercase()),
+            crate::TranslationUnit::Joined,
+        );
+        let translated = p.ocr_and_translate(ImageData::from_path("a.png")).unwrap();
+        assert_eq!(translated.translations, ["A\nB"]);
+
+        p.set_translator(
+            |text: &str| Ok(format!("[{}]", text)),
+            crate::TranslationUnit::PerDetection,
+        );
+        let translated = p.ocr_and_translate(ImageData::from_path("a.png")).unwrap();
+        assert_eq!(translated.text(), "[a]\n[b]");
+    }
+
+    #[test]
+    fn malformed_responses() {
+        let inputs: [&[u8]; 8] = [
+            b"",
+            b"\n",
+            b"\xff\xfe\n",
+            b"{\"code\":100}\n",
+            b"{\"code\":100,\"data\":[{\"box\":[[-1,0]],\"score\":1,\"text\":\"\"}]}\n",
+            b"{\"code\":100,\"data\":[{\"box\":[[1e99,0],[0,0],[0,0],[0,0]],\"score\":1,\"text\":1}]}\n",
+            b"[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[\n",
+            b"{\"code\":\"100\",\"data\":null}",
+        ];
+        for input in inputs {
+            let mut p = Ppocr::from_streams(std::io::Cursor::new(input), std::io::sink());
+            assert!(p.ocr_and_parse(ImageData::from_path("a.png")).is_err());
+        }
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn process_control() {
+        let mut command = std::process::Command::new("sh");
+        command.args(["-c", "echo 'OCR init completed.'; exec cat"]);
+        let mut p = Ppocr::from_command(command).unwrap();
+        assert!(p.pid().is_some());
+        assert!(p.try_wait().unwrap().is_none());
+        p.kill().unwrap();
+        assert!(!p.wait().unwrap().success());
+        p.kill().unwrap();
+
+        let mut p = Ppocr::from_streams(std::io::empty(), std::io::sink());
+        assert_eq!(p.pid(), None);
+        assert!(p.kill().is_err());
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn status() {
+        let mut command = std::process::Command::new("sh");
+        command.args([
+            "-c",
+            "echo 'PaddleOCR-json v1.4.0'; echo 'OCR init completed.'; exec cat",
+            "sh",
+            "--config_path",
+            "models/config_en.txt",
+        ]);
+        let mut p = Ppocr::from_command(command).unwrap();
+        p.ocr(ImageData::from_path("a.png")).unwrap();
+        let status = p.status();
+        assert_eq!(status.exe_path, Some("sh".into()));
+        assert_eq!(status.version.as_deref(), Some("1.4.0"));
+        assert_eq!(status.language.as_deref(), Some("en"));
+        assert!(status.uptime.is_some());
+        assert_eq!(status.requests, 1);
+        assert_eq!(status.alive, Some(true));
+        assert!(format!("{:?}", p).contains("alive: Some(true)"));
+        let init = p.init_report().unwrap();
+        assert!(init.warm_up.is_some());
+        assert_eq!(init.ready_after(), init.spawn + init.model_load);
+        assert_eq!(
+            Ppocr::from_streams(std::io::empty(), std::io::sink()).init_report(),
+            None
+        );
+        p.kill().unwrap();
+        p.wait().unwrap();
+        assert_eq!(p.status().alive, Some(false));
+    }
+
+    #[test]
+    fn response_too_large() {
+        let responses = format!("{}\n{{\"code\":101}}\n", "a".repeat(100));
+        let mut p = Ppocr::from_streams(std::io::Cursor::new(responses), std::io::sink());
+        p.set_max_response_size(Some(50));
+        let e = p.ocr(ImageData::from_path("a.png")).unwrap_err();
+        assert_eq!(e.kind(), std::io::ErrorKind::InvalidData);
+        let context = e.get_ref().unwrap().downcast_ref::<RequestError>().unwrap();
+        assert_eq!((context.input.as_str(), context.request_id), ("a.png", 1));
+        assert_eq!(context.payload_size, r#"{"image_path":"a.png"}"#.len() + 1);
+        assert!(context.elapsed.is_some());
+        assert!(e
+            .to_string()
+            .starts_with("OCR request #1 (a.png) failed after"));
+        assert_eq!(
+            context
+                .source()
+                .get_ref()
+                .unwrap()
+                .downcast_ref::<ResponseTooLarge>(),
+            Some(&ResponseTooLarge { limit: 50 })
+        );
+        let base64 = ImageData::from_base64("aGVsbG8=".into());
+        assert_eq!(base64.describe(), "base64, 5 bytes");
+        // The rest of the long line is skipped.
+        p.set_max_response_size(None);
+        assert_eq!(
+            p.ocr(ImageData::from_path("a.png")).unwrap(),
+            r#"{"code":101}"#
+        );
+    }
+
     #[test]
+    fn options() {
+        let written = SharedBuf::default();
+        let output = "{\"code\":101}\n".repeat(2);
+        let mut p = Ppocr::from_streams(std::io::Cursor::new(output), written.clone());
+        p.ocr(ImageData::from_path("a.png")).unwrap();
+        p.ocr_with(ImageData::from_path("b.png"), &OcrOptions::new().cls(true))
+            .unwrap();
+        assert_eq!(
+            String::from_utf8(written.0.lock().unwrap().clone()).unwrap(),
+            "{\"image_path\":\"a.png\"}\n{\"image_path\":\"b.png\",\"cls\":true}\n"
+        );
+
+        // An engine from before per-request options.
+        p.version = Some("1.3.1".into());
+        let e = p
+            .ocr_with(ImageData::from_path("a.png"), &OcrOptions::new().cls(true))
+            .unwrap_err();
+        assert_eq!(e.kind(), std::io::ErrorKind::Unsupported);
+    }
+
+    #[test]
+    fn self_test() {
+        let response = |text: &str| {
+            format!(
+                r#"{{"code":100,"data":[{{"box":[[16,16],[228,16],[228,44],[16,44]],"score":0.98,"text":"{}"}}]}}"#,
+                text
+            )
+        };
+        let mut p =
+            Ppocr::from_streams(std::io::Cursor::new(response("Hello 123")), std::io::sink());
+        assert_eq!(p.self_test(), Ok(()));
+        let mut p = Ppocr::from_streams(std::io::Cursor::new(response("HFIIO")), std::io::sink());
+        assert!(p.self_test().is_err());
+        let mut p = Ppocr::from_streams(std::io::empty(), std::io::sink());
+        assert!(p.self_test().is_err());
+    }
+
+    #[test]
+    fn framing() {
+        let output = concat!(
+            "[info] loading models\n",
+            "{\"code\":100,\n\"data\":[]}\n",
+            "{\"code\":101} {\"code\":102}\n",
+            "{\"code\":\n",
+        );
+        let mut p = Ppocr::from_streams(std::io::Cursor::new(output), std::io::sink());
+        let mut next = || p.ocr(ImageData::from_path("a.png")).unwrap();
+        assert_eq!(next(), "{\"code\":100,\n\"data\":[]}");
+        assert_eq!(next(), r#"{"code":101}"#);
+        assert_eq!(next(), r#"{"code":102}"#);
+        // Cut off by the engine exiting.
+        assert_eq!(next(), "{\"code\":\n");
+        assert_eq!(next(), "");
+    }
+
+    #[test]
+    fn malformed_response() {
+        let output = "{\"code\": oops}\n{\"code\":101}\n";
+        let mut p = Ppocr::from_streams(std::io::Cursor::new(output), std::io::sink());
+        assert_eq!(
+            p.ocr(ImageData::from_path("a.png")).unwrap(),
+            "{\"code\": oops}\n"
+        );
+        assert_eq!(
+            p.ocr(ImageData::from_path("a.png")).unwrap(),
+            r#"{"code":101}"#
+        );
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn restarts_after_response_too_large() {
+        let mut command = std::process::Command::new("sh");
+        command.args([
+            "-c",
+            "echo 'OCR init completed.'; read x; head -c 100000 /dev/zero | tr '\\0' a; exec cat",
+        ]);
+        let mut p = Ppocr::from_command(command).unwrap();
+        p.set_max_response_size(Some(1000));
+        let pid = p.pid();
+        assert!(p.ocr(ImageData::from_path("a.png")).is_err());
+        assert_ne!(p.pid(), pid);
+        assert_eq!(p.status().alive, Some(true));
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn pause_resume() {
+        let mut command = std::process::Command::new("sh");
+        command.args(["-c", "echo 'OCR init completed.'; exec cat"]);
+        let mut p = Ppocr::from_command(command).unwrap();
+        let state = |pid: u32| {
+            let stat = std::fs::read_to_string(format!("/proc/{}/stat", pid)).unwrap();
+            stat.rsplit(") ").next().unwrap().chars().next().unwrap()
+        };
+        let pid = p.pid().unwrap();
+        p.pause().unwrap();
+        std::thread::sleep(std::time::Duration::from_millis(50));
+        assert_eq!(state(pid), 'T');
+        p.resume().unwrap();
+        std::thread::sleep(std::time::Duration::from_millis(50));
+        assert_ne!(state(pid), 'T');
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn exit_callback() {
+        let mut command = std::process::Command::new("sh");
+        command.args([
+            "-c",
+            "echo 'OCR init completed.'; read x; echo 'model missing' >&2; exit 3",
+        ]);
+        let mut p = Ppocr::from_command(command).unwrap();
+        let (sender, exits) = std::sync::mpsc::channel();
+        p.on_exit(move |info| sender.send(info).unwrap()).unwrap();
+        assert!(p.ocr(ImageData::from_path("a.png")).unwrap().is_empty());
+        let info = exits
+            .recv_timeout(std::time::Duration::from_secs(5))
+            .unwrap();
+        assert_eq!(info.status.unwrap().code(), Some(3));
+        assert_eq!(info.stderr_tail, ["model missing"]);
+
+        // Killing on purpose is not reported.
+        let mut command = std::process::Command::new("sh");
+        command.args(["-c", "echo 'OCR init completed.'; exec cat"]);
+        let mut p = Ppocr::from_command(command).unwrap();
+        let (sender, exits) = std::sync::mpsc::channel();
+        p.on_exit(move |info| sender.send(info).unwrap()).unwrap();
+        p.kill().unwrap();
+        assert!(exits
+            .recv_timeout(std::time::Duration::from_millis(500))
+            .is_err());
+    }
+
+    #[test]
+    fn malformed_input_does_not_panic() {
+        let garbage: &[u8] =
+            b"\xff\xfe{\"code\":\xff}\n{\"code\":100,\"data\":[{\"box\":1}]}\n{\"code\":\n";
+        let mut p = Ppocr::from_streams(std::io::Cursor::new(garbage), std::io::sink());
+        for _ in 0..4 {
+            assert!(p.ocr_and_parse(ImageData::from_path("a.png")).is_err());
+        }
+        assert!(p
+            .ocr_response(ImageData::from_base64("not base64".into()))
+            .is_err());
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn dead_engine_does_not_panic() {
+        let mut command = std::process::Command::new("sh");
+        command.args(["-c", "echo 'OCR init completed.'; exit 1"]);
+        let mut p = Ppocr::from_command(command).unwrap();
+        p.wait().unwrap();
+        assert!(p.ocr_and_parse(ImageData::from_path("a.png")).is_err());
+        assert!(p.ocr_response(ImageData::from_path("a.png")).is_err());
+        assert!(p.ocr_clipboard().is_err());
+        assert!(p.self_test().is_err());
+        assert_eq!(p.status().alive, Some(false));
+        p.kill().ok();
+    }
+
+    #[test]
+    #[cfg(all(unix, feature = "bytes"))]
+    fn spill() {
+        // Answers with the path it got and that file's size, or code 200 for base64.
+        let script = r#"echo 'OCR init completed.'
+            while read x; do
+                f=$(echo "$x" | sed -n 's/.*"image_path":"\([^"]*\)".*/\1/p')
+                if [ -n "$f" ]; then echo "{\"code\":101,\"data\":\"$f $(wc -c < "$f")\"}"; else echo '{"code":200}'; fi
+            done"#;
+        let mut command = std::process::Command::new("sh");
+        command.args(["-c", script]);
+        let mut p = Ppocr::from_command(command).unwrap();
+        p.set_spill_threshold(Some(4));
+        let small = p.ocr(ImageData::from_bytes(b"BM")).unwrap();
+        assert!(small.contains("200"), "{}", small);
+        let large = p
+            .ocr_response(ImageData::from_bytes(b"\x89PNG...."))
+            .unwrap();
+        let (path, size) = large.message.as_deref().unwrap().split_once(' ').unwrap();
+        assert!(path.ends_with(".png"), "{}", path);
+        assert_eq!(size, "8");
+        assert!(!std::path::Path::new(path).exists());
+
+        // As for an engine over SSH or in a container.
+        p.local = false;
+        let remote = p.ocr(ImageData::from_bytes(b"\x89PNG....")).unwrap();
+        assert!(remote.contains("200"), "{}", remote);
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn log_forwarding() {
+        use crate::{LogLevel, LogLine};
+
+        let mut command = std::process::Command::new("sh");
+        command.args([
+            "-c",
+            "echo 'OCR init completed.'; read x; echo 'W1016 12:00:00.000 42 init.cc:7] slow CPU' >&2; \
+             echo 'failed to load dict' >&2; echo 'loading' >&2; echo '{}'; exec cat",
+        ]);
+        let mut p = Ppocr::from_command(command).unwrap();
+        let pid = p.pid().unwrap();
+        let (sender, lines) = std::sync::mpsc::channel();
+        p.on_log(move |line| sender.send(line).unwrap()).unwrap();
+        p.ocr(ImageData::from_path("a.png")).unwrap();
+        let lines: Vec<LogLine> = (0..3)
+            .map(|_| {
+                lines
+                    .recv_timeout(std::time::Duration::from_secs(5))
+                    .unwrap()
+            })
+            .collect();
+        assert!(lines.iter().all(|line| line.pid == pid));
+        let levels: Vec<_> = lines.iter().map(|line| line.level).collect();
+        assert_eq!(levels, [LogLevel::Warn, LogLevel::Error, LogLevel::Info]);
+        assert_eq!(lines[1].message, "failed to load dict");
+    }
+
+    #[test]
+    #[cfg(all(unix, feature = "log"))]
+    fn log_facade() {
+        use std::sync::Mutex;
+
+        /// Keeps the level, PID and message of the engine's records.
+        struct Capture(Mutex<Vec<(log::Level, Option<u64>, String)>>);
+        impl log::Log for Capture {
+            fn enabled(&self, metadata: &log::Metadata) -> bool {
+                metadata.target() == crate::LOG_TARGET
+            }
+            fn log(&self, record: &log::Record) {
+                if self.enabled(record.metadata()) {
+                    let pid = record
+                        .key_values()
+                        .get("pid".into())
+                        .and_then(|v| v.to_u64());
+                    let entry = (record.level(), pid, record.args().to_string());
+                    self.0.lock().unwrap().push(entry);
+                }
+            }
+            fn flush(&self) {}
+        }
+        static LOGGER: Capture = Capture(Mutex::new(Vec::new()));
+        log::set_logger(&LOGGER).unwrap();
+        log::set_max_level(log::LevelFilter::Info);
+
+        let mut command = std::process::Command::new("sh");
+        command.args([
+            "-c",
+            "echo 'OCR init completed.'; read x; echo 'E1016 12:00:00.000 42 ocr.cc:9] bad image' >&2; \
+             echo '{}'; exec cat",
+        ]);
+        let mut p = Ppocr::from_command(command).unwrap();
+        let pid = p.pid().unwrap();
+        p.ocr(ImageData::from_path("a.png")).unwrap();
+        // Other tests' engines may log meanwhile.
+        let ours = || {
+            let records = LOGGER.0.lock().unwrap();
+            records
+                .iter()
+                .find(|(_, p, _)| *p == Some(pid as u64))
+                .cloned()
+        };
+        let deadline = std::time::Instant::now() + std::time::Duration::from_secs(5);
+        while ours().is_none() && std::time::Instant::now() < deadline {
+            std::thread::sleep(std::time::Duration::from_millis(10));
+        }
+        let (level, _, message) = ours().unwrap();
+        assert_eq!(level, log::Level::Error);
+        assert!(message.starts_with(&format!("engine {}: E1016", pid)));
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn drop_behavior() {
+        use crate::DropBehavior;
+        use std::time::Duration;
+
+        let spawn = |script: &str| {
+            let mut command = std::process::Command::new("sh");
+            command.args(["-c", &format!("echo 'OCR init completed.'; {}", script)]);
+            Ppocr::from_command(command).unwrap()
+        };
+        let running = |pid: u32| {
+            std::fs::read_to_string(format!("/proc/{}/stat", pid))
+                .is_ok_and(|stat| !stat.rsplit(") ").next().unwrap().starts_with('Z'))
+        };
+
+        // Exits by itself once its input is closed.
+        let mut p = spawn("cat; echo done >&2");
+        p.set_drop_behavior(DropBehavior::Graceful(Duration::from_secs(5)));
+        let pid = p.pid().unwrap();
+        drop(p);
+        assert!(!running(pid));
+
+        // Ignores its input, so is killed at the deadline.
+        let mut p = spawn("exec sleep 30");
+        p.set_drop_behavior(DropBehavior::Graceful(Duration::from_millis(100)));
+        let pid = p.pid().unwrap();
+        let now = std::time::Instant::now();
+        drop(p);
+        assert!(now.elapsed() < Duration::from_secs(5));
+        assert!(!running(pid));
+
+        let mut p = spawn("exec sleep 30");
+        p.set_drop_behavior(DropBehavior::Detach);
+        let pid = p.pid().unwrap();
+        drop(p);
+        assert!(running(pid));
+        std::process::Command::new("kill")
+            .arg(pid.to_string())
+            .status()
+            .unwrap();
+    }
+
+    #[test]
+    #[cfg(target_os = "windows")]
     fn recognize() {
         let mut p = Ppocr::new(
             PathBuf::from(
                 "E:/code/paddleocr/v1.4.0/PaddleOCR-json.exe", // path to binary
             ),
-            Default::default(),
+            None,
         )
         .unwrap(); // initialize
 
@@ -419,10 +2579,11 @@ mod tests {
     }
 
     #[test]
+    #[cfg(target_os = "windows")]
     fn parse() {
         let mut p = Ppocr::new(
             PathBuf::from("E:/code/paddleocr/v1.4.0/PaddleOCR-json.exe"), // path to binary
-            Default::default(), // language config_path, default `zh_CN`
+            None, // language config_path, default `zh_CN`
         )
         .unwrap(); // initialize
 
@@ -435,4 +2596,22 @@ mod tests {
         )))
         .unwrap();
     }
+
+    #[test]
+    fn parse_streams() {
+        let output = concat!(
+            "OCR init completed.\n",
+            r#"{"code":100,"data":[{"box":[[1,2],[30,2],[30,12],[1,12]],"score":0.98,"text":"hello"}]}"#,
+            "\n",
+            r#"{"code":101,"data":"No text found in image. Path: \"a.png\""}"#,
+            "\n",
+        );
+        let mut p = Ppocr::from_streams(std::io::Cursor::new(output), std::io::sink());
+
+        let data = p.ocr_and_parse(ImageData::from_path("a.png")).unwrap();
+        assert_eq!(data.len(), 1);
+        assert_eq!(data[0].text, "hello");
+        assert_eq!(data[0].rect, [[1, 2], [30, 2], [30, 12], [1, 12]]);
+        assert!(p.ocr_and_parse(ImageData::from_path("a.png")).is_err());
+    }
 }
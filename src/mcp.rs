@@ -0,0 +1,319 @@
+//! A [Model Context Protocol](https://modelcontextprotocol.io) server exposing OCR as tools, enabled with the `mcp`
+//! feature.
+//!
+//! The server speaks newline-delimited JSON-RPC 2.0 over any reader/writer pair, normally the host process's
+//! stdin/stdout, and offers these tools:
+//!
+//! - `ocr_image`: OCRs an image given as a `path` or as `base64` data.
+//! - `ocr_clipboard`: OCRs the image currently in the clipboard, e.g. a screenshot taken with a snipping tool.
+//! - `ocr_screenshot`: OCRs a capture of the screen, taken by the function passed to
+//!   [`serve_stdio_with_screenshots`]; only offered by servers given one.
+//!
+//! Any [`OcrEngine`] can serve the tools; a [`PpocrPool`](crate::PpocrPool) lets several requests run at once.
+
+use std::io::{BufRead, Result as IoResult, Write};
+
+use serde_json::{json, Value};
+
+use crate::jsonrpc::{self, RpcError, INVALID_PARAMS};
+use crate::{ImageData, OcrEngine, OcrRec};
+
+const PROTOCOL_VERSION: &str = "2024-11-05";
+
+/**
+Serves MCP requests from stdin, answering on stdout, until stdin is closed.
+
+# Examples
+
+```no_run
+let mut pool = paddleocr::PpocrPool::new(
+    (0..2).map(|_| paddleocr::Ppocr::new(".../PaddleOCR-json.exe", None).unwrap()),
+);
+paddleocr::mcp::serve_stdio(&mut pool).unwrap();
+```
+*/
+pub fn serve_stdio<E: OcrEngine>(engine: &mut E) -> IoResult<()> {
+    serve(engine, std::io::stdin().lock(), std::io::stdout().lock())
+}
+
+/**
+Like [`serve_stdio`], also offering the `ocr_screenshot` tool, which OCRs the image `screenshot` returns, e.g. a
+capture of the screen encoded with [`Frame::encode_bmp`](crate::frame::Frame::encode_bmp).
+
+# Examples
+
+```no_run
+let mut pool = paddleocr::PpocrPool::new(
+    (0..2).map(|_| paddleocr::Ppocr::new(".../PaddleOCR-json.exe", None).unwrap()),
+);
+// Saved by a screen capture tool of your choice.
+let screenshot = || Ok(paddleocr::ImageData::from_path(".../screen.png"));
+paddleocr::mcp::serve_stdio_with_screenshots(&mut pool, screenshot).unwrap();
+```
+*/
+pub fn serve_stdio_with_screenshots<E, F>(engine: &mut E, screenshot: F) -> IoResult<()>
+where
+    E: OcrEngine,
+    F: FnMut() -> IoResult<ImageData>,
+{
+    serve_with_screenshots(
+        engine,
+        screenshot,
+        std::io::stdin().lock(),
+        std::io::stdout().lock(),
+    )
+}
+
+/**
+ * Serves MCP requests read line by line from `input`, writing one response line per request to `output`.
+ */
+pub fn serve<E, R, W>(engine: &mut E, input: R, output: W) -> IoResult<()>
+where
+    E: OcrEngine,
+    R: BufRead,
+    W: Write,
+{
+    jsonrpc::serve(input, output, |method, params| {
+        handle(engine, None, method, params)
+    })
+}
+
+/**
+ * Like [`serve`], also offering the `ocr_screenshot` tool; see [`serve_stdio_with_screenshots`].
+ */
+pub fn serve_with_screenshots<E, F, R, W>(
+    engine: &mut E,
+    mut screenshot: F,
+    input: R,
+    output: W,
+) -> IoResult<()>
+where
+    E: OcrEngine,
+    F: FnMut() -> IoResult<ImageData>,
+    R: BufRead,
+    W: Write,
+{
+    jsonrpc::serve(input, output, |method, params| {
+        handle(engine, Some(&mut screenshot), method, params)
+    })
+}
+
+/// Takes the screenshots of the `ocr_screenshot` tool.
+type Screenshot<'a> = &'a mut dyn FnMut() -> IoResult<ImageData>;
+
+/// The result of one request.
+fn handle<E: OcrEngine>(
+    engine: &mut E,
+    screenshot: Option<Screenshot>,
+    method: &str,
+    params: &Value,
+) -> Result<Value, RpcError> {
+    Ok(match method {
+        "initialize" => json!({
+            "protocolVersion": PROTOCOL_VERSION,
+            "capabilities": { "tools": {} },
+            "serverInfo": { "name": env!("CARGO_PKG_NAME"), "version": env!("CARGO_PKG_VERSION") },
+        }),
+        "ping" => json!({}),
+        "tools/list" => json!({ "tools": tools(screenshot.is_some()) }),
+        "tools/call" => call_tool(engine, screenshot, params)?,
+        _ => return Err(RpcError::method_not_found(method)),
+    })
+}
+
+fn tools(screenshots: bool) -> Value {
+    let mut tools = json!([
+        {
+            "name": "ocr_image",
+            "description": "Recognize the text in an image file or base64-encoded image. Returns one line per detected text box.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "path": { "type": "string", "description": "Path of the image file." },
+                    "base64": { "type": "string", "description": "Base64-encoded image data, without a data: prefix." },
+                },
+            },
+        },
+        {
+            "name": "ocr_clipboard",
+            "description": "Recognize the text in the image currently in the clipboard, such as a screenshot. Returns one line per detected text box.",
+            "inputSchema": { "type": "object", "properties": {} },
+        },
+    ]);
+    if screenshots {
+        tools.as_array_mut().unwrap().push(json!({
+            "name": "ocr_screenshot",
+            "description": "Recognize the text currently on the screen. Returns one line per detected text box.",
+            "inputSchema": { "type": "object", "properties": {} },
+        }));
+    }
+    tools
+}
+
+fn call_tool<E: OcrEngine>(
+    engine: &mut E,
+    screenshot: Option<Screenshot>,
+    params: &Value,
+) -> Result<Value, RpcError> {
+    let invalid = |message: String| RpcError::new(INVALID_PARAMS, message);
+    let image = match (params["name"].as_str(), screenshot) {
+        (Some("ocr_image"), _) => jsonrpc::image_param(&params["arguments"])
+            .ok_or_else(|| invalid("ocr_image needs either `path` or `base64`".to_string()))?,
+        (Some("ocr_clipboard"), _) => ImageData::from_path("clipboard"),
+        (Some("ocr_screenshot"), Some(screenshot)) => match screenshot() {
+            Ok(image) => image,
+            Err(e) => return Ok(tool_result(format!("Screenshot failed: {}", e), true)),
+        },
+        (Some(name), _) => return Err(invalid(format!("Unknown tool: {}", name))),
+        (None, _) => return Err(invalid("Missing tool name".to_string())),
+    };
+
+    // Engine failures are reported as tool errors, which the model gets to see, rather than protocol errors.
+    let (text, is_error) = match engine.ocr(image) {
+        Ok(response) => match serde_json::from_str::<OcrRec>(&response) {
+            Ok(OcrRec::Content { data, .. }) => (
+                data.into_iter()
+                    .map(|c| c.text)
+                    .collect::<Vec<_>>()
+                    .join("\n"),
+                false,
+            ),
+            Ok(OcrRec::Message { code, data }) => (data, code != 101),
+            Err(e) => (format!("Response JSON parse failed: {}", e), true),
+        },
+        Err(e) => (format!("OCR failed: {}", e), true),
+    };
+    Ok(tool_result(text, is_error))
+}
+
+fn tool_result(text: String, is_error: bool) -> Value {
+    json!({ "content": [{ "type": "text", "text": text }], "isError": is_error })
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Value};
+
+    use crate::{ImageData, OcrEngine};
+
+    struct Canned(&'static str);
+    impl OcrEngine for Canned {
+        fn ocr(&mut self, _image: ImageData) -> std::io::Result<String> {
+            Ok(self.0.to_string())
+        }
+    }
+
+    fn run(engine: &mut Canned, input: &str) -> Vec<Value> {
+        let mut output = Vec::new();
+        super::serve(engine, input.as_bytes(), &mut output).unwrap();
+        String::from_utf8(output)
+            .unwrap()
+            .lines()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn session() {
+        let mut engine = Canned(
+            r#"{"code":100,"data":[{"box":[[0,0],[1,0],[1,1],[0,1]],"score":0.9,"text":"a"},{"box":[[0,2],[1,2],[1,3],[0,3]],"score":0.9,"text":"b"}]}"#,
+        );
+        let responses = run(
+            &mut engine,
+            concat!(
+                r#"{"jsonrpc":"2.0","id":1,"method":"initialize","params":{}}"#,
+                "\n",
+                r#"{"jsonrpc":"2.0","method":"notifications/initialized"}"#,
+                "\n",
+                r#"{"jsonrpc":"2.0","id":2,"method":"tools/list"}"#,
+                "\n",
+                r#"{"jsonrpc":"2.0","id":3,"method":"tools/call","params":{"name":"ocr_image","arguments":{"path":"a.png"}}}"#,
+                "\n",
+                r#"{"jsonrpc":"2.0","id":4,"method":"tools/call","params":{"name":"ocr_image","arguments":{}}}"#,
+                "\n",
+                r#"{"jsonrpc":"2.0","id":5,"method":"nope"}"#,
+                "\n",
+            ),
+        );
+        assert_eq!(responses.len(), 5);
+        assert_eq!(responses[0]["result"]["protocolVersion"], "2024-11-05");
+        assert_eq!(responses[1]["result"]["tools"][0]["name"], "ocr_image");
+        assert_eq!(
+            responses[2]["result"],
+            json!({ "content": [{ "type": "text", "text": "a\nb" }], "isError": false })
+        );
+        assert_eq!(responses[3]["error"]["code"], -32602);
+        assert_eq!(responses[4]["error"]["code"], -32601);
+    }
+
+    #[test]
+    fn engine_errors() {
+        let mut engine = Canned(r#"{"code":211,"data":"Clipboard is empty."}"#);
+        let responses = run(
+            &mut engine,
+            r#"{"jsonrpc":"2.0","id":1,"method":"tools/call","params":{"name":"ocr_clipboard"}}"#,
+        );
+        assert_eq!(responses[0]["result"]["isError"], true);
+        assert_eq!(
+            responses[0]["result"]["content"][0]["text"],
+            "Clipboard is empty."
+        );
+    }
+
+    #[test]
+    fn screenshots() {
+        let mut pool = crate::PpocrPool::new([Canned(
+            r#"{"code":100,"data":[{"box":[[0,0],[1,0],[1,1],[0,1]],"score":0.9,"text":"on screen"}]}"#,
+        )]);
+        let mut taken = 0;
+        let mut output = Vec::new();
+        super::serve_with_screenshots(
+            &mut pool,
+            || {
+                taken += 1;
+                match taken {
+                    1 => Ok(ImageData::from_path("screen.png")),
+                    _ => Err(std::io::Error::other("no display")),
+                }
+            },
+            concat!(
+                r#"{"jsonrpc":"2.0","id":1,"method":"tools/list"}"#,
+                "\n",
+                r#"{"jsonrpc":"2.0","id":2,"method":"tools/call","params":{"name":"ocr_screenshot"}}"#,
+                "\n",
+                r#"{"jsonrpc":"2.0","id":3,"method":"tools/call","params":{"name":"ocr_screenshot"}}"#,
+                "\n",
+            )
+            .as_bytes(),
+            &mut output,
+        )
+        .unwrap();
+        let responses: Vec<Value> = String::from_utf8(output)
+            .unwrap()
+            .lines()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect();
+        assert_eq!(responses[0]["result"]["tools"][2]["name"], "ocr_screenshot");
+        assert_eq!(
+            responses[1]["result"],
+            json!({ "content": [{ "type": "text", "text": "on screen" }], "isError": false })
+        );
+        assert_eq!(
+            responses[2]["result"],
+            json!({ "content": [{ "type": "text", "text": "Screenshot failed: no display" }], "isError": true })
+        );
+
+        // Without screenshots, the tool is neither offered nor served.
+        let responses = run(
+            &mut Canned(""),
+            concat!(
+                r#"{"jsonrpc":"2.0","id":1,"method":"tools/list"}"#,
+                "\n",
+                r#"{"jsonrpc":"2.0","id":2,"method":"tools/call","params":{"name":"ocr_screenshot"}}"#,
+                "\n",
+            ),
+        );
+        assert_eq!(responses[0]["result"]["tools"].as_array().unwrap().len(), 2);
+        assert_eq!(responses[1]["error"]["code"], -32602);
+    }
+}
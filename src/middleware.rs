@@ -0,0 +1,164 @@
+//! Middleware around an [`OcrEngine`]: logging, caching, retries, request rewriting, redaction and the like,
+//! without touching the engine itself.
+//!
+//! A [`Layer`] receives each request together with the rest of the chain, and decides whether, how and how often to
+//! pass it on. [`Layered`] stacks layers over an engine and is an engine itself.
+//!
+//! ```no_run
+//! use paddleocr::middleware::Layered;
+//! use paddleocr::OcrEngine;
+//!
+//! let p = paddleocr::Ppocr::new(".../PaddleOCR-json.exe", None).unwrap();
+//! let mut engine = Layered::new(p).layer(|image, next: &mut dyn OcrEngine| {
+//!     let now = std::time::Instant::now();
+//!     let response = next.ocr(image);
+//!     eprintln!("OCR took {:.2?}", now.elapsed());
+//!     response
+//! });
+//! engine.ocr(Path::new(".../test.png").into()).unwrap();
+//! ```
+
+use std::io::Result as IoResult;
+
+use crate::{ImageData, OcrEngine, Terminator};
+
+/**
+ * A step in the request/response path of an engine.
+ */
+pub trait Layer {
+    /**
+     * Handles one request. `next` is the rest of the chain, ending with the engine; call its `ocr` to pass the
+     * (possibly rewritten) request on, or don't to answer it here.
+     */
+    fn call(&mut self, image: ImageData, next: &mut dyn OcrEngine) -> IoResult<String>;
+}
+
+impl<F> Layer for F
+where
+    F: FnMut(ImageData, &mut dyn OcrEngine) -> IoResult<String>,
+{
+    fn call(&mut self, image: ImageData, next: &mut dyn OcrEngine) -> IoResult<String> {
+        self(image, next)
+    }
+}
+
+/**
+ * An engine wrapped in a stack of [`Layer`]s. The first layer added sees requests first and responses last.
+ */
+pub struct Layered<E> {
+    engine: E,
+    layers: Vec<Box<dyn Layer>>,
+}
+
+impl<E: OcrEngine> Layered<E> {
+    /**
+     * Wraps `engine` without any layers.
+     */
+    pub fn new(engine: E) -> Layered<E> {
+        Layered {
+            engine,
+            layers: Vec::new(),
+        }
+    }
+
+    /**
+     * Adds a layer below the ones already added, i.e. closer to the engine.
+     */
+    pub fn layer<L: Layer + 'static>(mut self, layer: L) -> Layered<E> {
+        self.layers.push(Box::new(layer));
+        self
+    }
+
+    /**
+     * The wrapped engine.
+     */
+    pub fn get_mut(&mut self) -> &mut E {
+        &mut self.engine
+    }
+
+    /**
+     * Unwraps the engine, dropping the layers.
+     */
+    pub fn into_inner(self) -> E {
+        self.engine
+    }
+}
+
+/// The remainder of a chain, as seen by the layer before it.
+struct Next<'a> {
+    layers: &'a mut [Box<dyn Layer>],
+    engine: &'a mut dyn OcrEngine,
+}
+
+impl OcrEngine for Next<'_> {
+    fn ocr(&mut self, image: ImageData) -> IoResult<String> {
+        match self.layers.split_first_mut() {
+            Some((layer, layers)) => layer.call(
+                image,
+                &mut Next {
+                    layers,
+                    engine: &mut *self.engine,
+                },
+            ),
+            None => self.engine.ocr(image),
+        }
+    }
+}
+
+impl<E: OcrEngine> OcrEngine for Layered<E> {
+    fn ocr(&mut self, image: ImageData) -> IoResult<String> {
+        Next {
+            layers: &mut self.layers,
+            engine: &mut self.engine,
+        }
+        .ocr(image)
+    }
+
+    fn terminator(&self) -> Option<Terminator> {
+        self.engine.terminator()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use super::Layered;
+    use crate::{ImageData, OcrEngine};
+
+    struct Echo;
+    impl OcrEngine for Echo {
+        fn ocr(&mut self, image: ImageData) -> std::io::Result<String> {
+            Ok(serde_json::to_string(&image).unwrap())
+        }
+    }
+
+    #[test]
+    fn chain_order() {
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let (outer, inner) = (log.clone(), log.clone());
+        let mut engine = Layered::new(Echo)
+            .layer(move |image, next: &mut dyn OcrEngine| {
+                outer.lock().unwrap().push("outer");
+                next.ocr(image).map(|r| format!("<{}>", r))
+            })
+            .layer(move |_image, next: &mut dyn OcrEngine| {
+                inner.lock().unwrap().push("inner");
+                next.ocr(ImageData::from_path("rewritten.png"))
+            });
+
+        assert_eq!(
+            engine.ocr(ImageData::from_path("a.png")).unwrap(),
+            r#"<{"image_path":"rewritten.png"}>"#
+        );
+        assert_eq!(*log.lock().unwrap(), ["outer", "inner"]);
+    }
+
+    #[test]
+    fn short_circuit() {
+        let mut engine = Layered::new(Echo).layer(|_image, _next: &mut dyn OcrEngine| {
+            Ok(r#"{"code":101,"data":"cached"}"#.to_string())
+        });
+        assert!(engine.ocr_and_parse(ImageData::from_path("a.png")).is_err());
+    }
+}
@@ -0,0 +1,95 @@
+//! Options sent along with a single request.
+
+use serde::Serialize;
+
+/**
+ * Per-request settings, overriding the engine's startup configuration for one image. Unset fields keep the
+ * engine's setting. Requires an engine with [`Capabilities::per_call_options`](crate::Capabilities).
+ */
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct OcrOptions {
+    /// Whether to detect and correct text rotated by 180°. Worth it for photos, wasted time on screenshots.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cls: Option<bool>,
+    /// The side length in pixels that images are resized to for text detection, as set by
+    /// [`limit_type`](OcrOptions::limit_type). Lower is faster on large images, at the cost of missing small text.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit_side_len: Option<u32>,
+    /// Which side [`limit_side_len`](OcrOptions::limit_side_len) applies to.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit_type: Option<LimitType>,
+    /// Lines recognized with a lower confidence are dropped by the engine, so they are neither sent nor parsed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub drop_score: Option<f64>,
+}
+
+/**
+ * How [`OcrOptions::limit_side_len`] resizes images for detection.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LimitType {
+    /// Shrink images whose longer side exceeds the limit.
+    Max,
+    /// Enlarge images whose shorter side is below the limit.
+    Min,
+}
+
+impl OcrOptions {
+    /**
+     * Options that keep every setting of the engine.
+     */
+    pub fn new() -> OcrOptions {
+        OcrOptions::default()
+    }
+
+    /**
+     * Sets [`cls`](OcrOptions::cls).
+     */
+    pub fn cls(mut self, cls: bool) -> OcrOptions {
+        self.cls = Some(cls);
+        self
+    }
+
+    /**
+     * Sets [`limit_side_len`](OcrOptions::limit_side_len) and [`limit_type`](OcrOptions::limit_type).
+     */
+    pub fn limit_side_len(mut self, len: u32, limit_type: LimitType) -> OcrOptions {
+        self.limit_side_len = Some(len);
+        self.limit_type = Some(limit_type);
+        self
+    }
+
+    /**
+     * Sets [`drop_score`](OcrOptions::drop_score).
+     */
+    pub fn drop_score(mut self, drop_score: f64) -> OcrOptions {
+        self.drop_score = Some(drop_score);
+        self
+    }
+
+    /**
+     * Whether no setting is overridden.
+     */
+    pub fn is_empty(&self) -> bool {
+        *self == OcrOptions::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{LimitType, OcrOptions};
+
+    #[test]
+    fn serialize() {
+        assert_eq!(serde_json::to_string(&OcrOptions::new()).unwrap(), "{}");
+        let options = OcrOptions::new()
+            .cls(false)
+            .limit_side_len(960, LimitType::Max)
+            .drop_score(0.6);
+        assert_eq!(
+            serde_json::to_string(&options).unwrap(),
+            r#"{"cls":false,"limit_side_len":960,"limit_type":"max","drop_score":0.6}"#
+        );
+    }
+}
@@ -0,0 +1,169 @@
+//! Finding and terminating engines leaked by crashed hosts. Spawned engines carry the [`OWNER_ENV`] variable, set
+//! to the PID of the process that spawned them, so they can be told apart from engines started by anything else.
+
+use std::io::Result as IoResult;
+
+/// The environment variable marking engines spawned by this crate, holding the spawning process's PID.
+pub(crate) const OWNER_ENV: &str = "PADDLEOCR_RS_OWNER";
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+mod sys {
+    use std::ffi::c_int;
+    use std::io::Result as IoResult;
+
+    use super::OWNER_ENV;
+
+    extern "C" {
+        fn kill(pid: c_int, signal: c_int) -> c_int;
+    }
+
+    const SIGKILL: c_int = 9;
+
+    /// The owner recorded in the environment of process `pid`, if it has one and it can be read.
+    fn owner(pid: u32) -> Option<u32> {
+        let environ = std::fs::read(format!("/proc/{}/environ", pid)).ok()?;
+        let prefix = format!("{}=", OWNER_ENV);
+        environ
+            .split(|&b| b == 0)
+            .find_map(|var| var.strip_prefix(prefix.as_bytes()))
+            .and_then(|value| std::str::from_utf8(value).ok()?.parse().ok())
+    }
+
+    /// The parent of process `pid`, from the fourth field of its `stat`, after the parenthesized command name.
+    fn parent(pid: u32) -> Option<u32> {
+        let stat = std::fs::read_to_string(format!("/proc/{}/stat", pid)).ok()?;
+        let (_, fields) = stat.rsplit_once(')')?;
+        fields.split_whitespace().nth(1)?.parse().ok()
+    }
+
+    /// Whether `ancestor` is among the ancestors of process `pid`.
+    fn descends_from(pid: u32, ancestor: u32) -> bool {
+        let mut current = pid;
+        // Bounded in case of a cycle, which PID reuse during the walk could make.
+        for _ in 0..1024 {
+            match parent(current) {
+                Some(p) if p == ancestor => return true,
+                Some(p) if p > 1 => current = p,
+                _ => return false,
+            }
+        }
+        false
+    }
+
+    /// The PIDs of all running processes.
+    pub(super) fn pids() -> IoResult<Vec<u32>> {
+        let mut pids = Vec::new();
+        for entry in std::fs::read_dir("/proc")? {
+            if let Some(pid) = entry?.file_name().to_str().and_then(|n| n.parse().ok()) {
+                pids.push(pid);
+            }
+        }
+        Ok(pids)
+    }
+
+    /// The engines among processes `pids` whose owner is not among their ancestors.
+    pub(super) fn orphans_among<I: IntoIterator<Item = u32>>(pids: I) -> Vec<u32> {
+        pids.into_iter()
+            .filter(|&pid| {
+                owner(pid)
+                    .is_some_and(|owner| pid != std::process::id() && !descends_from(pid, owner))
+            })
+            .collect()
+    }
+
+    pub(super) fn orphans() -> IoResult<Vec<u32>> {
+        Ok(orphans_among(pids()?))
+    }
+
+    pub(super) fn terminate(pid: u32) -> IoResult<()> {
+        // SAFETY: `kill` has no memory-safety preconditions.
+        if unsafe { kill(pid as c_int, SIGKILL) } == 0 {
+            Ok(())
+        } else {
+            Err(std::io::Error::last_os_error())
+        }
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+mod sys {
+    pub(super) fn orphans() -> std::io::Result<Vec<u32>> {
+        Err(std::io::Error::new(
+            std::io::ErrorKind::Unsupported,
+            "reading the environment of other processes is not supported on this OS",
+        ))
+    }
+
+    pub(super) fn terminate(_: u32) -> std::io::Result<()> {
+        unreachable!("there are no orphans to terminate")
+    }
+}
+
+/**
+Terminates engines spawned by this crate whose spawning process is gone, e.g. after it crashed or was killed
+before it could stop them, and returns their PIDs. Engines of running processes, including other programs using
+this crate, are left alone. Engines are recognized by the `PADDLEOCR_RS_OWNER` environment variable set when they
+are spawned; processes they started, such as those of a Docker or SSH client, carry it too.
+
+Only supported on Linux, where the environment of other processes can be read from `/proc`; elsewhere this fails
+with [`ErrorKind::Unsupported`](std::io::ErrorKind::Unsupported). Engines of other users can't be read nor
+terminated, and are skipped.
+
+# Examples
+
+```no_run
+for pid in paddleocr::cleanup_orphans().unwrap() {
+    eprintln!("terminated leftover engine {}", pid);
+}
+```
+*/
+pub fn cleanup_orphans() -> IoResult<Vec<u32>> {
+    Ok(terminate(sys::orphans()?))
+}
+
+/// Terminates `orphans`, returning those that were.
+fn terminate(mut orphans: Vec<u32>) -> Vec<u32> {
+    // Those that fail exited meanwhile, or aren't ours to terminate.
+    orphans.retain(|&pid| sys::terminate(pid).is_ok());
+    orphans
+}
+
+#[cfg(test)]
+#[cfg(target_os = "linux")]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use super::{sys, terminate, OWNER_ENV};
+
+    #[test]
+    fn orphans() {
+        let spawn = |owner: &str| {
+            std::process::Command::new("sleep")
+                .arg("30")
+                .env(OWNER_ENV, owner)
+                .spawn()
+                .unwrap()
+        };
+        let mut ours = spawn(&std::process::id().to_string());
+        // Claims an owner that isn't among its ancestors, as if reparented after the owner died.
+        let mut orphan = spawn("4000000000");
+        let spawned = [ours.id(), orphan.id()];
+
+        // Until `sleep` is executed, the children have the environment of this process, without the marker.
+        let deadline = Instant::now() + Duration::from_secs(10);
+        let found = loop {
+            let found = sys::orphans_among(spawned);
+            if !found.is_empty() || Instant::now() > deadline {
+                break found;
+            }
+            std::thread::sleep(Duration::from_millis(10));
+        };
+        assert_eq!(found, [orphan.id()]);
+        assert_eq!(terminate(found), [orphan.id()]);
+        assert!(!orphan.wait().unwrap().success());
+        assert!(ours.try_wait().unwrap().is_none());
+        assert!(sys::orphans_among(spawned).is_empty());
+        ours.kill().unwrap();
+        ours.wait().unwrap();
+    }
+}
@@ -0,0 +1,242 @@
+//! OpenTelemetry spans for OCR requests, so that OCR shows up in the distributed traces of larger systems.
+//!
+//! [`Tracing`] is a [`Layer`] starting a `paddleocr.ocr` span per request with an OpenTelemetry tracer, the global
+//! one by default, so spans go wherever the application's SDK exports them. Spans are children of the current
+//! [`Context`], e.g. one extracted from an incoming W3C `traceparent` header, and the context follows requests onto
+//! the worker threads of [`PpocrClient`](crate::PpocrClient) and [`PpocrPool`](crate::PpocrPool).
+//!
+//! ```no_run
+//! use opentelemetry::propagation::TextMapPropagator;
+//! use opentelemetry_sdk::propagation::TraceContextPropagator;
+//! use paddleocr::middleware::Layered;
+//! use paddleocr::otel::Tracing;
+//! use paddleocr::OcrEngine;
+//!
+//! let p = paddleocr::Ppocr::new(".../PaddleOCR-json.exe", None).unwrap();
+//! let version = p.status().version;
+//! let mut engine = Layered::new(p).layer(Tracing::global().engine_version(version));
+//! let headers = HashMap::from([(
+//!     "traceparent".to_string(),
+//!     "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01".to_string(),
+//! )]);
+//! let _guard = TraceContextPropagator::new().extract(&headers).attach();
+//! engine.ocr(Path::new(".../test.png").into()).unwrap();
+//! ```
+
+use std::io::Result as IoResult;
+
+use opentelemetry::global::{self, BoxedTracer};
+use opentelemetry::trace::{SpanKind, Status, TraceContextExt, Tracer};
+use opentelemetry::{Context, KeyValue};
+
+use crate::middleware::Layer;
+use crate::{ImageData, OcrEngine, OcrResponse};
+
+/// Wraps a job for a worker thread so that it runs in the context of the thread submitting it.
+pub(crate) fn propagate<T, F>(f: F) -> impl FnOnce(&mut dyn OcrEngine) -> T
+where
+    F: FnOnce(&mut dyn OcrEngine) -> T,
+{
+    let context = Context::current();
+    move |engine: &mut dyn OcrEngine| {
+        let _guard = context.attach();
+        f(engine)
+    }
+}
+
+/**
+A [`Layer`] recording a client span named `paddleocr.ocr` per request.
+
+Spans carry these attributes when known:
+- `paddleocr.image.source`: `path`, `base64` or `clipboard`.
+- `paddleocr.image.size`: the size of the image in bytes, decoded from base64 or read from the file's metadata.
+- `paddleocr.image.encoded_size`: the length of the base64 text sent to the engine.
+- `paddleocr.engine.version`: as set with [`engine_version`](Tracing::engine_version).
+- `paddleocr.code`: the engine's response code.
+
+Their status is an error if the request failed or the engine answered with a code other than 100 or 101.
+*/
+pub struct Tracing<T = BoxedTracer> {
+    tracer: T,
+    engine_version: Option<String>,
+}
+
+impl Tracing {
+    /**
+     * Records spans with the global tracer provider, as set up by the application's OpenTelemetry SDK.
+     */
+    pub fn global() -> Tracing {
+        Tracing::new(global::tracer("paddleocr"))
+    }
+}
+
+impl<T: Tracer> Tracing<T> {
+    pub fn new(tracer: T) -> Tracing<T> {
+        Tracing {
+            tracer,
+            engine_version: None,
+        }
+    }
+
+    /**
+     * Records the engine's version, e.g. from [`Ppocr::status`](crate::Ppocr::status), on every span.
+     */
+    pub fn engine_version(mut self, version: Option<String>) -> Tracing<T> {
+        self.engine_version = version;
+        self
+    }
+}
+
+impl<T> Layer for Tracing<T>
+where
+    T: Tracer,
+    T::Span: Send + Sync + 'static,
+{
+    fn call(&mut self, image: ImageData, next: &mut dyn OcrEngine) -> IoResult<String> {
+        let mut attributes = Vec::new();
+        match &image {
+            ImageData::ImagePathDict { image_path } if image_path == "clipboard" => {
+                attributes.push(KeyValue::new("paddleocr.image.source", "clipboard"));
+            }
+            ImageData::ImagePathDict { image_path } => {
+                attributes.push(KeyValue::new("paddleocr.image.source", "path"));
+                if let Ok(metadata) = std::fs::metadata(image_path) {
+                    attributes.push(KeyValue::new("paddleocr.image.size", metadata.len() as i64));
+                }
+            }
+            ImageData::ImageBase64Dict { image_base64 } => {
+                attributes.push(KeyValue::new("paddleocr.image.source", "base64"));
+                let padding = image_base64
+                    .bytes()
+                    .rev()
+                    .take_while(|&b| b == b'=')
+                    .count();
+                let size = (image_base64.len() / 4 * 3).saturating_sub(padding);
+                attributes.push(KeyValue::new("paddleocr.image.size", size as i64));
+                attributes.push(KeyValue::new(
+                    "paddleocr.image.encoded_size",
+                    image_base64.len() as i64,
+                ));
+            }
+        }
+        if let Some(version) = &self.engine_version {
+            attributes.push(KeyValue::new("paddleocr.engine.version", version.clone()));
+        }
+
+        let span = self
+            .tracer
+            .span_builder("paddleocr.ocr")
+            .with_kind(SpanKind::Client)
+            .with_attributes(attributes)
+            .start(&self.tracer);
+        let context = Context::current_with_span(span);
+        let response = {
+            let _guard = context.clone().attach();
+            next.ocr(image)
+        };
+
+        let span = context.span();
+        let error = match &response {
+            Ok(raw) => match OcrResponse::parse(raw.as_str()) {
+                Ok(parsed) => {
+                    span.set_attribute(KeyValue::new("paddleocr.code", parsed.code as i64));
+                    (parsed.code != 100 && parsed.code != 101)
+                        .then(|| format!("code {}", parsed.code))
+                }
+                Err(e) => Some(e.to_string()),
+            },
+            Err(e) => Some(e.to_string()),
+        };
+        if let Some(error) = error {
+            span.set_status(Status::error(error));
+        }
+        span.end();
+        response
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use opentelemetry::trace::{Span, Status, TraceContextExt, Tracer, TracerProvider};
+    use opentelemetry::{Context, KeyValue, Value};
+    use opentelemetry_sdk::trace::{InMemorySpanExporter, SdkTracerProvider};
+
+    use super::Tracing;
+    use crate::middleware::Layered;
+    use crate::{ImageData, OcrEngine, PpocrPool};
+
+    /// Answers with the trace and span ids of the context it runs in, as code 101, or with code 200 for `missing.png`.
+    struct Traced;
+    impl OcrEngine for Traced {
+        fn ocr(&mut self, image: ImageData) -> std::io::Result<String> {
+            if matches!(&image, ImageData::ImagePathDict { image_path } if image_path == "missing.png")
+            {
+                return Ok(r#"{"code":200,"data":"Image not found."}"#.to_string());
+            }
+            let context = Context::current();
+            let span = context.span();
+            Ok(format!(
+                r#"{{"code":101,"data":"{}-{}"}}"#,
+                span.span_context().trace_id(),
+                span.span_context().span_id()
+            ))
+        }
+    }
+
+    #[test]
+    fn spans() {
+        let exporter = InMemorySpanExporter::default();
+        let provider = SdkTracerProvider::builder()
+            .with_simple_exporter(exporter.clone())
+            .build();
+        let mut engine = Layered::new(PpocrPool::new([Traced]))
+            .layer(Tracing::new(provider.tracer("paddleocr")).engine_version(Some("1.4.0".into())));
+
+        let request = provider.tracer("app").start("request");
+        let parent = request.span_context().clone();
+        let response = {
+            let _guard = Context::current_with_span(request).attach();
+            engine.ocr(ImageData::from_base64("aGVsbG8=".into()))
+        }
+        .unwrap();
+        engine.ocr(ImageData::from_path("missing.png")).unwrap();
+
+        let spans = exporter.get_finished_spans().unwrap();
+        let ocr: Vec<_> = spans.iter().filter(|s| s.name == "paddleocr.ocr").collect();
+        assert_eq!(ocr.len(), 2);
+        let span = ocr[0];
+        assert_eq!(span.span_context.trace_id(), parent.trace_id());
+        assert_eq!(span.parent_span_id, parent.span_id());
+        // The engine, on a pool worker, ran in the span's context.
+        assert_eq!(
+            response,
+            format!(
+                r#"{{"code":101,"data":"{}-{}"}}"#,
+                span.span_context.trace_id(),
+                span.span_context.span_id()
+            )
+        );
+        let attribute = |span: &opentelemetry_sdk::trace::SpanData, name: &str| {
+            span.attributes
+                .iter()
+                .find(|kv| kv.key.as_str() == name)
+                .map(|kv| kv.value.clone())
+        };
+        assert_eq!(attribute(span, "paddleocr.image.size"), Some(Value::I64(5)));
+        assert_eq!(attribute(span, "paddleocr.code"), Some(Value::I64(101)));
+        assert_eq!(
+            attribute(span, "paddleocr.engine.version"),
+            Some(Value::from("1.4.0"))
+        );
+        assert_eq!(span.status, Status::Unset);
+
+        // Without a parent, a new trace; an engine error fails the span.
+        let failed = ocr[1];
+        assert_ne!(failed.span_context.trace_id(), parent.trace_id());
+        assert_eq!(attribute(failed, "paddleocr.code"), Some(Value::I64(200)));
+        assert_eq!(failed.status, Status::error("code 200"));
+        assert!(failed
+            .attributes
+            .contains(&KeyValue::new("paddleocr.image.source", "path")));
+    }
+}
@@ -0,0 +1,1009 @@
+//! Several engines serving requests in parallel.
+
+use std::collections::VecDeque;
+use std::io::{Error as IoError, ErrorKind, Result as IoResult};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::sync::{mpsc, Arc, Condvar, Mutex, MutexGuard, PoisonError};
+use std::task::Waker;
+use std::thread::JoinHandle;
+use std::time::Instant;
+
+use crate::{ContentData, ImageData, OcrEngine, OcrResponse, PpocrError, Terminator};
+
+type Job = Box<dyn FnOnce(&mut dyn OcrEngine) + Send>;
+
+/**
+ * What [`PpocrPool`] does with a request when its queue is full; see [`PpocrPool::queue_capacity`].
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QueueFull {
+    /// Wait until an engine takes a queued request.
+    Block,
+    /// Fail the new request with [`WouldBlock`](ErrorKind::WouldBlock).
+    Error,
+    /// Drop the oldest of the least urgent queued requests, which fails, to make room; for live captures where only
+    /// the latest matters. A request less urgent than all queued ones is dropped itself.
+    DropOldest,
+}
+
+/**
+ * How urgent a request is. An engine that becomes free takes the most urgent queued request, and the oldest among
+ * equally urgent ones.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub enum Priority {
+    /// Bulk work nobody waits for, such as indexing.
+    Background,
+    #[default]
+    Normal,
+    /// Requests a user is waiting for, such as OCR of what is on screen.
+    Interactive,
+}
+
+#[derive(Default)]
+struct State {
+    /// Most urgent first, then oldest first.
+    jobs: VecDeque<(Priority, Job)>,
+    closed: bool,
+    /// Jobs taken by an engine and not finished yet.
+    running: usize,
+    /// For each worker running a job, how to stop its engine, if it can be.
+    terminators: Vec<Option<Terminator>>,
+    /// Set when [`PpocrPool::shutdown`] gave up on the running jobs, so that dropping the pool doesn't wait for them.
+    abandoned: bool,
+    /// The most jobs waiting for an engine, and what to do beyond that; unbounded if `None`.
+    capacity: Option<(usize, QueueFull)>,
+    /// Async submissions waiting for [`space`](Queue::space), woken alongside it.
+    wakers: Vec<Waker>,
+}
+
+#[derive(Default)]
+struct Queue {
+    state: Mutex<State>,
+    /// Signalled when a job is added or the queue is closed.
+    available: Condvar,
+    /// Signalled when a job is taken off the queue or the queue is closed.
+    space: Condvar,
+    /// Signalled when a job finishes.
+    finished: Condvar,
+}
+
+impl Queue {
+    fn lock(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// The next job for `worker`, whose engine `terminator` stops, or `None` once the queue is closed and empty.
+    fn pop(&self, worker: usize, terminator: Option<Terminator>) -> Option<Job> {
+        let mut state = self.lock();
+        loop {
+            if let Some((_, job)) = state.jobs.pop_front() {
+                state.running += 1;
+                state.terminators[worker] = terminator;
+                self.space.notify_one();
+                state.wakers.drain(..).for_each(Waker::wake);
+                return Some(job);
+            }
+            if state.closed {
+                return None;
+            }
+            state = self
+                .available
+                .wait(state)
+                .unwrap_or_else(PoisonError::into_inner);
+        }
+    }
+
+    fn finish(&self, worker: usize) {
+        let mut state = self.lock();
+        state.running -= 1;
+        state.terminators[worker] = None;
+        drop(state);
+        self.finished.notify_all();
+    }
+
+    /// Stops accepting jobs and wakes everyone waiting, so workers exit once the queue is empty.
+    fn close(&self) {
+        let mut state = self.lock();
+        state.closed = true;
+        state.wakers.drain(..).for_each(Waker::wake);
+        drop(state);
+        self.available.notify_all();
+        self.space.notify_all();
+    }
+}
+
+fn stopped() -> IoError {
+    IoError::new(ErrorKind::BrokenPipe, "the OCR pool has stopped")
+}
+
+fn dropped() -> IoError {
+    IoError::other("the request was dropped from the full queue")
+}
+
+/// Sends the result of one image of [`PpocrPool::ocr_all`], or an error if the job is dropped unanswered.
+struct Reply {
+    sender: mpsc::Sender<(usize, Result<Vec<ContentData>, String>)>,
+    index: usize,
+    sent: bool,
+}
+
+impl Reply {
+    fn send(mut self, result: Result<Vec<ContentData>, String>) {
+        self.sent = true;
+        self.sender.send((self.index, result)).ok();
+    }
+}
+
+impl Drop for Reply {
+    fn drop(&mut self) {
+        if !self.sent {
+            self.sender
+                .send((self.index, Err(dropped().to_string())))
+                .ok();
+        }
+    }
+}
+
+/**
+A fixed set of engines, each on its own worker thread, taking requests from a shared queue.
+
+Whichever engine is free takes the most urgent queued request, see [`with_priority`](PpocrPool::with_priority),
+and the oldest among equally urgent ones. The pool is `Send + Sync`; share it with an [`Arc`]. Dropping it lets the workers finish the queued requests, then drops the engines; to bound how
+long that takes, call [`shutdown`](PpocrPool::shutdown) first.
+
+# Examples
+
+```no_run
+use paddleocr::PpocrPool;
+
+let pool = PpocrPool::new((0..4).map(|_| paddleocr::Ppocr::new(".../PaddleOCR-json.exe", None).unwrap()));
+let images = std::fs::read_dir(".../scans").unwrap().map(|entry| entry.unwrap().path().into());
+for result in pool.ocr_all(images, 8) {
+    println!("{:?}", result);
+}
+```
+*/
+pub struct PpocrPool {
+    queue: Arc<Queue>,
+    workers: Vec<JoinHandle<()>>,
+}
+
+impl PpocrPool {
+    /**
+     * Moves each of `engines` to a worker thread of its own.
+     */
+    pub fn new<E, I>(engines: I) -> PpocrPool
+    where
+        E: OcrEngine + Send + 'static,
+        I: IntoIterator<Item = E>,
+    {
+        let queue = Arc::new(Queue::default());
+        let workers: Vec<_> = engines
+            .into_iter()
+            .enumerate()
+            .map(|(i, mut engine)| {
+                let queue = queue.clone();
+                std::thread::spawn(move || {
+                    while let Some(job) = queue.pop(i, engine.terminator()) {
+                        // A panicking request fails on its own; the engine keeps serving the others.
+                        catch_unwind(AssertUnwindSafe(|| job(&mut engine))).ok();
+                        queue.finish(i);
+                    }
+                })
+            })
+            .collect();
+        queue.lock().terminators = vec![None; workers.len()];
+        PpocrPool { queue, workers }
+    }
+
+    /**
+    Limits how many requests may wait for an engine, and sets what happens to requests beyond that. The queue is
+    unbounded by default, so producers faster than the engines make it grow without end.
+
+    # Examples
+
+    ```no_run
+    use paddleocr::{PpocrPool, QueueFull};
+
+    let engines = (0..2).map(|_| paddleocr::Ppocr::new(".../PaddleOCR-json.exe", None).unwrap());
+    // Frames of a live capture: only the newest few are worth OCRing.
+    let pool = PpocrPool::new(engines).queue_capacity(4, QueueFull::DropOldest);
+    ```
+    */
+    pub fn queue_capacity(self, capacity: usize, when_full: QueueFull) -> PpocrPool {
+        self.queue.lock().capacity = Some((capacity.max(1), when_full));
+        self
+    }
+
+    /**
+     * The number of engines.
+     */
+    pub fn size(&self) -> usize {
+        self.workers.len()
+    }
+
+    /**
+    A handle submitting requests to this pool with `priority`, instead of [`Priority::Normal`].
+
+    # Examples
+
+    ```no_run
+    use paddleocr::{PpocrPool, Priority};
+
+    let pool = PpocrPool::new((0..2).map(|_| paddleocr::Ppocr::new(".../PaddleOCR-json.exe", None).unwrap()));
+    std::thread::scope(|scope| {
+        scope.spawn(|| {
+            let documents = std::fs::read_dir(".../documents").unwrap();
+            let images = documents.map(|entry| entry.unwrap().path().into());
+            pool.with_priority(Priority::Background).ocr_all(images, 4)
+        });
+        // Taken by the next free engine, ahead of the queued documents.
+        let on_screen = pool.with_priority(Priority::Interactive).ocr(Path::new(".../screen.png").into());
+    });
+    ```
+    */
+    pub fn with_priority(&self, priority: Priority) -> Prioritized<'_> {
+        Prioritized {
+            pool: self,
+            priority,
+        }
+    }
+
+    fn submit(&self, priority: Priority, mut job: Job) -> IoResult<()> {
+        let mut state = self.queue.lock();
+        while let Some(waiting) = self.try_submit(&mut state, priority, job)? {
+            job = waiting;
+            state = self
+                .queue
+                .space
+                .wait(state)
+                .unwrap_or_else(PoisonError::into_inner);
+        }
+        Ok(())
+    }
+
+    /// Queues `job`, or hands it back if it has to wait for space with [`QueueFull::Block`].
+    fn try_submit(&self, state: &mut State, priority: Priority, job: Job) -> IoResult<Option<Job>> {
+        if state.closed || self.workers.is_empty() {
+            return Err(stopped());
+        }
+        if let Some((capacity, when_full)) = state.capacity {
+            while state.jobs.len() >= capacity {
+                match when_full {
+                    QueueFull::Block => return Ok(Some(job)),
+                    QueueFull::Error => {
+                        return Err(IoError::new(ErrorKind::WouldBlock, "the OCR queue is full"));
+                    }
+                    QueueFull::DropOldest => {
+                        let least = state.jobs.back().map_or(priority, |(least, _)| *least);
+                        if least > priority {
+                            return Err(dropped());
+                        }
+                        let oldest = state.jobs.iter().position(|(p, _)| *p == least);
+                        drop(oldest.and_then(|oldest| state.jobs.remove(oldest)));
+                    }
+                }
+            }
+        }
+        let position = state
+            .jobs
+            .iter()
+            .position(|(p, _)| *p < priority)
+            .unwrap_or(state.jobs.len());
+        state.jobs.insert(position, (priority, job));
+        self.queue.available.notify_one();
+        Ok(None)
+    }
+
+    /// Like [`submit`](PpocrPool::submit), waiting for space without blocking the thread.
+    #[cfg(feature = "async")]
+    async fn submit_async(&self, priority: Priority, job: Job) -> IoResult<()> {
+        let mut job = Some(job);
+        std::future::poll_fn(|context| {
+            let mut state = self.queue.lock();
+            let Some(waiting) = job.take() else {
+                return std::task::Poll::Ready(Ok(()));
+            };
+            match self.try_submit(&mut state, priority, waiting) {
+                Ok(Some(waiting)) => {
+                    job = Some(waiting);
+                    state.wakers.push(context.waker().clone());
+                    std::task::Poll::Pending
+                }
+                Ok(None) => std::task::Poll::Ready(Ok(())),
+                Err(e) => std::task::Poll::Ready(Err(e)),
+            }
+        })
+        .await
+    }
+
+    /**
+     * OCRs the image on the next free engine. See [`Ppocr::ocr`](crate::Ppocr::ocr).
+     */
+    pub fn ocr(&self, image: ImageData) -> IoResult<String> {
+        self.with_priority(Priority::Normal).ocr(image)
+    }
+
+    /**
+     * OCRs the image on the next free engine and parses the response.
+     * See [`Ppocr::ocr_and_parse`](crate::Ppocr::ocr_and_parse).
+     */
+    pub fn ocr_and_parse(&self, image: ImageData) -> Result<Vec<ContentData>, String> {
+        self.with_priority(Priority::Normal).ocr_and_parse(image)
+    }
+
+    /**
+     * OCRs the image on the next free engine, keeping the raw JSON.
+     * See [`Ppocr::ocr_response`](crate::Ppocr::ocr_response).
+     */
+    pub fn ocr_response(&self, image: ImageData) -> Result<OcrResponse, String> {
+        self.with_priority(Priority::Normal).ocr_response(image)
+    }
+
+    /**
+     * OCRs the image on the next free engine and parses the response, with typed errors.
+     * See [`Ppocr::try_ocr`](crate::Ppocr::try_ocr).
+     */
+    pub fn try_ocr(&self, image: ImageData) -> Result<Vec<ContentData>, PpocrError> {
+        self.with_priority(Priority::Normal).try_ocr(image)
+    }
+
+    /**
+     * OCRs and parses all `images` in parallel, with at most `max_in_flight` of them queued or being processed at a
+     * time, so a long or lazy iterator is consumed at the pace of the engines. Results are in the order of `images`.
+     */
+    pub fn ocr_all<I>(
+        &self,
+        images: I,
+        max_in_flight: usize,
+    ) -> Vec<Result<Vec<ContentData>, String>>
+    where
+        I: IntoIterator<Item = ImageData>,
+    {
+        self.with_priority(Priority::Normal)
+            .ocr_all(images, max_in_flight)
+    }
+
+    /**
+     * Like [`ocr`](PpocrPool::ocr), as a future. Requires the `async` feature.
+     */
+    #[cfg(feature = "async")]
+    pub async fn ocr_async(&self, image: ImageData) -> IoResult<String> {
+        self.with_priority(Priority::Normal).ocr_async(image).await
+    }
+
+    /**
+     * Like [`ocr_and_parse`](PpocrPool::ocr_and_parse), as a future. Requires the `async` feature.
+     */
+    #[cfg(feature = "async")]
+    pub async fn ocr_and_parse_async(&self, image: ImageData) -> Result<Vec<ContentData>, String> {
+        self.with_priority(Priority::Normal)
+            .ocr_and_parse_async(image)
+            .await
+    }
+
+    /**
+    Like [`ocr_all`](PpocrPool::ocr_all), as a future: OCRs and parses all `images` in parallel, with at most
+    `max_in_flight` of them queued or being processed at a time. Results are in the order of `images`. Requires the
+    `async` feature.
+
+    Neither waiting for the engines nor for space in a queue full with [`QueueFull::Block`] blocks the executor.
+
+    # Examples
+
+    ```no_run
+    # async fn run() {
+    let pool = paddleocr::PpocrPool::new((0..4).map(|_| paddleocr::Ppocr::new(".../PaddleOCR-json.exe", None).unwrap()));
+    let images = std::fs::read_dir(".../scans").unwrap().map(|entry| entry.unwrap().path().into());
+    for result in pool.ocr_all_async(images, 8).await {
+        println!("{:?}", result);
+    }
+    # }
+    ```
+    */
+    #[cfg(feature = "async")]
+    pub async fn ocr_all_async<I>(
+        &self,
+        images: I,
+        max_in_flight: usize,
+    ) -> Vec<Result<Vec<ContentData>, String>>
+    where
+        I: IntoIterator<Item = ImageData>,
+    {
+        self.with_priority(Priority::Normal)
+            .ocr_all_async(images, max_in_flight)
+            .await
+    }
+
+    /**
+    Drains the pool for a graceful exit, e.g. on `SIGTERM`: new requests fail right away, while queued and running
+    ones get until `deadline` to finish. Returns whether they all did.
+
+    Past the deadline, requests still queued fail as dropped, and the engines of running ones are stopped through
+    their [`terminator`](OcrEngine::terminator), which kills spawned engines, so those requests fail too. Engines
+    without one can't be interrupted; their workers exit as soon as they finish, and dropping the pool no longer
+    waits for them.
+
+    # Examples
+
+    ```no_run
+    use std::time::{Duration, Instant};
+
+    let pool = paddleocr::PpocrPool::new((0..4).map(|_| paddleocr::Ppocr::new(".../PaddleOCR-json.exe", None).unwrap()));
+    // ... serve requests until asked to terminate ...
+    if !pool.shutdown(Instant::now() + Duration::from_secs(10)) {
+        eprintln!("gave up on requests still running");
+    }
+    ```
+    */
+    pub fn shutdown(&self, deadline: Instant) -> bool {
+        self.queue.close();
+        let mut state = self.queue.lock();
+        while !state.jobs.is_empty() || state.running > 0 {
+            let now = Instant::now();
+            if now >= deadline {
+                state.abandoned = true;
+                let late: Vec<_> = state.jobs.drain(..).collect();
+                let terminators: Vec<_> = state.terminators.iter().flatten().cloned().collect();
+                drop(state);
+                drop(late);
+                for terminate in terminators {
+                    terminate();
+                }
+                return false;
+            }
+            state = self
+                .queue
+                .finished
+                .wait_timeout(state, deadline - now)
+                .unwrap_or_else(PoisonError::into_inner)
+                .0;
+        }
+        true
+    }
+}
+
+/**
+ * Requests to a [`PpocrPool`] at a given [`Priority`], from [`PpocrPool::with_priority`].
+ */
+#[derive(Clone, Copy)]
+pub struct Prioritized<'a> {
+    pool: &'a PpocrPool,
+    priority: Priority,
+}
+
+impl Prioritized<'_> {
+    /**
+     * Runs `f` with the next free engine, waiting for its result.
+     */
+    fn call<T, F>(&self, f: F) -> IoResult<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(&mut dyn OcrEngine) -> T + Send + 'static,
+    {
+        #[cfg(feature = "otel")]
+        let f = crate::otel::propagate(f);
+        let (result, receiver) = mpsc::sync_channel(1);
+        self.pool.submit(
+            self.priority,
+            Box::new(move |engine| {
+                result
+                    .send(catch_unwind(AssertUnwindSafe(|| f(engine))))
+                    .ok();
+            }),
+        )?;
+        match receiver.recv() {
+            Ok(Ok(value)) => Ok(value),
+            Ok(Err(_)) => Err(IoError::other("the engine panicked")),
+            // The job was dropped without running.
+            Err(_) => Err(dropped()),
+        }
+    }
+
+    /**
+     * OCRs the image on the next free engine. See [`Ppocr::ocr`](crate::Ppocr::ocr).
+     */
+    pub fn ocr(&self, image: ImageData) -> IoResult<String> {
+        self.call(move |engine| engine.ocr(image))?
+    }
+
+    /**
+     * OCRs the image on the next free engine and parses the response.
+     * See [`Ppocr::ocr_and_parse`](crate::Ppocr::ocr_and_parse).
+     */
+    pub fn ocr_and_parse(&self, image: ImageData) -> Result<Vec<ContentData>, String> {
+        self.call(move |engine| engine.ocr_and_parse(image))
+            .map_err(|e| e.to_string())?
+    }
+
+    /**
+     * OCRs the image on the next free engine, keeping the raw JSON.
+     * See [`Ppocr::ocr_response`](crate::Ppocr::ocr_response).
+     */
+    pub fn ocr_response(&self, image: ImageData) -> Result<OcrResponse, String> {
+        self.call(move |engine| engine.ocr_response(image))
+            .map_err(|e| e.to_string())?
+    }
+
+    /**
+     * OCRs the image on the next free engine and parses the response, with typed errors.
+     * See [`Ppocr::try_ocr`](crate::Ppocr::try_ocr).
+     */
+    pub fn try_ocr(&self, image: ImageData) -> Result<Vec<ContentData>, PpocrError> {
+        self.call(move |engine| engine.try_ocr(image))?
+    }
+
+    /**
+     * OCRs and parses all `images` in parallel, with at most `max_in_flight` of them queued or being processed at a
+     * time, so a long or lazy iterator is consumed at the pace of the engines. Results are in the order of `images`.
+     */
+    pub fn ocr_all<I>(
+        &self,
+        images: I,
+        max_in_flight: usize,
+    ) -> Vec<Result<Vec<ContentData>, String>>
+    where
+        I: IntoIterator<Item = ImageData>,
+    {
+        let (sender, receiver) = mpsc::channel();
+        let mut images = images.into_iter().enumerate();
+        let mut results = Vec::new();
+        let mut in_flight = 0;
+        loop {
+            while in_flight < max_in_flight.max(1) {
+                let Some((i, image)) = images.next() else {
+                    break;
+                };
+                results.push(None);
+                let reply = Reply {
+                    sender: sender.clone(),
+                    index: i,
+                    sent: false,
+                };
+                let submitted = self.pool.submit(
+                    self.priority,
+                    Box::new(move |engine| {
+                        let result = catch_unwind(AssertUnwindSafe(|| engine.ocr_and_parse(image)))
+                            .unwrap_or_else(|_| Err("the engine panicked".to_string()));
+                        reply.send(result);
+                    }),
+                );
+                match submitted {
+                    Ok(()) => in_flight += 1,
+                    Err(e) => results[i] = Some(Err(e.to_string())),
+                }
+            }
+            if in_flight == 0 {
+                break;
+            }
+            let Ok((i, result)) = receiver.recv() else {
+                break;
+            };
+            // A rejected request was never in flight: its reply, dropped with it, is stale, and its error recorded.
+            if results[i].is_some() {
+                continue;
+            }
+            results[i] = Some(result);
+            in_flight -= 1;
+        }
+        results
+            .into_iter()
+            .map(|result| result.unwrap_or_else(|| Err(stopped().to_string())))
+            .collect()
+    }
+}
+
+#[cfg(feature = "async")]
+impl Prioritized<'_> {
+    /**
+     * Runs `f` with the next free engine, resolving to its result.
+     */
+    async fn call_async<T, F>(&self, f: F) -> IoResult<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(&mut dyn OcrEngine) -> T + Send + 'static,
+    {
+        #[cfg(feature = "otel")]
+        let f = crate::otel::propagate(f);
+        let (result, receiver) = futures::channel::oneshot::channel();
+        self.pool
+            .submit_async(
+                self.priority,
+                Box::new(move |engine| {
+                    result
+                        .send(catch_unwind(AssertUnwindSafe(|| f(engine))))
+                        .ok();
+                }),
+            )
+            .await?;
+        match receiver.await {
+            Ok(Ok(value)) => Ok(value),
+            Ok(Err(_)) => Err(IoError::other("the engine panicked")),
+            // The job was dropped without running.
+            Err(_) => Err(dropped()),
+        }
+    }
+
+    /**
+     * Like [`ocr`](Prioritized::ocr), as a future. Requires the `async` feature.
+     */
+    pub async fn ocr_async(&self, image: ImageData) -> IoResult<String> {
+        self.call_async(move |engine| engine.ocr(image)).await?
+    }
+
+    /**
+     * Like [`ocr_and_parse`](Prioritized::ocr_and_parse), as a future. Requires the `async` feature.
+     */
+    pub async fn ocr_and_parse_async(&self, image: ImageData) -> Result<Vec<ContentData>, String> {
+        self.call_async(move |engine| engine.ocr_and_parse(image))
+            .await
+            .map_err(|e| e.to_string())?
+    }
+
+    /**
+     * Like [`PpocrPool::ocr_all_async`], at this priority. Requires the `async` feature.
+     */
+    pub async fn ocr_all_async<I>(
+        &self,
+        images: I,
+        max_in_flight: usize,
+    ) -> Vec<Result<Vec<ContentData>, String>>
+    where
+        I: IntoIterator<Item = ImageData>,
+    {
+        use futures::stream::{self, StreamExt};
+
+        // Each request is submitted when `buffer_unordered` first polls it, so at most `max_in_flight` are.
+        let mut results: Vec<_> = stream::iter(images.into_iter().enumerate())
+            .map(|(i, image)| async move { (i, self.ocr_and_parse_async(image).await) })
+            .buffer_unordered(max_in_flight.max(1))
+            .collect()
+            .await;
+        results.sort_unstable_by_key(|(i, _)| *i);
+        results.into_iter().map(|(_, result)| result).collect()
+    }
+}
+
+impl OcrEngine for Prioritized<'_> {
+    fn ocr(&mut self, image: ImageData) -> IoResult<String> {
+        Prioritized::ocr(self, image)
+    }
+
+    fn ocr_and_parse(&mut self, image: ImageData) -> Result<Vec<ContentData>, String> {
+        Prioritized::ocr_and_parse(self, image)
+    }
+
+    fn ocr_response(&mut self, image: ImageData) -> Result<OcrResponse, String> {
+        Prioritized::ocr_response(self, image)
+    }
+
+    fn try_ocr(&mut self, image: ImageData) -> Result<Vec<ContentData>, PpocrError> {
+        Prioritized::try_ocr(self, image)
+    }
+}
+
+impl OcrEngine for PpocrPool {
+    fn ocr(&mut self, image: ImageData) -> IoResult<String> {
+        PpocrPool::ocr(self, image)
+    }
+
+    fn ocr_and_parse(&mut self, image: ImageData) -> Result<Vec<ContentData>, String> {
+        PpocrPool::ocr_and_parse(self, image)
+    }
+
+    fn ocr_response(&mut self, image: ImageData) -> Result<OcrResponse, String> {
+        PpocrPool::ocr_response(self, image)
+    }
+
+    fn try_ocr(&mut self, image: ImageData) -> Result<Vec<ContentData>, PpocrError> {
+        PpocrPool::try_ocr(self, image)
+    }
+}
+
+impl Drop for PpocrPool {
+    fn drop(&mut self) {
+        self.queue.close();
+        if self.queue.lock().abandoned {
+            return;
+        }
+        for worker in self.workers.drain(..) {
+            worker.join().ok();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::{Arc, Condvar, Mutex};
+    use std::time::{Duration, Instant};
+
+    use super::{PpocrPool, Priority, QueueFull};
+    use crate::{ImageData, OcrEngine};
+
+    /// Answers with the image path as text, after a while; tracks how many requests run at once.
+    struct Slow {
+        running: Arc<AtomicUsize>,
+        most: Arc<AtomicUsize>,
+    }
+    impl OcrEngine for Slow {
+        fn ocr(&mut self, image: ImageData) -> std::io::Result<String> {
+            let ImageData::ImagePathDict { image_path } = image else {
+                unreachable!()
+            };
+            assert_ne!(image_path, "panic.png");
+            let running = self.running.fetch_add(1, Ordering::SeqCst) + 1;
+            self.most.fetch_max(running, Ordering::SeqCst);
+            std::thread::sleep(Duration::from_millis(5));
+            self.running.fetch_sub(1, Ordering::SeqCst);
+            Ok(format!(
+                r#"{{"code":100,"data":[{{"box":[[0,0],[1,0],[1,1],[0,1]],"score":1,"text":"{}"}}]}}"#,
+                image_path
+            ))
+        }
+    }
+
+    fn pool(size: usize) -> (PpocrPool, Arc<AtomicUsize>) {
+        let (running, most) = (Arc::default(), Arc::new(AtomicUsize::new(0)));
+        let engines = (0..size).map(|_| Slow {
+            running: Arc::clone(&running),
+            most: Arc::clone(&most),
+        });
+        (PpocrPool::new(engines), most)
+    }
+
+    #[test]
+    fn parallel() {
+        let (pool, most) = pool(3);
+        assert_eq!(pool.size(), 3);
+        let images = ["a", "b", "panic.png", "c", "d", "e"].map(ImageData::from_path);
+        let results = pool.ocr_all(images, 2);
+        let texts: Vec<_> = results
+            .into_iter()
+            .map(|r| r.map(|data| data[0].text.clone()))
+            .collect();
+        assert_eq!(texts[..2], [Ok("a".to_string()), Ok("b".to_string())]);
+        assert_eq!(texts[2], Err("the engine panicked".to_string()));
+        assert_eq!(texts[5], Ok("e".to_string()));
+        assert!(most.load(Ordering::SeqCst) <= 2);
+
+        // Still serving after the panic.
+        assert!(pool.ocr(ImageData::from_path("f")).is_ok());
+        assert!(PpocrPool::new(Vec::<Slow>::new())
+            .ocr(ImageData::from_path("a"))
+            .is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "async")]
+    fn parallel_async() {
+        let (pool, most) = pool(3);
+        let images = ["a", "b", "panic.png", "c", "d", "e"].map(ImageData::from_path);
+        let future = pool.ocr_all_async(images, 2);
+        // Can be spawned on multi-threaded executors.
+        fn assert_send<T: Send>(_: &T) {}
+        assert_send(&future);
+        let results = futures::executor::block_on(future);
+        let texts: Vec<_> = results
+            .into_iter()
+            .map(|r| r.map(|data| data[0].text.clone()))
+            .collect();
+        assert_eq!(texts[..2], [Ok("a".to_string()), Ok("b".to_string())]);
+        assert_eq!(texts[2], Err("the engine panicked".to_string()));
+        assert_eq!(texts[3..], ["c", "d", "e"].map(|t| Ok(t.to_string())));
+        assert!(most.load(Ordering::SeqCst) <= 2);
+
+        let one = futures::executor::block_on(pool.ocr_async(ImageData::from_path("f")));
+        assert!(one.unwrap().contains(r#""text":"f""#));
+        let pool = PpocrPool::new(Vec::<Slow>::new());
+        let stopped =
+            futures::executor::block_on(pool.ocr_and_parse_async(ImageData::from_path("a")));
+        assert!(stopped.is_err());
+    }
+
+    /// Records the image paths it is sent, blocking until `open` is set, so requests pile up in the queue.
+    #[derive(Clone, Default)]
+    struct Gate {
+        open: Arc<(Mutex<bool>, Condvar)>,
+        seen: Arc<Mutex<Vec<String>>>,
+    }
+    impl Gate {
+        fn seen(&self) -> Vec<String> {
+            self.seen.lock().unwrap().clone()
+        }
+        fn wait_seen(&self, count: usize) {
+            while self.seen().len() < count {
+                std::thread::sleep(Duration::from_millis(1));
+            }
+        }
+        fn release(&self) {
+            *self.open.0.lock().unwrap() = true;
+            self.open.1.notify_all();
+        }
+    }
+    impl OcrEngine for Gate {
+        fn ocr(&mut self, image: ImageData) -> std::io::Result<String> {
+            let ImageData::ImagePathDict { image_path } = image else {
+                unreachable!()
+            };
+            self.seen.lock().unwrap().push(image_path);
+            let (open, opened) = &*self.open;
+            let mut open = open.lock().unwrap();
+            while !*open {
+                open = opened.wait(open).unwrap();
+            }
+            if self.seen().contains(&"terminated".to_string()) {
+                return Err(std::io::ErrorKind::BrokenPipe.into());
+            }
+            Ok(r#"{"code":101,"data":"No text found in image."}"#.to_string())
+        }
+        fn terminator(&self) -> Option<crate::Terminator> {
+            let gate = self.clone();
+            Some(Arc::new(move || {
+                gate.seen.lock().unwrap().push("terminated".to_string());
+                gate.release();
+            }))
+        }
+    }
+
+    /// Sends a request from another thread.
+    fn request(
+        pool: &Arc<PpocrPool>,
+        priority: Priority,
+        name: &str,
+    ) -> std::thread::JoinHandle<std::io::Result<String>> {
+        let (pool, name) = (pool.clone(), name.to_string());
+        std::thread::spawn(move || pool.with_priority(priority).ocr(ImageData::from_path(name)))
+    }
+
+    fn wait_queued(pool: &PpocrPool, queued: usize) {
+        while pool.queue.lock().jobs.len() != queued {
+            std::thread::sleep(Duration::from_millis(1));
+        }
+    }
+
+    #[test]
+    fn full_queue() {
+        let gate = Gate::default();
+        let pool = Arc::new(PpocrPool::new([gate.clone()]).queue_capacity(1, QueueFull::Error));
+        // One request is taken by the engine, the next one waits in the queue.
+        let running = request(&pool, Priority::Normal, "running");
+        gate.wait_seen(1);
+        let queued = request(&pool, Priority::Normal, "queued");
+        wait_queued(&pool, 1);
+        let e = pool.ocr(ImageData::from_path("b.png")).unwrap_err();
+        assert_eq!(e.kind(), std::io::ErrorKind::WouldBlock);
+
+        pool.queue.lock().capacity = Some((1, QueueFull::DropOldest));
+        let newest = request(&pool, Priority::Normal, "newest");
+        let dropped = queued.join().unwrap().unwrap_err();
+        assert_eq!(
+            dropped.to_string(),
+            "the request was dropped from the full queue"
+        );
+
+        gate.release();
+        assert!(running.join().unwrap().is_ok());
+        assert!(newest.join().unwrap().is_ok());
+        assert_eq!(gate.seen(), ["running", "newest"]);
+    }
+
+    #[test]
+    fn full_queue_all() {
+        let gate = Gate::default();
+        let pool = Arc::new(PpocrPool::new([gate.clone()]).queue_capacity(1, QueueFull::Error));
+        let running = request(&pool, Priority::Normal, "running");
+        gate.wait_seen(1);
+        let all = {
+            let pool = pool.clone();
+            let images = (0..4).map(|i| ImageData::from_path(i.to_string()));
+            std::thread::spawn(move || pool.ocr_all(images, 4))
+        };
+        wait_queued(&pool, 1);
+
+        gate.release();
+        let results = all.join().unwrap();
+        assert!(running.join().unwrap().is_ok());
+        // The engine's own answer, not a stale or lost one.
+        assert_eq!(
+            results[0].as_ref().unwrap_err(),
+            "Error Message 101: No text found in image."
+        );
+        for result in &results[1..] {
+            assert_eq!(result.as_ref().unwrap_err(), "the OCR queue is full");
+        }
+        assert_eq!(gate.seen(), ["running", "0"]);
+    }
+
+    #[test]
+    #[cfg(feature = "async")]
+    fn full_queue_async() {
+        use futures::FutureExt;
+
+        let gate = Gate::default();
+        let pool = Arc::new(PpocrPool::new([gate.clone()]).queue_capacity(1, QueueFull::Block));
+        let running = request(&pool, Priority::Normal, "running");
+        gate.wait_seen(1);
+        let queued = request(&pool, Priority::Normal, "queued");
+        wait_queued(&pool, 1);
+        // Waiting for space leaves the thread free.
+        let mut waiting = Box::pin(pool.ocr_async(ImageData::from_path("waiting")));
+        assert!((&mut waiting).now_or_never().is_none());
+        assert_eq!(pool.queue.lock().wakers.len(), 1);
+
+        gate.release();
+        assert!(futures::executor::block_on(waiting).is_ok());
+        assert!(running.join().unwrap().is_ok());
+        assert!(queued.join().unwrap().is_ok());
+        assert_eq!(gate.seen(), ["running", "queued", "waiting"]);
+    }
+
+    #[test]
+    fn priorities() {
+        let gate = Gate::default();
+        let pool = Arc::new(PpocrPool::new([gate.clone()]));
+        let first = request(&pool, Priority::Normal, "first");
+        gate.wait_seen(1);
+        let mut queued = Vec::new();
+        for (priority, name) in [
+            (Priority::Background, "background"),
+            (Priority::Normal, "normal"),
+            (Priority::Interactive, "interactive"),
+        ] {
+            queued.push(request(&pool, priority, name));
+            wait_queued(&pool, queued.len());
+        }
+
+        // A full queue makes room by dropping the least urgent request, unless the new one is less urgent still.
+        pool.queue.lock().capacity = Some((3, QueueFull::DropOldest));
+        let urgent = request(&pool, Priority::Interactive, "urgent");
+        assert!(queued.remove(0).join().unwrap().is_err());
+        wait_queued(&pool, 3);
+        let e = pool
+            .with_priority(Priority::Background)
+            .ocr(ImageData::from_path("late"))
+            .unwrap_err();
+        assert_eq!(e.to_string(), "the request was dropped from the full queue");
+
+        gate.release();
+        for thread in [first, urgent].into_iter().chain(queued) {
+            assert!(thread.join().unwrap().is_ok());
+        }
+        assert_eq!(gate.seen(), ["first", "interactive", "urgent", "normal"]);
+    }
+
+    #[test]
+    fn shutdown() {
+        let gate = Gate::default();
+        let pool = Arc::new(PpocrPool::new([gate.clone()]));
+        let running = request(&pool, Priority::Normal, "running");
+        gate.wait_seen(1);
+        let queued = request(&pool, Priority::Normal, "queued");
+        wait_queued(&pool, 1);
+        let opener = gate.clone();
+        std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(20));
+            opener.release();
+        });
+        // Queued requests still run.
+        assert!(pool.shutdown(Instant::now() + Duration::from_secs(10)));
+        assert!(running.join().unwrap().is_ok());
+        assert!(queued.join().unwrap().is_ok());
+        assert_eq!(gate.seen(), ["running", "queued"]);
+        let e = pool.ocr(ImageData::from_path("late")).unwrap_err();
+        assert_eq!(e.kind(), std::io::ErrorKind::BrokenPipe);
+
+        // An engine that doesn't finish in time is terminated.
+        let gate = Gate::default();
+        let pool = Arc::new(PpocrPool::new([gate.clone()]));
+        let running = request(&pool, Priority::Normal, "running");
+        gate.wait_seen(1);
+        let queued = request(&pool, Priority::Normal, "queued");
+        wait_queued(&pool, 1);
+        assert!(!pool.shutdown(Instant::now() + Duration::from_millis(20)));
+        assert!(queued.join().unwrap().is_err());
+        assert!(running.join().unwrap().is_err());
+        assert_eq!(gate.seen(), ["running", "terminated"]);
+    }
+}
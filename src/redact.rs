@@ -0,0 +1,328 @@
+//! Hiding sensitive text in images, for privacy-safe screenshots. Images are [`Frame`]s, or `image::DynamicImage`s
+//! with the `image` feature.
+
+use crate::frame::{Frame, Image};
+use crate::geometry::{Aabb, RectangleExt};
+use crate::words::contains;
+use crate::{ContentData, Rectangle};
+
+/**
+ * How [`redact`] hides a detection.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Redaction {
+    /// Paints the box in a color, as `[r, g, b]`.
+    Fill([u8; 3]),
+    /// Blurs the box, averaging each pixel with those up to `radius` pixels away. Small radii may leave large text
+    /// legible.
+    Blur { radius: usize },
+}
+
+impl Default for Redaction {
+    /**
+     * Black boxes.
+     */
+    fn default() -> Self {
+        Redaction::Fill([0, 0, 0])
+    }
+}
+
+/**
+ * Which detections [`redact`] hides: a predicate on their text, such as [`contains_email`], or with the `regex`
+ * feature a `regex::Regex` finding a match in it.
+ */
+pub trait Matcher {
+    fn matches(&self, text: &str) -> bool;
+}
+
+impl<F: Fn(&str) -> bool> Matcher for F {
+    fn matches(&self, text: &str) -> bool {
+        self(text)
+    }
+}
+
+#[cfg(feature = "regex")]
+impl Matcher for regex::Regex {
+    fn matches(&self, text: &str) -> bool {
+        self.is_match(text)
+    }
+}
+
+#[cfg(feature = "regex")]
+impl Matcher for &regex::Regex {
+    fn matches(&self, text: &str) -> bool {
+        self.is_match(text)
+    }
+}
+
+/**
+Returns a copy of `image` with the boxes of the detections whose text `matcher` accepts filled or blurred, e.g. to
+hide e-mail addresses before sharing a screenshot. Boxes are grown by 2 pixels, so that no edge of the glyphs shows.
+
+# Examples
+
+```no_run
+use paddleocr::redact::{contains_email, contains_phone_number, redact, Redaction};
+use paddleocr::frame::{Frame, PixelFormat};
+use paddleocr::OcrEngine;
+
+let (width, height, bgra) = capture_screen();
+let frame = Frame::new(width, height, PixelFormat::Bgra, bgra).unwrap();
+let mut p = paddleocr::Ppocr::new(".../PaddleOCR-json.exe", None).unwrap();
+let results = p.ocr_and_parse(paddleocr::ImageData::from_path("screen.bmp")).unwrap();
+let sensitive = |text: &str| contains_email(text) || contains_phone_number(text);
+let safe = redact(&frame, &results, sensitive, Redaction::default());
+std::fs::write("safe.bmp", safe.encode_bmp(safe.bounds())).unwrap();
+```
+
+With the `image` and `regex` features, a decoded screenshot can be redacted where a pattern matches:
+
+```no_run
+use paddleocr::redact::{redact, Redaction};
+use paddleocr::OcrEngine;
+
+let screenshot = image::open("screen.png").unwrap();
+let mut p = paddleocr::Ppocr::new(".../PaddleOCR-json.exe", None).unwrap();
+let results = p.ocr_and_parse(paddleocr::ImageData::from_path("screen.png")).unwrap();
+let card_number = regex::Regex::new(r"\b(?:\d[ -]?){13,16}\b").unwrap();
+let safe = redact(&screenshot, &results, &card_number, Redaction::Blur { radius: 8 });
+safe.save("safe.png").unwrap();
+```
+*/
+pub fn redact<I: Image, M: Matcher>(
+    image: &I,
+    results: &[ContentData],
+    matcher: M,
+    redaction: Redaction,
+) -> I {
+    let frame = image.to_frame();
+    let mut redacted = Frame::clone(&frame);
+    for content in results.iter().filter(|c| matcher.matches(&c.text)) {
+        let rect = content.rect.expand(2);
+        let Some(bounds) = rect.aabb().intersection(&frame.bounds()) else {
+            continue;
+        };
+        match redaction {
+            Redaction::Fill([r, g, b]) => {
+                let pixel = match frame.format {
+                    crate::frame::PixelFormat::Rgba => [r, g, b, 255],
+                    crate::frame::PixelFormat::Bgra => [b, g, r, 255],
+                };
+                paint(&mut redacted, &rect, bounds, |_, _| pixel);
+            }
+            Redaction::Blur { radius } => {
+                let blurred = blur(&frame, bounds, radius);
+                paint(&mut redacted, &rect, bounds, |x, y| {
+                    let i = ((y - bounds.y) * bounds.w + x - bounds.x) * 4;
+                    blurred[i..i + 4].try_into().unwrap_or_default()
+                });
+            }
+        }
+    }
+    I::from_frame(redacted)
+}
+
+/// Sets the pixels of `frame` within the quadrilateral `rect`, whose bounds in the frame are `bounds`.
+fn paint<F: Fn(usize, usize) -> [u8; 4]>(
+    frame: &mut Frame,
+    rect: &Rectangle,
+    bounds: Aabb,
+    pixel: F,
+) {
+    for y in bounds.y..bounds.bottom() {
+        for x in bounds.x..bounds.right() {
+            if contains(rect, [x, y]) {
+                let i = (y * frame.width + x) * 4;
+                frame.data[i..i + 4].copy_from_slice(&pixel(x, y));
+            }
+        }
+    }
+}
+
+/**
+ * The `region` of `frame` blurred by two passes of a box filter, which is close to a Gaussian blur. Pixels outside
+ * the region count too, so the blur blends into its surroundings.
+ */
+fn blur(frame: &Frame, region: Aabb, radius: usize) -> Vec<u8> {
+    // The region and its surroundings, as far as they affect it.
+    let around = Aabb::from_edges(
+        region.x.saturating_sub(2 * radius),
+        region.y.saturating_sub(2 * radius),
+        (region.right() + 2 * radius).min(frame.width),
+        (region.bottom() + 2 * radius).min(frame.height),
+    );
+    let mut pixels: Vec<f64> = Vec::with_capacity(around.area() * 4);
+    for y in around.y..around.bottom() {
+        let start = (y * frame.width + around.x) * 4;
+        pixels.extend(
+            frame.data[start..start + around.w * 4]
+                .iter()
+                .map(|&v| v as f64),
+        );
+    }
+    for _ in 0..2 {
+        pixels = box_filter(&pixels, around.w, around.h, radius, true);
+        pixels = box_filter(&pixels, around.w, around.h, radius, false);
+    }
+    let mut blurred = Vec::with_capacity(region.area() * 4);
+    for y in region.y..region.bottom() {
+        let start = ((y - around.y) * around.w + region.x - around.x) * 4;
+        blurred.extend(
+            pixels[start..start + region.w * 4]
+                .iter()
+                .map(|&v| v.round() as u8),
+        );
+    }
+    blurred
+}
+
+/// Averages each pixel of a `width`×`height` image with its neighbors up to `radius` away along rows or columns.
+fn box_filter(pixels: &[f64], width: usize, height: usize, radius: usize, rows: bool) -> Vec<f64> {
+    let mut filtered = vec![0.0; pixels.len()];
+    let (lines, length) = if rows {
+        (height, width)
+    } else {
+        (width, height)
+    };
+    let index = |line: usize, at: usize| if rows { line * width + at } else { at * width + line } * 4;
+    for line in 0..lines {
+        for at in 0..length {
+            let (from, to) = (at.saturating_sub(radius), (at + radius).min(length - 1));
+            for channel in 0..4 {
+                let sum: f64 = (from..=to).map(|k| pixels[index(line, k) + channel]).sum();
+                filtered[index(line, at) + channel] = sum / (to - from + 1) as f64;
+            }
+        }
+    }
+    filtered
+}
+
+/**
+ * Whether `text` holds something like an e-mail address: a word of the form `name@domain.tld`.
+ */
+pub fn contains_email(text: &str) -> bool {
+    let is_word = |c: char| c.is_alphanumeric() || "._%+-@".contains(c);
+    text.split(|c: char| !is_word(c)).any(|word| {
+        let Some((name, domain)) = word.split_once('@') else {
+            return false;
+        };
+        let domain = domain.trim_end_matches('.');
+        !name.is_empty()
+            && !domain.contains('@')
+            && domain.rsplit_once('.').is_some_and(|(host, tld)| {
+                !host.is_empty() && tld.len() >= 2 && tld.chars().all(char::is_alphabetic)
+            })
+    })
+}
+
+/**
+ * Whether `text` holds something like a phone number: at least 7 digits, possibly after a `+`, grouped with spaces,
+ * dashes, dots or parentheses. Dates written with dashes, such as `2024-01-15`, match too.
+ */
+pub fn contains_phone_number(text: &str) -> bool {
+    let mut digits = 0;
+    for c in text.chars() {
+        match c {
+            '0'..='9' | '０'..='９' => {
+                digits += 1;
+                if digits >= 7 {
+                    return true;
+                }
+            }
+            ' ' | '-' | '.' | '(' | ')' | '+' if digits > 0 || c == '+' || c == '(' => {}
+            _ => digits = 0,
+        }
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{contains_email, contains_phone_number, redact, Redaction};
+    use crate::frame::{Frame, PixelFormat};
+    use crate::{Confidence, ContentData};
+
+    #[test]
+    fn matchers() {
+        assert!(contains_email("Contact: jane.doe+ocr@example.co.uk."));
+        assert!(!contains_email("@handle and a@b"));
+        assert!(!contains_email("user@localhost"));
+        assert!(contains_phone_number("Tel. +1 (555) 123-4567"));
+        assert!(contains_phone_number("電話 ０３１２３４５６７８"));
+        assert!(!contains_phone_number("Invoice 2024-01, 12 items"));
+    }
+
+    #[test]
+    fn redaction() {
+        // White, with a black and white stripe pattern in the middle.
+        let (width, height) = (20, 10);
+        let mut data = vec![255; width * height * 4];
+        for y in 3..7 {
+            for x in (4..16).step_by(2) {
+                data[(y * width + x) * 4..][..3].copy_from_slice(&[0, 0, 0]);
+            }
+        }
+        let frame = Frame::new(width, height, PixelFormat::Bgra, data).unwrap();
+        let item = |text: &str, rect| ContentData {
+            rect,
+            score: Confidence(0.9),
+            text: text.to_string(),
+            orientation: None,
+            extra: Default::default(),
+        };
+        let results = [
+            item("a@example.com", [[4, 3], [15, 3], [15, 6], [4, 6]]),
+            item("hello", [[0, 0], [1, 0], [1, 1], [0, 1]]),
+        ];
+        let pixel =
+            |frame: &Frame, x: usize, y: usize| frame.data[(y * width + x) * 4..][..4].to_vec();
+
+        let filled = redact(
+            &frame,
+            &results,
+            contains_email,
+            Redaction::Fill([255, 0, 0]),
+        );
+        assert_eq!(pixel(&filled, 5, 4), [0, 0, 255, 255]);
+        assert_eq!(pixel(&filled, 2, 1), [0, 0, 255, 255]);
+        assert_eq!(pixel(&filled, 0, 0), [255, 255, 255, 255]);
+        assert_eq!(pixel(&filled, 19, 9), [255, 255, 255, 255]);
+
+        let blurred = redact(
+            &frame,
+            &results,
+            contains_email,
+            Redaction::Blur { radius: 2 },
+        );
+        // The stripes are smoothed out into gray.
+        let (a, b) = (pixel(&blurred, 8, 4)[0], pixel(&blurred, 9, 4)[0]);
+        assert!(a.abs_diff(b) < 40 && a > 60 && a < 200, "{} {}", a, b);
+        assert_eq!(pixel(&blurred, 0, 0), [255, 255, 255, 255]);
+    }
+
+    #[test]
+    #[cfg(all(feature = "image", feature = "regex"))]
+    fn images_and_patterns() {
+        use image::{DynamicImage, Rgb, RgbImage, Rgba};
+        use regex::Regex;
+
+        let image = DynamicImage::ImageRgb8(RgbImage::from_pixel(20, 10, Rgb([255; 3])));
+        let item = |text: &str, rect| ContentData {
+            rect,
+            score: Confidence(0.9),
+            text: text.to_string(),
+            orientation: None,
+            extra: Default::default(),
+        };
+        let results = [
+            item("Order #12345", [[4, 3], [15, 3], [15, 6], [4, 6]]),
+            item("Thanks", [[0, 0], [1, 0], [1, 1], [0, 1]]),
+        ];
+        let order = Regex::new(r"#\d+").unwrap();
+        let redacted = redact(&image, &results, &order, Redaction::default()).to_rgba8();
+        assert_eq!(*redacted.get_pixel(8, 4), Rgba([0, 0, 0, 255]));
+        assert_eq!(*redacted.get_pixel(0, 0), Rgba([255, 255, 255, 255]));
+        let everything = redact(&image, &results, |_: &str| true, Redaction::default());
+        assert_eq!(*everything.to_rgba8().get_pixel(0, 0), Rgba([0, 0, 0, 255]));
+    }
+}
@@ -0,0 +1,284 @@
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{ClipboardError, ContentData, OcrRec, PpocrError, ProtocolError};
+
+/**
+ * A parsed engine response that keeps the exact JSON it was parsed from, so it can be logged or forwarded as is.
+ */
+#[derive(Serialize, Debug, Clone)]
+#[cfg_attr(
+    feature = "schemars",
+    derive(schemars::JsonSchema),
+    schemars(description = "The engine's answer to one image.")
+)]
+pub struct OcrResponse {
+    /// The response line as received from the engine, without its line terminator.
+    #[serde(skip)]
+    pub raw: String,
+    /// The engine's status code: `100` if text was found, `101` if none was, anything else on failure.
+    pub code: u32,
+    /// The detections; empty unless `code` is `100`.
+    pub data: Vec<ContentData>,
+    /// The engine's message for responses without detections.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "schemars", schemars(with = "String"))]
+    pub message: Option<String>,
+}
+
+impl OcrResponse {
+    /**
+     * Parses a response line, keeping it as `raw`.
+     */
+    pub fn parse<S: Into<String>>(raw: S) -> Result<OcrResponse, serde_json::Error> {
+        let mut raw = raw.into();
+        raw.truncate(raw.trim_end_matches(['\r', '\n']).len());
+        let rec = serde_json::from_str::<OcrRec>(&raw)?;
+        Ok(OcrResponse::from_rec(raw, rec))
+    }
+
+    fn from_rec(raw: String, rec: OcrRec) -> OcrResponse {
+        let (code, data, message) = match rec {
+            OcrRec::Content { code, data } => (code, data, None),
+            OcrRec::Message { code, data } => (code, Vec::new(), Some(data)),
+        };
+        OcrResponse {
+            raw,
+            code,
+            data,
+            message,
+        }
+    }
+
+    /**
+     * Why the clipboard couldn't be OCRed, for codes 210 to 217.
+     */
+    pub fn clipboard_error(&self) -> Option<ClipboardError> {
+        ClipboardError::from_code(self.code)
+    }
+
+    /**
+     * Why the engine couldn't make sense of the request, for codes 400 to 403.
+     */
+    pub fn protocol_error(&self) -> Option<ProtocolError> {
+        ProtocolError::from_response(self.code, self.message.as_deref().unwrap_or_default())
+    }
+
+    /**
+     * The detections, or the engine's message as an error if there are none — the same as
+     * [`Ppocr::ocr_and_parse`](crate::Ppocr::ocr_and_parse).
+     */
+    pub fn into_result(self) -> Result<Vec<ContentData>, String> {
+        self.try_into_result().map_err(|e| e.to_string())
+    }
+
+    /**
+     * Like [`OcrResponse::into_result`], with the engine's code and response in the error.
+     */
+    pub fn try_into_result(self) -> Result<Vec<ContentData>, PpocrError> {
+        match self.message {
+            None => Ok(self.data),
+            Some(message) => Err(PpocrError::from_response(self.code, message, self.raw)),
+        }
+    }
+}
+
+/**
+ * Parses a response from anywhere, e.g. a log, saying where it departs from the engine's schema when it does.
+ */
+impl FromStr for OcrResponse {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let raw = s.trim_end_matches(['\r', '\n']);
+        Ok(OcrResponse::from_rec(raw.to_string(), raw.parse()?))
+    }
+}
+
+impl FromStr for OcrRec {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let value: Value = serde_json::from_str(s).map_err(|e| format!("invalid JSON: {}", e))?;
+        let Value::Object(object) = value else {
+            return Err(format!(
+                "expected an object with `code` and `data`, found {}",
+                kind(&value)
+            ));
+        };
+        let code = match object.get("code") {
+            Some(code) => code
+                .as_u64()
+                .and_then(|code| u32::try_from(code).ok())
+                .ok_or_else(|| format!("`code` must be a non-negative integer, found {}", code))?,
+            None => return Err("missing `code`".to_string()),
+        };
+        match object.get("data") {
+            Some(Value::String(data)) => Ok(OcrRec::Message {
+                code,
+                data: data.clone(),
+            }),
+            Some(Value::Array(items)) => {
+                let data = items
+                    .iter()
+                    .enumerate()
+                    .map(|(i, item)| {
+                        ContentData::deserialize(item).map_err(|e| format!("`data[{}]`: {}", i, e))
+                    })
+                    .collect::<Result<_, _>>()?;
+                Ok(OcrRec::Content { code, data })
+            }
+            Some(data) => Err(format!(
+                "`data` must be a message or a list of detections, found {}",
+                kind(data)
+            )),
+            None => Err("missing `data`".to_string()),
+        }
+    }
+}
+
+fn kind(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "a boolean",
+        Value::Number(_) => "a number",
+        Value::String(_) => "a string",
+        Value::Array(_) => "an array",
+        Value::Object(_) => "an object",
+    }
+}
+
+impl fmt::Display for OcrResponse {
+    /**
+     * The recognized texts, one per line, or the code and message if nothing was recognized.
+     */
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.message {
+            Some(message) if self.data.is_empty() => write!(f, "{}: {}", self.code, message),
+            _ => {
+                for (i, content) in self.data.iter().enumerate() {
+                    if i > 0 {
+                        writeln!(f)?;
+                    }
+                    f.write_str(&content.text)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::OcrResponse;
+    use crate::{OcrRec, Orientation};
+
+    #[test]
+    fn display() {
+        let response = OcrResponse::parse(
+            r#"{"code":100,"data":[{"box":[[13,5],[161,5],[161,27],[13,27]],"score":0.9996442794799805,"text":"飞舞的因果交流"},{"box":[[13,30],[60,32],[60,50],[12,48]],"score":0.5,"text":"b"}]}"#,
+        )
+        .unwrap();
+        assert_eq!(response.to_string(), "飞舞的因果交流\nb");
+        assert_eq!(
+            response.data[0].to_string(),
+            r#""飞舞的因果交流" (0.9996) @ (13,5)-(161,27)"#
+        );
+        assert_eq!(
+            response.data[1].to_string(),
+            r#""b" (0.5000) @ (12,30)-(60,50)"#
+        );
+
+        let response =
+            OcrResponse::parse(r#"{"code":101,"data":"No text found in image."}"#).unwrap();
+        assert_eq!(response.to_string(), "101: No text found in image.");
+    }
+
+    #[test]
+    fn orientation() {
+        let response = OcrResponse::parse(
+            r#"{"code":100,"data":[{"box":[[0,0],[9,0],[9,9],[0,9]],"score":1,"text":"a","cls_label":1},{"box":[[0,0],[10,0],[10,40],[0,40]],"score":1,"text":"縦書き"}]}"#,
+        )
+        .unwrap();
+        assert_eq!(response.data[0].orientation, Some(Orientation::Rotated180));
+        assert_eq!(response.data[1].orientation, None);
+        assert!(!response.data[0].is_vertical() && response.data[1].is_vertical());
+        assert!(serde_json::to_string(&response.data[0])
+            .unwrap()
+            .contains(r#""cls_label":1"#));
+        // Unknown labels, and the -1 of lines the classifier didn't run on, don't fail the response.
+        for label in ["7", "-1", "256", "\"1\""] {
+            let response = OcrResponse::parse(format!(
+                r#"{{"code":100,"data":[{{"box":[[0,0],[9,0],[9,9],[0,9]],"score":1,"text":"a","cls_label":{}}}]}}"#,
+                label
+            ))
+            .unwrap();
+            assert_eq!(response.data[0].orientation, None);
+        }
+    }
+
+    #[test]
+    fn extra_fields() {
+        let response = OcrResponse::parse(
+            r#"{"code":100,"data":[{"box":[[0,0],[9,0],[9,9],[0,9]],"score":1,"text":"ab","angle":1.5,"words":[{"text":"a"}]}]}"#,
+        )
+        .unwrap();
+        let item = &response.data[0];
+        assert_eq!(item.extra.len(), 2);
+        assert_eq!(item.extra_field::<f64>("angle"), Some(1.5));
+        assert_eq!(item.extra_field::<String>("angle"), None);
+        assert_eq!(item.extra_field::<f64>("missing"), None);
+        let json = serde_json::to_string(item).unwrap();
+        assert!(json.contains(r#""angle":1.5"#) && json.contains(r#""words":[{"text":"a"}]"#));
+        assert!(!json.contains("extra"));
+    }
+
+    #[test]
+    fn keeps_raw() {
+        let line = "{\"code\":100,\"data\":[{\"box\":[[0,0],[1,0],[1,1],[0,1]],\"score\":0.5,\"text\":\"a\"}], \"extra\": 1}\r\n";
+        let response = OcrResponse::parse(line).unwrap();
+        assert_eq!(response.raw, line.trim_end());
+        assert_eq!((response.code, response.data.len()), (100, 1));
+        assert_eq!(response.into_result().unwrap()[0].text, "a");
+
+        let response =
+            OcrResponse::parse(r#"{"code":101,"data":"No text found in image."}"#).unwrap();
+        assert!(response.data.is_empty());
+        assert_eq!(
+            response.into_result().unwrap_err(),
+            "Error Message 101: No text found in image."
+        );
+        assert!(OcrResponse::parse("nope").is_err());
+    }
+
+    #[test]
+    fn from_str() {
+        let response: OcrResponse =
+            r#"{"code":101,"data":"No text found in image."}"#.parse().unwrap();
+        assert_eq!(response.message.as_deref(), Some("No text found in image."));
+        let rec: OcrRec =
+            r#"{"code":100,"data":[{"box":[[0,0],[1,0],[1,1],[0,1]],"score":0.5,"text":"a"}]}"#
+                .parse()
+                .unwrap();
+        assert!(matches!(rec, OcrRec::Content { code: 100, data } if data[0].text == "a"));
+
+        let error = |s: &str| s.parse::<OcrResponse>().unwrap_err();
+        assert!(error("{").starts_with("invalid JSON"));
+        assert_eq!(
+            error("[]"),
+            "expected an object with `code` and `data`, found an array"
+        );
+        assert_eq!(error(r#"{"data":"x"}"#), "missing `code`");
+        assert_eq!(
+            error(r#"{"code":100,"data":7}"#),
+            "`data` must be a message or a list of detections, found a number"
+        );
+        assert_eq!(
+            error(r#"{"code":100,"data":[{"box":[[0,0],[1,0],[1,1],[0,1]],"text":"a"}]}"#),
+            "`data[0]`: missing field `score`"
+        );
+    }
+}
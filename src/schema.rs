@@ -0,0 +1,108 @@
+//! JSON Schemas (draft 2020-12) of the serialized results, for services that forward them over their own APIs and
+//! want to publish or validate what they send. Enabled with the `schemars` feature, which also implements
+//! [`JsonSchema`] for [`OcrResponse`] and [`ContentData`], so they can be part of larger derived schemas.
+//!
+//! The schemas describe results as this crate serializes them: `cls_label` and `message` may be missing, and fields
+//! of [`ContentData::extra`] appear alongside the known ones.
+
+use schemars::generate::SchemaSettings;
+use schemars::{JsonSchema, Schema};
+
+use crate::export::JsonlRecord;
+use crate::{ContentData, OcrResponse};
+
+fn schema_for<T: JsonSchema + ?Sized>() -> Schema {
+    SchemaSettings::draft2020_12()
+        .for_serialize()
+        .into_generator()
+        .into_root_schema_for::<T>()
+}
+
+/**
+ * The schema of a serialized [`ContentData`].
+ */
+pub fn content_data() -> Schema {
+    schema_for::<ContentData>()
+}
+
+/**
+ * The schema of a serialized [`OcrResponse`].
+ */
+pub fn ocr_response() -> Schema {
+    schema_for::<OcrResponse>()
+}
+
+/**
+ * The schema of each line written by [`export::to_jsonl`](crate::export::to_jsonl).
+ */
+pub fn jsonl_record() -> Schema {
+    schema_for::<JsonlRecord>()
+}
+
+#[cfg(test)]
+mod tests {
+    use schemars::Schema;
+    use serde_json::{json, Value};
+
+    use crate::OcrResponse;
+
+    /// Whether `value` is valid against `schema`, printing why not.
+    fn valid(schema: &Schema, value: &Value) -> bool {
+        let validator = jsonschema::draft202012::new(schema.as_value()).unwrap();
+        validator
+            .iter_errors(value)
+            .map(|error| eprintln!("{}: {}", error.instance_path, error))
+            .count()
+            == 0
+    }
+
+    #[test]
+    fn schemas() {
+        let response = OcrResponse::parse(concat!(
+            r#"{"code":100,"data":[{"box":[[0,0],[9,0],[9,9],[0,9]],"#,
+            r#""score":0.9,"text":"a","cls_label":1,"cls_score":0.98,"rec_model":"v5"},"#,
+            r#"{"box":[[10,0],[19,0],[19,9],[10,9]],"score":0.5,"text":"b"}]}"#
+        ))
+        .unwrap();
+        assert_eq!(response.data[0].extra.len(), 2);
+        let schema = super::ocr_response();
+        assert_eq!(schema.get("title"), Some(&json!("OcrResponse")));
+        let value = serde_json::to_value(&response).unwrap();
+        assert!(valid(&schema, &value));
+        assert!(valid(&super::content_data(), &value["data"][0]));
+        assert!(valid(&super::content_data(), &value["data"][1]));
+
+        let no_text =
+            OcrResponse::parse(r#"{"code":101,"data":"No text found in image."}"#).unwrap();
+        let message = serde_json::to_value(&no_text).unwrap();
+        assert!(valid(&schema, &message));
+
+        let mut lines = Vec::new();
+        crate::export::to_jsonl(&mut lines, [("a.png", response), ("b.png", no_text)]).unwrap();
+        for line in lines.split(|&b| b == b'\n').filter(|line| !line.is_empty()) {
+            let record = serde_json::from_slice::<Value>(line).unwrap();
+            assert!(valid(&super::jsonl_record(), &record));
+        }
+
+        // What this crate never writes.
+        let content = super::content_data();
+        let mut bad = value["data"][0].clone();
+        bad["cls_label"] = json!(2);
+        assert!(!valid(&content, &bad));
+        bad = value["data"][0].clone();
+        bad.as_object_mut().unwrap().remove("text");
+        assert!(!valid(&content, &bad));
+        bad = value["data"][0].clone();
+        bad["box"] = json!([[0, 0], [9, 0], [9, 9]]);
+        assert!(!valid(&content, &bad));
+        bad = value["data"][0].clone();
+        bad["cls_label"] = Value::Null;
+        assert!(!valid(&content, &bad));
+        assert!(!valid(&schema, &json!({ "code": 100 })));
+        assert!(!valid(
+            &schema,
+            &json!({ "code": 101, "data": [], "message": null })
+        ));
+        assert!(!valid(&super::jsonl_record(), &message));
+    }
+}
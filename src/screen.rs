@@ -0,0 +1,267 @@
+//! OCR of live screen captures, for overlays that follow what is on screen. Enabled with the `bytes` feature.
+//!
+//! Consecutive frames of a screen mostly look the same, so [`IncrementalOcr`] compares each frame with the previous
+//! one and only OCRs the regions that changed, keeping the earlier results for the rest.
+
+use crate::geometry::{Aabb, CoordTransform, RectangleExt};
+use crate::{ContentData, ImageData, OcrEngine};
+
+pub use crate::frame::{Frame, Image, PixelFormat};
+
+/// Side of the square cells frames are compared in, in pixels.
+const CELL: usize = 32;
+/// How far dirty regions are grown, so that new text starting just outside a changed cell is recognized whole. Text
+/// detected in earlier frames is covered by growing regions over its boxes, see [`cover_results`].
+const MARGIN: usize = 16;
+
+impl Frame {
+    /// The regions that differ from `previous`, grown by [`MARGIN`] and merged where they overlap.
+    fn dirty_regions(&self, previous: &Frame) -> Vec<Aabb> {
+        let whole = Aabb {
+            x: 0,
+            y: 0,
+            w: self.width,
+            h: self.height,
+        };
+        if (previous.width, previous.height, previous.format)
+            != (self.width, self.height, self.format)
+        {
+            return vec![whole];
+        }
+        let (columns, rows) = (self.width.div_ceil(CELL), self.height.div_ceil(CELL));
+        let mut dirty = vec![false; columns * rows];
+        for y in 0..self.height {
+            let row = y * self.width * 4;
+            for column in 0..columns {
+                let start = row + column * CELL * 4;
+                let end = row + ((column + 1) * CELL).min(self.width) * 4;
+                if self.data[start..end] != previous.data[start..end] {
+                    dirty[y / CELL * columns + column] = true;
+                }
+            }
+        }
+
+        let mut regions: Vec<Aabb> = Vec::new();
+        for (i, _) in dirty.iter().enumerate().filter(|(_, &d)| d) {
+            let (x, y) = (i % columns * CELL, i / columns * CELL);
+            let cell = Aabb::from_edges(
+                x.saturating_sub(MARGIN),
+                y.saturating_sub(MARGIN),
+                (x + CELL + MARGIN).min(self.width),
+                (y + CELL + MARGIN).min(self.height),
+            );
+            regions.push(cell);
+            // Merge until no two regions overlap, so each part of the screen is OCRed once.
+            while let Some((a, b)) = overlapping_pair(&regions) {
+                let merged = regions[a].union(&regions[b]);
+                regions.swap_remove(b);
+                regions[a] = merged;
+            }
+        }
+        regions
+    }
+}
+
+/**
+ * `regions` grown over the boxes of the `results` they overlap, within `bounds`, and merged where they then overlap,
+ * until no region overlaps a box it doesn't cover. A change anywhere in a line of text thus re-OCRs the whole line,
+ * rather than a fragment that would replace it.
+ */
+fn cover_results(mut regions: Vec<Aabb>, results: &[ContentData], bounds: Aabb) -> Vec<Aabb> {
+    let boxes: Vec<Aabb> = results
+        .iter()
+        .filter_map(|c| c.rect.aabb().intersection(&bounds))
+        .collect();
+    loop {
+        let mut grown = false;
+        for region in &mut regions {
+            for b in &boxes {
+                if region.intersection(b).is_some() && region.union(b) != *region {
+                    *region = region.union(b);
+                    grown = true;
+                }
+            }
+        }
+        while let Some((a, b)) = overlapping_pair(&regions) {
+            let merged = regions[a].union(&regions[b]);
+            regions.swap_remove(b);
+            regions[a] = merged;
+        }
+        if !grown {
+            return regions;
+        }
+    }
+}
+
+fn overlapping_pair(regions: &[Aabb]) -> Option<(usize, usize)> {
+    (0..regions.len()).find_map(|a| {
+        (a + 1..regions.len())
+            .find(|&b| regions[a].intersection(&regions[b]).is_some())
+            .map(|b| (a, b))
+    })
+}
+
+/**
+Results for a sequence of frames, re-OCRing only what changed since the previous frame.
+
+# Examples
+
+```no_run
+use paddleocr::screen::{Frame, IncrementalOcr, PixelFormat};
+
+let mut p = paddleocr::Ppocr::new(".../PaddleOCR-json.exe", None).unwrap();
+let mut screen = IncrementalOcr::new();
+loop {
+    let (width, height, bgra) = capture_screen();
+    let frame = Frame::new(width, height, PixelFormat::Bgra, bgra).unwrap();
+    for content in screen.update(&mut p, frame).unwrap() {
+        draw_translation(content);
+    }
+}
+```
+*/
+#[derive(Debug, Default)]
+pub struct IncrementalOcr {
+    previous: Option<Frame>,
+    results: Vec<ContentData>,
+}
+
+impl IncrementalOcr {
+    pub fn new() -> IncrementalOcr {
+        IncrementalOcr::default()
+    }
+
+    /**
+     * The results for `frame`. Detections overlapping a changed region are replaced by what the engine finds in the
+     * region grown over them now; the others are kept from earlier frames.
+     */
+    pub fn update<E: OcrEngine + ?Sized>(
+        &mut self,
+        engine: &mut E,
+        frame: Frame,
+    ) -> Result<&[ContentData], String> {
+        let regions = match &self.previous {
+            Some(previous)
+                if (previous.width, previous.height, previous.format)
+                    == (frame.width, frame.height, frame.format) =>
+            {
+                cover_results(frame.dirty_regions(previous), &self.results, frame.bounds())
+            }
+            _ => {
+                // Nothing found in a frame of another size or format applies to this one.
+                self.results.clear();
+                vec![frame.bounds()]
+            }
+        };
+        for region in regions {
+            let response = engine.ocr_response(ImageData::from_bytes(frame.encode_bmp(region)))?;
+            if response.code != 100 && response.code != 101 {
+                // Leave the cache untouched, so the next frame retries the regions not yet done.
+                return response.into_result().map(|_| self.results.as_slice());
+            }
+            self.results
+                .retain(|c| c.rect.aabb().intersection(&region).is_none());
+            let back = CoordTransform::cropped(region.x, region.y);
+            self.results.extend(response.data.into_iter().map(|mut c| {
+                c.rect = back.apply(&c.rect);
+                c
+            }));
+        }
+        self.previous = Some(frame);
+        Ok(&self.results)
+    }
+
+    /**
+     * The results for the last frame passed to [`update`](IncrementalOcr::update).
+     */
+    pub fn results(&self) -> &[ContentData] {
+        &self.results
+    }
+
+    /**
+     * Forgets the previous frame, so the next one is OCRed in full.
+     */
+    pub fn reset(&mut self) {
+        self.previous = None;
+        self.results.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    use super::{Frame, IncrementalOcr, PixelFormat};
+    use crate::{ImageData, OcrEngine};
+
+    /// Records the width of every image it is sent, reading it back from the BMP header, and answers with `.1`.
+    struct Recorder(Rc<RefCell<Vec<usize>>>, &'static str);
+    impl OcrEngine for Recorder {
+        fn ocr(&mut self, image: ImageData) -> std::io::Result<String> {
+            use base64::Engine;
+            let ImageData::ImageBase64Dict { image_base64 } = image else {
+                unreachable!()
+            };
+            let bmp = base64::engine::general_purpose::STANDARD
+                .decode(image_base64)
+                .unwrap();
+            self.0
+                .borrow_mut()
+                .push(i32::from_le_bytes(bmp[18..22].try_into().unwrap()) as usize);
+            Ok(self.1.to_string())
+        }
+    }
+
+    const SMALL: &str =
+        r#"{"code":100,"data":[{"box":[[0,0],[10,0],[10,10],[0,10]],"score":0.9,"text":"x"}]}"#;
+
+    #[test]
+    fn incremental() {
+        let widths = Rc::new(RefCell::new(Vec::new()));
+        let mut engine = Recorder(widths.clone(), SMALL);
+        let mut screen = IncrementalOcr::new();
+        let blank = Frame::new(256, 256, PixelFormat::Bgra, vec![0; 256 * 256 * 4]).unwrap();
+
+        assert_eq!(screen.update(&mut engine, blank.clone()).unwrap().len(), 1);
+        assert!(screen.update(&mut engine, blank.clone()).unwrap().len() == 1);
+        assert_eq!(*widths.borrow(), [256]);
+
+        let mut changed = blank.clone();
+        changed.data[(200 * 256 + 200) * 4] = 255;
+        let results = screen.update(&mut engine, changed).unwrap();
+        // The cell at (192, 192), grown by the margin.
+        assert_eq!(*widths.borrow(), [256, 32 + 2 * 16]);
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[1].rect[0], [176, 176]);
+
+        // A smaller frame drops the results outside of it too.
+        let small = Frame::new(128, 128, PixelFormat::Bgra, vec![0; 128 * 128 * 4]).unwrap();
+        let results = screen.update(&mut engine, small).unwrap();
+        assert_eq!(*widths.borrow(), [256, 64, 128]);
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].rect[0], [0, 0]);
+    }
+
+    #[test]
+    fn whole_lines() {
+        let widths = Rc::new(RefCell::new(Vec::new()));
+        let mut screen = IncrementalOcr::new();
+        let blank = Frame::new(256, 256, PixelFormat::Bgra, vec![0; 256 * 256 * 4]).unwrap();
+        let line = r#"{"code":100,"data":[{"box":[[20,100],[220,100],[220,120],[20,120]],"score":0.9,"text":"12:00 meeting"}]}"#;
+        screen
+            .update(&mut Recorder(widths.clone(), line), blank.clone())
+            .unwrap();
+
+        // A clock digit changing in the middle of the line.
+        let mut changed = blank.clone();
+        changed.data[(110 * 256 + 120) * 4] = 255;
+        let results = screen
+            .update(&mut Recorder(widths.clone(), SMALL), changed)
+            .unwrap();
+        // The cell at (96, 96) grown by the margin, then over the line: from x = 20 to 220.
+        assert_eq!(*widths.borrow(), [256, 200]);
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].rect[0], [20, 80]);
+    }
+}
@@ -0,0 +1,349 @@
+//! Full-text search over OCR results, for "search my screenshots" kinds of applications.
+//!
+//! An [`Indexer`] keeps a [tantivy](https://docs.rs/tantivy) index of the recognized text of each image, with its
+//! path, boxes and when it was indexed, ranks matches with BM25 and persists the index in a directory, so it
+//! survives restarts:
+//!
+//! ```no_run
+//! use paddleocr::search::Indexer;
+//! use paddleocr::OcrEngine;
+//!
+//! let mut index = Indexer::open("screenshots.index").unwrap();
+//! let mut p = paddleocr::Ppocr::new(".../PaddleOCR-json.exe", None).unwrap();
+//! let response = p.ocr_response(paddleocr::ImageData::from_path("shot.png")).unwrap();
+//! index.add("shot.png", &response.data).unwrap();
+//! for hit in index.search("invoice total", 10).unwrap() {
+//!     println!("{} ({:.2}): {:?}", hit.path, hit.score, hit.matches.first().map(|m| &m.text));
+//! }
+//! ```
+
+use std::io::{Error as IoError, Result as IoResult};
+use std::path::Path;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use tantivy::collector::TopDocs;
+use tantivy::directory::MmapDirectory;
+use tantivy::query::QueryParser;
+use tantivy::schema::{
+    Field, IndexRecordOption, Schema, TextFieldIndexing, TextOptions, Value, STORED, STRING,
+};
+use tantivy::tokenizer::{Token, TokenStream, Tokenizer};
+use tantivy::{
+    doc, DateTime, Index, IndexReader, IndexWriter, ReloadPolicy, TantivyDocument, Term,
+};
+
+use crate::ContentData;
+
+/// The name [`Terms`] is registered under.
+const TOKENIZER: &str = "paddleocr";
+/// The indexing memory budget, tantivy's minimum.
+const WRITER_MEMORY: usize = 15_000_000;
+
+/**
+ * An image matching a query.
+ */
+#[derive(Debug, Clone)]
+pub struct Hit {
+    pub path: String,
+    /// The BM25 score; higher is better.
+    pub score: f32,
+    pub indexed: SystemTime,
+    /// The detections holding any of the query's terms, with their boxes.
+    pub matches: Vec<ContentData>,
+}
+
+/**
+ * Lowercased terms: runs of letters and digits, and single CJK characters since such text isn't spaced.
+ */
+fn tokens(text: &str) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut word: Option<Token> = None;
+    for (offset, c) in text.char_indices() {
+        if c.is_alphanumeric() && !crate::words::is_wide(c) {
+            let token = word.get_or_insert_with(|| Token {
+                offset_from: offset,
+                ..Token::default()
+            });
+            token.text.extend(c.to_lowercase());
+            token.offset_to = offset + c.len_utf8();
+            continue;
+        }
+        tokens.extend(word.take());
+        if c.is_alphanumeric() {
+            tokens.push(Token {
+                offset_from: offset,
+                offset_to: offset + c.len_utf8(),
+                text: c.to_string(),
+                ..Token::default()
+            });
+        }
+    }
+    tokens.extend(word);
+    for (position, token) in tokens.iter_mut().enumerate() {
+        token.position = position;
+    }
+    tokens
+}
+
+fn terms(text: &str) -> Vec<String> {
+    tokens(text).into_iter().map(|token| token.text).collect()
+}
+
+/// [`tokens`] as a tantivy tokenizer, for indexing and parsing queries alike.
+#[derive(Clone)]
+struct Terms;
+
+struct TermStream {
+    tokens: std::vec::IntoIter<Token>,
+    token: Token,
+}
+
+impl Tokenizer for Terms {
+    type TokenStream<'a> = TermStream;
+
+    fn token_stream<'a>(&'a mut self, text: &'a str) -> TermStream {
+        TermStream {
+            tokens: tokens(text).into_iter(),
+            token: Token::default(),
+        }
+    }
+}
+
+impl TokenStream for TermStream {
+    fn advance(&mut self) -> bool {
+        self.tokens.next().map(|token| self.token = token).is_some()
+    }
+
+    fn token(&self) -> &Token {
+        &self.token
+    }
+
+    fn token_mut(&mut self) -> &mut Token {
+        &mut self.token
+    }
+}
+
+/// The fields of an index.
+#[derive(Clone, Copy)]
+struct Fields {
+    path: Field,
+    /// The text of each detection, as a separate value.
+    text: Field,
+    indexed: Field,
+    /// The detections, as JSON.
+    data: Field,
+}
+
+impl Fields {
+    fn schema() -> (Schema, Fields) {
+        let mut builder = Schema::builder();
+        let text = TextOptions::default().set_indexing_options(
+            TextFieldIndexing::default()
+                .set_tokenizer(TOKENIZER)
+                .set_index_option(IndexRecordOption::WithFreqsAndPositions),
+        );
+        let fields = Fields {
+            path: builder.add_text_field("path", STRING | STORED),
+            text: builder.add_text_field("text", text),
+            indexed: builder.add_date_field("indexed", STORED),
+            data: builder.add_text_field("data", STORED),
+        };
+        (builder.build(), fields)
+    }
+}
+
+/**
+ * A full-text index of OCR results, one document per image path. See the [module documentation](self).
+ */
+pub struct Indexer {
+    index: Index,
+    writer: IndexWriter,
+    reader: IndexReader,
+    fields: Fields,
+}
+
+impl Indexer {
+    /**
+     * An empty index that isn't saved anywhere.
+     */
+    pub fn in_memory() -> IoResult<Indexer> {
+        let (schema, fields) = Fields::schema();
+        Indexer::with_index(Index::create_in_ram(schema), fields)
+    }
+
+    /**
+     * The index saved in the directory `path`, created if missing. Everything added later is saved there. Only one
+     * `Indexer` at a time can have a directory open.
+     */
+    pub fn open<P: AsRef<Path>>(path: P) -> IoResult<Indexer> {
+        std::fs::create_dir_all(&path)?;
+        let directory = MmapDirectory::open(path).map_err(IoError::other)?;
+        let (schema, fields) = Fields::schema();
+        let index = Index::open_or_create(directory, schema).map_err(IoError::other)?;
+        Indexer::with_index(index, fields)
+    }
+
+    fn with_index(index: Index, fields: Fields) -> IoResult<Indexer> {
+        index.tokenizers().register(TOKENIZER, Terms);
+        let writer = index
+            .writer_with_num_threads(1, WRITER_MEMORY)
+            .map_err(IoError::other)?;
+        let reader = index
+            .reader_builder()
+            .reload_policy(ReloadPolicy::Manual)
+            .try_into()
+            .map_err(IoError::other)?;
+        Ok(Indexer {
+            index,
+            writer,
+            reader,
+            fields,
+        })
+    }
+
+    /**
+     * Indexes the detections of the image at `path`, replacing what was indexed for it before, and commits them.
+     */
+    pub fn add(&mut self, path: &str, data: &[ContentData]) -> IoResult<()> {
+        let Fields {
+            path: path_field,
+            text,
+            indexed,
+            data: data_field,
+        } = self.fields;
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        let mut document = doc!(
+            path_field => path,
+            indexed => DateTime::from_timestamp_secs(now as i64),
+            data_field => serde_json::to_string(data)?,
+        );
+        for content in data {
+            document.add_text(text, &content.text);
+        }
+        self.writer
+            .delete_term(Term::from_field_text(path_field, path));
+        self.writer.add_document(document).map_err(IoError::other)?;
+        self.writer.commit().map_err(IoError::other)?;
+        self.reader.reload().map_err(IoError::other)
+    }
+
+    /**
+     * How many images are indexed.
+     */
+    pub fn len(&self) -> usize {
+        self.reader.searcher().num_docs() as usize
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /**
+     * The `limit` images best matching `query`, best first. By default an image matches if it holds any of the
+     * query's terms; those holding rare terms, more of the terms, or the terms more often rank higher. The query
+     * can use [tantivy's syntax](https://docs.rs/tantivy/latest/tantivy/query/struct.QueryParser.html), such as
+     * `"total due"` for a phrase, `+total` for a required term and `-draft` for an excluded one; what doesn't parse
+     * is searched as text.
+     */
+    pub fn search(&self, query: &str, limit: usize) -> IoResult<Vec<Hit>> {
+        if limit == 0 {
+            return Ok(Vec::new());
+        }
+        let (query, _) =
+            QueryParser::for_index(&self.index, vec![self.fields.text]).parse_query_lenient(query);
+        let mut wanted = Vec::new();
+        query.query_terms(&mut |term, _| {
+            if let Some(text) = term.value().as_str() {
+                wanted.push(text.to_string());
+            }
+        });
+        let searcher = self.reader.searcher();
+        let top = searcher
+            .search(&query, &TopDocs::with_limit(limit))
+            .map_err(IoError::other)?;
+        top.into_iter()
+            .map(|(score, address)| {
+                let document: TantivyDocument = searcher.doc(address).map_err(IoError::other)?;
+                let stored = |field| document.get_first(field);
+                let path = stored(self.fields.path).and_then(|v| v.as_str());
+                let indexed = stored(self.fields.indexed).and_then(|v| v.as_datetime());
+                let data = stored(self.fields.data).and_then(|v| v.as_str());
+                let data: Vec<ContentData> = serde_json::from_str(data.unwrap_or("[]"))?;
+                let matches = data
+                    .into_iter()
+                    .filter(|c| terms(&c.text).iter().any(|t| wanted.contains(t)))
+                    .collect();
+                let seconds = indexed.map_or(0, |date| date.into_timestamp_secs());
+                Ok(Hit {
+                    path: path.unwrap_or_default().to_string(),
+                    score,
+                    indexed: UNIX_EPOCH + Duration::from_secs(seconds.max(0) as u64),
+                    matches,
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{terms, Indexer};
+    use crate::{detection, ContentData};
+
+    fn item(text: &str) -> ContentData {
+        detection(text, [[0, 0], [10, 0], [10, 10], [0, 10]], 0.9)
+    }
+
+    #[test]
+    fn index_and_search() {
+        assert_eq!(
+            terms("Total: 12,50€ 合計"),
+            ["total", "12", "50", "合", "計"]
+        );
+
+        let path = std::env::temp_dir().join(format!("paddleocr-search-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&path);
+        {
+            let mut index = Indexer::open(&path).unwrap();
+            index
+                .add("a.png", &[item("Invoice"), item("Total due")])
+                .unwrap();
+            index.add("b.png", &[item("total total total")]).unwrap();
+            index.add("c.png", &[item("weather")]).unwrap();
+            index.add("c.png", &[item("合計 total")]).unwrap();
+            assert_eq!(index.len(), 3);
+        }
+
+        let index = Indexer::open(&path).unwrap();
+        assert_eq!(index.len(), 3);
+        let hits = index.search("TOTAL invoice", 10).unwrap();
+        assert_eq!(hits.len(), 3);
+        // The only one with the rare term comes first.
+        assert_eq!(hits[0].path, "a.png");
+        assert_eq!(hits[0].matches.len(), 2);
+        assert_eq!(
+            hits[0].matches[0].rect,
+            [[0, 0], [10, 0], [10, 10], [0, 10]]
+        );
+        assert!(hits[0].indexed.elapsed().unwrap().as_secs() < 60);
+        assert!(index.search("weather", 10).unwrap().is_empty());
+        let cjk = index.search("合計", 1).unwrap();
+        assert_eq!((cjk.len(), cjk[0].path.as_str()), (1, "c.png"));
+        assert_eq!(cjk[0].matches[0].text, "合計 total");
+        let phrase = index.search("\"total due\" -invoice", 10).unwrap();
+        assert!(phrase.is_empty());
+        let required = index.search("+total -合計", 10).unwrap();
+        let paths: Vec<&str> = required.iter().map(|hit| hit.path.as_str()).collect();
+        assert_eq!(paths, ["b.png", "a.png"]);
+        assert!(index.search("total", 0).unwrap().is_empty());
+        drop(index);
+        std::fs::remove_dir_all(&path).unwrap();
+
+        let index = Indexer::in_memory().unwrap();
+        assert!(index.is_empty());
+        assert!(index.search("total", 10).unwrap().is_empty());
+    }
+}
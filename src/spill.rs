@@ -0,0 +1,104 @@
+//! Sending large in-memory images to a local engine as temporary files instead of base64.
+
+use std::fs::OpenOptions;
+use std::io::{Error as IoError, ErrorKind, Result as IoResult, Write};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use base64::Engine;
+
+/// Numbers the files of this process.
+static COUNTER: AtomicU64 = AtomicU64::new(0);
+
+/**
+ * A temporary image file, removed when dropped.
+ */
+pub(crate) struct SpillFile(PathBuf);
+
+impl SpillFile {
+    /**
+     * Decodes `image_base64` into a new file in the temporary directory, named with an extension for its format so
+     * that the engine recognizes it. The file is always created anew, under another name if one is taken, so that a
+     * file or link planted in the shared directory isn't written through.
+     */
+    pub(crate) fn write(image_base64: &str) -> IoResult<SpillFile> {
+        let bytes = base64::engine::general_purpose::STANDARD
+            .decode(image_base64)
+            .map_err(|e| IoError::new(ErrorKind::InvalidInput, e))?;
+        loop {
+            let path = std::env::temp_dir().join(format!(
+                "paddleocr-spill-{}-{}.{}",
+                std::process::id(),
+                COUNTER.fetch_add(1, Ordering::Relaxed),
+                extension(&bytes)
+            ));
+            let mut file = match OpenOptions::new().write(true).create_new(true).open(&path) {
+                Ok(file) => file,
+                Err(e) if e.kind() == ErrorKind::AlreadyExists => continue,
+                Err(e) => return Err(e),
+            };
+            // Removed again if writing fails.
+            let spill = SpillFile(path);
+            file.write_all(&bytes)?;
+            return Ok(spill);
+        }
+    }
+
+    pub(crate) fn path(&self) -> &std::path::Path {
+        &self.0
+    }
+}
+
+impl Drop for SpillFile {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.0);
+    }
+}
+
+/// The usual extension of an image, guessed from its first bytes.
+fn extension(bytes: &[u8]) -> &'static str {
+    const SIGNATURES: [(&[u8], &str); 6] = [
+        (b"\x89PNG", "png"),
+        (b"\xff\xd8\xff", "jpg"),
+        (b"BM", "bmp"),
+        (b"II*\0", "tif"),
+        (b"MM\0*", "tif"),
+        (b"GIF8", "gif"),
+    ];
+    if bytes.starts_with(b"RIFF") && bytes.get(8..12) == Some(b"WEBP") {
+        return "webp";
+    }
+    SIGNATURES
+        .iter()
+        .find(|(signature, _)| bytes.starts_with(signature))
+        .map_or("img", |(_, extension)| extension)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::Ordering;
+
+    use super::SpillFile;
+
+    #[test]
+    fn taken_names() {
+        // Whatever already has the next name is left alone.
+        let next = |ext| {
+            std::env::temp_dir().join(format!(
+                "paddleocr-spill-{}-{}.{}",
+                std::process::id(),
+                super::COUNTER.load(Ordering::Relaxed),
+                ext
+            ))
+        };
+        let planted = next("img");
+        std::fs::write(&planted, "planted").unwrap();
+        let spill = SpillFile::write("aGVsbG8=");
+        let kept = std::fs::read_to_string(&planted);
+        std::fs::remove_file(&planted).unwrap();
+        let spill = spill.unwrap();
+        assert_eq!(kept.unwrap(), "planted");
+        assert_ne!(spill.path(), planted);
+        assert_eq!(std::fs::read(spill.path()).unwrap(), b"hello");
+    }
+}
@@ -0,0 +1,147 @@
+use std::error::Error;
+use std::io::Result as IoResult;
+use std::process;
+
+use crate::{ImageData, OcrEngine, Ppocr, Terminator};
+
+/**
+ * A paddleocr-json engine running on a remote host, launched over `ssh` and talked to through the tunnelled stdio.
+ *
+ * The remote side is expected to have a POSIX shell, i.e. run the Linux build of the engine. Image paths are
+ * resolved on the remote host, so local images should be sent as bytes or base64.
+ */
+pub struct SshPpocr {
+    inner: Ppocr,
+}
+
+impl SshPpocr {
+    /**
+    Starts the engine at `remote_exe` on `destination` (`[user@]host`) and waits for it to initialize.
+
+    The engine is run from its own directory, like [`Ppocr::new`] does locally. `ssh` runs in batch mode, so
+    authentication has to work without prompting, e.g. with keys or an agent.
+
+    # Examples
+
+    ```no_run
+    use paddleocr::OcrEngine;
+
+    let mut p = paddleocr::SshPpocr::connect(
+        "ocr@gpu-server",
+        "/opt/PaddleOCR-json/PaddleOCR-json",
+        ["--config_path", "models/config_en.txt"],
+    )
+    .unwrap();
+    println!("{}", p.ocr(paddleocr::ImageData::from_base64("...".into())).unwrap());
+    ```
+    */
+    pub fn connect<I, S>(
+        destination: &str,
+        remote_exe: &str,
+        engine_args: I,
+    ) -> Result<SshPpocr, Box<dyn Error>>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        SshPpocr::connect_with_options(&[] as &[&str], destination, remote_exe, engine_args)
+    }
+
+    /**
+    Like [`SshPpocr::connect`], with extra options passed to `ssh` before the destination, e.g. `["-p", "2222"]`.
+    */
+    pub fn connect_with_options<O, I, S>(
+        ssh_options: &[O],
+        destination: &str,
+        remote_exe: &str,
+        engine_args: I,
+    ) -> Result<SshPpocr, Box<dyn Error>>
+    where
+        O: AsRef<str>,
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let command = ssh_command(ssh_options, destination, remote_exe, engine_args);
+        let mut inner = Ppocr::from_command(command)?;
+        inner.local = false;
+        Ok(SshPpocr { inner })
+    }
+}
+
+fn ssh_command<O, I, S>(
+    ssh_options: &[O],
+    destination: &str,
+    remote_exe: &str,
+    engine_args: I,
+) -> process::Command
+where
+    O: AsRef<str>,
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    let remote_dir = match remote_exe.rfind('/') {
+        Some(0) => "/",
+        Some(i) => &remote_exe[..i],
+        None => ".",
+    };
+    let mut remote_command = format!(
+        "cd {} && exec {}",
+        shell_quote(remote_dir),
+        shell_quote(remote_exe)
+    );
+    for arg in engine_args {
+        remote_command.push(' ');
+        remote_command.push_str(&shell_quote(arg.as_ref()));
+    }
+
+    let mut command = process::Command::new("ssh");
+    command
+        .args(["-T", "-o", "BatchMode=yes"])
+        .args(ssh_options.iter().map(AsRef::as_ref))
+        .args([destination, "--", &remote_command]);
+    command
+}
+
+/// Quotes `s` for a POSIX shell.
+fn shell_quote(s: &str) -> String {
+    format!("'{}'", s.replace('\'', r"'\''"))
+}
+
+impl OcrEngine for SshPpocr {
+    fn ocr(&mut self, image: ImageData) -> IoResult<String> {
+        self.inner.ocr(image)
+    }
+
+    fn terminator(&self) -> Option<Terminator> {
+        self.inner.terminator()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ssh_command;
+
+    #[test]
+    fn command() {
+        let command = ssh_command(
+            &["-p", "2222"],
+            "ocr@host",
+            "/opt/engine/PaddleOCR-json",
+            ["--config_path", "it's.txt"],
+        );
+        let args: Vec<_> = command.get_args().map(|a| a.to_str().unwrap()).collect();
+        assert_eq!(
+            args,
+            [
+                "-T",
+                "-o",
+                "BatchMode=yes",
+                "-p",
+                "2222",
+                "ocr@host",
+                "--",
+                r"cd '/opt/engine' && exec '/opt/engine/PaddleOCR-json' '--config_path' 'it'\''s.txt'",
+            ]
+        );
+    }
+}
@@ -0,0 +1,335 @@
+//! Keeping an engine alive across crashes.
+
+use std::error::Error;
+use std::io::{Error as IoError, ErrorKind, Result as IoResult};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::Duration;
+
+use crate::{ImageData, OcrEngine, Terminator};
+
+/**
+ * A change in a [`Supervisor`]'s engine.
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SupervisorEvent {
+    /// An engine is being started; `attempt` counts from 1 since the last successful request.
+    Starting { attempt: u32 },
+    /// The engine started and is ready for requests.
+    Ready,
+    /// The engine failed to start, or failed while handling a request.
+    Crashed { error: String },
+    /// The restart budget is used up; requests fail until [`Supervisor::reset`].
+    GivenUp,
+}
+
+type Factory<E> = Box<dyn FnMut() -> Result<E, Box<dyn Error>> + Send>;
+type EventHandler = Box<dyn FnMut(&SupervisorEvent) + Send>;
+
+/**
+An engine that is restarted when it crashes, waiting exponentially longer between consecutive failed attempts.
+
+The engine is started on the first request. A request that fails because the engine died is retried on a fresh
+engine; after `max_restarts` consecutive failures without a successful request in between, the supervisor gives up.
+Requests failing because of the request itself, such as a missing file or an oversized payload, return their error
+and leave the engine running.
+
+# Examples
+
+```no_run
+use paddleocr::supervisor::Supervisor;
+use paddleocr::OcrEngine;
+
+let mut engine = Supervisor::new(|| paddleocr::Ppocr::new(".../PaddleOCR-json.exe", None))
+    .max_restarts(10)
+    .on_event(|event| eprintln!("engine: {:?}", event));
+println!("{}", engine.ocr(Path::new(".../test.png").into()).unwrap());
+```
+*/
+pub struct Supervisor<E> {
+    factory: Factory<E>,
+    engine: Option<E>,
+    on_event: Option<EventHandler>,
+    max_restarts: u32,
+    initial_backoff: Duration,
+    max_backoff: Duration,
+    /// Consecutive failures since the last successful request.
+    failures: u32,
+    given_up: bool,
+    /// The running engine's terminator, for [`OcrEngine::terminator`] to reach whichever engine is current.
+    current: Arc<Mutex<Option<Terminator>>>,
+    /// Set by the terminator, so that the engine isn't restarted after being stopped.
+    terminated: Arc<AtomicBool>,
+}
+
+impl<E: OcrEngine> Supervisor<E> {
+    /**
+     * Supervises engines made by `factory`, with up to 5 restarts, waiting from 0.5 up to 30 seconds in between.
+     */
+    pub fn new<F>(factory: F) -> Supervisor<E>
+    where
+        F: FnMut() -> Result<E, Box<dyn Error>> + Send + 'static,
+    {
+        Supervisor {
+            factory: Box::new(factory),
+            engine: None,
+            on_event: None,
+            max_restarts: 5,
+            initial_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(30),
+            failures: 0,
+            given_up: false,
+            current: Arc::default(),
+            terminated: Arc::default(),
+        }
+    }
+
+    /**
+     * Sets how many consecutive failures are tolerated before giving up.
+     */
+    pub fn max_restarts(mut self, max_restarts: u32) -> Supervisor<E> {
+        self.max_restarts = max_restarts;
+        self
+    }
+
+    /**
+     * Sets the wait before the first restart, doubled for each further consecutive one up to `max`.
+     */
+    pub fn backoff(mut self, initial: Duration, max: Duration) -> Supervisor<E> {
+        self.initial_backoff = initial;
+        self.max_backoff = max;
+        self
+    }
+
+    /**
+     * Sets a handler called with every [`SupervisorEvent`].
+     */
+    pub fn on_event<F>(mut self, handler: F) -> Supervisor<E>
+    where
+        F: FnMut(&SupervisorEvent) + Send + 'static,
+    {
+        self.on_event = Some(Box::new(handler));
+        self
+    }
+
+    /**
+     * The running engine, if any.
+     */
+    pub fn get_mut(&mut self) -> Option<&mut E> {
+        self.engine.as_mut()
+    }
+
+    /**
+     * Clears the failure count after giving up, so the next request starts a new engine.
+     */
+    pub fn reset(&mut self) {
+        self.failures = 0;
+        self.given_up = false;
+    }
+
+    fn emit(&mut self, event: SupervisorEvent) {
+        if let Some(handler) = self.on_event.as_mut() {
+            handler(&event);
+        }
+    }
+
+    fn set_current(&self, terminator: Option<Terminator>) {
+        *self.current.lock().unwrap_or_else(PoisonError::into_inner) = terminator;
+    }
+
+    fn crashed(&mut self, error: String) {
+        self.engine = None;
+        self.set_current(None);
+        self.failures += 1;
+        self.emit(SupervisorEvent::Crashed { error });
+        if self.failures > self.max_restarts {
+            self.given_up = true;
+            self.emit(SupervisorEvent::GivenUp);
+        }
+    }
+
+    fn engine(&mut self) -> IoResult<&mut E> {
+        while self.engine.is_none() {
+            if self.terminated.load(Ordering::SeqCst) {
+                return Err(IoError::new(
+                    ErrorKind::BrokenPipe,
+                    "the engine was terminated",
+                ));
+            }
+            if self.given_up {
+                return Err(IoError::other(
+                    "the engine kept crashing and was given up on",
+                ));
+            }
+            if self.failures > 0 {
+                let backoff = self
+                    .initial_backoff
+                    .saturating_mul(1 << (self.failures - 1).min(16))
+                    .min(self.max_backoff);
+                std::thread::sleep(backoff);
+            }
+            self.emit(SupervisorEvent::Starting {
+                attempt: self.failures + 1,
+            });
+            match (self.factory)() {
+                Ok(engine) => {
+                    self.set_current(engine.terminator());
+                    self.engine = Some(engine);
+                    self.emit(SupervisorEvent::Ready);
+                }
+                Err(e) => self.crashed(e.to_string()),
+            }
+        }
+        self.engine
+            .as_mut()
+            .ok_or_else(|| IoError::other("the engine failed to start"))
+    }
+}
+
+impl<E: OcrEngine> OcrEngine for Supervisor<E> {
+    fn ocr(&mut self, image: ImageData) -> IoResult<String> {
+        loop {
+            match self.engine()?.ocr(image.clone()) {
+                Ok(response) if !response.is_empty() => {
+                    self.failures = 0;
+                    return Ok(response);
+                }
+                // An engine that exits closes its stdout, which reads as an empty response.
+                Ok(_) => self.crashed(IoError::from(ErrorKind::UnexpectedEof).to_string()),
+                Err(e) if crate::error::is_retryable_kind(e.kind()) => self.crashed(e.to_string()),
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    /// Stops the current engine, if it can be, and keeps the supervisor from starting another one.
+    fn terminator(&self) -> Option<Terminator> {
+        let (current, terminated) = (self.current.clone(), self.terminated.clone());
+        Some(Arc::new(move || {
+            terminated.store(true, Ordering::SeqCst);
+            let terminator = current
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .clone();
+            if let Some(terminate) = terminator {
+                terminate();
+            }
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    use super::{Supervisor, SupervisorEvent};
+    use crate::{ImageData, OcrEngine};
+
+    /// Answers `remaining` requests, then behaves as if it had crashed.
+    struct Flaky {
+        remaining: usize,
+    }
+    impl OcrEngine for Flaky {
+        fn ocr(&mut self, _image: ImageData) -> std::io::Result<String> {
+            if self.remaining == 0 {
+                return Ok(String::new());
+            }
+            self.remaining -= 1;
+            Ok(r#"{"code":101,"data":"No text found in image."}"#.to_string())
+        }
+    }
+
+    #[test]
+    fn restarts() {
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let log = events.clone();
+        let mut starts = 0;
+        let mut engine = Supervisor::new(move || {
+            starts += 1;
+            match starts {
+                2 => Err("failed to start".into()),
+                _ => Ok(Flaky { remaining: 1 }),
+            }
+        })
+        .backoff(Duration::ZERO, Duration::ZERO)
+        .max_restarts(2)
+        .on_event(move |e| log.lock().unwrap().push(e.clone()));
+
+        let image = || ImageData::from_path("a.png");
+        assert!(engine.ocr(image()).is_ok());
+        // The engine dies, fails to restart once, then comes back.
+        assert!(engine.ocr(image()).is_ok());
+        assert_eq!(
+            *events.lock().unwrap(),
+            [
+                SupervisorEvent::Starting { attempt: 1 },
+                SupervisorEvent::Ready,
+                SupervisorEvent::Crashed {
+                    error: "unexpected end of file".into()
+                },
+                SupervisorEvent::Starting { attempt: 2 },
+                SupervisorEvent::Crashed {
+                    error: "failed to start".into()
+                },
+                SupervisorEvent::Starting { attempt: 3 },
+                SupervisorEvent::Ready,
+            ]
+        );
+    }
+
+    /// Rejects every request, as `Ppocr` does oversized payloads.
+    struct Picky;
+    impl OcrEngine for Picky {
+        fn ocr(&mut self, _image: ImageData) -> std::io::Result<String> {
+            Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "the payload is too large",
+            ))
+        }
+    }
+
+    #[test]
+    fn input_errors() {
+        let mut starts = 0;
+        let mut engine = Supervisor::new(move || {
+            starts += 1;
+            assert_eq!(starts, 1, "the engine was restarted");
+            Ok(Picky)
+        })
+        .backoff(Duration::from_secs(60), Duration::from_secs(60))
+        .max_restarts(0);
+        for _ in 0..3 {
+            let error = engine.ocr(ImageData::from_path("a.png")).unwrap_err();
+            assert_eq!(error.kind(), std::io::ErrorKind::InvalidInput);
+        }
+        assert!(engine.get_mut().is_some());
+        assert_eq!(engine.failures, 0);
+    }
+
+    #[test]
+    fn terminated() {
+        let mut starts = 0;
+        let mut engine = Supervisor::new(move || {
+            starts += 1;
+            assert_eq!(starts, 1, "the engine was restarted");
+            Ok(Flaky { remaining: 1 })
+        })
+        .backoff(Duration::ZERO, Duration::ZERO);
+        assert!(engine.ocr(ImageData::from_path("a.png")).is_ok());
+        engine.terminator().unwrap()();
+        let error = engine.ocr(ImageData::from_path("a.png")).unwrap_err();
+        assert_eq!(error.to_string(), "the engine was terminated");
+    }
+
+    #[test]
+    fn gives_up() {
+        let mut engine = Supervisor::new(|| Ok(Flaky { remaining: 0 }))
+            .backoff(Duration::ZERO, Duration::ZERO)
+            .max_restarts(3);
+        assert!(engine.ocr(ImageData::from_path("a.png")).is_err());
+        assert!(engine.ocr(ImageData::from_path("a.png")).is_err());
+        engine.reset();
+        assert!(engine.get_mut().is_none());
+    }
+}
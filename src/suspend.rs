@@ -0,0 +1,103 @@
+//! Suspending and resuming a child process, with `SIGSTOP`/`SIGCONT` on Unix and the undocumented but long-stable
+//! `NtSuspendProcess`/`NtResumeProcess` on Windows.
+
+use std::io::{Error, ErrorKind, Result as IoResult};
+use std::process::Child;
+
+#[cfg(unix)]
+mod sys {
+    use std::ffi::c_int;
+
+    extern "C" {
+        fn kill(pid: c_int, signal: c_int) -> c_int;
+    }
+
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    const SIGSTOP: c_int = 19;
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    const SIGCONT: c_int = 18;
+    #[cfg(not(any(target_os = "linux", target_os = "android")))]
+    const SIGSTOP: c_int = 17;
+    #[cfg(not(any(target_os = "linux", target_os = "android")))]
+    const SIGCONT: c_int = 19;
+
+    fn signal(child: &std::process::Child, signal: c_int) -> std::io::Result<()> {
+        // SAFETY: `kill` has no memory-safety preconditions. The PID stays ours until the child is waited for.
+        if unsafe { kill(child.id() as c_int, signal) } == 0 {
+            Ok(())
+        } else {
+            Err(std::io::Error::last_os_error())
+        }
+    }
+
+    pub(super) fn pause(child: &std::process::Child) -> std::io::Result<()> {
+        signal(child, SIGSTOP)
+    }
+
+    pub(super) fn resume(child: &std::process::Child) -> std::io::Result<()> {
+        signal(child, SIGCONT)
+    }
+}
+
+#[cfg(windows)]
+mod sys {
+    use std::ffi::c_void;
+    use std::os::windows::io::AsRawHandle;
+
+    #[link(name = "ntdll")]
+    extern "system" {
+        fn NtSuspendProcess(process: *mut c_void) -> i32;
+        fn NtResumeProcess(process: *mut c_void) -> i32;
+    }
+
+    fn status(status: i32) -> std::io::Result<()> {
+        if status >= 0 {
+            Ok(())
+        } else {
+            Err(std::io::Error::other(format!("NTSTATUS {:#010x}", status)))
+        }
+    }
+
+    pub(super) fn pause(child: &std::process::Child) -> std::io::Result<()> {
+        // SAFETY: the handle is owned by `child` and stays valid for the duration of the call.
+        status(unsafe { NtSuspendProcess(child.as_raw_handle()) })
+    }
+
+    pub(super) fn resume(child: &std::process::Child) -> std::io::Result<()> {
+        // SAFETY: as above.
+        status(unsafe { NtResumeProcess(child.as_raw_handle()) })
+    }
+}
+
+#[cfg(not(any(unix, windows)))]
+mod sys {
+    pub(super) fn pause(_: &std::process::Child) -> std::io::Result<()> {
+        Err(super::unsupported())
+    }
+
+    pub(super) fn resume(_: &std::process::Child) -> std::io::Result<()> {
+        Err(super::unsupported())
+    }
+}
+
+#[allow(dead_code)]
+fn unsupported() -> Error {
+    Error::new(
+        ErrorKind::Unsupported,
+        "suspending processes is not supported on this OS",
+    )
+}
+
+pub(crate) fn pause(child: &mut Child) -> IoResult<()> {
+    if child.try_wait()?.is_some() {
+        return Err(Error::new(ErrorKind::BrokenPipe, "the engine has exited"));
+    }
+    sys::pause(child)
+}
+
+pub(crate) fn resume(child: &mut Child) -> IoResult<()> {
+    if child.try_wait()?.is_some() {
+        return Err(Error::new(ErrorKind::BrokenPipe, "the engine has exited"));
+    }
+    sys::resume(child)
+}
@@ -0,0 +1,406 @@
+//! Field extraction from common document types, returning typed values together with where they were read from.
+//!
+//! Extraction works on the lines found by [`layout::lines`](crate::layout::lines): a field is usually a label and
+//! its value on the same line, e.g. `Total ... 12.50`, or a value recognizable on its own, such as a date.
+
+use std::collections::HashMap;
+
+use crate::geometry::{homography, Aabb, RectangleExt, UNIT_SQUARE};
+use crate::layout::{self, Line};
+use crate::{Confidence, ContentData, Rectangle};
+
+/**
+ * An extracted value, with the box and confidence of the detection it was read from.
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct Field<T> {
+    pub value: T,
+    pub rect: Rectangle,
+    pub score: Confidence,
+}
+
+impl<T> Field<T> {
+    fn from(value: T, source: &ContentData) -> Field<T> {
+        Field {
+            value,
+            rect: source.rect,
+            score: source.score,
+        }
+    }
+}
+
+/**
+ * A calendar date as printed on a document.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Date {
+    pub year: u16,
+    pub month: u8,
+    pub day: u8,
+}
+
+/**
+ * The fields found on a receipt or invoice; any of them may be missing.
+ */
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Receipt {
+    /// The first line of text that is not a date or an amount, normally the shop's name.
+    pub merchant: Option<Field<String>>,
+    pub date: Option<Field<Date>>,
+    pub total: Option<Field<f64>>,
+    pub tax: Option<Field<f64>>,
+}
+
+/**
+ * Which labels to look for on a receipt. Labels are matched case-insensitively anywhere in a line, and the default
+ * covers common English and Chinese receipts.
+ */
+#[derive(Debug, Clone)]
+pub struct ReceiptTemplate {
+    /// Labels of the amount to pay. Where several lines match, the last one wins, as grand totals come last.
+    pub total_labels: Vec<String>,
+    pub tax_labels: Vec<String>,
+    /// Labels that look like a total label but are not, such as `Subtotal`; lines with these are skipped.
+    pub ignored_labels: Vec<String>,
+    /// Whether `01/02/2024` is the 1st of February rather than January 2nd.
+    pub day_first: bool,
+}
+
+impl Default for ReceiptTemplate {
+    fn default() -> Self {
+        let labels = |labels: &[&str]| labels.iter().map(|l| l.to_string()).collect();
+        ReceiptTemplate {
+            total_labels: labels(&["total", "amount due", "合计", "总计", "实收", "应付"]),
+            tax_labels: labels(&["tax", "vat", "gst", "税额", "税"]),
+            ignored_labels: labels(&["subtotal", "sub-total", "sub total", "小计"]),
+            day_first: false,
+        }
+    }
+}
+
+impl ReceiptTemplate {
+    /**
+    Extracts the receipt fields from one page of results.
+
+    # Examples
+
+    ```no_run
+    use paddleocr::templates::ReceiptTemplate;
+    use paddleocr::OcrEngine;
+
+    let mut p = paddleocr::Ppocr::new(".../PaddleOCR-json.exe", None).unwrap();
+    let results = p.ocr_and_parse(Path::new("receipt.jpg").into()).unwrap();
+    let receipt = ReceiptTemplate::default().extract(&results);
+    if let Some(total) = receipt.total {
+        println!("paid {:.2} (confidence {})", total.value, total.score);
+    }
+    ```
+    */
+    pub fn extract(&self, results: &[ContentData]) -> Receipt {
+        let lines = layout::lines(results);
+        let has = |line: &Line, labels: &[String]| {
+            let text = line.text().to_lowercase();
+            labels.iter().any(|l| text.contains(&l.to_lowercase()))
+        };
+
+        let mut receipt = Receipt::default();
+        for line in &lines {
+            if receipt.date.is_none() {
+                receipt.date = line
+                    .items
+                    .iter()
+                    .find_map(|c| parse_date(&c.text, self.day_first).map(|d| Field::from(d, c)));
+            }
+            if has(line, &self.ignored_labels) {
+                continue;
+            }
+            // Tax first, so "Total tax" is not taken for the total.
+            if has(line, &self.tax_labels) {
+                receipt.tax = labelled_amount(line).or(receipt.tax);
+            } else if has(line, &self.total_labels) {
+                receipt.total = labelled_amount(line).or(receipt.total);
+            }
+        }
+        receipt.merchant = lines.iter().find_map(|line| {
+            let first = line.items.first()?;
+            let text = line.text();
+            let plain = parse_date(&text, self.day_first).is_none()
+                && parse_amount(&text).is_none()
+                && text.chars().any(char::is_alphabetic);
+            plain.then(|| Field::from(text, first))
+        });
+        receipt
+    }
+}
+
+/// The amount on a labelled line: the last number on it, which follows the label.
+fn labelled_amount(line: &Line) -> Option<Field<f64>> {
+    line.items
+        .iter()
+        .rev()
+        .find_map(|c| parse_amount(&c.text).map(|amount| Field::from(amount, c)))
+}
+
+/**
+ * The last number in `text`, reading `,` as a thousands separator unless it is followed by exactly two digits and
+ * is the only separator, as in European `12,50`.
+ */
+pub fn parse_amount(text: &str) -> Option<f64> {
+    let number = text
+        .split(|c: char| !(c.is_ascii_digit() || c == '.' || c == ','))
+        .rfind(|run| run.chars().any(|c| c.is_ascii_digit()))?
+        .trim_matches(|c| c == '.' || c == ',');
+    let normalized = match (number.rfind('.'), number.rfind(',')) {
+        (Some(dot), Some(comma)) if comma > dot => number.replace('.', "").replace(',', "."),
+        (None, Some(comma)) if number.len() - comma == 3 && number.matches(',').count() == 1 => {
+            number.replace(',', ".")
+        }
+        _ => number.replace(',', ""),
+    };
+    normalized.parse().ok()
+}
+
+/**
+ * The first date in `text`, written as `2024-03-05`, `2024/3/5`, `2024.03.05`, `2024年3月5日` or, with the year
+ * last, `03/05/2024`, which `day_first` decides how to read when both numbers could be a month.
+ */
+pub fn parse_date(text: &str, day_first: bool) -> Option<Date> {
+    let numbers: Vec<&str> = text
+        .split(|c: char| !c.is_ascii_digit())
+        .filter(|n| !n.is_empty())
+        .collect();
+    numbers.windows(3).find_map(|w| {
+        let [a, b, c] = [w[0], w[1], w[2]].map(|n| n.parse::<u16>().ok());
+        let (a, b, c) = (a?, b?, c?);
+        let (year, month, day) = if w[0].len() == 4 {
+            (a, b, c)
+        } else if w[2].len() == 4 {
+            if (day_first && b <= 12) || a > 12 {
+                (c, b, a)
+            } else {
+                (c, a, b)
+            }
+        } else {
+            return None;
+        };
+        let date = Date {
+            year,
+            month: u8::try_from(month).ok()?,
+            day: u8::try_from(day).ok()?,
+        };
+        ((1..=12).contains(&date.month) && (1..=31).contains(&date.day)).then_some(date)
+    })
+}
+
+/**
+ * A named field of a card-like document, at a fixed position relative to the card: `region` is `[left, top, right,
+ * bottom]`, each from 0 at the card's top-left corner to 1 at its bottom-right one.
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct CardField {
+    pub name: String,
+    pub region: [f64; 4],
+}
+
+/**
+Extraction of fixed-layout documents such as ID cards, driving licences or membership cards, whose fields always sit
+at the same place on the card.
+
+# Examples
+
+```no_run
+use paddleocr::templates::CardTemplate;
+use paddleocr::OcrEngine;
+
+let template = CardTemplate::new()
+    .field("name", [0.05, 0.15, 0.6, 0.3])
+    .field("number", [0.3, 0.8, 0.95, 0.95]);
+let mut p = paddleocr::Ppocr::new(".../PaddleOCR-json.exe", None).unwrap();
+let results = p.ocr_and_parse(Path::new("card.jpg").into()).unwrap();
+let fields = template.extract(&results);
+println!("{:?}", fields.get("number").map(|f| &f.value));
+```
+*/
+#[derive(Debug, Clone, Default)]
+pub struct CardTemplate {
+    pub fields: Vec<CardField>,
+}
+
+impl CardTemplate {
+    pub fn new() -> CardTemplate {
+        CardTemplate::default()
+    }
+
+    /**
+     * Adds a field; see [`CardField`] for `region`.
+     */
+    pub fn field<S: Into<String>>(mut self, name: S, region: [f64; 4]) -> CardTemplate {
+        self.fields.push(CardField {
+            name: name.into(),
+            region,
+        });
+        self
+    }
+
+    /**
+     * Extracts the fields from a photo or scan in which the card is upright and takes up the area spanned by the
+     * detections, as with a tightly cropped scan.
+     */
+    pub fn extract(&self, results: &[ContentData]) -> HashMap<String, Field<String>> {
+        let Some(bounds) = results
+            .iter()
+            .map(|c| c.rect.aabb())
+            .reduce(|a, b| a.union(&b))
+        else {
+            return HashMap::new();
+        };
+        let corners = [
+            [bounds.x, bounds.y],
+            [bounds.right(), bounds.y],
+            [bounds.right(), bounds.bottom()],
+            [bounds.x, bounds.bottom()],
+        ];
+        self.extract_in(results, corners.map(|[x, y]| [x as f64, y as f64]))
+    }
+
+    /**
+     * Extracts the fields from a photo in which the card's corners, top-left, top-right, bottom-right and
+     * bottom-left, are at `corners`, e.g. as found by a document detector. Perspective from photographing the card
+     * at an angle is corrected for, so fields are found wherever the card is in the image.
+     */
+    pub fn extract_in(
+        &self,
+        results: &[ContentData],
+        corners: [[f64; 2]; 4],
+    ) -> HashMap<String, Field<String>> {
+        let Some(to_card) = homography(corners, UNIT_SQUARE) else {
+            return HashMap::new();
+        };
+        let mut fields = HashMap::new();
+        for field in &self.fields {
+            let [left, top, right, bottom] = field.region;
+            let inside: Vec<ContentData> = results
+                .iter()
+                .filter(|c| {
+                    let (x, y) = c.rect.aabb().center();
+                    let [u, v] = to_card([x, y]);
+                    (left..right).contains(&u) && (top..bottom).contains(&v)
+                })
+                .cloned()
+                .collect();
+            let lines = layout::lines(&inside);
+            let Some(bounds) = inside
+                .iter()
+                .map(|c| c.rect.aabb())
+                .reduce(|a, b| a.union(&b))
+            else {
+                continue;
+            };
+            let score = inside.iter().map(|c| c.score).min_by(Confidence::total_cmp);
+            fields.insert(
+                field.name.clone(),
+                Field {
+                    value: lines.iter().map(Line::text).collect::<Vec<_>>().join(" "),
+                    rect: rectangle(bounds),
+                    score: score.unwrap_or_default(),
+                },
+            );
+        }
+        fields
+    }
+}
+
+fn rectangle(b: Aabb) -> Rectangle {
+    [
+        [b.x, b.y],
+        [b.right(), b.y],
+        [b.right(), b.bottom()],
+        [b.x, b.bottom()],
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_amount, parse_date, CardTemplate, Date, ReceiptTemplate};
+    use crate::{detection, ContentData};
+
+    fn item(text: &str, x: usize, y: usize) -> ContentData {
+        let rect = [[x, y], [x + 100, y], [x + 100, y + 20], [x, y + 20]];
+        detection(text, rect, 0.9)
+    }
+
+    #[test]
+    fn amounts_and_dates() {
+        assert_eq!(parse_amount("Total: $1,234.50"), Some(1234.5));
+        assert_eq!(parse_amount("12,50 €"), Some(12.5));
+        assert_eq!(parse_amount("1.234,50"), Some(1234.5));
+        assert_eq!(parse_amount("￥88."), Some(88.0));
+        assert_eq!(parse_amount("Thank you"), None);
+
+        let date = |year, month, day| Some(Date { year, month, day });
+        assert_eq!(parse_date("2024年3月5日 14:02", false), date(2024, 3, 5));
+        assert_eq!(parse_date("Date: 03/05/2024", false), date(2024, 3, 5));
+        assert_eq!(parse_date("Date: 03/05/2024", true), date(2024, 5, 3));
+        assert_eq!(parse_date("25/12/2023", false), date(2023, 12, 25));
+        assert_eq!(parse_date("Tel 0755-8888-1234", false), None);
+    }
+
+    #[test]
+    fn receipt() {
+        let results = [
+            item("Corner Cafe", 0, 0),
+            item("2024-03-05 09:41", 0, 30),
+            item("Latte", 0, 60),
+            item("4.50", 300, 60),
+            item("Subtotal", 0, 90),
+            item("9.00", 300, 90),
+            item("Tax", 0, 120),
+            item("0.72", 300, 120),
+            item("TOTAL", 0, 150),
+            item("9.72", 300, 150),
+        ];
+        let receipt = ReceiptTemplate::default().extract(&results);
+        assert_eq!(receipt.merchant.unwrap().value, "Corner Cafe");
+        assert_eq!(
+            receipt.date.unwrap().value,
+            Date {
+                year: 2024,
+                month: 3,
+                day: 5
+            }
+        );
+        assert_eq!(receipt.tax.unwrap().value, 0.72);
+        let total = receipt.total.unwrap();
+        assert_eq!(total.value, 9.72);
+        assert_eq!(total.rect, results[9].rect);
+    }
+
+    #[test]
+    fn card() {
+        let template = CardTemplate::new()
+            .field("name", [0.0, 0.0, 0.5, 0.5])
+            .field("number", [0.5, 0.5, 1.0, 1.0]);
+
+        let results = [
+            item("Jane", 0, 0),
+            item("Doe", 110, 0),
+            item("No. 1234", 300, 180),
+        ];
+        let fields = template.extract(&results);
+        assert_eq!(fields["name"].value, "Jane Doe");
+        assert_eq!(fields["name"].rect, [[0, 0], [210, 0], [210, 20], [0, 20]]);
+        assert_eq!(fields["number"].value, "No. 1234");
+
+        // The same card photographed at an angle: a trapezoid narrowing towards the top.
+        let corners = [
+            [1100.0, 1000.0],
+            [1300.0, 1000.0],
+            [1400.0, 1200.0],
+            [1000.0, 1200.0],
+        ];
+        let results = [item("Jane", 1100, 1010), item("No. 1234", 1250, 1150)];
+        let fields = template.extract_in(&results, corners);
+        assert_eq!(fields["name"].value, "Jane");
+        assert_eq!(fields["number"].value, "No. 1234");
+    }
+}
@@ -0,0 +1,264 @@
+//! Golden-file snapshot helpers for regression tests of OCR pipelines, enabled with the `testing` feature.
+//!
+//! Results are serialized deterministically, with sorted keys and rounded scores so that tiny numeric drift between
+//! runs or engine builds doesn't fail the comparison, and compared against a checked-in file. Run the tests with
+//! `PADDLEOCR_UPDATE_SNAPSHOTS=1` to (re)write the snapshot files instead.
+//!
+//! The [`strategy`] module generates random results for property tests of code consuming them, with [`proptest`].
+//!
+//! ```no_run
+//! # let mut p: paddleocr::Ppocr = todo!();
+//! let response = p.ocr_response(std::path::Path::new("tests/images/receipt.png").into()).unwrap();
+//! paddleocr::testing::assert_snapshot("tests/snapshots/receipt.json", &response);
+//! ```
+
+use std::path::Path;
+
+use serde::Serialize;
+use serde_json::Value;
+
+/// Environment variable that makes [`assert_snapshot`] write snapshots instead of comparing against them.
+pub const UPDATE_ENV: &str = "PADDLEOCR_UPDATE_SNAPSHOTS";
+
+/// Number of decimals scores are rounded to by [`to_snapshot`].
+pub const SCORE_DECIMALS: i32 = 3;
+
+/**
+ * Serializes `value` as pretty-printed JSON with sorted object keys, and every `score` field rounded to
+ * [`SCORE_DECIMALS`] decimals.
+ */
+pub fn to_snapshot<T: Serialize + ?Sized>(value: &T) -> String {
+    let mut value = serde_json::to_value(value).unwrap_or(Value::Null);
+    round_scores(&mut value);
+    // `serde_json::Map` keeps keys sorted unless the `preserve_order` feature is enabled somewhere in the build, so
+    // sort explicitly.
+    let value = sort_keys(value);
+    let mut snapshot = serde_json::to_string_pretty(&value).unwrap_or_default();
+    snapshot.push('\n');
+    snapshot
+}
+
+fn round_scores(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            for (key, v) in map.iter_mut() {
+                match v.as_f64() {
+                    Some(score) if key == "score" => {
+                        let factor = 10f64.powi(SCORE_DECIMALS);
+                        *v = Value::from((score * factor).round() / factor);
+                    }
+                    _ => round_scores(v),
+                }
+            }
+        }
+        Value::Array(values) => values.iter_mut().for_each(round_scores),
+        _ => {}
+    }
+}
+
+fn sort_keys(value: Value) -> Value {
+    match value {
+        Value::Object(map) => {
+            let mut entries: Vec<_> = map.into_iter().collect();
+            entries.sort_by(|a, b| a.0.cmp(&b.0));
+            Value::Object(
+                entries
+                    .into_iter()
+                    .map(|(k, v)| (k, sort_keys(v)))
+                    .collect(),
+            )
+        }
+        Value::Array(values) => Value::Array(values.into_iter().map(sort_keys).collect()),
+        value => value,
+    }
+}
+
+/**
+ * Compares the snapshot of `value` against the file at `path`, panicking with a line diff if they differ.
+ *
+ * The file is written instead when it doesn't exist yet or [`UPDATE_ENV`] is set.
+ */
+#[track_caller]
+pub fn assert_snapshot<P: AsRef<Path>, T: Serialize + ?Sized>(path: P, value: &T) {
+    let path = path.as_ref();
+    let actual = to_snapshot(value);
+    let update = std::env::var_os(UPDATE_ENV).is_some_and(|v| !v.is_empty() && v != "0");
+    if update || !path.exists() {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).ok();
+        }
+        if let Err(e) = std::fs::write(path, &actual) {
+            panic!("failed to write snapshot {}: {}", path.display(), e);
+        }
+        return;
+    }
+    let expected = match std::fs::read_to_string(path) {
+        Ok(expected) => expected.replace("\r\n", "\n"),
+        Err(e) => panic!("failed to read snapshot {}: {}", path.display(), e),
+    };
+    if expected != actual {
+        panic!(
+            "snapshot {} does not match (set {}=1 to update):\n{}",
+            path.display(),
+            UPDATE_ENV,
+            diff(&expected, &actual)
+        );
+    }
+}
+
+/**
+ * A line diff of two texts: unchanged lines are prefixed with two spaces, removed ones with `- ` and added ones
+ * with `+ `.
+ */
+pub fn diff(old: &str, new: &str) -> String {
+    let old: Vec<_> = old.lines().collect();
+    let new: Vec<_> = new.lines().collect();
+    // Longest common subsequence table, filled from the end.
+    let mut lcs = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lcs[i][j] = if old[i] == new[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut out = String::new();
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            out += &format!("  {}\n", old[i]);
+            i += 1;
+            j += 1;
+        } else if i < old.len() && (j == new.len() || lcs[i + 1][j] >= lcs[i][j + 1]) {
+            out += &format!("- {}\n", old[i]);
+            i += 1;
+        } else {
+            out += &format!("+ {}\n", new[j]);
+            j += 1;
+        }
+    }
+    out
+}
+
+/**
+ * Strategies generating random detections, for property tests with [`proptest`].
+ *
+ * Failing cases shrink towards fewer detections, smaller boxes near the origin, and shorter texts. Boxes are
+ * axis-aligned or slightly rotated quadrilaterals in the engine's corner order, within a 2000×2000 canvas.
+ *
+ * ```
+ * use paddleocr::testing::strategy;
+ * use proptest::prelude::*;
+ *
+ * proptest!(|(results in strategy::results(10))| {
+ *     // assert invariants of the code under test...
+ * });
+ * ```
+ */
+pub mod strategy {
+    use proptest::prelude::*;
+
+    use crate::{Confidence, ContentData, Rectangle};
+
+    /**
+     * A confidence score in `0.0..=1.0`.
+     */
+    pub fn score() -> impl Strategy<Value = Confidence> {
+        (0.0..=1.0f64).prop_map(Confidence)
+    }
+
+    /**
+     * A box of at least 1×1 pixels, possibly skewed by a few pixels like a slightly rotated text line.
+     */
+    pub fn rectangle() -> impl Strategy<Value = Rectangle> {
+        (
+            10..1810usize,
+            10..1910usize,
+            1..181usize,
+            1..61usize,
+            0..10usize,
+            any::<bool>(),
+        )
+            .prop_map(|(x, y, w, h, skew, right_lower)| {
+                let (top, bottom) = if right_lower {
+                    (y, y + skew)
+                } else {
+                    (y + skew, y)
+                };
+                [[x, top], [x + w, bottom], [x + w, bottom + h], [x, top + h]]
+            })
+    }
+
+    /**
+     * A short ASCII text of one to three words.
+     */
+    pub fn text() -> impl Strategy<Value = String> {
+        const WORDS: [&str; 8] = ["lorem", "ipsum", "dolor", "sit", "amet", "42", "OCR", "x"];
+        prop::collection::vec(prop::sample::select(WORDS.to_vec()), 1..=3)
+            .prop_map(|words| words.join(" "))
+    }
+
+    pub fn content_data() -> impl Strategy<Value = ContentData> {
+        (rectangle(), score(), text()).prop_map(|(rect, score, text)| ContentData {
+            rect,
+            score,
+            text,
+            orientation: None,
+            extra: Default::default(),
+        })
+    }
+
+    /**
+     * Up to `max_len` detections.
+     */
+    pub fn results(max_len: usize) -> impl Strategy<Value = Vec<ContentData>> {
+        prop::collection::vec(content_data(), 0..=max_len)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{assert_snapshot, diff, to_snapshot};
+    use crate::OcrRec;
+
+    fn rec(score: f64, text: &str) -> OcrRec {
+        serde_json::from_str(&format!(
+            r#"{{"code":100,"data":[{{"text":"{}","score":{},"box":[[0,0],[1,0],[1,1],[0,1]]}}]}}"#,
+            text, score
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn deterministic() {
+        let snapshot = to_snapshot(&rec(0.99964427, "hi"));
+        assert!(snapshot.contains(r#""score": 1.0"#));
+        let keys: Vec<_> = snapshot
+            .lines()
+            .filter_map(|l| l.trim().strip_prefix('"')?.split('"').next())
+            .collect();
+        assert_eq!(keys, ["code", "data", "box", "score", "text"]);
+        assert_eq!(snapshot, to_snapshot(&rec(0.9998, "hi")));
+    }
+
+    #[test]
+    fn snapshot_files() {
+        let path =
+            std::env::temp_dir().join(format!("paddleocr-snapshot-{}.json", std::process::id()));
+        assert_snapshot(&path, &rec(0.5, "hi"));
+        assert_snapshot(&path, &rec(0.5, "hi"));
+        let mismatch = std::panic::catch_unwind(|| assert_snapshot(&path, &rec(0.5, "ho")));
+        std::fs::remove_file(&path).unwrap();
+        let message = *mismatch.unwrap_err().downcast::<String>().unwrap();
+        assert!(message.contains("-       \"text\": \"hi\"\n+       \"text\": \"ho\""));
+    }
+
+    #[test]
+    fn line_diff() {
+        assert_eq!(diff("a\nb\nc", "a\nx\nc"), "  a\n- b\n+ x\n  c\n");
+        assert_eq!(diff("", "a"), "+ a\n");
+    }
+}
@@ -0,0 +1,214 @@
+//! OCR of images too large for one pass, such as stitched long screenshots or big scans, by splitting them into
+//! overlapping tiles.
+//!
+//! Decoding and cropping stay with the caller, who knows the image format and may already have the pixels in
+//! memory: [`ocr_tiled`] plans the tiles, asks for each one as [`ImageData`], maps the detections back to image
+//! coordinates and removes the duplicates found in the overlaps.
+
+use crate::geometry::{Aabb, CoordTransform, RectangleExt};
+use crate::{ContentData, ImageData, OcrEngine, OcrResponse};
+
+/**
+ * Splits a `width`×`height` image into tiles of at most `tile_size`×`tile_size`, each overlapping its neighbours by
+ * `overlap` pixels. Tiles are ordered row by row; the last row and column are shifted back to stay within the image
+ * rather than shrunk.
+ */
+pub fn plan_tiles(width: usize, height: usize, tile_size: usize, overlap: usize) -> Vec<Aabb> {
+    let tile_size = tile_size.max(1);
+    let stride = tile_size.saturating_sub(overlap).max(1);
+    let starts = |length: usize| -> Vec<usize> {
+        if length <= tile_size {
+            return vec![0];
+        }
+        let last = length - tile_size;
+        let mut starts: Vec<usize> = (0..last).step_by(stride).collect();
+        starts.push(last);
+        starts
+    };
+    let mut tiles = Vec::new();
+    for &y in &starts(height) {
+        for &x in &starts(width) {
+            tiles.push(Aabb {
+                x,
+                y,
+                w: tile_size.min(width),
+                h: tile_size.min(height),
+            });
+        }
+    }
+    tiles
+}
+
+/**
+OCRs a `width`×`height` image tile by tile. `crop` is called with each tile's region and returns that part of the
+image, e.g. encoded with [`ImageData::from_bytes`].
+
+The overlap should be at least the height of a text line, so that every line lies entirely within some tile; the
+truncated copies of it in neighbouring tiles are then dropped in favour of the complete one.
+
+# Examples
+
+```no_run
+let mut p = paddleocr::Ppocr::new(".../PaddleOCR-json.exe", None).unwrap();
+let image = image::open("long-screenshot.png").unwrap();
+let results = paddleocr::tiling::ocr_tiled(&mut p, (image.width() as usize, image.height() as usize), 2000, 200, |tile| {
+    let mut png = Vec::new();
+    image
+        .crop_imm(tile.x as u32, tile.y as u32, tile.w as u32, tile.h as u32)
+        .write_to(&mut std::io::Cursor::new(&mut png), image::ImageFormat::Png)
+        .unwrap();
+    paddleocr::ImageData::from_bytes(png)
+})
+.unwrap();
+```
+*/
+pub fn ocr_tiled<E, F>(
+    engine: &mut E,
+    (width, height): (usize, usize),
+    tile_size: usize,
+    overlap: usize,
+    mut crop: F,
+) -> Result<Vec<ContentData>, String>
+where
+    E: OcrEngine + ?Sized,
+    F: FnMut(Aabb) -> ImageData,
+{
+    let mut detections = Vec::new();
+    for tile in plan_tiles(width, height, tile_size, overlap) {
+        let response: OcrResponse = engine.ocr_response(crop(tile))?;
+        if response.code != 100 && response.code != 101 {
+            return Err(format!(
+                "Error Message {} in tile at ({}, {}): {}",
+                response.code,
+                tile.x,
+                tile.y,
+                response.message.unwrap_or_default()
+            ));
+        }
+        let back = CoordTransform::cropped(tile.x, tile.y);
+        detections.extend(response.data.into_iter().map(|mut c| {
+            c.rect = back.apply(&c.rect);
+            (tile, c)
+        }));
+    }
+    Ok(deduplicate(detections))
+}
+
+/**
+ * Drops detections that mostly lie within a larger one from another, overlapping tile, keeping the more complete
+ * copy of text seen by several tiles. `detections` are paired with the tile they were found in; those of one tile
+ * are all kept, such as a short line nested in a longer one. The order of the remaining detections is preserved.
+ */
+pub fn deduplicate(detections: Vec<(Aabb, ContentData)>) -> Vec<ContentData> {
+    let boxes: Vec<Aabb> = detections.iter().map(|(_, c)| c.rect.aabb()).collect();
+    let dominated = |i: usize| {
+        boxes.iter().enumerate().any(|(j, other)| {
+            let (tile, other_tile) = (detections[i].0, detections[j].0);
+            if tile == other_tile || tile.intersection(&other_tile).is_none() {
+                return false;
+            }
+            let Some(overlap) = boxes[i].intersection(other) else {
+                return false;
+            };
+            let (area, other_area) = (boxes[i].area(), other.area());
+            let covered = overlap.area() as f64 / area.min(other_area).max(1) as f64;
+            // the smaller box goes; between equal boxes, the lower score, then the later one
+            let loses = (area, detections[i].1.score.value(), j)
+                < (other_area, detections[j].1.score.value(), i);
+            covered >= 0.8 && loses
+        })
+    };
+    let keep: Vec<bool> = (0..detections.len()).map(|i| !dominated(i)).collect();
+    detections
+        .into_iter()
+        .zip(keep)
+        .filter_map(|((_, c), keep)| keep.then_some(c))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{deduplicate, ocr_tiled, plan_tiles};
+    use crate::geometry::Aabb;
+    use crate::{detection, ImageData, OcrEngine};
+
+    #[test]
+    fn tile_plan() {
+        assert_eq!(
+            plan_tiles(500, 300, 1000, 100),
+            [Aabb {
+                x: 0,
+                y: 0,
+                w: 500,
+                h: 300
+            }]
+        );
+        let tiles = plan_tiles(2500, 1000, 1000, 200);
+        let xs: Vec<_> = tiles.iter().map(|t| t.x).collect();
+        assert_eq!(xs, [0, 800, 1500]);
+        assert!(tiles.iter().all(|t| t.y == 0 && t.w == 1000 && t.h == 1000));
+        assert_eq!(plan_tiles(10, 3000, 1000, 0).len(), 3);
+    }
+
+    /// Pretends to be an engine looking at a 1000×100 image holding one line of text at x 400..600.
+    struct OneLine;
+    impl OcrEngine for OneLine {
+        fn ocr(&mut self, image: ImageData) -> std::io::Result<String> {
+            let ImageData::ImagePathDict { image_path } = image else {
+                unreachable!()
+            };
+            let x: usize = image_path.parse().unwrap();
+            let (left, right) = (400usize.saturating_sub(x).min(500), (600 - x).min(500));
+            if right <= left {
+                return Ok(r#"{"code":101,"data":"No text found in image."}"#.to_string());
+            }
+            let text = if right - left == 200 { "whole" } else { "cut" };
+            Ok(format!(
+                r#"{{"code":100,"data":[{{"box":[[{left},10],[{right},10],[{right},30],[{left},30]],"score":0.9,"text":"{text}"}}]}}"#
+            ))
+        }
+    }
+
+    #[test]
+    fn merges_tiles() {
+        let results = ocr_tiled(&mut OneLine, (1000, 100), 500, 250, |tile| {
+            ImageData::from_path(tile.x.to_string())
+        })
+        .unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].text, "whole");
+        assert_eq!(
+            results[0].rect,
+            [[400, 10], [600, 10], [600, 30], [400, 30]]
+        );
+    }
+
+    #[test]
+    fn nested_detections() {
+        let tile = |x| Aabb {
+            x,
+            y: 0,
+            w: 500,
+            h: 100,
+        };
+        let line = |text, left, right| {
+            detection(
+                text,
+                [[left, 10], [right, 10], [right, 30], [left, 30]],
+                0.9,
+            )
+        };
+        let kept = deduplicate(vec![
+            // A line and a shorter one nested in it, found in the same tile.
+            (tile(0), line("Total: 12.50", 10, 200)),
+            (tile(0), line("12.50", 120, 200)),
+            // A cut copy of a line in the next, overlapping tile, and the whole one.
+            (tile(250), line("cut", 400, 500)),
+            (tile(0), line("whole", 300, 500)),
+            // A tile that doesn't overlap the first.
+            (tile(600), line("apart", 150, 190)),
+        ]);
+        let texts: Vec<&str> = kept.iter().map(|c| c.text.as_str()).collect();
+        assert_eq!(texts, ["Total: 12.50", "12.50", "whole", "apart"]);
+    }
+}
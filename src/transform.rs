@@ -0,0 +1,138 @@
+//! Post-processing applied to parsed results by [`Ppocr::ocr_and_parse`](crate::Ppocr::ocr_and_parse).
+//!
+//! Register transforms once with [`Ppocr::add_transform`](crate::Ppocr::add_transform) instead of repeating the
+//! same sorting or filtering after every call. Transforms run in the order they were added.
+
+use crate::ContentData;
+
+/**
+ * A post-processing step over the detections of one image.
+ */
+pub trait ResultTransform {
+    fn transform(&self, data: Vec<ContentData>) -> Vec<ContentData>;
+}
+
+impl<F> ResultTransform for F
+where
+    F: Fn(Vec<ContentData>) -> Vec<ContentData>,
+{
+    fn transform(&self, data: Vec<ContentData>) -> Vec<ContentData> {
+        self(data)
+    }
+}
+
+/**
+ * Drops detections scoring below the given confidence.
+ */
+#[derive(Debug, Clone, Copy)]
+pub struct MinScore(pub f64);
+
+impl ResultTransform for MinScore {
+    fn transform(&self, mut data: Vec<ContentData>) -> Vec<ContentData> {
+        data.retain(|c| c.score >= self.0);
+        data
+    }
+}
+
+/**
+ * Sorts detections top to bottom, then left to right, by the top-left corner of their boxes.
+ */
+#[derive(Debug, Clone, Copy)]
+pub struct SortByPosition;
+
+impl ResultTransform for SortByPosition {
+    fn transform(&self, mut data: Vec<ContentData>) -> Vec<ContentData> {
+        data.sort_by_key(|c| (c.rect[0][1], c.rect[0][0]));
+        data
+    }
+}
+
+/**
+ * Trims surrounding whitespace from each text, dropping detections left empty.
+ */
+#[derive(Debug, Clone, Copy)]
+pub struct TrimText;
+
+impl ResultTransform for TrimText {
+    fn transform(&self, data: Vec<ContentData>) -> Vec<ContentData> {
+        data.into_iter()
+            .filter_map(|mut c| {
+                let trimmed = c.text.trim();
+                if trimmed.is_empty() {
+                    return None;
+                }
+                if trimmed.len() != c.text.len() {
+                    c.text = trimmed.to_string();
+                }
+                Some(c)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::{prop_assert, prop_assert_eq, proptest};
+
+    use super::{MinScore, ResultTransform, SortByPosition, TrimText};
+    use crate::testing::strategy;
+    use crate::{detection, ContentData};
+
+    fn content(x: usize, y: usize, score: f64, text: &str) -> ContentData {
+        detection(
+            text,
+            [[x, y], [x + 10, y], [x + 10, y + 10], [x, y + 10]],
+            score,
+        )
+    }
+
+    #[test]
+    fn builtin_transforms() {
+        let data = vec![
+            content(50, 0, 0.9, " b "),
+            content(0, 20, 0.9, "c"),
+            content(0, 0, 0.9, "a"),
+            content(0, 40, 0.2, "noise"),
+            content(0, 60, 0.9, "  "),
+        ];
+        let data = MinScore(0.5).transform(data);
+        let data = TrimText.transform(data);
+        let data = SortByPosition.transform(data);
+        let texts: Vec<_> = data.iter().map(|c| c.text.as_str()).collect();
+        assert_eq!(texts, ["a", "b", "c"]);
+    }
+
+    proptest! {
+        #[test]
+        fn sort_properties(data in strategy::results(30)) {
+            let sorted = SortByPosition.transform(data.clone());
+
+            // a permutation of the input...
+            let key = |c: &ContentData| format!("{:?}{}{}", c.rect, c.score, c.text);
+            let mut before: Vec<_> = data.iter().map(key).collect();
+            let mut after: Vec<_> = sorted.iter().map(key).collect();
+            before.sort();
+            after.sort();
+            prop_assert_eq!(&before, &after);
+
+            // ...that is ordered, and stays the same when sorted again
+            prop_assert!(sorted
+                .windows(2)
+                .all(|w| (w[0].rect[0][1], w[0].rect[0][0]) <= (w[1].rect[0][1], w[1].rect[0][0])));
+            let again = SortByPosition.transform(sorted.clone());
+            prop_assert_eq!(after.len(), again.len());
+            prop_assert!(sorted.iter().zip(&again).all(|(a, b)| key(a) == key(b)));
+        }
+
+        #[test]
+        fn filter_properties(data in strategy::results(30), threshold in strategy::score()) {
+            let threshold = threshold.value();
+            let kept = MinScore(threshold).transform(data.clone());
+            prop_assert!(kept.iter().all(|c| c.score >= threshold));
+            prop_assert_eq!(
+                kept.len(),
+                data.iter().filter(|c| c.score >= threshold).count()
+            );
+        }
+    }
+}
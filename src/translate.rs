@@ -0,0 +1,77 @@
+//! A hook for machine translation of recognized text, for screen-translation style pipelines.
+//!
+//! Set a [`Translator`] with [`Ppocr::set_translator`](crate::Ppocr::set_translator), then call
+//! [`Ppocr::ocr_and_translate`](crate::Ppocr::ocr_and_translate) to get the detections together with their
+//! translation.
+
+use crate::ContentData;
+
+/**
+ * A machine-translation backend, e.g. a client for an online service or a local model.
+ */
+pub trait Translator {
+    fn translate(&self, text: &str) -> Result<String, String>;
+}
+
+impl<F> Translator for F
+where
+    F: Fn(&str) -> Result<String, String>,
+{
+    fn translate(&self, text: &str) -> Result<String, String> {
+        self(text)
+    }
+}
+
+/**
+ * What the translator is called with.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TranslationUnit {
+    /// Once per image, with all texts joined by newlines. Gives the translator the most context.
+    #[default]
+    Joined,
+    /// Once per detection, so each translation can be drawn over its own box.
+    PerDetection,
+}
+
+/**
+ * Detections of an image together with their translation.
+ */
+#[derive(Debug, Clone)]
+pub struct Translated {
+    pub data: Vec<ContentData>,
+    /// One translation for [`TranslationUnit::Joined`], or one per entry of `data` for
+    /// [`TranslationUnit::PerDetection`].
+    pub translations: Vec<String>,
+}
+
+impl Translated {
+    /**
+     * All translations joined by newlines.
+     */
+    pub fn text(&self) -> String {
+        self.translations.join("\n")
+    }
+}
+
+pub(crate) fn translate(
+    translator: &dyn Translator,
+    unit: TranslationUnit,
+    data: Vec<ContentData>,
+) -> Result<Translated, String> {
+    let translations = match unit {
+        TranslationUnit::Joined => {
+            let text = data
+                .iter()
+                .map(|c| c.text.as_str())
+                .collect::<Vec<_>>()
+                .join("\n");
+            vec![translator.translate(&text)?]
+        }
+        TranslationUnit::PerDetection => data
+            .iter()
+            .map(|c| translator.translate(&c.text))
+            .collect::<Result<_, _>>()?,
+    };
+    Ok(Translated { data, translations })
+}
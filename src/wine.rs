@@ -0,0 +1,132 @@
+use std::error::Error;
+use std::path::{Path, PathBuf};
+use std::process;
+
+use crate::{ImageData, Ppocr};
+
+/**
+ * How to run the Windows engine through Wine.
+ */
+#[derive(Debug, Clone)]
+pub struct WineConfig {
+    /// The `wine` executable, `wine` from `PATH` by default.
+    pub wine_path: PathBuf,
+    /// The `WINEPREFIX` to run in, or Wine's default prefix if `None`.
+    pub prefix: Option<PathBuf>,
+}
+
+impl Default for WineConfig {
+    fn default() -> Self {
+        WineConfig {
+            wine_path: PathBuf::from("wine"),
+            prefix: None,
+        }
+    }
+}
+
+impl Ppocr {
+    /**
+    Initialize a new instance of the Windows engine, running it through Wine.
+
+    This is a stopgap for Linux users who only have the Windows binary. Absolute image paths passed with
+    [`ImageData::from_path`] are translated to Wine's `Z:\...` form before they are sent, as is `config_path`.
+
+    # Examples
+
+    ```no_run
+    let mut p = paddleocr::Ppocr::new_wine(
+        ".../PaddleOCR-json.exe", // path to binary
+        None, // language config_path, default `zh_CN`
+        &Default::default(), // `wine` from PATH, default prefix
+    )
+    .unwrap(); // initialize
+    println!("{}", p.ocr(Path::new("/home/me/test.png").into()).unwrap());
+    ```
+    */
+    pub fn new_wine<P, C>(
+        exe_path: P,
+        config_path: C,
+        wine: &WineConfig,
+    ) -> Result<Ppocr, Box<dyn Error>>
+    where
+        P: AsRef<Path>,
+        C: Into<Option<PathBuf>>,
+    {
+        let (exe_path, config_path) = (exe_path.as_ref(), config_path.into());
+        if !exe_path.exists() {
+            return Err(Box::new(std::io::Error::new(
+                std::io::ErrorKind::NotFound,
+                "Executable not found",
+            )));
+        }
+        let exe_path = exe_path.canonicalize()?;
+        let wd = exe_path
+            .parent()
+            .ok_or("No parent directory found")?
+            .to_path_buf();
+
+        let mut command = process::Command::new(&wine.wine_path);
+        command.current_dir(wd).arg(&exe_path);
+        // Wine is chatty on stderr, which would crowd the engine's own messages out of `ExitInfo::stderr_tail`.
+        command.env("WINEDEBUG", "-all");
+        if let Some(prefix) = &wine.prefix {
+            command.env("WINEPREFIX", prefix);
+        }
+        if let Some(config_path) = config_path {
+            command.args(["--config_path", &to_wine_path(&config_path)]);
+        }
+
+        let mut p = Ppocr::from_command(command)?;
+        p.exe_path = Some(exe_path);
+        p.wine_paths = true;
+        Ok(p)
+    }
+}
+
+/**
+ * Converts an absolute Unix path into the `Z:\...` form under which Wine exposes the root filesystem.
+ * Relative paths are only converted to backslashes, as the engine resolves them against its own directory.
+ */
+pub fn to_wine_path<P: AsRef<Path>>(path: P) -> String {
+    let path = path.as_ref().to_string_lossy();
+    let converted = path.replace('/', "\\");
+    if path.starts_with('/') {
+        format!("Z:{}", converted)
+    } else {
+        converted
+    }
+}
+
+impl ImageData {
+    pub(crate) fn into_wine(self) -> ImageData {
+        match self {
+            ImageData::ImagePathDict { image_path } if image_path.starts_with('/') => {
+                ImageData::ImagePathDict {
+                    image_path: to_wine_path(image_path),
+                }
+            }
+            image => image,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::to_wine_path;
+    use crate::ImageData;
+
+    #[test]
+    fn wine_paths() {
+        assert_eq!(to_wine_path("/home/me/a b.png"), r"Z:\home\me\a b.png");
+        assert_eq!(
+            to_wine_path("models/config_en.txt"),
+            r"models\config_en.txt"
+        );
+
+        let ImageData::ImagePathDict { image_path } = ImageData::from_path("clipboard").into_wine()
+        else {
+            unreachable!()
+        };
+        assert_eq!(image_path, "clipboard");
+    }
+}
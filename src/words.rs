@@ -0,0 +1,261 @@
+//! Word and character boxes estimated from line boxes, for click-to-select, highlighting and hit-testing.
+//!
+//! The engine only reports a box per text line. These helpers place each character along the line's quadrilateral
+//! in proportion to its width, full-width (CJK) characters counting double, which is close for printed text in a
+//! single font and drifts a little on proportional fonts.
+
+use std::ops::Range;
+
+use crate::{ContentData, Rectangle};
+
+/**
+ * A word of a detection, with its estimated box.
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Word<'a> {
+    pub text: &'a str,
+    /// The word's characters (not bytes) in the detection's text.
+    pub chars: Range<usize>,
+    pub rect: Rectangle,
+}
+
+/// Whether `c` takes two columns, as CJK characters and full-width forms do.
+pub(crate) fn is_wide(c: char) -> bool {
+    matches!(c as u32,
+        0x1100..=0x115f
+        | 0x2e80..=0x303e
+        | 0x3041..=0x33ff
+        | 0x3400..=0x4dbf
+        | 0x4e00..=0x9fff
+        | 0xa000..=0xa4cf
+        | 0xac00..=0xd7a3
+        | 0xf900..=0xfaff
+        | 0xfe30..=0xfe4f
+        | 0xff00..=0xff60
+        | 0xffe0..=0xffe6
+        | 0x20000..=0x3fffd)
+}
+
+/**
+ * Where each character of `item` starts along its line, from `0.0` to `1.0`, followed by `1.0` for the end of the
+ * last one. Vertical text gives every character the same height.
+ */
+fn offsets(item: &ContentData) -> Vec<f64> {
+    let vertical = item.is_vertical();
+    let mut offsets = vec![0.0];
+    let mut total = 0.0;
+    for c in item.text.chars() {
+        total += if !vertical && is_wide(c) { 2.0 } else { 1.0 };
+        offsets.push(total);
+    }
+    if total > 0.0 {
+        offsets.iter_mut().for_each(|offset| *offset /= total);
+    }
+    offsets
+}
+
+/// The point a fraction `t` of the way from `a` to `b`.
+fn lerp(a: [usize; 2], b: [usize; 2], t: f64) -> [f64; 2] {
+    [
+        a[0] as f64 + (b[0] as f64 - a[0] as f64) * t,
+        a[1] as f64 + (b[1] as f64 - a[1] as f64) * t,
+    ]
+}
+
+/**
+ * The part of `rect` from a fraction `start` to `end` of the way along its reading direction: left to right, or
+ * top to bottom for `vertical` text.
+ */
+fn slice(rect: &Rectangle, vertical: bool, start: f64, end: f64) -> Rectangle {
+    let [a, b, c, d] = *rect;
+    // The two edges the text runs along, both in reading direction.
+    let (first, second) = if vertical {
+        ((a, d), (b, c))
+    } else {
+        ((a, b), (d, c))
+    };
+    let round = |[x, y]: [f64; 2]| [x.round().max(0.0) as usize, y.round().max(0.0) as usize];
+    let (p0, p1) = (lerp(first.0, first.1, start), lerp(first.0, first.1, end));
+    let (p2, p3) = (
+        lerp(second.0, second.1, end),
+        lerp(second.0, second.1, start),
+    );
+    if vertical {
+        // Keep the corners clockwise from the top left.
+        [round(p0), round(p3), round(p2), round(p1)]
+    } else {
+        [round(p0), round(p1), round(p2), round(p3)]
+    }
+}
+
+/**
+ * The estimated box of the characters `chars` of `item`, for highlighting part of a line.
+ */
+pub fn span(item: &ContentData, chars: Range<usize>) -> Rectangle {
+    let offsets = offsets(item);
+    let last = offsets.len() - 1;
+    let (start, end) = (
+        chars.start.min(last),
+        chars.end.clamp(chars.start.min(last), last),
+    );
+    slice(&item.rect, item.is_vertical(), offsets[start], offsets[end])
+}
+
+/**
+ * Splits the text of `item` into words, with their estimated boxes. Words are separated by whitespace, and each
+ * CJK character is a word of its own, since such text isn't spaced.
+ */
+pub fn words(item: &ContentData) -> Vec<Word<'_>> {
+    let offsets = offsets(item);
+    let vertical = item.is_vertical();
+    let mut words = Vec::new();
+    // The byte and character index of the start of the current word, if in one.
+    let mut start: Option<(usize, usize)> = None;
+    let mut push = |from: (usize, usize), to: (usize, usize)| {
+        words.push(Word {
+            text: &item.text[from.0..to.0],
+            chars: from.1..to.1,
+            rect: slice(&item.rect, vertical, offsets[from.1], offsets[to.1]),
+        })
+    };
+    for (i, (byte, c)) in item.text.char_indices().enumerate() {
+        if c.is_whitespace() || is_wide(c) {
+            if let Some(from) = start.take() {
+                push(from, (byte, i));
+            }
+            if is_wide(c) && !c.is_whitespace() {
+                push((byte, i), (byte + c.len_utf8(), i + 1));
+            }
+        } else if start.is_none() {
+            start = Some((byte, i));
+        }
+    }
+    if let Some(from) = start {
+        push(from, (item.text.len(), offsets.len() - 1));
+    }
+    words
+}
+
+/// Whether `point` is inside the convex quadrilateral `rect`, or on its edges.
+pub(crate) fn contains(rect: &Rectangle, [x, y]: [usize; 2]) -> bool {
+    let (x, y) = (x as f64, y as f64);
+    let crosses = (0..4).map(|i| {
+        let ([ax, ay], [bx, by]) = (rect[i], rect[(i + 1) % 4]);
+        let (ax, ay, bx, by) = (ax as f64, ay as f64, bx as f64, by as f64);
+        (bx - ax) * (y - ay) - (by - ay) * (x - ax)
+    });
+    let (mut positive, mut negative) = (false, false);
+    for cross in crosses {
+        positive |= cross > 0.0;
+        negative |= cross < 0.0;
+    }
+    !(positive && negative)
+}
+
+/**
+ * The detection of `results` and the character of its text under `point`, as indices, for mapping a click on an
+ * overlay back to the text. Of overlapping detections, the smallest wins.
+ */
+pub fn hit_test(results: &[ContentData], point: [usize; 2]) -> Option<(usize, usize)> {
+    let (index, item) = results
+        .iter()
+        .enumerate()
+        .filter(|(_, item)| !item.text.is_empty() && contains(&item.rect, point))
+        .min_by_key(|(_, item)| crate::geometry::RectangleExt::aabb(&item.rect).area())?;
+
+    // How far along the line's middle the point is, from 0 to 1.
+    let [a, b, c, d] = item.rect.map(|[x, y]| [x as f64, y as f64]);
+    let middle = |p: [f64; 2], q: [f64; 2]| [(p[0] + q[0]) / 2.0, (p[1] + q[1]) / 2.0];
+    let (start, end) = if item.is_vertical() {
+        (middle(a, b), middle(d, c))
+    } else {
+        (middle(a, d), middle(b, c))
+    };
+    let (dx, dy) = (end[0] - start[0], end[1] - start[1]);
+    let length = dx * dx + dy * dy;
+    let along = if length > 0.0 {
+        ((point[0] as f64 - start[0]) * dx + (point[1] as f64 - start[1]) * dy) / length
+    } else {
+        0.0
+    };
+
+    let offsets = offsets(item);
+    let chars = offsets.len() - 1;
+    let char_index = offsets[1..]
+        .iter()
+        .position(|&offset| along < offset)
+        .unwrap_or(chars - 1);
+    Some((index, char_index))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{hit_test, span, words};
+    use crate::{detection, ContentData};
+
+    fn item(text: &str, rect: crate::Rectangle) -> ContentData {
+        detection(text, rect, 0.9)
+    }
+
+    #[test]
+    fn split() {
+        let line = item("ab  cde", [[0, 0], [70, 0], [70, 10], [0, 10]]);
+        let found = words(&line);
+        assert_eq!(
+            found
+                .iter()
+                .map(|w| (w.text, w.chars.clone()))
+                .collect::<Vec<_>>(),
+            [("ab", 0..2), ("cde", 4..7)]
+        );
+        assert_eq!(found[0].rect, [[0, 0], [20, 0], [20, 10], [0, 10]]);
+        assert_eq!(found[1].rect, [[40, 0], [70, 0], [70, 10], [40, 10]]);
+
+        // Full-width characters are twice as wide, and words of their own.
+        let mixed = item("漢字ab", [[0, 0], [60, 0], [60, 10], [0, 10]]);
+        let rects = words(&mixed).iter().map(|w| w.rect).collect::<Vec<_>>();
+        assert_eq!(
+            rects,
+            [
+                [[0, 0], [20, 0], [20, 10], [0, 10]],
+                [[20, 0], [40, 0], [40, 10], [20, 10]],
+                [[40, 0], [60, 0], [60, 10], [40, 10]],
+            ]
+        );
+
+        // A slanted line keeps its slant.
+        let slanted = item("abcd", [[0, 0], [40, 20], [40, 30], [0, 10]]);
+        assert_eq!(
+            span(&slanted, 2..4),
+            [[20, 10], [40, 20], [40, 30], [20, 20]]
+        );
+
+        // Vertical text runs top to bottom.
+        let vertical = item("縦書き", [[0, 0], [10, 0], [10, 30], [0, 30]]);
+        assert_eq!(
+            words(&vertical)[1].rect,
+            [[0, 10], [10, 10], [10, 20], [0, 20]]
+        );
+        assert!(words(&item("", [[0, 0]; 4])).is_empty());
+    }
+
+    #[test]
+    fn hits() {
+        let results = [
+            item("ab cd", [[0, 0], [50, 0], [50, 10], [0, 10]]),
+            item("縦書き", [[100, 0], [110, 0], [110, 30], [100, 30]]),
+            // Inside the first, and smaller.
+            item("x", [[40, 2], [48, 2], [48, 8], [40, 8]]),
+            item("slant", [[0, 20], [50, 40], [50, 50], [0, 30]]),
+        ];
+        assert_eq!(hit_test(&results, [5, 5]), Some((0, 0)));
+        assert_eq!(hit_test(&results, [25, 5]), Some((0, 2)));
+        assert_eq!(hit_test(&results, [50, 10]), Some((0, 4)));
+        assert_eq!(hit_test(&results, [44, 5]), Some((2, 0)));
+        assert_eq!(hit_test(&results, [105, 25]), Some((1, 2)));
+        assert_eq!(hit_test(&results, [45, 42]), Some((3, 4)));
+        // Outside the slanted box, though inside its bounds.
+        assert_eq!(hit_test(&results, [45, 30]), None);
+        assert_eq!(hit_test(&results, [80, 5]), None);
+    }
+}
@@ -0,0 +1,310 @@
+//! A minimal ZIP writer for the document exporters, and reader for bundled engines. Written entries are stored
+//! uncompressed, which every reader accepts and which EPUB requires for its `mimetype` entry anyway; read ones may
+//! also be deflated, as most tools make them. The CRC-32 is shared with PNG.
+
+#[cfg(feature = "bundled")]
+use std::borrow::Cow;
+#[cfg(any(feature = "bundled", feature = "docx", feature = "epub", test))]
+use std::io::Result as IoResult;
+#[cfg(any(feature = "docx", feature = "epub", test))]
+use std::io::Write;
+#[cfg(any(feature = "bundled", feature = "docx", feature = "epub", test))]
+use std::io::{Error as IoError, ErrorKind};
+
+/// 1980-01-01 00:00 in MS-DOS format, the earliest representable timestamp.
+#[cfg(any(feature = "docx", feature = "epub", test))]
+const DOS_DATE: u16 = (1 << 5) | 1;
+
+#[cfg(any(feature = "docx", feature = "epub", test))]
+struct Entry {
+    name: String,
+    crc: u32,
+    size: u32,
+    offset: u32,
+}
+
+/**
+ * Writes a ZIP archive entry by entry. Archives needing ZIP64, at 4 GiB or 65535 entries, fail with
+ * [`Unsupported`](ErrorKind::Unsupported) instead.
+ */
+#[cfg(any(feature = "docx", feature = "epub", test))]
+pub(crate) struct ZipWriter<W: Write> {
+    writer: W,
+    written: u64,
+    entries: Vec<Entry>,
+}
+
+#[cfg(any(feature = "docx", feature = "epub", test))]
+fn too_large() -> IoError {
+    IoError::new(
+        ErrorKind::Unsupported,
+        "the ZIP archive is too large; ZIP64 isn't supported",
+    )
+}
+
+/// `value` as a field of `T`, or an error if it needs ZIP64.
+#[cfg(any(feature = "docx", feature = "epub", test))]
+fn field<T: TryFrom<V>, V>(value: V) -> IoResult<T> {
+    T::try_from(value).map_err(|_| too_large())
+}
+
+#[cfg(any(feature = "docx", feature = "epub", test))]
+impl<W: Write> ZipWriter<W> {
+    pub(crate) fn new(writer: W) -> ZipWriter<W> {
+        ZipWriter {
+            writer,
+            written: 0,
+            entries: Vec::new(),
+        }
+    }
+
+    pub(crate) fn add(&mut self, name: &str, data: &[u8]) -> IoResult<()> {
+        // 0xffff entries already means the count is in the ZIP64 record.
+        if self.entries.len() >= u16::MAX as usize - 1 {
+            return Err(too_large());
+        }
+        let entry = Entry {
+            name: name.to_string(),
+            crc: crc32(data),
+            size: field(data.len())?,
+            offset: field(self.written)?,
+        };
+        let name_len: u16 = field(name.len())?;
+        let mut header = Vec::with_capacity(30 + name.len());
+        header.extend(0x04034b50u32.to_le_bytes());
+        header.extend(20u16.to_le_bytes()); // version needed: 2.0
+        header.extend(0u16.to_le_bytes()); // flags
+        header.extend(0u16.to_le_bytes()); // method: stored
+        header.extend(0u16.to_le_bytes()); // time
+        header.extend(DOS_DATE.to_le_bytes());
+        header.extend(entry.crc.to_le_bytes());
+        header.extend(entry.size.to_le_bytes()); // compressed size
+        header.extend(entry.size.to_le_bytes());
+        header.extend(name_len.to_le_bytes());
+        header.extend(0u16.to_le_bytes()); // extra field length
+        header.extend(name.as_bytes());
+        self.write(&header)?;
+        self.write(data)?;
+        self.entries.push(entry);
+        Ok(())
+    }
+
+    /**
+     * Writes the central directory and returns the underlying writer.
+     */
+    pub(crate) fn finish(mut self) -> IoResult<W> {
+        let start: u32 = field(self.written)?;
+        let mut directory = Vec::new();
+        for entry in &self.entries {
+            directory.extend(0x02014b50u32.to_le_bytes());
+            directory.extend(20u16.to_le_bytes()); // version made by
+            directory.extend(20u16.to_le_bytes()); // version needed
+            directory.extend(0u16.to_le_bytes()); // flags
+            directory.extend(0u16.to_le_bytes()); // method
+            directory.extend(0u16.to_le_bytes()); // time
+            directory.extend(DOS_DATE.to_le_bytes());
+            directory.extend(entry.crc.to_le_bytes());
+            directory.extend(entry.size.to_le_bytes());
+            directory.extend(entry.size.to_le_bytes());
+            directory.extend((entry.name.len() as u16).to_le_bytes());
+            directory.extend([0; 12]); // extra and comment lengths, disk, internal and external attributes
+            directory.extend(entry.offset.to_le_bytes());
+            directory.extend(entry.name.as_bytes());
+        }
+        let count = self.entries.len() as u16; // Bounded by `add`.
+        let mut end = Vec::with_capacity(22);
+        end.extend(0x06054b50u32.to_le_bytes());
+        end.extend([0; 4]); // disk numbers
+        end.extend(count.to_le_bytes());
+        end.extend(count.to_le_bytes());
+        end.extend(field::<u32, _>(directory.len())?.to_le_bytes());
+        end.extend(start.to_le_bytes());
+        end.extend(0u16.to_le_bytes()); // comment length
+        self.write(&directory)?;
+        self.write(&end)?;
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+
+    fn write(&mut self, data: &[u8]) -> IoResult<()> {
+        self.writer.write_all(data)?;
+        self.written += data.len() as u64;
+        Ok(())
+    }
+}
+
+/// A file read from an archive: its name and contents, borrowed unless they were deflated.
+#[cfg(feature = "bundled")]
+pub(crate) type File<'a> = (&'a str, Cow<'a, [u8]>);
+
+/**
+ * The files of a ZIP archive with stored or deflated entries, by name, checked against their CRCs. Directory entries
+ * are skipped.
+ */
+#[cfg(feature = "bundled")]
+pub(crate) fn read(archive: &[u8]) -> IoResult<Vec<File<'_>>> {
+    let invalid = |message: &str| IoError::new(ErrorKind::InvalidData, message.to_string());
+    let u16_at = |at: usize| -> IoResult<usize> {
+        let bytes = archive
+            .get(at..at + 2)
+            .ok_or_else(|| invalid("truncated ZIP archive"))?;
+        Ok(u16::from_le_bytes([bytes[0], bytes[1]]) as usize)
+    };
+    let u32_at = |at: usize| -> IoResult<usize> {
+        let bytes = archive
+            .get(at..at + 4)
+            .ok_or_else(|| invalid("truncated ZIP archive"))?;
+        Ok(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as usize)
+    };
+
+    let end = (0..archive.len().saturating_sub(21))
+        .rev()
+        .find(|&at| archive[at..].starts_with(b"PK\x05\x06"))
+        .ok_or_else(|| invalid("not a ZIP archive"))?;
+    let count = u16_at(end + 10)?;
+    let mut at = u32_at(end + 16)?;
+    let mut files = Vec::with_capacity(count);
+    for _ in 0..count {
+        if u32_at(at)? != 0x02014b50 {
+            return Err(invalid("corrupt ZIP central directory"));
+        }
+        let (method, crc) = (u16_at(at + 10)?, u32_at(at + 16)?);
+        let (compressed, size) = (u32_at(at + 20)?, u32_at(at + 24)?);
+        let (name_len, extra_len, comment_len) =
+            (u16_at(at + 28)?, u16_at(at + 30)?, u16_at(at + 32)?);
+        let offset = u32_at(at + 42)?;
+        let name = archive
+            .get(at + 46..at + 46 + name_len)
+            .and_then(|name| std::str::from_utf8(name).ok())
+            .ok_or_else(|| invalid("bad ZIP entry name"))?;
+        at += 46 + name_len + extra_len + comment_len;
+        if name.ends_with('/') {
+            continue;
+        }
+        let start = offset + 30 + u16_at(offset + 26)? + u16_at(offset + 28)?;
+        let data = archive
+            .get(start..start + compressed)
+            .ok_or_else(|| invalid("truncated ZIP archive"))?;
+        let data = match method {
+            0 => Cow::Borrowed(data),
+            8 => miniz_oxide::inflate::decompress_to_vec_with_limit(data, size)
+                .map(Cow::Owned)
+                .map_err(|_| invalid(&format!("{} is corrupt", name)))?,
+            _ => {
+                return Err(IoError::new(
+                    ErrorKind::Unsupported,
+                    format!(
+                        "{} uses compression method {}; only stored and deflated ZIP entries are supported",
+                        name, method
+                    ),
+                ))
+            }
+        };
+        if data.len() != size || crc32(&data) as usize != crc {
+            return Err(invalid(&format!("{} is corrupt", name)));
+        }
+        files.push((name, data));
+    }
+    Ok(files)
+}
+
+pub(crate) fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &byte in data {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = (crc >> 1) ^ (0xedb88320 & (crc & 1).wrapping_neg());
+        }
+    }
+    !crc
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{crc32, ZipWriter};
+
+    #[test]
+    #[cfg(feature = "bundled")]
+    fn read_back() {
+        let mut zip = ZipWriter::new(Vec::new());
+        zip.add("a.txt", b"hello").unwrap();
+        zip.add("b/c.txt", b"").unwrap();
+        let mut bytes = zip.finish().unwrap();
+        let files = super::read(&bytes).unwrap();
+        let files: Vec<_> = files
+            .iter()
+            .map(|(name, data)| (*name, &data[..]))
+            .collect();
+        assert_eq!(files, [("a.txt", &b"hello"[..]), ("b/c.txt", &b""[..])]);
+        bytes[35] = b'j';
+        assert!(super::read(&bytes).is_err());
+        assert!(super::read(b"not a zip").is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "bundled")]
+    fn read_deflated() {
+        let data = b"hello hello hello hello";
+        let deflated = miniz_oxide::deflate::compress_to_vec(data, 6);
+        // A stored entry of the same name and size, switched to method 8 with the deflated data.
+        let mut zip = ZipWriter::new(Vec::new());
+        zip.add("a.txt", data).unwrap();
+        let stored = zip.finish().unwrap();
+        let directory = &stored[30 + 5 + data.len()..stored.len() - 22];
+        let mut bytes = stored[..30 + 5].to_vec();
+        bytes[8] = 8;
+        bytes[18..22].copy_from_slice(&(deflated.len() as u32).to_le_bytes());
+        bytes.extend(&deflated);
+        let start = bytes.len() as u32;
+        bytes.extend(directory);
+        bytes[start as usize + 10] = 8;
+        bytes[start as usize + 20..start as usize + 24]
+            .copy_from_slice(&(deflated.len() as u32).to_le_bytes());
+        let mut end = stored[stored.len() - 22..].to_vec();
+        end[16..20].copy_from_slice(&start.to_le_bytes());
+        bytes.extend(end);
+
+        let files = super::read(&bytes).unwrap();
+        assert_eq!((files[0].0, &files[0].1[..]), ("a.txt", &data[..]));
+        assert!(matches!(files[0].1, std::borrow::Cow::Owned(_)));
+        let last = 30 + 5 + deflated.len() - 1;
+        bytes[last] ^= 0xff;
+        assert!(super::read(&bytes).is_err());
+    }
+
+    #[test]
+    fn stored_archive() {
+        assert_eq!(crc32(b"123456789"), 0xcbf43926);
+
+        let mut zip = ZipWriter::new(Vec::new());
+        zip.add("a.txt", b"hello").unwrap();
+        zip.add("b/c.txt", b"").unwrap();
+        let bytes = zip.finish().unwrap();
+        assert_eq!(&bytes[..4], b"PK\x03\x04");
+        assert_eq!(&bytes[30..35], b"a.txt");
+        assert_eq!(&bytes[35..40], b"hello");
+        let end = &bytes[bytes.len() - 22..];
+        assert_eq!(&end[..4], b"PK\x05\x06");
+        assert_eq!(end[10], 2); // entries
+        let directory = u32::from_le_bytes(end[16..20].try_into().unwrap()) as usize;
+        assert_eq!(&bytes[directory..directory + 4], b"PK\x01\x02");
+    }
+
+    #[test]
+    fn too_large() {
+        let mut zip = ZipWriter::new(std::io::sink());
+        zip.written = u32::MAX as u64 + 1;
+        let e = zip.add("a.txt", b"").unwrap_err();
+        assert_eq!(e.kind(), std::io::ErrorKind::Unsupported);
+        zip.written = u32::MAX as u64;
+        zip.add("a.txt", b"hello").unwrap();
+        assert!(zip.finish().is_err());
+
+        let mut zip = ZipWriter::new(std::io::sink());
+        for _ in 0..u16::MAX - 1 {
+            zip.add("", b"").unwrap();
+        }
+        assert!(zip.add("", b"").is_err());
+        assert!(zip.finish().is_ok());
+    }
+}